@@ -0,0 +1,177 @@
+// Time-window aggregation: with high-rate telemetry (a 1 kHz sensor) a table
+// row per line is more than the table and plots can keep up with. With a
+// window set, parsed rows are collected per window of host time, aligned to
+// the clock so 1 s windows start on whole seconds, and each window becomes a
+// single row: numeric columns hold the mean, with the minimum and maximum
+// kept for the cell tooltips and the plot band; text columns hold their last
+// value. The full-rate rows are kept as well, for CSV export.
+
+use chrono::{DateTime, Local};
+
+use crate::settings::Settings;
+use crate::timestamp::Timestamp;
+
+/// Sample count and per-column value range of an aggregated row
+#[derive(Clone)]
+pub struct Spread {
+    pub count: usize,
+    /// Minimum and maximum of each column; None for columns without numbers
+    pub ranges: Vec<Option<(f64, f64)>>,
+}
+
+impl Spread {
+    pub fn range(&self, col: usize) -> Option<(f64, f64)> {
+        self.ranges.get(col).copied().flatten()
+    }
+
+    /// Tooltip for a cell of the row
+    pub fn describe(&self, col: usize) -> String {
+        match self.range(col) {
+            Some((min, max)) => format!(
+                "Mean of {} samples\nmin {}\nmax {}",
+                self.count,
+                format_value(min),
+                format_value(max)
+            ),
+            None => format!("Last of {} samples", self.count),
+        }
+    }
+}
+
+/// One closed window
+pub struct Window {
+    /// Time of the window's first sample
+    pub time: Timestamp,
+    pub columns: Vec<String>,
+    pub values: Vec<Option<f64>>,
+    /// Frames the sequence counter reported missing within the window
+    pub dropped: u64,
+    pub spread: Spread,
+}
+
+/// Samples of the window being collected
+struct Pending {
+    /// Window number, counted from the Unix epoch
+    index: i64,
+    time: Timestamp,
+    count: usize,
+    /// Sum and count of the numeric values of each column
+    sums: Vec<(f64, usize)>,
+    ranges: Vec<Option<(f64, f64)>>,
+    /// Text of the last sample
+    columns: Vec<String>,
+    dropped: u64,
+}
+
+impl Pending {
+    fn close(self) -> Window {
+        let width = self.columns.len().max(self.sums.len());
+        let mut columns = self.columns;
+        columns.resize(width, String::new());
+        let mut values = vec![None; width];
+        for (col, &(sum, n)) in self.sums.iter().enumerate() {
+            if n > 0 {
+                let mean = sum / n as f64;
+                values[col] = Some(mean);
+                columns[col] = format_value(mean);
+            }
+        }
+        Window {
+            time: self.time,
+            columns,
+            values,
+            dropped: self.dropped,
+            spread: Spread {
+                count: self.count,
+                ranges: self.ranges,
+            },
+        }
+    }
+}
+
+pub struct Aggregator {
+    window_ms: i64,
+    pending: Option<Pending>,
+}
+
+impl Aggregator {
+    /// None unless an aggregation window is set
+    pub fn from_settings(settings: &Settings) -> Option<Self> {
+        (settings.aggregate_ms > 0).then_some(Self {
+            window_ms: settings.aggregate_ms as i64,
+            pending: None,
+        })
+    }
+
+    fn index(&self, time: DateTime<Local>) -> i64 {
+        time.timestamp_millis().div_euclid(self.window_ms)
+    }
+
+    /// Adds a parsed row; returns the previous window when this row starts
+    /// a new one
+    pub fn push(
+        &mut self,
+        time: Timestamp,
+        columns: &[String],
+        values: &[Option<f64>],
+        dropped: u64,
+    ) -> Option<Window> {
+        let index = self.index(time.wall);
+        let closed = match &self.pending {
+            Some(pending) if pending.index != index => self.pending.take().map(Pending::close),
+            _ => None,
+        };
+        let pending = self.pending.get_or_insert_with(|| Pending {
+            index,
+            time,
+            count: 0,
+            sums: Vec::new(),
+            ranges: Vec::new(),
+            columns: Vec::new(),
+            dropped: 0,
+        });
+        pending.count += 1;
+        pending.dropped += dropped;
+        pending.columns = columns.to_vec();
+        if pending.sums.len() < values.len() {
+            pending.sums.resize(values.len(), (0.0, 0));
+            pending.ranges.resize(values.len(), None);
+        }
+        for (col, value) in values.iter().enumerate() {
+            let Some(v) = *value else {
+                continue;
+            };
+            let (sum, n) = &mut pending.sums[col];
+            *sum += v;
+            *n += 1;
+            let range = &mut pending.ranges[col];
+            *range = Some(range.map_or((v, v), |(lo, hi)| (lo.min(v), hi.max(v))));
+        }
+        closed
+    }
+
+    /// Closes the pending window once `now` is past it, so the last window
+    /// shows up even when the device goes quiet
+    pub fn flush_due(&mut self, now: DateTime<Local>) -> Option<Window> {
+        let index = self.index(now);
+        match &self.pending {
+            Some(pending) if pending.index < index => self.pending.take().map(Pending::close),
+            _ => None,
+        }
+    }
+
+    /// Drops the pending window
+    pub fn reset(&mut self) {
+        self.pending = None;
+    }
+}
+
+/// A mean or bound as cell text: whole numbers without decimals, others
+/// rounded to four places
+pub fn format_value(v: f64) -> String {
+    if v.fract() == 0.0 && v.abs() < 1e15 {
+        return format!("{}", v as i64);
+    }
+    let text = format!("{:.4}", v);
+    text.trim_end_matches('0').trim_end_matches('.').to_string()
+}
@@ -0,0 +1,259 @@
+// Alert rules: conditions on parsed values ("Temperature > 80") or on the
+// received line (/ERROR|assert/) that are checked on every row, for captures
+// nobody watches as they scroll by. Rows that break a rule are highlighted,
+// hits are counted in the status bar and rules can raise a desktop
+// notification, at most one per rule every `NOTIFY_INTERVAL`.
+
+use std::thread;
+use std::time::{Duration, Instant};
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::regex_guard;
+use crate::settings::Settings;
+
+/// Minimum time between two notifications of the same rule
+const NOTIFY_INTERVAL: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub enum AlertKind {
+    /// A parsed column compared with a threshold
+    #[default]
+    Value,
+    /// A regex searched in the received line
+    Line,
+}
+
+impl AlertKind {
+    pub fn label(&self) -> &'static str {
+        match self {
+            AlertKind::Value => "Column value",
+            AlertKind::Line => "Line matches",
+        }
+    }
+    pub fn all() -> &'static [AlertKind] {
+        &[AlertKind::Value, AlertKind::Line]
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub enum Comparison {
+    #[default]
+    Above,
+    AtLeast,
+    Below,
+    AtMost,
+    Equal,
+    NotEqual,
+}
+
+impl Comparison {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Comparison::Above => ">",
+            Comparison::AtLeast => "≥",
+            Comparison::Below => "<",
+            Comparison::AtMost => "≤",
+            Comparison::Equal => "=",
+            Comparison::NotEqual => "≠",
+        }
+    }
+    pub fn all() -> &'static [Comparison] {
+        &[
+            Comparison::Above,
+            Comparison::AtLeast,
+            Comparison::Below,
+            Comparison::AtMost,
+            Comparison::Equal,
+            Comparison::NotEqual,
+        ]
+    }
+    fn holds(&self, value: f64, threshold: f64) -> bool {
+        match self {
+            Comparison::Above => value > threshold,
+            Comparison::AtLeast => value >= threshold,
+            Comparison::Below => value < threshold,
+            Comparison::AtMost => value <= threshold,
+            Comparison::Equal => value == threshold,
+            Comparison::NotEqual => value != threshold,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AlertRule {
+    pub kind: AlertKind,
+    /// Column of a value rule (0-based)
+    pub column: usize,
+    pub comparison: Comparison,
+    pub threshold: f64,
+    /// Regex of a line rule
+    pub pattern: String,
+    /// Raise a desktop notification when the rule fires
+    pub notify: bool,
+}
+
+impl AlertRule {
+    /// "Temperature > 80" or "line matches /ERROR/"
+    pub fn describe(&self, header: &str) -> String {
+        match self.kind {
+            AlertKind::Value => {
+                format!("{} {} {}", header, self.comparison.label(), self.threshold)
+            }
+            AlertKind::Line => format!("line matches /{}/", self.pattern),
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct AlertRules {
+    rules: Vec<(AlertRule, Option<Regex>)>,
+    /// Last notification of each rule
+    notified: Vec<Option<Instant>>,
+}
+
+impl AlertRules {
+    /// Line rules without a pattern are skipped
+    pub fn from_settings(settings: &Settings) -> Result<Self, String> {
+        let mut rules = Vec::new();
+        for rule in &settings.alert_rules {
+            let regex = match rule.kind {
+                AlertKind::Value => None,
+                AlertKind::Line if rule.pattern.is_empty() => continue,
+                AlertKind::Line => Some(
+                    regex_guard::compile(&rule.pattern)
+                        .map_err(|e| format!("Alert rule /{}/: {}", rule.pattern, e))?,
+                ),
+            };
+            rules.push((rule.clone(), regex));
+        }
+        Ok(Self {
+            notified: vec![None; rules.len()],
+            rules,
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    pub fn rule(&self, index: usize) -> &AlertRule {
+        &self.rules[index].0
+    }
+
+    /// Indices of the rules the row breaks; `values` is empty for lines the
+    /// parser didn't match
+    pub fn check(&self, line: &str, values: &[Option<f64>]) -> Vec<usize> {
+        self.rules
+            .iter()
+            .enumerate()
+            .filter(|(_, (rule, regex))| match regex {
+                Some(regex) => regex.is_match(line),
+                None => values
+                    .get(rule.column)
+                    .copied()
+                    .flatten()
+                    .is_some_and(|v| rule.comparison.holds(v, rule.threshold)),
+            })
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    /// Whether a hit of the rule should raise a notification now
+    pub fn notify_due(&mut self, index: usize) -> bool {
+        if !self.rules[index].0.notify {
+            return false;
+        }
+        let last = &mut self.notified[index];
+        if last.is_some_and(|t| t.elapsed() < NOTIFY_INTERVAL) {
+            return false;
+        }
+        *last = Some(Instant::now());
+        true
+    }
+}
+
+/// Shows a desktop notification; sent from a thread as it can block on the
+/// notification service
+pub fn notify(body: String) {
+    thread::spawn(move || {
+        let _ = notify_rust::Notification::new()
+            .summary("UART Console alert")
+            .body(&body)
+            .show();
+    });
+}
+
+/// Settings editor for the alert rules
+pub fn alert_editor(ui: &mut egui::Ui, rules: &mut Vec<AlertRule>) {
+    let mut remove = None;
+
+    egui::Grid::new("alert_grid")
+        .num_columns(4)
+        .spacing([6.0, 4.0])
+        .striped(true)
+        .show(ui, |ui| {
+            ui.strong("Condition");
+            ui.strong("");
+            ui.strong("Notify");
+            ui.end_row();
+
+            for (i, rule) in rules.iter_mut().enumerate() {
+                egui::ComboBox::from_id_salt(("alert_kind", i))
+                    .selected_text(rule.kind.label())
+                    .width(110.0)
+                    .show_ui(ui, |ui| {
+                        for kind in AlertKind::all() {
+                            ui.selectable_value(&mut rule.kind, *kind, kind.label());
+                        }
+                    });
+                ui.horizontal(|ui| match rule.kind {
+                    AlertKind::Value => {
+                        let mut col = rule.column + 1;
+                        ui.add(egui::DragValue::new(&mut col).range(1..=64).prefix("col "));
+                        rule.column = col - 1;
+                        egui::ComboBox::from_id_salt(("alert_cmp", i))
+                            .selected_text(rule.comparison.label())
+                            .width(40.0)
+                            .show_ui(ui, |ui| {
+                                for cmp in Comparison::all() {
+                                    ui.selectable_value(&mut rule.comparison, *cmp, cmp.label());
+                                }
+                            });
+                        ui.add(egui::DragValue::new(&mut rule.threshold).speed(0.1));
+                    }
+                    AlertKind::Line => {
+                        ui.add(
+                            egui::TextEdit::singleline(&mut rule.pattern)
+                                .hint_text("ERROR|assert")
+                                .desired_width(180.0),
+                        );
+                    }
+                });
+                ui.checkbox(&mut rule.notify, "");
+                if ui.small_button("Remove").clicked() {
+                    remove = Some(i);
+                }
+                ui.end_row();
+            }
+        });
+
+    if let Some(i) = remove {
+        rules.remove(i);
+    }
+    if ui.button("Add rule").clicked() {
+        rules.push(AlertRule::default());
+    }
+    for rule in rules.iter().filter(|r| r.kind == AlertKind::Line) {
+        if let Err(e) = regex_guard::compile(&rule.pattern) {
+            ui.colored_label(egui::Color32::RED, format!("/{}/: {}", rule.pattern, e));
+        }
+    }
+    ui.label(
+        "Rows that break a rule are shown red and counted in the status bar. Value rules \
+         check the numeric value of the column (1-based). Desktop notifications are sent at \
+         most every 30 s per rule.",
+    );
+}
@@ -0,0 +1,220 @@
+// ANSI escape sequences: firmware loggers (Zephyr, ESP-IDF, RTOS shells)
+// color their output with SGR sequences such as `ESC[0;32m`. The raw log
+// renders the colors; the parser, trigger, routes and table get the text with
+// every escape sequence removed, so patterns don't have to allow for them.
+
+use std::borrow::Cow;
+
+use egui::text::{LayoutJob, TextFormat};
+use egui::{Color32, FontId};
+
+const ESC: char = '\x1b';
+
+/// The 16 standard colors, normal then bright, tuned for a dark background
+const PALETTE: [Color32; 16] = [
+    Color32::from_rgb(0, 0, 0),
+    Color32::from_rgb(205, 49, 49),
+    Color32::from_rgb(13, 188, 121),
+    Color32::from_rgb(229, 229, 16),
+    Color32::from_rgb(36, 114, 200),
+    Color32::from_rgb(188, 63, 188),
+    Color32::from_rgb(17, 168, 205),
+    Color32::from_rgb(229, 229, 229),
+    Color32::from_rgb(102, 102, 102),
+    Color32::from_rgb(241, 76, 76),
+    Color32::from_rgb(35, 209, 139),
+    Color32::from_rgb(245, 245, 67),
+    Color32::from_rgb(59, 142, 234),
+    Color32::from_rgb(214, 112, 214),
+    Color32::from_rgb(41, 184, 219),
+    Color32::from_rgb(255, 255, 255),
+];
+
+/// The text without escape sequences
+pub fn strip(text: &str) -> Cow<'_, str> {
+    if !text.contains(ESC) {
+        return Cow::Borrowed(text);
+    }
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find(ESC) {
+        out.push_str(&rest[..start]);
+        rest = take_sequence(&rest[start..]).1;
+    }
+    out.push_str(rest);
+    Cow::Owned(out)
+}
+
+/// Splits the escape sequence off the start of `text`, which begins with
+/// ESC. Returns the parameters and final character of a CSI sequence, None
+/// for other sequences, and the text after it; a sequence cut off at the end
+/// takes the rest of the text.
+pub fn take_sequence(text: &str) -> (Option<(&str, char)>, &str) {
+    let bytes = text.as_bytes();
+    match bytes.get(1) {
+        Some(b'[') => {
+            let mut i = 2;
+            while i < bytes.len() && (0x20..=0x3f).contains(&bytes[i]) {
+                i += 1;
+            }
+            match bytes.get(i) {
+                Some(&b) if (0x40..=0x7e).contains(&b) => {
+                    (Some((&text[2..i], b as char)), &text[i + 1..])
+                }
+                _ => (None, &text[i..]),
+            }
+        }
+        // Operating system command (window title), ended by BEL or ESC \
+        Some(b']') => {
+            let end = text
+                .find('\x07')
+                .map(|i| i + 1)
+                .or_else(|| text.find("\x1b\\").map(|i| i + 2))
+                .unwrap_or(text.len());
+            (None, &text[end..])
+        }
+        Some(b) if b.is_ascii() => (None, &text[2..]),
+        _ => (None, &text[1..]),
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Color {
+    /// Palette entry 0-255
+    Index(u8),
+    Rgb(Color32),
+}
+
+/// Text attributes set by SGR sequences
+#[derive(Clone, Copy, Default, PartialEq)]
+pub struct Style {
+    fg: Option<Color>,
+    bg: Option<Color>,
+    bold: bool,
+    inverse: bool,
+}
+
+impl Style {
+    /// No colors or attributes
+    pub const PLAIN: Style = Style {
+        fg: None,
+        bg: None,
+        bold: false,
+        inverse: false,
+    };
+
+    pub fn toggle_inverse(&mut self) {
+        self.inverse = !self.inverse;
+    }
+
+    /// Applies the parameters of an SGR (`ESC[...m`) sequence
+    pub fn apply_sgr(&mut self, params: &str) {
+        let codes: Vec<u16> = params.split(';').map(|p| p.parse().unwrap_or(0)).collect();
+        let mut i = 0;
+        while i < codes.len() {
+            match codes[i] {
+                0 => *self = Style::default(),
+                1 => self.bold = true,
+                22 => self.bold = false,
+                7 => self.inverse = true,
+                27 => self.inverse = false,
+                n @ 30..=37 => self.fg = Some(Color::Index((n - 30) as u8)),
+                n @ 90..=97 => self.fg = Some(Color::Index((n - 90 + 8) as u8)),
+                39 => self.fg = None,
+                n @ 40..=47 => self.bg = Some(Color::Index((n - 40) as u8)),
+                n @ 100..=107 => self.bg = Some(Color::Index((n - 100 + 8) as u8)),
+                49 => self.bg = None,
+                n @ (38 | 48) => {
+                    let (color, used) = extended_color(&codes[i + 1..]);
+                    if n == 38 {
+                        self.fg = color.or(self.fg);
+                    } else {
+                        self.bg = color.or(self.bg);
+                    }
+                    i += used;
+                }
+                _ => {}
+            }
+            i += 1;
+        }
+    }
+
+    /// Foreground and background colors; None keeps the default
+    pub fn colors(&self, default_fg: Color32) -> (Color32, Option<Color32>) {
+        let fg = match self.fg {
+            // Bold makes the eight standard colors bright, as most terminals do
+            Some(Color::Index(i)) if self.bold && i < 8 => color(Color::Index(i + 8)),
+            Some(c) => color(c),
+            None => default_fg,
+        };
+        let bg = self.bg.map(color);
+        if self.inverse {
+            (bg.unwrap_or(Color32::BLACK), Some(fg))
+        } else {
+            (fg, bg)
+        }
+    }
+
+    pub fn format(&self, font_id: &FontId, default_fg: Color32) -> TextFormat {
+        let (color, background) = self.colors(default_fg);
+        TextFormat {
+            font_id: font_id.clone(),
+            color,
+            background: background.unwrap_or(Color32::TRANSPARENT),
+            ..Default::default()
+        }
+    }
+}
+
+/// Color of a `38;5;n` or `38;2;r;g;b` sequence and the number of codes it
+/// took after the 38/48
+fn extended_color(codes: &[u16]) -> (Option<Color>, usize) {
+    match codes {
+        [5, n, ..] => (Some(Color::Index(*n as u8)), 2),
+        [2, r, g, b, ..] => (
+            Some(Color::Rgb(Color32::from_rgb(*r as u8, *g as u8, *b as u8))),
+            4,
+        ),
+        _ => (None, codes.len()),
+    }
+}
+
+fn color(color: Color) -> Color32 {
+    match color {
+        Color::Rgb(c) => c,
+        Color::Index(n) if n < 16 => PALETTE[n as usize],
+        // 6×6×6 color cube
+        Color::Index(n @ 16..=231) => {
+            let level = |v: u8| if v == 0 { 0 } else { 55 + v * 40 };
+            let n = n - 16;
+            Color32::from_rgb(level(n / 36), level(n / 6 % 6), level(n % 6))
+        }
+        // Grayscale ramp
+        Color::Index(n) => {
+            let v = 8 + (n - 232) * 10;
+            Color32::from_rgb(v, v, v)
+        }
+    }
+}
+
+/// The text colored by its SGR sequences; other escape sequences are dropped
+pub fn layout(text: &str, font_id: &FontId, default_fg: Color32) -> LayoutJob {
+    let mut job = LayoutJob::default();
+    let mut style = Style::default();
+    let mut rest = text;
+    loop {
+        let end = rest.find(ESC).unwrap_or(rest.len());
+        if end > 0 {
+            job.append(&rest[..end], 0.0, style.format(font_id, default_fg));
+        }
+        if end == rest.len() {
+            break;
+        }
+        let (csi, after) = take_sequence(&rest[end..]);
+        if let Some((params, 'm')) = csi {
+            style.apply_sgr(params);
+        }
+        rest = after;
+    }
+    job
+}
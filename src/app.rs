@@ -1,462 +1,3517 @@
-use chrono::Local;
+use std::borrow::Cow;
+use std::collections::BTreeSet;
+use std::time::{Duration, Instant};
+
 use egui::RichText;
 use egui_extras::{Column, TableBuilder};
 use regex::Regex;
 
-use crate::serial_port::{SerialEvent, SerialPortManager};
-use crate::settings::{Settings, SettingsWindow};
+use crate::aggregate::{format_value, Aggregator, Spread, Window};
+use crate::alerts::{self, AlertRules};
+use crate::ansi;
+use crate::baud_scan::{BaudScanner, MismatchHint};
+use crate::capture_store::{load_latest_capture, save_capture, SavedCapture};
+use crate::checksum::LineChecksum;
+use crate::column_types::{convert_row, ColumnType};
+use crate::command_palette::{self, Command, CommandPalette, Panel};
+use crate::control_lines::ControlLinesWindow;
+use crate::dashboard::DashboardPanel;
+use crate::decoder_stats::DecoderStatsWindow;
+use crate::defmt::DefmtTable;
+use crate::derived::DerivedColumns;
+use crate::device_info::{info_grid, DeviceInfo};
+use crate::export::{escape_field, ExportTemplate};
+use crate::frame_fields::FramePattern;
+use crate::framing::to_hex;
+use crate::hex_dump::{self, HexDump};
+use crate::histogram::HistogramWindow;
+use crate::line_filter::LineFilter;
+use crate::line_script::{LineScript, ScriptOutput};
+use crate::log_level::{LevelDetector, LogLevel};
+use crate::macros::pressed_macro;
+use crate::modbus::ModbusWindow;
+use crate::parser::LineParser;
+use crate::plot::{show_plots, toggle_plot_column, Sample};
+use crate::port_watch::{PortWatcher, RESCAN_INTERVAL};
+use crate::proto_schema::ProtoSchema;
+use crate::records::RecordAssembler;
+use crate::regex_guard;
+use crate::routing::{show_route_table, Router};
+use crate::search::Search;
+use crate::selection::Selection;
+use crate::sequence::SequenceTracker;
+use crate::serial_port::{Direction, SerialEvent, SerialPortManager, XOFF, XON};
+use crate::session::{SecondarySession, TimedLine};
+use crate::session_summary::{SessionRecorder, SessionSummaryWindow};
+use crate::settings::{
+    port_picker, AppFlowControl, ByteTotal, ConnectionKind, Density, ParserMode, RxFraming,
+    Settings, SettingsAction, SettingsWindow, ViewLayout, WindowAlert,
+};
+use crate::symbols::SymbolTable;
+use crate::table_view::{compare_cells, SortKey, TableView};
+use crate::terminal::Terminal;
+use crate::theme::Palette;
+use crate::timestamp::{OffsetEstimate, TimeDisplay, Timestamp};
+use crate::tlv::TlvDecoder;
+use crate::tx_map;
+use crate::units;
+
+/// Text zoom of a view after Ctrl+scroll over `ui`; None if unchanged
+fn scrolled_zoom(ui: &egui::Ui, zoom: f32) -> Option<f32> {
+    if !ui.rect_contains_pointer(ui.max_rect()) {
+        return None;
+    }
+    let delta = ui.input(|i| i.zoom_delta());
+    let new = (zoom * delta).clamp(*ZOOM_RANGE.start(), *ZOOM_RANGE.end());
+    (new != zoom).then_some(new)
+}
+
+/// Formats a byte count with a binary unit prefix
+fn format_bytes(bytes: f64) -> String {
+    if bytes < 1024.0 {
+        format!("{:.0} B", bytes)
+    } else if bytes < 1024.0 * 1024.0 {
+        format!("{:.1} KiB", bytes / 1024.0)
+    } else {
+        format!("{:.1} MiB", bytes / (1024.0 * 1024.0))
+    }
+}
+
+/// Formats a rate of received bytes or lines per second
+fn format_rate(rate: f64, in_lines: bool) -> String {
+    if in_lines {
+        format!("{:.0} lines/s", rate)
+    } else {
+        format!("{}/s", format_bytes(rate))
+    }
+}
+
+/// Draws a tiny line chart of `samples` scaled to their peak; clicking it
+/// switches between bytes and lines per second
+fn sparkline(
+    ui: &mut egui::Ui,
+    samples: &std::collections::VecDeque<f64>,
+    in_lines: bool,
+) -> egui::Response {
+    let (rect, response) = ui.allocate_exact_size(egui::vec2(80.0, 14.0), egui::Sense::click());
+    let painter = ui.painter_at(rect);
+    painter.rect_filled(rect, 2.0, ui.visuals().extreme_bg_color);
+    let peak = samples.iter().copied().fold(0.0, f64::max);
+    if samples.len() >= 2 && peak > 0.0 {
+        let step = rect.width() / (samples.len() - 1) as f32;
+        let points: Vec<egui::Pos2> = samples
+            .iter()
+            .enumerate()
+            .map(|(i, &v)| {
+                let y = rect.bottom() - (v / peak) as f32 * (rect.height() - 2.0) - 1.0;
+                egui::pos2(rect.left() + i as f32 * step, y)
+            })
+            .collect();
+        painter.add(egui::Shape::line(
+            points,
+            egui::Stroke::new(1.0, egui::Color32::from_rgb(90, 170, 250)),
+        ));
+    }
+    response.on_hover_text(format!(
+        "RX rate, last {} s (peak {})\nClick to show {} per second",
+        samples.len(),
+        format_rate(peak, in_lines),
+        if in_lines { "bytes" } else { "lines" }
+    ))
+}
+
+/// Formats a row timestamp for display
+fn format_time(time: &Timestamp, display: &TimeDisplay) -> String {
+    time.format(display, "%H:%M:%S%.3f")
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum RowKind {
+    /// Line received from the device
+    Data,
+    /// Marker inserted by the app (e.g. when the trigger fires)
+    Marker,
+    /// Data sent to the device, echoed into the log
+    Sent,
+    /// Received line the line script dropped when the rows were parsed
+    /// again; kept hidden so a later script can bring it back
+    Dropped,
+}
+
+/// Size of a row for the size retention policy
+fn row_size(row: &DataRow) -> usize {
+    row.raw.len()
+}
+
+/// Size of a raw log line for the size retention policy
+fn line_size(line: &TimedLine) -> usize {
+    line.text.len()
+}
+
+/// Orders two rows by the given sort key
+fn compare_rows(a: &DataRow, b: &DataRow, key: SortKey) -> std::cmp::Ordering {
+    match key {
+        SortKey::Time => a.time.cmp(&b.time),
+        SortKey::Column(i) => compare_cells(
+            a.columns.get(i).map_or("", String::as_str),
+            b.columns.get(i).map_or("", String::as_str),
+        ),
+    }
+}
+
+/// Header label that toggles sorting when clicked
+fn sort_header(ui: &mut egui::Ui, title: &str, arrow: &str) -> bool {
+    ui.add(
+        egui::Label::new(RichText::new(format!("{}{}", title, arrow)).strong())
+            .sense(egui::Sense::click()),
+    )
+    .on_hover_text("Click to sort")
+    .clicked()
+}
+
+/// Table cell text, coloured unless lightweight mode is on
+fn cell_label(
+    ui: &mut egui::Ui,
+    plain: bool,
+    color: egui::Color32,
+    text: &str,
+) -> egui::Response {
+    // Multi-line records show their first line; the rest is on hover
+    let (shown, rest) = text.split_once('\n').unwrap_or((text, ""));
+    let response = if plain {
+        ui.label(shown)
+    } else {
+        ui.colored_label(color, shown)
+    };
+    if rest.is_empty() {
+        response
+    } else {
+        response.on_hover_text(RichText::new(text).monospace())
+    }
+}
+
+/// `cell_label` with the matches of an open search highlighted
+fn search_cell_label(
+    ui: &mut egui::Ui,
+    plain: bool,
+    color: egui::Color32,
+    text: &str,
+    search: &Search,
+    current: bool,
+) -> egui::Response {
+    let shown = text.split_once('\n').map_or(text, |(first, _)| first);
+    let font_id = egui::TextStyle::Body.resolve(ui.style());
+    match search.highlight(shown, &font_id, color, current) {
+        Some(job) if shown.len() == text.len() => ui.label(job),
+        Some(job) => ui.label(job).on_hover_text(RichText::new(text).monospace()),
+        None => cell_label(ui, plain, color, text),
+    }
+}
+
+/// What the central panel shows besides the raw log and timeline
+#[derive(Clone, Copy, PartialEq)]
+enum DataTab {
+    /// The main table
+    All,
+    /// A route table
+    Route(usize),
+    /// The capture restored from disk at startup
+    Restored,
+}
+
+/// Repaint interval while data is streaming
+const REPAINT_INTERVAL: Duration = Duration::from_millis(30);
+const LIGHTWEIGHT_REPAINT_INTERVAL: Duration = Duration::from_millis(200);
+
+/// Upper bound for the exponential connect backoff
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(30);
+
+/// Seconds without data after which the status bar reports a stall
+const STALL_SECS: usize = 3;
+
+/// Text sizes Ctrl+scroll can zoom the table and raw log to
+const ZOOM_RANGE: std::ops::RangeInclusive<f32> = 0.5..=3.0;
+
+const BOOKMARK_COLOR: egui::Color32 = egui::Color32::from_rgb(80, 150, 255);
+
+/// Pending connect retry after the port failed to open
+struct ConnectRetry {
+    /// Number of the next attempt (1-based)
+    attempt: u32,
+    next_at: Instant,
+}
 
 /// One parsed data row
 #[derive(Clone)]
 struct DataRow {
-    timestamp: String,
+    kind: RowKind,
+    /// Line number since the capture started or was cleared; 0 for
+    /// aggregated windows
+    line_no: u64,
+    /// Host time the line was received
+    time: Timestamp,
     raw: String,
     columns: Vec<String>,
+    /// Numeric value of each column, converted once on receipt
+    values: Vec<Option<f64>>,
     matched: bool,
+    /// Traffic direction in sniffer mode
+    dir: Option<Direction>,
+    /// The UART reported framing/parity errors while receiving this line
+    line_error: bool,
+    /// Frames missing before this row according to the sequence column
+    dropped: u64,
+    /// Result of the checksum column; None if the row has no checksum
+    checksum: Option<bool>,
+    /// The row breaks one of the alert rules
+    alert: bool,
+    /// Severity marker found in the line
+    level: Option<LogLevel>,
+    /// Bytes received for the row, line ending included; empty for rows that
+    /// didn't come from the serial port
+    bytes: Vec<u8>,
+    /// Sample count and value ranges of a row aggregated over a time window
+    spread: Option<Spread>,
+}
+
+/// Table row of an aggregated time window
+fn window_row(window: Window) -> DataRow {
+    DataRow {
+        kind: RowKind::Data,
+        line_no: 0,
+        time: window.time,
+        raw: format!("{} samples", window.spread.count),
+        columns: window.columns,
+        values: window.values,
+        matched: true,
+        dir: None,
+        line_error: false,
+        dropped: window.dropped,
+        checksum: None,
+        alert: false,
+        level: None,
+        bytes: Vec::new(),
+        spread: Some(window.spread),
+    }
 }
 
 pub struct UartConsoleApp {
     settings: Settings,
+    /// Committed settings while display settings are being previewed
+    preview_backup: Option<Settings>,
     settings_win: SettingsWindow,
+    dashboard: DashboardPanel,
+    baud_scanner: BaudScanner,
+    /// Suggests the baud scanner when received data looks garbled
+    mismatch_hint: MismatchHint,
+    /// Modem line levels and their transition history
+    control_lines: ControlLinesWindow,
+    /// Modbus RTU master requests and polling
+    modbus: ModbusWindow,
+    /// Frame counters of the binary decoders
+    decoder_stats: DecoderStatsWindow,
+    /// Value distribution of a column
+    histogram: HistogramWindow,
     serial: SerialPortManager,
+    /// Ports offered by the toolbar picker
+    port_watcher: PortWatcher,
+    /// Identification responses pinned above the table
+    device_info: DeviceInfo,
+    /// Second device for the merged timeline view
+    session_b: SecondarySession,
+    /// Statistics of the running connection
+    session_stats: SessionRecorder,
+    /// Summary of the last connection, shown after disconnecting
+    session_summary: SessionSummaryWindow,
     rows: Vec<DataRow>,
-    raw_log: Vec<String>,
-    compiled_regex: Option<Regex>,
+    /// Collects rows into time windows, if configured; `rows` then holds
+    /// the windows and `samples` every row
+    aggregator: Option<Aggregator>,
+    samples: Vec<DataRow>,
+    /// Sizes of `rows`, `samples` and `raw_log` for the size retention policy
+    row_bytes: ByteTotal,
+    sample_bytes: ByteTotal,
+    raw_bytes: ByteTotal,
+    /// Sorted view of `rows` shown by the table
+    table_view: TableView,
+    /// Matched and unmatched data rows in the retained buffer
+    match_counts: (usize, usize),
+    /// Table shows only rows the regex didn't match
+    only_unmatched: bool,
+    /// Gap detection on the sequence counter column
+    seq_tracker: SequenceTracker,
+    /// Device clock offset for offset-corrected device time
+    device_offset: OffsetEstimate,
+    raw_log: Vec<TimedLine>,
+    /// Splits lines into columns; None shows them unsplit
+    parser: Option<LineParser>,
+    /// Type of each parsed column; missing entries are text
+    column_types: Vec<ColumnType>,
+    /// Extra columns computed from the parsed ones, if configured
+    derived: Option<DerivedColumns>,
+    /// Per-route tables of lines matching the route patterns
+    router: Router,
+    /// Table shown in the central panel
+    tab: DataTab,
+    /// Previous session's capture, shown read-only
+    restored: Option<SavedCapture>,
+    /// Format strings for defmt framing, from the firmware ELF
+    defmt: Option<DefmtTable>,
+    /// ELF the defmt table was last loaded from
+    defmt_elf: Option<String>,
+    /// Message type for protobuf framing, from the schema file
+    proto: Option<ProtoSchema>,
+    /// Schema file and message type the message was last loaded from
+    proto_source: Option<(String, String)>,
+    frame_pattern: Option<FramePattern>,
+    /// Splits records for TLV framing
+    tlv: Option<TlvDecoder>,
+    /// Symbols that replace addresses in received lines, from the ELF or map
+    symbols: Option<SymbolTable>,
+    /// File the symbols were last loaded from
+    symbol_file: Option<String>,
+    /// Joins multi-line records before parsing, if configured
+    records: Option<RecordAssembler>,
+    /// Direction of the lines in the pending record
+    record_dir: Option<Direction>,
+    /// Transforms lines before parsing, if configured
+    line_script: Option<LineScript>,
+    /// Most columns a line script has returned
+    script_columns: usize,
+    /// Validates the checksum of each row, if configured
+    line_checksum: Option<LineChecksum>,
+    trigger_regex: Option<Regex>,
+    alerts: AlertRules,
+    /// Rows that broke an alert rule since the counters were reset
+    alert_hits: u64,
+    /// Time and rule of the latest alert, for the status bar
+    last_alert: Option<String>,
+    /// Table shows only rows that broke an alert rule
+    only_alerts: bool,
+    /// Finds the log level of each line, if level detection is on
+    levels: Option<LevelDetector>,
+    /// Rows below this level are hidden
+    min_level: LogLevel,
+    /// Hides rows and raw log lines that don't match the filter bar
+    filter: LineFilter,
+    search: Search,
+    palette: CommandPalette,
+    /// Raw log line to scroll to, after jumping to a search match
+    raw_scroll_to: Option<usize>,
+    /// Raw log line clicked last
+    raw_selected: Option<usize>,
+    /// Pattern the send bar input should match
+    tx_regex: Option<Regex>,
+    /// Send was refused because the input doesn't match `tx_regex`
+    tx_blocked: bool,
+    /// Lines left to capture after the trigger fired before stopping
+    post_trigger_left: Option<usize>,
+    /// Capture stopped after the trigger; received data is dropped until the
+    /// next connect
+    capture_stopped: bool,
+    /// A watched event happened; the window alert is shown next frame
+    alert_pending: bool,
+    retry: Option<ConnectRetry>,
     send_input: String,
+    /// The next received line follows a UART line error
+    pending_line_error: bool,
+    /// Bytes received since the last row, for the row inspector
+    pending_bytes: Vec<u8>,
+    /// XOFF was sent manually and the device should be paused
+    xoff_sent: bool,
     auto_scroll: bool,
-    show_raw: bool,
+    /// Rows and raw log lines there were when the view was paused; later ones
+    /// keep arriving but aren't shown until it's resumed
+    paused: Option<(usize, usize)>,
+    /// Show received bytes as a hex dump
+    show_hex: bool,
+    hex_dump: HexDump,
+    /// Show received data in the terminal emulator, keys go to the port
+    show_terminal: bool,
+    terminal: Terminal,
+    show_dashboard: bool,
+    show_plots: bool,
+    show_timeline: bool,
     status_msg: String,
     status_is_error: bool,
-    /// Number of capture groups (columns) from the current regex
-    num_columns: usize,
+    /// Rows selected by keyboard or mouse
+    selection: Selection,
+    /// Time of the row the Δref column counts from
+    time_reference: Option<Timestamp>,
+    /// Line numbers of the bookmarked rows
+    bookmarks: BTreeSet<u64>,
+    /// Rows numbered so far
+    line_count: u64,
+    /// The status bar rate is in lines rather than bytes per second
+    rate_in_lines: bool,
+    /// Set when the selection moved and the table should scroll to it
+    scroll_to_selected: bool,
+    /// Row shown in the inspector window
+    inspected_row: Option<DataRow>,
 }
 
 impl UartConsoleApp {
     pub fn new(_cc: &eframe::CreationContext<'_>) -> Self {
         let settings = Settings::load();
         let settings_win = SettingsWindow::new(&settings);
+        let session_b = SecondarySession::new(&settings);
         let mut app = Self {
             settings_win,
+            session_b,
+            session_stats: SessionRecorder::default(),
+            session_summary: SessionSummaryWindow::new(),
+            device_info: DeviceInfo::new(),
+            dashboard: DashboardPanel::new(),
+            baud_scanner: BaudScanner::new(),
+            mismatch_hint: MismatchHint::default(),
+            control_lines: ControlLinesWindow::new(),
+            modbus: ModbusWindow::new(),
+            decoder_stats: DecoderStatsWindow::new(),
+            histogram: HistogramWindow::new(),
             serial: SerialPortManager::new(),
+            port_watcher: PortWatcher::new(),
             rows: Vec::new(),
+            aggregator: None,
+            samples: Vec::new(),
+            row_bytes: ByteTotal::default(),
+            sample_bytes: ByteTotal::default(),
+            raw_bytes: ByteTotal::default(),
+            table_view: TableView::new(),
+            match_counts: (0, 0),
+            only_unmatched: false,
+            seq_tracker: SequenceTracker::default(),
+            device_offset: OffsetEstimate::default(),
             raw_log: Vec::new(),
-            compiled_regex: None,
+            parser: None,
+            line_checksum: None,
+            column_types: Vec::new(),
+            derived: None,
+            router: Router::default(),
+            tab: DataTab::All,
+            restored: None,
+            defmt: None,
+            defmt_elf: None,
+            proto: None,
+            proto_source: None,
+            frame_pattern: None,
+            tlv: None,
+            symbols: None,
+            symbol_file: None,
+            records: None,
+            record_dir: None,
+            line_script: None,
+            script_columns: 0,
+            trigger_regex: None,
+            alerts: AlertRules::default(),
+            alert_hits: 0,
+            last_alert: None,
+            only_alerts: false,
+            levels: None,
+            min_level: LogLevel::Trace,
+            filter: LineFilter::default(),
+            search: Search::default(),
+            palette: CommandPalette::default(),
+            raw_scroll_to: None,
+            raw_selected: None,
+            tx_regex: None,
+            tx_blocked: false,
+            post_trigger_left: None,
+            capture_stopped: false,
+            alert_pending: false,
+            retry: None,
             send_input: String::new(),
+            xoff_sent: false,
+            pending_line_error: false,
+            pending_bytes: Vec::new(),
             auto_scroll: true,
-            show_raw: false,
+            paused: None,
+            show_hex: false,
+            show_terminal: false,
+            terminal: Terminal::default(),
+            hex_dump: HexDump::default(),
+            show_dashboard: false,
+            show_plots: false,
+            show_timeline: false,
             status_msg: "Disconnected".to_string(),
             status_is_error: false,
-            num_columns: 0,
+            selection: Selection::default(),
+            time_reference: None,
+            bookmarks: BTreeSet::new(),
+            line_count: 0,
+            rate_in_lines: false,
+            scroll_to_selected: false,
+            inspected_row: None,
             settings: Settings::default(),
+            preview_backup: None,
         };
         app.apply_settings(settings);
+        if app.settings.keep_captures > 0 && app.settings.restore_capture {
+            match load_latest_capture() {
+                Ok(Some(capture)) => {
+                    app.restored = Some(capture);
+                    app.tab = DataTab::Restored;
+                }
+                Ok(None) => {}
+                Err(e) => app.set_error(format!("Cannot load saved capture: {}", e)),
+            }
+        }
         app
     }
 
     fn apply_settings(&mut self, settings: Settings) {
         self.settings = settings;
+        self.table_view.invalidate();
+        self.line_checksum = LineChecksum::from_settings(&self.settings);
+        self.compile_defmt(false);
+        self.compile_proto(false);
+        self.tlv = TlvDecoder::from_settings(&self.settings);
+        self.compile_frame_pattern();
+        self.compile_symbols(false);
+        self.compile_records();
+        self.compile_line_script();
+        self.compile_column_types();
+        self.compile_derived();
+        self.compile_alerts();
+        self.compile_levels();
+        self.compile_aggregation();
         self.compile_regex();
+        self.compile_trigger();
+        self.compile_tx_pattern();
+        self.compile_routes();
     }
 
-    fn compile_regex(&mut self) {
-        let pattern = &self.settings.regex_pattern;
-        if pattern.is_empty() {
-            self.compiled_regex = None;
-            self.num_columns = 0;
-        } else {
-            match Regex::new(pattern) {
-                Ok(re) => {
-                    self.num_columns = re.captures_len().saturating_sub(1);
-                    self.compiled_regex = Some(re);
-                }
-                Err(e) => {
-                    self.set_error(format!("Regex error: {}", e));
-                    self.compiled_regex = None;
-                    self.num_columns = 0;
-                }
-            }
+    /// Applies display-only settings without committing them
+    fn preview_display(&mut self, preview: &Settings) {
+        if preview.rx_line_ending != self.settings.rx_line_ending {
+            self.serial.set_rx_line_ending(preview.rx_line_ending.clone());
         }
-        // re-parse existing raw lines
-        self.reparse_all();
+        if preview.text_encoding != self.settings.text_encoding {
+            self.serial.set_encoding(preview.text_encoding.encoding());
+        }
+        self.settings.copy_display(preview);
+        self.table_view.invalidate();
     }
 
-    fn reparse_all(&mut self) {
-        let raws: Vec<String> = self.rows.iter().map(|r| r.raw.clone()).collect();
-        self.rows = raws.iter().map(|raw| self.parse_line(raw)).collect();
+    fn compile_trigger(&mut self) {
+        self.trigger_regex = None;
+        if self.settings.trigger_pattern.is_empty() {
+            return;
+        }
+        match regex_guard::compile(&self.settings.trigger_pattern) {
+            Ok(re) => self.trigger_regex = Some(re),
+            Err(e) => self.set_error(format!("Trigger regex error: {}", e)),
+        }
     }
 
-    fn parse_line(&self, line: &str) -> DataRow {
-        let timestamp = if self.settings.show_timestamp {
-            Local::now().format("%H:%M:%S%.3f").to_string()
-        } else {
-            String::new()
+    fn compile_alerts(&mut self) {
+        self.alerts = match AlertRules::from_settings(&self.settings) {
+            Ok(alerts) => alerts,
+            Err(e) => {
+                self.set_error(e);
+                AlertRules::default()
+            }
         };
+    }
 
-        let (columns, matched) = if let Some(re) = &self.compiled_regex {
-            if let Some(caps) = re.captures(line) {
-                let cols: Vec<String> = (1..caps.len())
-                    .map(|i| caps.get(i).map_or("", |m| m.as_str()).to_string())
-                    .collect();
-                (cols, true)
-            } else {
-                (vec!["<no match>".to_string()], false)
+    fn compile_levels(&mut self) {
+        self.levels = match LevelDetector::from_settings(&self.settings) {
+            Ok(levels) => levels,
+            Err(e) => {
+                self.set_error(e);
+                None
             }
-        } else {
-            (vec![line.to_string()], true)
         };
+    }
 
-        DataRow {
-            timestamp,
-            raw: line.to_string(),
-            columns,
-            matched,
-        }
+    fn compile_column_types(&mut self) {
+        self.column_types = match ColumnType::parse_list(&self.settings.column_types) {
+            Ok(types) => types,
+            Err(e) => {
+                self.set_error(e);
+                Vec::new()
+            }
+        };
     }
 
-    fn ingest_line(&mut self, line: String) {
-        self.raw_log.push(line.clone());
-        let row = self.parse_line(&line);
-        self.rows.push(row);
+    fn compile_derived(&mut self) {
+        self.derived = match DerivedColumns::from_settings(&self.settings) {
+            Ok(derived) => derived,
+            Err(e) => {
+                self.set_error(e);
+                None
+            }
+        };
+    }
 
-        let max = self.settings.max_rows;
-        if self.rows.len() > max {
-            let drain = self.rows.len() - max;
-            self.rows.drain(..drain);
+    /// Loads the defmt table when defmt framing is selected and the ELF is
+    /// not the one already loaded; `reload` reads it again anyway
+    fn compile_defmt(&mut self, reload: bool) {
+        if self.settings.rx_framing != RxFraming::Defmt
+            || (!reload && self.defmt_elf.as_ref() == Some(&self.settings.defmt_elf))
+        {
+            return;
+        }
+        self.defmt_elf = Some(self.settings.defmt_elf.clone());
+        self.defmt = None;
+        if self.settings.defmt_elf.is_empty() {
+            self.set_error("defmt framing needs the firmware ELF");
+            return;
         }
-        if self.raw_log.len() > max {
-            let drain = self.raw_log.len() - max;
-            self.raw_log.drain(..drain);
+        match DefmtTable::load(&self.settings.defmt_elf) {
+            Ok(table) => {
+                self.set_status(format!("Loaded {} defmt format strings", table.len()));
+                self.defmt = Some(table);
+            }
+            Err(e) => self.set_error(e),
         }
     }
 
-    fn set_status(&mut self, msg: impl Into<String>) {
-        self.status_msg = msg.into();
-        self.status_is_error = false;
+    /// Loads the message type when protobuf framing is selected and the
+    /// schema or type changed since the last load; `reload` reads it anyway
+    fn compile_proto(&mut self, reload: bool) {
+        let source = (self.settings.proto_schema.clone(), self.settings.proto_message.clone());
+        if self.settings.rx_framing != RxFraming::Protobuf
+            || (!reload && self.proto_source.as_ref() == Some(&source))
+        {
+            return;
+        }
+        self.proto_source = Some(source);
+        self.proto = None;
+        if self.settings.proto_schema.is_empty() {
+            self.set_error("Protobuf framing needs a .proto file or descriptor set");
+            return;
+        }
+        match ProtoSchema::load(&self.settings.proto_schema, &self.settings.proto_message) {
+            Ok(schema) => {
+                self.set_status(format!("Decoding frames as {}", schema.name()));
+                self.proto = Some(schema);
+            }
+            Err(e) => self.set_error(e),
+        }
     }
 
-    fn set_error(&mut self, msg: impl Into<String>) {
-        self.status_msg = msg.into();
-        self.status_is_error = true;
+    /// Reads the firmware files again, e.g. after a rebuild
+    fn reload_files(&mut self) {
+        self.compile_defmt(true);
+        self.compile_proto(true);
+        self.compile_symbols(true);
     }
 
-    fn connect(&mut self) {
-        match self.serial.connect(&self.settings) {
-            Ok(()) => self.set_status("Connecting..."),
+    fn compile_frame_pattern(&mut self) {
+        self.frame_pattern = match FramePattern::from_settings(&self.settings) {
+            Ok(pattern) => pattern,
+            Err(e) => {
+                self.set_error(e);
+                None
+            }
+        };
+    }
+
+    /// Loads the symbols for address rewriting if a file is set and it is not
+    /// the one already loaded; `reload` reads it again anyway
+    fn compile_symbols(&mut self, reload: bool) {
+        if !reload && self.symbol_file.as_ref() == Some(&self.settings.symbol_file) {
+            return;
+        }
+        self.symbol_file = Some(self.settings.symbol_file.clone());
+        self.symbols = None;
+        if self.settings.symbol_file.is_empty() {
+            return;
+        }
+        match SymbolTable::load(&self.settings.symbol_file) {
+            Ok(table) => {
+                let lines = if table.has_lines() { " with source lines" } else { "" };
+                self.set_status(format!("Loaded {} symbols{}", table.len(), lines));
+                self.symbols = Some(table);
+            }
             Err(e) => self.set_error(e),
         }
     }
 
-    fn disconnect(&mut self) {
-        self.serial.disconnect();
-        self.set_status("Disconnected");
+    /// Restarts record assembly; buffered rows keep their records
+    fn compile_records(&mut self) {
+        self.records = match RecordAssembler::from_settings(&self.settings) {
+            Ok(records) => records,
+            Err(e) => {
+                self.set_error(e);
+                None
+            }
+        };
     }
 
-    fn send_input(&mut self) {
-        if self.send_input.is_empty() {
+    fn compile_line_script(&mut self) {
+        self.script_columns = 0;
+        self.line_script = match LineScript::from_settings(&self.settings) {
+            Ok(script) => script,
+            Err(e) => {
+                self.set_error(e);
+                None
+            }
+        };
+    }
+
+    /// Rebuilds the route tables and fills them from the buffered lines
+    fn compile_routes(&mut self) {
+        self.router = match Router::from_settings(&self.settings) {
+            Ok(router) => router,
+            Err(e) => {
+                self.set_error(e);
+                Router::default()
+            }
+        };
+        let rows = match self.aggregator {
+            Some(_) => &self.samples,
+            None => &self.rows,
+        };
+        for row in rows.iter().filter(|r| r.kind == RowKind::Data) {
+            if row.dir != Some(Direction::Tx) {
+                self.router.route(&row.raw, row.time);
+            }
+        }
+        if matches!(self.tab, DataTab::Route(i) if i >= self.router.tables.len()) {
+            self.tab = DataTab::All;
+        }
+    }
+
+    fn compile_tx_pattern(&mut self) {
+        self.tx_regex = None;
+        if self.settings.tx_pattern.is_empty() {
             return;
         }
-        let mut data = self.send_input.as_bytes().to_vec();
-        data.extend_from_slice(self.settings.tx_line_ending.as_bytes());
-        self.serial.send(data);
-        self.send_input.clear();
+        match regex_guard::compile(&self.settings.tx_pattern) {
+            Ok(re) => self.tx_regex = Some(re),
+            Err(e) => self.set_error(format!("TX pattern error: {}", e)),
+        }
     }
 
-    fn clear_data(&mut self) {
-        self.rows.clear();
-        self.raw_log.clear();
+    /// True if the send bar input passes the TX validation pattern (empty
+    /// input is not checked)
+    fn send_input_valid(&self) -> bool {
+        self.send_input.is_empty()
+            || self
+                .tx_regex
+                .as_ref()
+                .is_none_or(|re| re.is_match(&self.send_input))
     }
 
-    fn column_header(&self, idx: usize) -> String {
-        let names = self.settings.column_names_list();
-        if idx < names.len() {
-            names[idx].clone()
-        } else {
-            // Try named capture groups
-            if let Some(re) = &self.compiled_regex {
-                let capture_names: Vec<Option<&str>> = re.capture_names().collect();
-                // capture_names[0] = None (whole match), [1..] = groups
-                if idx + 1 < capture_names.len() {
-                    if let Some(name) = capture_names[idx + 1] {
-                        return name.to_string();
-                    }
+    /// Moves the rows between `rows` and `samples` when aggregation is turned
+    /// on or off; the windows are rebuilt by the re-parse that follows
+    fn compile_aggregation(&mut self) {
+        let was_on = self.aggregator.is_some();
+        self.aggregator = Aggregator::from_settings(&self.settings);
+        match (was_on, self.aggregator.is_some()) {
+            (false, true) => self.samples = std::mem::take(&mut self.rows),
+            (true, false) => self.rows = std::mem::take(&mut self.samples),
+            _ => return,
+        }
+        self.row_bytes.reset();
+        self.sample_bytes.reset();
+        self.selection.clear();
+    }
+
+    /// Every received row; the table's rows are windows while aggregating
+    fn stream(&self) -> &[DataRow] {
+        match self.aggregator {
+            Some(_) => &self.samples,
+            None => &self.rows,
+        }
+    }
+
+    fn compile_regex(&mut self) {
+        self.parser = match self.settings.build_parser() {
+            Ok(parser) => parser,
+            Err(e) => {
+                self.set_error(e);
+                None
+            }
+        };
+        // re-parse existing raw lines
+        self.reparse_all();
+    }
+
+    fn reparse_all(&mut self) {
+        self.row_bytes.reset();
+        self.sample_bytes.reset();
+        let rows = match self.aggregator {
+            Some(_) => std::mem::take(&mut self.samples),
+            None => std::mem::take(&mut self.rows),
+        };
+        let width = self.parsed_columns();
+        self.seq_tracker.reset();
+        self.device_offset.reset();
+        let rows: Vec<DataRow> = rows
+            .into_iter()
+            .map(|row| match row.kind {
+                RowKind::Data | RowKind::Dropped => {
+                    let Some(parsed) = self.parse_line(&row.raw, row.time) else {
+                        return DataRow {
+                            kind: RowKind::Dropped,
+                            columns: Vec::new(),
+                            values: Vec::new(),
+                            matched: false,
+                            dropped: 0,
+                            checksum: None,
+                            alert: false,
+                            level: None,
+                            ..row
+                        };
+                    };
+                    let mut parsed = DataRow {
+                        line_no: row.line_no,
+                        dir: row.dir,
+                        line_error: row.line_error,
+                        bytes: row.bytes,
+                        ..parsed
+                    };
+                    parsed.dropped = self.check_sequence(&parsed);
+                    parsed
+                }
+                RowKind::Marker | RowKind::Sent => row,
+            })
+            .collect();
+        match &mut self.aggregator {
+            Some(aggregator) => {
+                aggregator.reset();
+                self.rows.clear();
+                for row in rows {
+                    self.push_row(row);
                 }
             }
-            format!("Col {}", idx + 1)
+            None => self.rows = rows,
+        }
+        self.table_view.invalidate();
+        // Rows parsed before a new key or field turned up have their derived
+        // columns too far left
+        if self.derived.is_some() && self.parsed_columns() != width {
+            self.reparse_all();
         }
     }
 
-    fn export_csv(&self) {
-        use std::io::Write;
-        let path = format!(
-            "uart_export_{}.csv",
-            Local::now().format("%Y%m%d_%H%M%S")
+    /// Frames missing before `row`, if a sequence column is configured
+    fn check_sequence(&mut self, row: &DataRow) -> u64 {
+        match self.settings.seq_column {
+            Some(col) if row.matched => row
+                .columns
+                .get(col)
+                .map_or(0, |value| self.seq_tracker.check(value)),
+            _ => 0,
+        }
+    }
+
+    /// Updates the table view if rows, sort order or display settings changed
+    fn update_view(&mut self) {
+        // A previewed retention smaller than the buffer hides the oldest rows
+        let start = self.rows_excess();
+        let end = self.rows_shown();
+        if self.table_view.is_current(start, end) {
+            return;
+        }
+        let rows = &self.rows;
+        let data = rows[start..end].iter().filter(|r| r.kind == RowKind::Data);
+        let matched = data.clone().filter(|r| r.matched).count();
+        self.match_counts = (matched, data.count() - matched);
+        let only_unmatched = self.only_unmatched;
+        // Showing only the unmatched rows wins over hiding them
+        let hide_unmatched = self.settings.hide_unmatched && !only_unmatched;
+        let only_alerts = self.only_alerts;
+        let min_level = self.min_level;
+        let filter = &self.filter;
+        let key = self.table_view.sort.map_or(SortKey::Time, |(k, _)| k);
+        self.table_view.update(
+            start,
+            end,
+            |i| {
+                let unmatched = rows[i].kind == RowKind::Data && !rows[i].matched;
+                rows[i].kind != RowKind::Dropped
+                    && (!only_unmatched || unmatched)
+                    && (!hide_unmatched || !unmatched)
+                    && (!only_alerts || rows[i].alert)
+                    && rows[i].level.is_none_or(|l| l >= min_level)
+                    && filter.matches(&rows[i].raw)
+            },
+            |a, b| compare_rows(&rows[a], &rows[b], key),
         );
-        if let Ok(mut file) = std::fs::File::create(&path) {
-            // header
-            let mut header = if self.settings.show_timestamp {
-                vec!["Timestamp".to_string()]
-            } else {
-                vec![]
-            };
-            if self.compiled_regex.is_some() {
-                for i in 0..self.num_columns {
-                    header.push(self.column_header(i));
+        self.search.invalidate();
+    }
+
+    /// Parses a received line into a row; None if the line script dropped it
+    fn parse_line(&mut self, line: &str, mut time: Timestamp) -> Option<DataRow> {
+        let output = match &self.line_script {
+            Some(script) => script.run(line),
+            None => Ok(ScriptOutput::Keep),
+        };
+        let text = match &output {
+            Ok(ScriptOutput::Text(text)) => text.as_str(),
+            _ => line,
+        };
+        // Time the regex took on a slow line, and the line's length
+        let mut slow_match = None;
+        let (columns, matched) = match output {
+            Ok(ScriptOutput::Drop) => return None,
+            Ok(ScriptOutput::Columns(columns)) => {
+                self.script_columns = self.script_columns.max(columns.len());
+                (columns, true)
+            }
+            Err(e) => {
+                self.set_error(format!("Line script: {}", e));
+                (vec!["<script error>".to_string()], false)
+            }
+            Ok(_) => {
+                if let Some(parser) = &mut self.parser {
+                    let started = Instant::now();
+                    let parsed = if text.len() > regex_guard::MAX_MATCH_LEN {
+                        Err("<line too long>")
+                    } else {
+                        parser.parse(text).ok_or("<no match>")
+                    };
+                    let took = started.elapsed();
+                    if matches!(parser, LineParser::Regex(_)) && took > regex_guard::MATCH_BUDGET {
+                        slow_match = Some((took, text.len()));
+                    }
+                    match parsed {
+                        Ok(cols) => (cols, true),
+                        Err(e) => (vec![e.to_string()], false),
+                    }
+                } else {
+                    (vec![text.to_string()], true)
                 }
-            } else {
-                header.push("Data".to_string());
             }
-            let _ = writeln!(file, "{}", header.join(","));
+        };
 
-            for row in &self.rows {
-                let mut cells: Vec<String> = if self.settings.show_timestamp {
-                    vec![row.timestamp.clone()]
-                } else {
-                    vec![]
-                };
-                cells.extend(row.columns.iter().cloned());
-                let _ = writeln!(file, "{}", cells.join(","));
+        if let Some((took, len)) = slow_match {
+            self.set_error(format!(
+                "Regex took {} ms on a {}-byte line; consider simplifying the pattern",
+                took.as_millis(),
+                len
+            ));
+        }
+
+        let mut columns = columns;
+        let values = if matched {
+            let width = self.parsed_columns();
+            if let Some(derived) = self.derived.as_ref().filter(|_| width > 0) {
+                columns.resize(width, String::new());
+                let headers: Vec<String> = (0..width).map(|i| self.column_header(i)).collect();
+                let values = convert_row(&self.column_types, &columns);
+                derived.evaluate(&headers, &values, &mut columns);
             }
+            let mut values = convert_row(&self.column_types, &columns);
+            units::scale_values(&self.settings.column_scales, &mut values);
+            values
+        } else {
+            Vec::new()
+        };
+        if matched {
+            let format = self.settings.device_time_format;
+            time.device = self
+                .settings
+                .device_time_column
+                .and_then(|c| format.parse(columns.get(c)?));
+            self.device_offset.add(&time);
         }
+        let alert = !self.alerts.check(line, &values).is_empty();
+        let level = self.levels.as_ref().and_then(|d| d.detect(line));
+        Some(DataRow {
+            kind: RowKind::Data,
+            line_no: 0,
+            time,
+            raw: line.to_string(),
+            columns,
+            values,
+            matched,
+            dir: None,
+            line_error: false,
+            dropped: 0,
+            checksum: self.line_checksum.as_ref().and_then(|c| c.check(line)),
+            alert,
+            level,
+            bytes: Vec::new(),
+            spread: None,
+        })
     }
 
-    fn poll_serial_events(&mut self) {
-        let events = self.serial.poll_events();
-        for ev in events {
-            match ev {
-                SerialEvent::Connected => {
-                    self.serial.is_connected = true;
-                    self.set_status(format!(
-                        "Connected to {} @ {} baud",
-                        self.settings.port_name, self.settings.baud_rate
-                    ));
-                }
-                SerialEvent::Disconnected => {
-                    self.serial.is_connected = false;
-                    self.set_status("Disconnected");
-                }
-                SerialEvent::Data(line) => {
-                    self.ingest_line(line);
-                }
-                SerialEvent::Error(e) => {
-                    self.serial.is_connected = false;
-                    self.set_error(e);
+    fn ingest_line(&mut self, mut line: String, dir: Option<Direction>) {
+        let mut frames = Vec::new();
+        if let Some(symbols) = self.symbols.as_ref().filter(|_| dir != Some(Direction::Tx)) {
+            // A backtrace keeps its addresses and gets the frames after it
+            match symbols
+                .expand_backtrace(&line)
+                .filter(|_| self.settings.expand_backtraces)
+            {
+                Some(expanded) => frames = expanded,
+                None => {
+                    if let Cow::Owned(rewritten) = symbols.rewrite(&line) {
+                        line = rewritten;
+                    }
                 }
             }
         }
+        self.ingest_text(line, dir);
+        for frame in frames {
+            self.ingest_text(frame, dir);
+        }
     }
 
-    // --- UI rendering ---
+    fn ingest_text(&mut self, raw: String, dir: Option<Direction>) {
+        let now = Timestamp::now();
+        // Only the raw log keeps the escape sequences, to render their colors
+        let line = ansi::strip(&raw).into_owned();
+        if self.trigger_regex.as_ref().is_some_and(|re| re.is_match(&line)) {
+            self.fire_trigger(&line, now);
+        }
 
-    fn render_toolbar(&mut self, ui: &mut egui::Ui) {
-        ui.horizontal(|ui| {
-            let connected = self.serial.is_connected;
+        if dir != Some(Direction::Tx) {
+            self.device_info.capture(&line);
+            self.router.route(&line, now);
+        }
 
-            // Connect / Disconnect
-            if connected {
-                if ui
-                    .add_sized(
-                        [110.0, 28.0],
-                        egui::Button::new(
-                            RichText::new("Disconnect").color(egui::Color32::from_rgb(255, 80, 80)),
-                        ),
-                    )
-                    .clicked()
-                {
-                    self.disconnect();
+        let text = match dir {
+            Some(dir) => format!("{} {}", dir.label(), raw),
+            None => raw,
+        };
+        self.raw_log.push(TimedLine {
+            time: now,
+            text,
+            dir,
+        });
+
+        match &mut self.records {
+            Some(records) if dir != Some(Direction::Tx) => {
+                self.record_dir = dir;
+                for record in records.push(line, now) {
+                    self.ingest_record(record.text, record.time, dir);
                 }
+            }
+            _ => self.ingest_record(line, now, dir),
+        }
+    }
+
+    /// Passes on a record that was still being assembled, e.g. the last
+    /// stack trace before the device went quiet
+    fn flush_record(&mut self, idle_only: bool) {
+        let Some(records) = &mut self.records else {
+            return;
+        };
+        let record = if idle_only {
+            records.flush_idle()
+        } else {
+            records.flush()
+        };
+        if let Some(record) = record {
+            self.ingest_record(record.text, record.time, self.record_dir);
+        }
+    }
+
+    /// Parses a line, or a multi-line record, into a row
+    fn ingest_record(&mut self, line: String, now: Timestamp, dir: Option<Direction>) {
+        let width = self.parsed_columns();
+        let line_error = std::mem::take(&mut self.pending_line_error);
+        let bytes = std::mem::take(&mut self.pending_bytes);
+        let Some(parsed) = self.parse_line(&line, now) else {
+            return;
+        };
+        let mut row = DataRow {
+            line_no: self.next_line_no(),
+            dir,
+            line_error,
+            bytes,
+            ..parsed
+        };
+        row.dropped = self.check_sequence(&row);
+        self.record_row_stats(&row);
+        if row.alert {
+            self.fire_alerts(&row);
+        }
+        self.push_row(row);
+        // Derived columns follow the parsed ones; move them when this row
+        // widened the parsed part
+        if self.derived.is_some() && self.parsed_columns() != width {
+            self.reparse_all();
+        }
+
+        if let Some(left) = self.post_trigger_left {
+            if left == 0 {
+                // The port is closed once the current batch is done
+                self.post_trigger_left = None;
+                self.capture_stopped = true;
             } else {
-                if ui
-                    .add_sized(
-                        [110.0, 28.0],
-                        egui::Button::new(
-                            RichText::new("Connect").color(egui::Color32::from_rgb(80, 200, 80)),
-                        ),
-                    )
-                    .clicked()
-                {
-                    self.connect();
-                }
+                self.post_trigger_left = Some(left - 1);
             }
+        }
+    }
 
-            ui.separator();
+    /// Numbers rows in arrival order; the numbers carry on when old rows are
+    /// dropped by the retention limit
+    fn next_line_no(&mut self) -> u64 {
+        self.line_count += 1;
+        self.line_count
+    }
 
-            // Settings
-            if ui
-                .add_sized([90.0, 28.0], egui::Button::new("Settings"))
-                .clicked()
-            {
+    /// Adds a row to the table; while aggregating, rows that parsed cleanly
+    /// go into the current window instead and everything else is shown as it
+    /// is, so markers and bad lines stay visible
+    fn push_row(&mut self, row: DataRow) {
+        if row.kind == RowKind::Dropped && self.aggregator.is_some() {
+            self.samples.push(row);
+            return;
+        }
+        let Some(aggregator) = &mut self.aggregator else {
+            self.rows.push(row);
+            return;
+        };
+        let clean = row.kind == RowKind::Data
+            && row.dir != Some(Direction::Tx)
+            && row.matched
+            && !row.alert
+            && !row.line_error
+            && row.checksum != Some(false);
+        if clean {
+            let window = aggregator.push(row.time, &row.columns, &row.values, row.dropped);
+            if let Some(window) = window {
+                self.rows.push(window_row(window));
+            }
+        } else {
+            self.rows.push(row.clone());
+        }
+        self.samples.push(row);
+    }
+
+    /// Adds the pending window to the table once its time is over
+    fn flush_window(&mut self) {
+        let window = self
+            .aggregator
+            .as_mut()
+            .and_then(|a| a.flush_due(chrono::Local::now()));
+        if let Some(window) = window {
+            self.rows.push(window_row(window));
+        }
+    }
+
+    /// Counts a row that broke alert rules and sends the notifications due
+    fn fire_alerts(&mut self, row: &DataRow) {
+        self.alert_hits += 1;
+        let mut described = Vec::new();
+        for i in self.alerts.check(&row.raw, &row.values) {
+            let rule = self.alerts.rule(i);
+            let text = rule.describe(&self.column_title(rule.column));
+            if self.alerts.notify_due(i) {
+                alerts::notify(format!("{}\n{}", text, row.raw));
+            }
+            described.push(text);
+        }
+        let time = format_time(&row.time, &self.time_display());
+        self.last_alert = Some(format!("{}: {}", time, described.join(", ")));
+    }
+
+    /// Counts a received row in the session statistics
+    fn record_row_stats(&mut self, row: &DataRow) {
+        let parsed = self.parser.is_some();
+        let Some(stats) = self.session_stats.current() else {
+            return;
+        };
+        if parsed {
+            if row.matched {
+                stats.matched += 1;
+            } else {
+                stats.unmatched += 1;
+            }
+        }
+        stats.dropped += row.dropped;
+        if row.matched {
+            let alarms = self.settings.dashboard.iter().map(|w| {
+                row.values
+                    .get(w.column)
+                    .copied()
+                    .flatten()
+                    .is_some_and(|v| w.in_alarm(v))
+            });
+            self.session_stats.record_alarms(alarms);
+        }
+    }
+
+    /// Byte and line counters for the session statistics
+    fn counter_snapshot(&self) -> (u64, u64, u64) {
+        (
+            self.serial.rx_bytes(),
+            self.serial.tx_bytes(),
+            self.serial.lines,
+        )
+    }
+
+    /// Ends the running session, showing and/or archiving its summary
+    fn finish_session(&mut self) {
+        let Some(summary) = self.session_stats.finish(self.counter_snapshot()) else {
+            return;
+        };
+        if self.settings.keep_captures > 0 {
+            let start = self.raw_log.partition_point(|l| l.time.wall < summary.started);
+            let lines = &self.raw_log[start..];
+            let info = &self.device_info.entries;
+            if let Err(e) = save_capture(lines, info, self.settings.keep_captures) {
+                self.set_error(format!("Saving the capture failed: {}", e));
+            }
+        }
+        if self.settings.session_summary {
+            self.session_summary.open(summary, self.settings.archive_sessions);
+        } else if self.settings.archive_sessions {
+            if let Err(e) = summary.append_to_archive() {
+                self.set_error(format!("Session archive failed: {}", e));
+            }
+        }
+    }
+
+    fn fire_trigger(&mut self, line: &str, time: Timestamp) {
+        if let Some(stats) = self.session_stats.current() {
+            stats.triggers += 1;
+        }
+        self.alert_pending |= self.settings.alert_on_trigger;
+        if self.settings.trigger_pause_scroll {
+            self.auto_scroll = false;
+        }
+        if self.settings.trigger_insert_marker {
+            let text = format!("--- trigger: {} ---", line);
+            self.raw_log.push(TimedLine {
+                time,
+                text: text.clone(),
+                dir: None,
+            });
+            let line_no = self.next_line_no();
+            self.push_row(DataRow {
+                kind: RowKind::Marker,
+                line_no,
+                time,
+                raw: text.clone(),
+                columns: vec![text],
+                values: Vec::new(),
+                matched: true,
+                dir: None,
+                line_error: false,
+                dropped: 0,
+                checksum: None,
+                alert: false,
+                level: None,
+                bytes: Vec::new(),
+                spread: None,
+            });
+        }
+        if self.settings.trigger_stop_capture && self.post_trigger_left.is_none() {
+            self.post_trigger_left = Some(self.settings.trigger_post_lines);
+        }
+        let time = format_time(&time, &self.time_display());
+        self.set_status(format!("Trigger fired at {}", time));
+    }
+
+    /// Drops data outside the committed retention policy
+    fn trim_buffers(&mut self) {
+        // Previewed settings only hide rows; nothing is dropped until applied
+        let committed = self.preview_backup.as_ref().unwrap_or(&self.settings);
+        // The paused view keeps the rows it shows; retention applies to
+        // those received since
+        let (row_base, line_base) = self.paused.map_or((0, 0), |(rows, lines)| {
+            (rows.min(self.rows.len()), lines.min(self.raw_log.len()))
+        });
+        let time = |r: &DataRow| r.time.wall;
+        let tail = &self.rows[row_base..];
+        let drain = committed.retention_excess(tail, &self.row_bytes, time, row_size);
+        if drain > 0 {
+            self.row_bytes.remove(&tail[..drain], row_size);
+            self.rows.drain(row_base..row_base + drain);
+            if row_base == 0 {
+                self.table_view.drain(drain);
+                // keep the selection on the same row
+                self.selection.drain(drain);
+            }
+        }
+        let drain = committed.retention_excess(&self.samples, &self.sample_bytes, time, row_size);
+        self.sample_bytes.remove(&self.samples[..drain], row_size);
+        self.samples.drain(..drain);
+        let time = |l: &TimedLine| l.time.wall;
+        let tail = &self.raw_log[line_base..];
+        let drain = committed.retention_excess(tail, &self.raw_bytes, time, line_size);
+        self.raw_bytes.remove(&tail[..drain], line_size);
+        self.raw_log.drain(line_base..line_base + drain);
+        if line_base == 0 {
+            self.raw_selected = self.raw_selected.and_then(|i| i.checked_sub(drain));
+        }
+        // Forget bookmarks of rows no longer kept
+        if let Some(first) = self.stream().first().map(|r| r.line_no) {
+            self.bookmarks = self.bookmarks.split_off(&first);
+        }
+        self.router.trim(committed);
+    }
+
+    /// Rows up to the pause point, or all of them
+    fn rows_shown(&self) -> usize {
+        self.paused.map_or(self.rows.len(), |(rows, _)| rows.min(self.rows.len()))
+    }
+
+    /// Raw log lines up to the pause point, or all of them
+    fn raw_lines_shown(&self) -> usize {
+        self.paused.map_or(self.raw_log.len(), |(_, lines)| lines.min(self.raw_log.len()))
+    }
+
+    /// Freezes the table and raw log, or shows everything received since
+    fn toggle_pause(&mut self) {
+        self.paused = match self.paused {
+            Some(_) => None,
+            None => Some((self.rows.len(), self.raw_log.len())),
+        };
+        // Retention now covers a different part of the buffers
+        self.row_bytes.reset();
+        self.raw_bytes.reset();
+        self.table_view.invalidate();
+    }
+
+    /// Rows hidden by the current (possibly previewed) retention policy;
+    /// none while paused
+    fn rows_excess(&self) -> usize {
+        if self.paused.is_some() {
+            return 0;
+        }
+        self.settings.retention_excess(&self.rows, &self.row_bytes, |r| r.time.wall, row_size)
+    }
+
+    fn set_status(&mut self, msg: impl Into<String>) {
+        self.status_msg = msg.into();
+        self.status_is_error = false;
+    }
+
+    fn set_error(&mut self, msg: impl Into<String>) {
+        self.status_msg = msg.into();
+        self.status_is_error = true;
+    }
+
+    fn connect(&mut self) {
+        self.retry = None;
+        match self.serial.connect(&self.settings) {
+            Ok(()) => self.set_status("Connecting..."),
+            Err(e) => {
+                if self.settings.connect_retries > 0 {
+                    self.schedule_retry(1);
+                }
+                self.set_error(e);
+            }
+        }
+    }
+
+    fn retry_delay(&self, attempt: u32) -> Duration {
+        let base = Duration::from_millis(self.settings.retry_delay_ms);
+        let factor = 1u32 << (attempt - 1).min(16);
+        base.saturating_mul(factor).min(MAX_RETRY_DELAY)
+    }
+
+    fn schedule_retry(&mut self, attempt: u32) {
+        self.retry = Some(ConnectRetry {
+            attempt,
+            next_at: Instant::now() + self.retry_delay(attempt),
+        });
+    }
+
+    /// Runs a pending connect retry once its backoff delay has elapsed
+    fn poll_retry(&mut self, ctx: &egui::Context) {
+        let Some(retry) = &self.retry else {
+            return;
+        };
+        let now = Instant::now();
+        if now < retry.next_at {
+            // keep the countdown in the status bar ticking
+            ctx.request_repaint_after((retry.next_at - now).min(Duration::from_millis(100)));
+            return;
+        }
+        let attempt = retry.attempt;
+        match self.serial.connect(&self.settings) {
+            Ok(()) => {
+                self.retry = None;
+                self.set_status("Connecting...");
+            }
+            Err(e) if attempt < self.settings.connect_retries => {
+                self.schedule_retry(attempt + 1);
+                self.set_error(e);
+            }
+            Err(e) => {
+                self.retry = None;
+                self.set_error(format!("{} (gave up after {} retries)", e, attempt));
+            }
+        }
+    }
+
+    fn disconnect(&mut self) {
+        self.retry = None;
+        self.serial.disconnect();
+        self.set_status("Disconnected");
+    }
+
+    /// Rescans the ports while disconnected and reports plugged/unplugged
+    /// devices. With no port chosen yet, a single new port is selected.
+    fn poll_ports(&mut self, ctx: &egui::Context) {
+        if self.serial.is_connected || self.settings.connection == ConnectionKind::Simulator {
+            return;
+        }
+        ctx.request_repaint_after(RESCAN_INTERVAL);
+        let Some(changes) = self.port_watcher.poll() else {
+            return;
+        };
+        if changes.is_empty() {
+            return;
+        }
+        if let [port] = changes.added.as_slice() {
+            if self.settings.port_name.is_empty() {
+                self.settings.port_name = port.clone();
+                self.settings.save();
+            }
+        }
+        self.set_status(changes.describe());
+    }
+
+    /// Close and reopen the current port with the same settings
+    fn reconnect(&mut self) {
+        self.serial.disconnect();
+        self.connect();
+    }
+
+    /// Sends the send bar input. Input that fails the TX pattern is held
+    /// back until `force` confirms it.
+    fn send_input(&mut self, force: bool) {
+        if self.send_input.is_empty() {
+            return;
+        }
+        if !force && !self.send_input_valid() {
+            self.tx_blocked = true;
+            self.set_error("Input doesn't match the TX pattern; not sent");
+            return;
+        }
+        self.tx_blocked = false;
+        let text = std::mem::take(&mut self.send_input);
+        self.send_text(&text);
+    }
+
+    /// Sends a string followed by the TX line ending
+    fn send_text(&mut self, text: &str) {
+        let mut data = self.settings.text_encoding.encode(text);
+        data.extend_from_slice(self.settings.tx_line_ending.as_bytes());
+        let data = tx_map::apply(&self.settings.tx_char_map, &data);
+        self.echo_sent(text.to_string(), data.clone());
+        self.serial.send(data);
+    }
+
+    /// Adds sent data to the raw log and table as a TX row, if echo is on
+    fn echo_sent(&mut self, text: String, bytes: Vec<u8>) {
+        if !self.settings.echo_tx || !self.serial.is_connected {
+            return;
+        }
+        let time = Timestamp::now();
+        self.raw_log.push(TimedLine {
+            time,
+            text: format!("{} {}", Direction::Tx.label(), text),
+            dir: Some(Direction::Tx),
+        });
+        let line_no = self.next_line_no();
+        self.push_row(DataRow {
+            kind: RowKind::Sent,
+            line_no,
+            time,
+            raw: text.clone(),
+            columns: vec![text],
+            values: Vec::new(),
+            matched: true,
+            dir: Some(Direction::Tx),
+            line_error: false,
+            dropped: 0,
+            checksum: None,
+            alert: false,
+            level: None,
+            bytes,
+            spread: None,
+        });
+        self.table_view.invalidate();
+    }
+
+    /// Manually pauses (XOFF) or resumes (XON) the device
+    fn send_flow_control(&mut self, pause: bool) {
+        self.serial.send(vec![if pause { XOFF } else { XON }]);
+        self.xoff_sent = pause;
+        self.set_status(if pause { "Sent XOFF" } else { "Sent XON" });
+    }
+
+    /// Sends the identification commands and captures their responses
+    fn identify_device(&mut self) {
+        self.device_info.start(self.settings.id_commands_list());
+    }
+
+    fn select_profile(&mut self, name: &str) {
+        let mut new_settings = self.settings.clone();
+        new_settings.select_profile(name);
+        new_settings.save();
+        self.apply_settings(new_settings);
+        self.set_status(format!("Parser profile \"{}\"", name));
+    }
+
+    fn run_macro(&mut self, idx: usize) {
+        if let Some(m) = self.settings.macros.get(idx) {
+            let text = m.text.clone();
+            self.send_text(&text);
+        }
+    }
+
+    fn clear_data(&mut self) {
+        self.rows.clear();
+        self.samples.clear();
+        self.row_bytes.reset();
+        self.sample_bytes.reset();
+        self.raw_bytes.reset();
+        if let Some(aggregator) = &mut self.aggregator {
+            aggregator.reset();
+        }
+        self.seq_tracker.reset();
+        self.device_offset.reset();
+        self.raw_log.clear();
+        self.hex_dump.clear();
+        self.terminal.clear();
+        self.router.clear();
+        if let Some(records) = &mut self.records {
+            records.flush();
+        }
+        self.table_view.invalidate();
+        self.selection.clear();
+        self.raw_selected = None;
+        self.time_reference = None;
+        self.bookmarks.clear();
+        self.line_count = 0;
+        self.paused = None;
+    }
+
+    /// The row has a bookmark; aggregated windows never do
+    fn is_bookmarked(&self, row: &DataRow) -> bool {
+        row.line_no > 0 && self.bookmarks.contains(&row.line_no)
+    }
+
+    /// Bookmarks the selected rows, or removes their bookmarks if they all
+    /// have one; aggregated windows can't be bookmarked
+    fn toggle_bookmarks(&mut self, rows: &[usize]) {
+        let numbers: Vec<u64> = rows
+            .iter()
+            .filter_map(|&i| self.rows.get(i))
+            .map(|r| r.line_no)
+            .filter(|&n| n > 0)
+            .collect();
+        if numbers.iter().all(|n| self.bookmarks.contains(n)) {
+            for n in &numbers {
+                self.bookmarks.remove(n);
+            }
+        } else {
+            self.bookmarks.extend(numbers);
+        }
+    }
+
+    /// Selects the next or previous bookmarked row in display order,
+    /// wrapping around
+    fn jump_to_bookmark(&mut self, forward: bool) {
+        self.update_view();
+        let len = self.table_view.len();
+        let current = self.selection.cursor().and_then(|r| self.table_view.pos_of(r));
+        let start = match (current, forward) {
+            (Some(pos), true) => pos + 1,
+            (Some(pos), false) => pos + len - 1,
+            (None, true) => 0,
+            (None, false) => len.saturating_sub(1),
+        };
+        let found = (0..len)
+            .map(|step| {
+                if forward {
+                    (start + step) % len
+                } else {
+                    (start + len - step) % len
+                }
+            })
+            .filter_map(|pos| self.table_view.row(pos))
+            .find(|&row| self.is_bookmarked(&self.rows[row]));
+        match found {
+            Some(row) => {
+                self.selection.select(row);
+                self.scroll_to_selected = true;
+                self.auto_scroll = false;
+            }
+            None => self.set_status("No bookmarked rows in view"),
+        }
+    }
+
+    /// The raw log is shown instead of the table
+    fn raw_only(&self) -> bool {
+        self.settings.view_layout == ViewLayout::Raw
+    }
+
+    fn handle_table_keys(&mut self, ctx: &egui::Context) {
+        if self.raw_only()
+            || self.show_hex
+            || self.show_terminal
+            || self.show_timeline
+            || self.tab != DataTab::All
+            || self.rows.is_empty()
+            || ctx.wants_keyboard_input()
+        {
+            return;
+        }
+        self.update_view();
+        if self.table_view.is_empty() {
+            return;
+        }
+        // Navigation moves through display positions, not buffer indices
+        const PAGE: usize = 20;
+        let last = self.table_view.len() - 1;
+        let current = self.selection.cursor().and_then(|r| self.table_view.pos_of(r));
+
+        let new_sel = ctx.input(|i| {
+            if i.key_pressed(egui::Key::ArrowDown) {
+                Some(current.map_or(last, |c| (c + 1).min(last)))
+            } else if i.key_pressed(egui::Key::ArrowUp) {
+                Some(current.map_or(last, |c| c.saturating_sub(1)))
+            } else if i.key_pressed(egui::Key::PageDown) {
+                Some(current.map_or(last, |c| (c + PAGE).min(last)))
+            } else if i.key_pressed(egui::Key::PageUp) {
+                Some(current.map_or(last, |c| c.saturating_sub(PAGE)))
+            } else if i.key_pressed(egui::Key::Home) {
+                // First visible row; hidden rows and a previewed retention's
+                // dropped rows aren't in the view
+                Some(0)
+            } else if i.key_pressed(egui::Key::End) {
+                Some(last)
+            } else {
+                None
+            }
+        });
+
+        // Shift extends the selection from the anchor
+        if let Some(row) = new_sel.and_then(|pos| self.table_view.row(pos)) {
+            if ctx.input(|i| i.modifiers.shift) {
+                self.selection.extend(row, &self.table_view);
+            } else {
+                self.selection.select(row);
+            }
+            self.scroll_to_selected = true;
+            self.auto_scroll = false;
+        }
+
+        if ctx.input(|i| i.key_pressed(egui::Key::Enter)) {
+            if let Some(sel) = self.selection.cursor() {
+                self.inspected_row = self.rows.get(sel).cloned();
+            }
+        }
+        if ctx.input(|i| i.key_pressed(egui::Key::Escape)) {
+            self.selection.clear();
+        }
+        if ctx.input(|i| i.modifiers.command && i.key_pressed(egui::Key::A)) {
+            self.selection.select_all(&self.table_view);
+        }
+        if ctx.input(|i| i.modifiers.command && i.key_pressed(egui::Key::B)) {
+            self.toggle_bookmarks(&self.selection.rows());
+        }
+        if ctx.input(|i| i.key_pressed(egui::Key::F2)) {
+            self.jump_to_bookmark(!ctx.input(|i| i.modifiers.shift));
+        }
+        // Ctrl+C arrives as a copy event rather than a key press
+        let copy = ctx.input(|i| i.events.iter().any(|e| matches!(e, egui::Event::Copy)));
+        if copy && !self.selection.is_empty() {
+            self.copy_rows(ctx, &self.selection.rows(), false);
+        }
+    }
+
+    fn column_header(&self, idx: usize) -> String {
+        let parsed = self.parsed_columns();
+        if let Some(name) = idx
+            .checked_sub(parsed)
+            .and_then(|i| self.derived.as_ref()?.name(i))
+        {
+            return name.to_string();
+        }
+        let names = self.settings.column_names_list();
+        if idx < names.len() {
+            names[idx].clone()
+        } else {
+            // Named capture groups or JSON keys
+            match self.parser.as_ref().and_then(|p| p.column_name(idx)) {
+                Some(name) => name.to_string(),
+                None => format!("Col {}", idx + 1),
+            }
+        }
+    }
+
+    /// Header followed by the unit of a scaled column, e.g. "Vbat [V]"
+    fn column_title(&self, idx: usize) -> String {
+        let header = self.column_header(idx);
+        match units::scale_of(&self.settings.column_scales, idx) {
+            Some(scale) if !scale.unit.is_empty() => format!("{} [{}]", header, scale.unit),
+            _ => header,
+        }
+    }
+
+    /// Number of table columns (0 when lines are shown unsplit)
+    fn num_columns(&self) -> usize {
+        let parsed = self.parsed_columns();
+        match &self.derived {
+            Some(derived) if parsed > 0 => parsed + derived.len(),
+            _ => parsed,
+        }
+    }
+
+    /// Number of columns from the parser or line script
+    fn parsed_columns(&self) -> usize {
+        self.parser
+            .as_ref()
+            .map_or(0, LineParser::num_columns)
+            .max(self.script_columns)
+    }
+
+    /// Headers of the columns currently shown in the table
+    fn column_headers(&self) -> Vec<String> {
+        if self.num_columns() > 0 {
+            (0..self.num_columns())
+                .map(|i| self.column_title(i))
+                .collect()
+        } else {
+            vec!["Data".to_string()]
+        }
+    }
+
+    /// Most recent non-empty value of each column among matched rows
+    fn latest_values(&self, num_cols: usize) -> Vec<Option<String>> {
+        let mut latest = vec![None; num_cols];
+        let mut missing = num_cols;
+        for row in self
+            .rows
+            .iter()
+            .rev()
+            .filter(|r| r.kind == RowKind::Data && r.matched)
+        {
+            for (i, slot) in latest.iter_mut().enumerate() {
+                if slot.is_none() {
+                    if let Some(val) = row.columns.get(i).filter(|v| !v.is_empty()) {
+                        *slot = Some(self.cell_text(row, i).unwrap_or_else(|| val.clone()));
+                        missing -= 1;
+                    }
+                }
+            }
+            if missing == 0 {
+                break;
+            }
+        }
+        latest
+    }
+
+    /// A typed or scaled cell's value as a plain number; None for text
+    /// columns and cells that don't parse
+    fn cell_text(&self, row: &DataRow, col: usize) -> Option<String> {
+        if let Some(scale) = units::scale_of(&self.settings.column_scales, col) {
+            let value = row.values.get(col).copied().flatten()?;
+            return Some(scale.format(value));
+        }
+        let kind = self.column_types.get(col).filter(|t| t.is_numeric())?;
+        let value = row.values.get(col).copied().flatten()?;
+        Some(kind.format(value))
+    }
+
+    /// How timestamps are shown and exported
+    fn time_display(&self) -> TimeDisplay {
+        TimeDisplay {
+            source: self.settings.time_source,
+            device_format: self.settings.device_time_format,
+            device_offset: self.device_offset.get(),
+        }
+    }
+
+    /// The direction column is shown while sniffing two ports
+    fn show_direction(&self) -> bool {
+        self.settings.connection == ConnectionKind::Sniffer
+    }
+
+    /// Layout of the plain "Export CSV" button
+    fn default_export_template(&self) -> ExportTemplate {
+        ExportTemplate {
+            timestamp_format: if self.settings.show_timestamp {
+                "%H:%M:%S%.3f".to_string()
+            } else {
+                String::new()
+            },
+            ..ExportTemplate::default()
+        }
+    }
+
+    /// Writes the rows to a CSV file laid out by `template`; returns the path
+    fn export_csv(&self, template: &ExportTemplate) -> std::io::Result<String> {
+        use std::io::Write;
+        if !template.timestamp_format_valid() {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("invalid timestamp format \"{}\"", template.timestamp_format),
+            ));
+        }
+        let path = template.file_name(&self.settings.active_profile);
+        let mut file = std::io::BufWriter::new(std::fs::File::create(&path)?);
+        let delim = template.delimiter();
+        let headers = self.column_headers();
+        let columns = template.column_indices(headers.len());
+        let with_ts = !template.timestamp_format.is_empty();
+        let display = self.time_display();
+
+        // Device info responses as comment lines above the header
+        let info = &self.device_info;
+        if let Some(captured) = info.captured_at.filter(|_| !info.entries.is_empty()) {
+            writeln!(file, "# Device info captured {}", captured.to_rfc3339())?;
+            for entry in &info.entries {
+                for line in entry.response_text().lines() {
+                    writeln!(file, "# {}: {}", entry.command, line)?;
+                }
+            }
+        }
+
+        let mut header = Vec::new();
+        if with_ts {
+            header.push("Timestamp".to_string());
+        }
+        if self.show_direction() {
+            header.push("Dir".to_string());
+        }
+        for (n, &col) in columns.iter().enumerate() {
+            header.push(template.header(n).unwrap_or_else(|| headers[col].clone()));
+        }
+        let with_bookmarks = !self.bookmarks.is_empty();
+        if with_bookmarks {
+            header.push("Bookmark".to_string());
+        }
+        let header: Vec<String> = header.iter().map(|h| escape_field(h, delim)).collect();
+        writeln!(file, "{}", header.join(delim))?;
+
+        // While aggregating the table shows windows; the export has every row
+        for row in self.stream().iter().filter(|r| r.kind != RowKind::Dropped) {
+            let mut cells = Vec::new();
+            if with_ts {
+                cells.push(row.time.format(&display, &template.timestamp_format));
+            }
+            if self.show_direction() {
+                cells.push(row.dir.map_or("", |d| d.label()).to_string());
+            }
+            for &col in &columns {
+                let text = self.cell_text(row, col);
+                cells.push(text.or_else(|| row.columns.get(col).cloned()).unwrap_or_default());
+            }
+            if with_bookmarks {
+                let mark = if self.is_bookmarked(row) { "1" } else { "" };
+                cells.push(mark.to_string());
+            }
+            let cells: Vec<String> = cells.iter().map(|c| escape_field(c, delim)).collect();
+            writeln!(file, "{}", cells.join(delim))?;
+        }
+        file.flush()?;
+        Ok(path)
+    }
+
+    fn run_export(&mut self, template: &ExportTemplate) {
+        match self.export_csv(template) {
+            Ok(path) => self.set_status(format!("Exported to {}", path)),
+            Err(e) => self.set_error(format!("Export failed: {}", e)),
+        }
+    }
+
+    /// Rows as CSV laid out like the table: timestamp, direction while
+    /// sniffing, then the shown columns in display order, with a header line
+    fn rows_csv(&self, rows: &[usize]) -> String {
+        let num_cols = self.num_columns();
+        let columns = self.settings.column_layout.visible(num_cols);
+        let show_dir = self.show_direction();
+        let mut header = vec!["Timestamp".to_string()];
+        if show_dir {
+            header.push("Dir".to_string());
+        }
+        if num_cols > 0 {
+            header.extend(columns.iter().map(|&col| self.column_title(col)));
+        } else {
+            header.push("Data".to_string());
+        }
+        let mut lines = vec![header];
+        let display = self.time_display();
+        for row in rows.iter().filter_map(|&idx| self.rows.get(idx)) {
+            let mut cells = vec![format_time(&row.time, &display)];
+            if show_dir {
+                cells.push(row.dir.map_or("", |d| d.label()).to_string());
+            }
+            if num_cols > 0 {
+                for &col in &columns {
+                    let text = self.cell_text(row, col);
+                    cells.push(text.or_else(|| row.columns.get(col).cloned()).unwrap_or_default());
+                }
+            } else {
+                cells.push(row.raw.clone());
+            }
+            lines.push(cells);
+        }
+        lines
+            .iter()
+            .map(|cells| {
+                let cells: Vec<String> = cells.iter().map(|c| escape_field(c, ",")).collect();
+                cells.join(",") + "\n"
+            })
+            .collect()
+    }
+
+    /// Copies raw log lines without escape sequences to the clipboard, as
+    /// text or as CSV with their receive time
+    fn copy_raw_lines(&mut self, ctx: &egui::Context, lines: &[usize], csv: bool) {
+        let display = self.time_display();
+        let mut text = if csv { "Timestamp,Line\n".to_string() } else { String::new() };
+        for line in lines.iter().filter_map(|&i| self.raw_log.get(i)) {
+            let plain = ansi::strip(&line.text);
+            if csv {
+                let time = format_time(&line.time, &display);
+                text.push_str(&format!("{},{}\n", time, escape_field(&plain, ",")));
+            } else {
+                text.push_str(&plain);
+                text.push('\n');
+            }
+        }
+        ctx.copy_text(text);
+        self.set_status(format!("Copied {} lines", lines.len()));
+    }
+
+    /// Copies rows to the clipboard as their raw lines or as CSV
+    fn copy_rows(&mut self, ctx: &egui::Context, rows: &[usize], csv: bool) {
+        let text = if csv {
+            self.rows_csv(rows)
+        } else {
+            let lines = rows.iter().filter_map(|&idx| self.rows.get(idx));
+            lines.map(|row| row.raw.clone() + "\n").collect()
+        };
+        ctx.copy_text(text);
+        self.set_status(format!("Copied {} rows", rows.len()));
+    }
+
+    fn poll_serial_events(&mut self) {
+        let events = self.serial.poll_events();
+        let was_stopped = self.capture_stopped;
+        for ev in events {
+            if self.capture_stopped
+                && matches!(
+                    ev,
+                    SerialEvent::Data(_)
+                        | SerialEvent::Traffic(..)
+                        | SerialEvent::Frame(_)
+                        | SerialEvent::RawBytes(_)
+                        | SerialEvent::LineBytes(_)
+                        | SerialEvent::LineError(_)
+                )
+            {
+                continue;
+            }
+            match ev {
+                SerialEvent::Connected => {
+                    self.capture_stopped = false;
+                    self.serial.is_connected = true;
+                    self.xoff_sent = false;
+                    self.control_lines.reset_levels();
+                    self.mismatch_hint.reset();
+                    // A reconnect starts a new session
+                    self.finish_session();
+                    let target = self.settings.target_label();
+                    self.session_stats.start(target.clone(), self.counter_snapshot());
+                    self.set_status(format!("Connected to {}", target));
+                    if self.settings.id_on_connect {
+                        self.identify_device();
+                    }
+                }
+                SerialEvent::Disconnected => {
+                    self.flush_record(false);
+                    // Keep the message of an app-initiated disconnect
+                    if self.serial.is_connected {
+                        self.set_status("Disconnected");
+                        self.alert_pending |= self.settings.alert_on_disconnect;
+                    }
+                    self.serial.is_connected = false;
+                }
+                SerialEvent::LineBytes(bytes) => self.pending_bytes.extend(bytes),
+                SerialEvent::Data(line) => {
+                    self.ingest_line(line, None);
+                }
+                SerialEvent::Traffic(dir, line) => {
+                    self.ingest_line(line, Some(dir));
+                }
+                SerialEvent::RawBytes(bytes) => {
+                    if self.settings.rx_framing == RxFraming::Lines {
+                        let encoding = self.settings.text_encoding.encoding();
+                        self.mismatch_hint.push(&bytes, encoding);
+                    }
+                    self.hex_dump.push(&bytes);
+                    let encoding = self.settings.text_encoding.encoding();
+                    self.terminal.active = self.show_terminal && !self.show_timeline;
+                    self.terminal.feed(&bytes, encoding);
+                }
+                SerialEvent::Frame(frame) => {
+                    self.pending_bytes = frame.clone();
+                    let line = match self.settings.rx_framing {
+                        RxFraming::ModbusRtu => self.modbus.response(&frame),
+                        RxFraming::Defmt => match &self.defmt {
+                            Some(table) => match table.decode(&frame) {
+                                Ok(line) => Some(line),
+                                Err(e) => {
+                                    self.pending_line_error = true;
+                                    Some(format!("<defmt: {}> {}", e, to_hex(&frame)))
+                                }
+                            },
+                            None => None,
+                        },
+                        RxFraming::Protobuf => match &self.proto {
+                            Some(schema) => match schema.decode(&frame) {
+                                Ok(line) => Some(line),
+                                Err(e) => {
+                                    self.pending_line_error = true;
+                                    Some(format!("<protobuf: {}> {}", e, to_hex(&frame)))
+                                }
+                            },
+                            None => None,
+                        },
+                        RxFraming::Tlv => self.tlv.as_ref().map(|tlv| tlv.decode(&frame)),
+                        _ => self.frame_pattern.as_ref().and_then(|p| p.extract(&frame)),
+                    };
+                    self.ingest_line(line.unwrap_or_else(|| to_hex(&frame)), None);
+                }
+                SerialEvent::Error(e) => {
+                    self.flush_record(false);
+                    if self.serial.is_connected {
+                        self.alert_pending |= self.settings.alert_on_disconnect;
+                    }
+                    self.serial.is_connected = false;
+                    self.count_warning();
+                    self.set_error(e);
+                }
+                SerialEvent::Warning(w) => {
+                    self.count_warning();
+                    self.set_error(w);
+                }
+                SerialEvent::LineError(e) => {
+                    self.pending_line_error = true;
+                    if let Some(stats) = self.session_stats.current() {
+                        stats.line_errors += 1;
+                    }
+                    self.set_error(e);
+                }
+                SerialEvent::ControlLine(line, level) => {
+                    self.control_lines.record(line, level);
+                }
+                SerialEvent::LineEndingDetected(le) => {
+                    self.set_status(format!("RX line ending detected: {}", le.label()));
+                }
+            }
+        }
+        self.flush_record(true);
+        self.flush_window();
+        if self.capture_stopped && !was_stopped {
+            self.serial.disconnect();
+            self.set_status(format!(
+                "Capture stopped {} lines after trigger",
+                self.settings.trigger_post_lines
+            ));
+        }
+    }
+
+    /// Flashes or raises the window after a watched event, unless it already
+    /// has focus
+    fn poll_alert(&mut self, ctx: &egui::Context) {
+        if !std::mem::take(&mut self.alert_pending) {
+            return;
+        }
+        if ctx.input(|i| i.viewport().focused) == Some(true) {
+            return;
+        }
+        match self.settings.window_alert {
+            WindowAlert::Off => {}
+            WindowAlert::Flash => {
+                ctx.send_viewport_cmd(egui::ViewportCommand::RequestUserAttention(
+                    egui::UserAttentionType::Critical,
+                ));
+            }
+            WindowAlert::Raise => {
+                ctx.send_viewport_cmd(egui::ViewportCommand::Minimized(false));
+                ctx.send_viewport_cmd(egui::ViewportCommand::Focus);
+            }
+        }
+    }
+
+    fn count_warning(&mut self) {
+        if let Some(stats) = self.session_stats.current() {
+            stats.warnings += 1;
+        }
+    }
+
+    // --- UI rendering ---
+
+    fn render_toolbar(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            let connected = self.serial.is_connected;
+
+            // Connect / Disconnect
+            if connected {
+                if ui
+                    .add_sized(
+                        [110.0, 28.0],
+                        egui::Button::new(
+                            RichText::new("Disconnect").color(egui::Color32::from_rgb(255, 80, 80)),
+                        ),
+                    )
+                    .clicked()
+                {
+                    self.disconnect();
+                }
+            } else {
+                if ui
+                    .add_sized(
+                        [110.0, 28.0],
+                        egui::Button::new(
+                            RichText::new("Connect").color(egui::Color32::from_rgb(80, 200, 80)),
+                        ),
+                    )
+                    .clicked()
+                {
+                    self.connect();
+                }
+            }
+
+            // Port picker
+            if self.settings.connection != ConnectionKind::Simulator {
+                let mut port_name = self.settings.port_name.clone();
+                ui.add_enabled_ui(!connected, |ui| {
+                    port_picker(ui, &mut port_name, &self.port_watcher.ports);
+                    if ui
+                        .button("⟳")
+                        .on_hover_text("Refresh the port list")
+                        .clicked()
+                    {
+                        let changes = self.port_watcher.refresh();
+                        if !changes.is_empty() {
+                            self.set_status(changes.describe());
+                        }
+                    }
+                });
+                if !connected && !port_name.is_empty() && !self.port_watcher.contains(&port_name) {
+                    ui.colored_label(egui::Color32::from_rgb(255, 170, 60), "⚠")
+                        .on_hover_text(
+                            "Port not found (unplugged, or a path that isn't enumerated)",
+                        );
+                }
+                if port_name != self.settings.port_name {
+                    self.settings.port_name = port_name;
+                    self.settings.save();
+                }
+            }
+
+            // Reconnect
+            if ui
+                .add_enabled(
+                    self.settings.has_target(),
+                    egui::Button::new("Reconnect").min_size(egui::vec2(90.0, 28.0)),
+                )
+                .on_hover_text("Close and reopen the port (F5)")
+                .clicked()
+            {
+                self.reconnect();
+            }
+
+            // Flush OS buffers
+            if ui
+                .add_enabled(
+                    self.serial.is_connected,
+                    egui::Button::new("Flush").min_size(egui::vec2(60.0, 28.0)),
+                )
+                .on_hover_text("Discard data queued in the port's input/output buffers")
+                .clicked()
+            {
+                self.serial.flush_buffers();
+                self.set_status("Port buffers flushed");
+            }
+
+            ui.separator();
+
+            // Settings
+            if ui
+                .add_sized([90.0, 28.0], egui::Button::new("Settings"))
+                .clicked()
+            {
                 self.settings_win.open(&self.settings);
             }
 
-            ui.separator();
-
-            // Regex pattern (quick edit in toolbar)
-            ui.label("Regex:");
-            let re_resp = ui.add(
-                egui::TextEdit::singleline(&mut self.settings.regex_pattern)
-                    .hint_text("(group1)(group2)...")
-                    .desired_width(280.0),
-            );
-            if re_resp.lost_focus() || re_resp.changed() {
-                let new_settings = self.settings.clone();
-                self.apply_settings(new_settings);
+            // Baud rate scanner
+            if ui
+                .add_sized([90.0, 28.0], egui::Button::new("Scan Baud"))
+                .on_hover_text("Try common baud rates and pick the most readable one")
+                .clicked()
+            {
+                self.baud_scanner.is_open = true;
+            }
+
+            // Modem control lines
+            if ui
+                .add_sized([70.0, 28.0], egui::Button::new("Lines"))
+                .on_hover_text("CTS/DSR/DCD/RI levels, RTS/DTR toggles and a transition timeline")
+                .clicked()
+            {
+                self.control_lines.is_open = true;
+            }
+
+            // Frame decoder statistics
+            if self.settings.rx_framing != RxFraming::Lines
+                && ui
+                    .add_sized([70.0, 28.0], egui::Button::new("Decoder"))
+                    .on_hover_text("Frames, CRC failures, resyncs and average frame size")
+                    .clicked()
+            {
+                self.decoder_stats.is_open = true;
+            }
+
+            // Modbus RTU master
+            if self.settings.rx_framing == RxFraming::ModbusRtu
+                && ui
+                    .add_sized([70.0, 28.0], egui::Button::new("Modbus"))
+                    .on_hover_text("Build read/write requests and poll registers")
+                    .clicked()
+            {
+                self.modbus.is_open = true;
+            }
+
+            ui.separator();
+
+            // Parser profiles
+            if !self.settings.profiles.is_empty() {
+                ui.label("Profile:");
+                let selected = if self.settings.active_profile.is_empty() {
+                    "(none)".to_string()
+                } else {
+                    self.settings.active_profile.clone()
+                };
+                let mut chosen = None;
+                egui::ComboBox::from_id_salt("toolbar_profile")
+                    .selected_text(selected)
+                    .width(110.0)
+                    .show_ui(ui, |ui| {
+                        for p in &self.settings.profiles {
+                            if ui
+                                .selectable_label(p.name == self.settings.active_profile, &p.name)
+                                .clicked()
+                            {
+                                chosen = Some(p.name.clone());
+                            }
+                        }
+                    });
+                if let Some(name) = chosen {
+                    self.select_profile(&name);
+                }
+            }
+
+            // Regex pattern (quick edit in toolbar)
+            if self.settings.parser_mode == ParserMode::Regex {
+                ui.label("Regex:");
+                let re_resp = ui.add(
+                    egui::TextEdit::singleline(&mut self.settings.regex_pattern)
+                        .hint_text("(group1)(group2)...")
+                        .desired_width(280.0),
+                );
+                if re_resp.lost_focus() || re_resp.changed() {
+                    let new_settings = self.settings.clone();
+                    self.apply_settings(new_settings);
+                }
+            } else {
+                ui.label(format!("Parser: {}", self.settings.parser_mode.label()));
+            }
+
+            ui.separator();
+
+            // Auto-scroll toggle
+            ui.checkbox(&mut self.auto_scroll, "Auto-scroll");
+            let pause_label = if self.paused.is_some() { "▶ Resume" } else { "⏸ Pause" };
+            if ui
+                .button(pause_label)
+                .on_hover_text("Freeze the table and raw log; data keeps being received")
+                .clicked()
+            {
+                self.toggle_pause();
+            }
+            if let Some((rows, lines)) = self.paused {
+                let new = if self.raw_only() {
+                    self.raw_log.len().saturating_sub(lines)
+                } else {
+                    self.rows.len().saturating_sub(rows)
+                };
+                ui.colored_label(egui::Color32::YELLOW, format!("{} new lines", new));
+            }
+
+            // Table, raw log or both
+            let mut layout = self.settings.view_layout;
+            egui::ComboBox::from_id_salt("view_layout")
+                .selected_text(layout.label())
+                .width(80.0)
+                .show_ui(ui, |ui| {
+                    for l in ViewLayout::all() {
+                        ui.selectable_value(&mut layout, *l, l.label());
+                    }
+                })
+                .response
+                .on_hover_text("Parsed table, raw log, or both side by side or stacked");
+            if layout != self.settings.view_layout {
+                self.settings.view_layout = layout;
+                self.settings.save();
+            }
+            if layout != ViewLayout::Table
+                && ui
+                    .checkbox(&mut self.settings.raw_wrap, "Wrap")
+                    .on_hover_text("Wrap long lines instead of scrolling sideways")
+                    .changed()
+            {
+                self.settings.save();
+            }
+            let num_cols = self.num_columns();
+            if !self.raw_only() && num_cols > 0 {
+                let headers: Vec<String> = (0..num_cols).map(|i| self.column_title(i)).collect();
+                ui.menu_button("Columns", |ui| {
+                    if self.settings.column_layout.show(ui, &headers) {
+                        self.settings.save();
+                    }
+                })
+                .response
+                .on_hover_text("Show, hide and reorder the table columns");
+            }
+            ui.checkbox(&mut self.show_hex, "Hex dump")
+                .on_hover_text("Received bytes before line splitting, 16 per row");
+            ui.checkbox(&mut self.show_terminal, "Terminal")
+                .on_hover_text("VT100 terminal for interactive shells; click it to type");
+
+            // Dashboard toggle
+            ui.checkbox(&mut self.show_dashboard, "Dashboard");
+            ui.add_enabled(
+                !self.settings.lightweight,
+                egui::Checkbox::new(&mut self.show_plots, "Plots"),
+            )
+            .on_disabled_hover_text("Plots are off in lightweight mode");
+            if ui
+                .add_enabled(num_cols > 0, egui::Button::new("Histogram"))
+                .on_hover_text("Distribution of a numeric column's values")
+                .clicked()
+            {
+                self.histogram.is_open = true;
+            }
+
+            // Device identification
+            if ui
+                .add_enabled(
+                    self.serial.is_connected && !self.settings.id_commands_list().is_empty(),
+                    egui::Button::new("Identify").min_size(egui::vec2(70.0, 28.0)),
+                )
+                .on_hover_text("Send the identification commands and pin the responses")
+                .clicked()
+            {
+                self.identify_device();
+            }
+
+            // Second session and merged timeline
+            if ui
+                .add_sized([80.0, 28.0], egui::Button::new("Session B"))
+                .on_hover_text("Open a second port for the merged timeline")
+                .clicked()
+            {
+                self.session_b.open();
+            }
+            ui.checkbox(&mut self.show_timeline, "Timeline");
+
+            ui.separator();
+
+            // Clear
+            if ui
+                .add_sized([60.0, 28.0], egui::Button::new("Clear"))
+                .clicked()
+            {
+                self.clear_data();
+            }
+
+            // Export
+            if self.settings.export_templates.is_empty() {
+                if ui
+                    .add_sized([90.0, 28.0], egui::Button::new("Export CSV"))
+                    .clicked()
+                {
+                    self.run_export(&self.default_export_template());
+                }
+            } else {
+                let mut chosen = None;
+                ui.menu_button("Export CSV", |ui| {
+                    if ui.button("Default").clicked() {
+                        chosen = Some(self.default_export_template());
+                        ui.close_menu();
+                    }
+                    ui.separator();
+                    for t in &self.settings.export_templates {
+                        if ui.button(&t.name).clicked() {
+                            chosen = Some(t.clone());
+                            ui.close_menu();
+                        }
+                    }
+                });
+                if let Some(template) = chosen {
+                    self.run_export(&template);
+                }
+            }
+        });
+    }
+
+    /// Entries of the command palette for the current state
+    fn palette_commands(&self) -> Vec<(String, Command)> {
+        let mut commands = Vec::new();
+        let mut add = |label: String, command| commands.push((label, command));
+        let connected = self.serial.is_connected;
+        if connected {
+            add("Disconnect".into(), Command::Disconnect);
+            add("Flush port buffers".into(), Command::FlushBuffers);
+        } else {
+            add(format!("Connect to {}", self.settings.target_label()), Command::Connect);
+        }
+        if self.settings.has_target() {
+            add("Reconnect (F5)".into(), Command::Reconnect);
+        }
+        add("Open settings".into(), Command::OpenSettings);
+        add("Scan baud rate".into(), Command::ScanBaud);
+        add("Modem control lines".into(), Command::ControlLines);
+        let framing = &self.settings.rx_framing;
+        if matches!(framing, RxFraming::Defmt | RxFraming::Protobuf)
+            || !self.settings.symbol_file.is_empty()
+        {
+            add("Reload firmware files".into(), Command::ReloadFiles);
+        }
+        if self.settings.rx_framing != RxFraming::Lines {
+            add("Decoder statistics".into(), Command::DecoderStats);
+        }
+        if self.settings.rx_framing == RxFraming::ModbusRtu {
+            add("Modbus master".into(), Command::Modbus);
+        }
+        if self.num_columns() > 0 {
+            add("Histogram".into(), Command::Histogram);
+        }
+        if connected && !self.settings.id_commands_list().is_empty() {
+            add("Identify device".into(), Command::Identify);
+        }
+        add("Open session B".into(), Command::SessionB);
+        for p in &self.settings.profiles {
+            add(format!("Parser profile: {}", p.name), Command::SelectProfile(p.name.clone()));
+        }
+        add("Export CSV".into(), Command::Export(self.default_export_template()));
+        for t in &self.settings.export_templates {
+            add(format!("Export CSV: {}", t.name), Command::Export(t.clone()));
+        }
+        add("Clear data".into(), Command::Clear);
+        let pause = if self.paused.is_some() { "Resume view" } else { "Pause view" };
+        add(pause.into(), Command::TogglePause);
+        let scroll = if self.auto_scroll { "Turn auto-scroll off" } else { "Turn auto-scroll on" };
+        add(scroll.into(), Command::ToggleAutoScroll);
+        add("Find (Ctrl+F)".into(), Command::Find);
+        if !self.selection.is_empty() {
+            add("Toggle bookmark on selected rows (Ctrl+B)".into(), Command::ToggleBookmarks);
+        }
+        if !self.bookmarks.is_empty() {
+            add("Next bookmark (F2)".into(), Command::JumpToBookmark(true));
+            add("Previous bookmark (Shift+F2)".into(), Command::JumpToBookmark(false));
+        }
+        for layout in ViewLayout::all() {
+            add(format!("View: {}", layout.label()), Command::SetLayout(*layout));
+        }
+        for density in Density::all() {
+            add(format!("Table density: {}", density.label()), Command::SetDensity(*density));
+        }
+        if self.settings.table_zoom != 1.0 || self.settings.raw_zoom != 1.0 {
+            add("Reset table and raw log text size".into(), Command::ResetZoom);
+        }
+        let panels = [
+            (Panel::HexDump, "hex dump", self.show_hex),
+            (Panel::Terminal, "terminal", self.show_terminal),
+            (Panel::Dashboard, "dashboard", self.show_dashboard),
+            (Panel::Plots, "plots", self.show_plots),
+            (Panel::Timeline, "timeline", self.show_timeline),
+        ];
+        for (panel, name, shown) in panels {
+            if panel == Panel::Plots && self.settings.lightweight {
+                continue;
+            }
+            let verb = if shown { "Hide" } else { "Show" };
+            add(format!("{} {}", verb, name), Command::TogglePanel(panel));
+        }
+        for (i, m) in self.settings.macros.iter().enumerate() {
+            add(format!("Run macro: {}", m.name), Command::RunMacro(i));
+        }
+        commands
+    }
+
+    fn run_command(&mut self, command: Command) {
+        match command {
+            Command::Connect => self.connect(),
+            Command::Disconnect => self.disconnect(),
+            Command::Reconnect => self.reconnect(),
+            Command::FlushBuffers => {
+                self.serial.flush_buffers();
+                self.set_status("Port buffers flushed");
+            }
+            Command::OpenSettings => self.settings_win.open(&self.settings),
+            Command::ScanBaud => self.baud_scanner.is_open = true,
+            Command::ControlLines => self.control_lines.is_open = true,
+            Command::DecoderStats => self.decoder_stats.is_open = true,
+            Command::ReloadFiles => self.reload_files(),
+            Command::Modbus => self.modbus.is_open = true,
+            Command::Histogram => self.histogram.is_open = true,
+            Command::SessionB => self.session_b.open(),
+            Command::Identify => self.identify_device(),
+            Command::SelectProfile(name) => self.select_profile(&name),
+            Command::Export(template) => self.run_export(&template),
+            Command::Clear => self.clear_data(),
+            Command::TogglePause => self.toggle_pause(),
+            Command::ToggleAutoScroll => self.auto_scroll = !self.auto_scroll,
+            Command::Find => self.search.open(),
+            Command::ToggleBookmarks => self.toggle_bookmarks(&self.selection.rows()),
+            Command::JumpToBookmark(forward) => self.jump_to_bookmark(forward),
+            Command::SetLayout(layout) => {
+                self.settings.view_layout = layout;
+                self.settings.save();
+            }
+            Command::SetDensity(density) => {
+                self.settings.density = density;
+                self.settings.save();
+            }
+            Command::ResetZoom => {
+                self.settings.table_zoom = 1.0;
+                self.settings.raw_zoom = 1.0;
+                self.settings.save();
+            }
+            Command::TogglePanel(panel) => {
+                let shown = match panel {
+                    Panel::HexDump => &mut self.show_hex,
+                    Panel::Terminal => &mut self.show_terminal,
+                    Panel::Dashboard => &mut self.show_dashboard,
+                    Panel::Plots => &mut self.show_plots,
+                    Panel::Timeline => &mut self.show_timeline,
+                };
+                *shown = !*shown;
+            }
+            Command::RunMacro(idx) => self.run_macro(idx),
+        }
+    }
+
+    fn render_data_table(&mut self, ui: &mut egui::Ui) {
+        let show_ts = self.settings.show_timestamp;
+        let show_line_no = self.settings.show_line_numbers;
+        let show_delta = self.settings.show_delta;
+        let reference = self.time_reference.filter(|_| show_delta);
+        let display = self.time_display();
+        let show_dir = self.show_direction();
+        let show_checksum = self.line_checksum.is_some();
+        let num_cols = self.num_columns();
+        let plain = self.settings.lightweight;
+        let palette = Palette::new(&self.settings.colors, ui.visuals());
+        if let Some(zoom) = scrolled_zoom(ui, self.settings.table_zoom) {
+            self.settings.table_zoom = zoom;
+            self.settings.save();
+            self.set_status(format!("Table text size {:.0}%", zoom * 100.0));
+        }
+        let zoom = self.settings.table_zoom;
+        let density = self.settings.density;
+        density.apply(ui, zoom);
+
+        // Build column layout
+        let mut builder = TableBuilder::new(ui)
+            .striped(!plain)
+            .resizable(true)
+            .sense(egui::Sense::click())
+            .cell_layout(egui::Layout::left_to_right(egui::Align::Center))
+            .auto_shrink(false);
+
+        self.update_view();
+        let view_len = self.table_view.len();
+
+        if self.scroll_to_selected {
+            if let Some(pos) = self.selection.cursor().and_then(|r| self.table_view.pos_of(r)) {
+                builder = builder.scroll_to_row(pos, None);
+            }
+            self.scroll_to_selected = false;
+        } else if self.auto_scroll && self.paused.is_none() {
+            // Follow the newest row wherever the sort order puts it
+            if let Some(pos) = self.table_view.newest_pos() {
+                builder = builder.scroll_to_row(pos, Some(egui::Align::BOTTOM));
+            }
+        }
+
+        // Bookmark gutter
+        builder = builder.column(Column::exact(18.0));
+        // Line number column
+        if show_line_no {
+            builder = builder.column(Column::initial(60.0).at_least(40.0).resizable(true));
+        }
+        // Timestamp column
+        if show_ts {
+            builder = builder.column(Column::initial(110.0).at_least(80.0).resizable(true));
+        }
+        // Delta time columns
+        if show_delta {
+            builder = builder.column(Column::initial(80.0).at_least(60.0).resizable(true));
+        }
+        if reference.is_some() {
+            builder = builder.column(Column::initial(90.0).at_least(60.0).resizable(true));
+        }
+        // Direction column
+        if show_dir {
+            builder = builder.column(Column::exact(32.0));
+        }
+        // Checksum column
+        if show_checksum {
+            builder = builder.column(Column::exact(44.0));
+        }
+
+        // Parsed columns in display order, without hidden ones
+        let shown = self.settings.column_layout.visible(num_cols);
+        if num_cols > 0 {
+            for _ in &shown {
+                // Long values are cut off with "…" and shown whole on hover
+                let column = Column::initial(120.0).at_least(60.0).clip(true);
+                builder = builder.column(column.resizable(true));
+            }
+        } else {
+            // Raw data column
+            builder = builder.column(Column::remainder().at_least(100.0));
+        }
+
+        // Columns that currently hold numbers get a plot toggle
+        let numeric: Vec<bool> = self
+            .latest_values(num_cols)
+            .iter()
+            .map(|v| v.as_ref().is_some_and(|v| v.trim().parse::<f64>().is_ok()))
+            .collect();
+        let plotted: &[usize] = self.settings.plots.first().map_or(&[], |p| &p.columns);
+
+        let mut sort_clicked = None;
+        let mut plot_clicked = None;
+        let mut clear_reference = false;
+        // Rows dropped by the retention limit, as the numbers don't start at 1
+        let trimmed = self.stream().first().map_or(0, |r| r.line_no.saturating_sub(1));
+        let table = builder.header(density.header_height(zoom), |mut header| {
+            let view = &self.table_view;
+            header.col(|ui| {
+                ui.weak("🔖").on_hover_text(
+                    "Click a row here or press Ctrl+B to bookmark it; F2 / Shift+F2 jump \
+                     between bookmarks",
+                );
+            });
+            if show_line_no {
+                header.col(|ui| {
+                    ui.strong("#").on_hover_text(format!(
+                        "Line number; {} older lines were dropped by the retention limit",
+                        trimmed
+                    ));
+                });
+            }
+            if show_ts {
+                header.col(|ui| {
+                    let arrow = view.sort_indicator(SortKey::Time);
+                    if sort_header(ui, "Timestamp", arrow) {
+                        sort_clicked = Some(SortKey::Time);
+                    }
+                });
+            }
+            if show_delta {
+                header.col(|ui| {
+                    ui.strong("Δt (ms)")
+                        .on_hover_text("Time since the previous row received");
+                });
+            }
+            if reference.is_some() {
+                header.col(|ui| {
+                    ui.strong("Δref (s)").on_hover_text("Time since the reference row");
+                    if ui.small_button("✖").on_hover_text("Clear the reference").clicked() {
+                        clear_reference = true;
+                    }
+                });
+            }
+            if show_dir {
+                header.col(|ui| {
+                    ui.strong("Dir");
+                });
+            }
+            if show_checksum {
+                header.col(|ui| {
+                    ui.strong("Sum");
+                });
+            }
+            if num_cols > 0 {
+                for &i in &shown {
+                    let is_numeric = numeric[i];
+                    header.col(|ui| {
+                        let arrow = view.sort_indicator(SortKey::Column(i));
+                        if sort_header(ui, &self.column_title(i), arrow) {
+                            sort_clicked = Some(SortKey::Column(i));
+                        }
+                        if is_numeric {
+                            let on = plotted.contains(&i);
+                            let hover = if on { "Remove from plot" } else { "Add to plot" };
+                            if ui.selectable_label(on, "📈").on_hover_text(hover).clicked() {
+                                plot_clicked = Some(i);
+                            }
+                        }
+                    });
+                }
+            } else {
+                header.col(|ui| {
+                    let arrow = view.sort_indicator(SortKey::Column(0));
+                    if sort_header(ui, "Data", arrow) {
+                        sort_clicked = Some(SortKey::Column(0));
+                    }
+                });
+            }
+        });
+
+        // Row count for borrow
+        let selection = &self.selection;
+        let search = &self.search;
+        let current_match = search.current_pos().and_then(|pos| self.table_view.row(pos));
+        let mut clicked_row = None;
+        let mut inspect_row = None;
+        let mut copy_row = None;
+        let mut reference_row = None;
+        let mut bookmark_row = None;
+
+        table.body(|body| {
+            body.rows(density.row_height(zoom), view_len, |mut row_widget| {
+                let Some(idx) = self.table_view.row(row_widget.index()) else {
+                    return;
+                };
+                row_widget.set_selected(selection.contains(idx));
+                let row = &self.rows[idx];
+                let current = current_match == Some(idx);
+                let color = if row.kind == RowKind::Marker {
+                    egui::Color32::from_rgb(255, 170, 60)
+                } else if row.alert {
+                    egui::Color32::from_rgb(255, 70, 70)
+                } else if row.line_error {
+                    egui::Color32::from_rgb(210, 110, 230)
+                } else if row.checksum == Some(false) {
+                    egui::Color32::from_rgb(255, 120, 60)
+                } else if row.dropped > 0 {
+                    egui::Color32::from_rgb(240, 200, 60)
+                } else if let Some(level) = row.level.and_then(|l| l.color()) {
+                    level
+                } else if row.dir == Some(Direction::Tx) {
+                    palette.tx
+                } else if !row.matched {
+                    palette.unmatched
+                } else {
+                    palette.matched
+                };
+
+                let bookmarked = self.is_bookmarked(row);
+                row_widget.col(|ui| {
+                    if row.line_no == 0 {
+                        return;
+                    }
+                    let size = ui.available_size();
+                    let (rect, response) = ui.allocate_exact_size(size, egui::Sense::click());
+                    // A faint mark on hover shows where to click
+                    let color = if bookmarked {
+                        Some(BOOKMARK_COLOR)
+                    } else {
+                        response.hovered().then(|| ui.visuals().weak_text_color())
+                    };
+                    if let Some(color) = color {
+                        ui.painter().text(
+                            rect.center(),
+                            egui::Align2::CENTER_CENTER,
+                            "🔖",
+                            egui::FontId::proportional(12.0),
+                            color,
+                        );
+                    }
+                    if response.clicked() {
+                        bookmark_row = Some(idx);
+                    }
+                });
+                if show_line_no {
+                    row_widget.col(|ui| {
+                        if row.line_no > 0 {
+                            let color = ui.visuals().weak_text_color();
+                            cell_label(ui, plain, color, &row.line_no.to_string());
+                        }
+                    });
+                }
+                if show_ts {
+                    row_widget.col(|ui| {
+                        let time = format_time(&row.time, &display);
+                        cell_label(ui, plain, palette.timestamp, &time);
+                    });
+                }
+
+                if show_delta {
+                    row_widget.col(|ui| {
+                        // Since the row received before, whatever the sort
+                        if let Some(prev) = idx.checked_sub(1).and_then(|i| self.rows.get(i)) {
+                            let ms = (row.time.elapsed_secs() - prev.time.elapsed_secs()) * 1000.0;
+                            cell_label(ui, plain, palette.timestamp, &format!("{:.1}", ms));
+                        }
+                    });
+                }
+                if let Some(reference) = &reference {
+                    row_widget.col(|ui| {
+                        let secs = row.time.elapsed_secs() - reference.elapsed_secs();
+                        let text = if row.time == *reference {
+                            "ref".to_string()
+                        } else {
+                            format!("{:+.3}", secs)
+                        };
+                        cell_label(ui, plain, palette.timestamp, &text);
+                    });
+                }
+
+                if show_dir {
+                    row_widget.col(|ui| match row.dir {
+                        Some(Direction::Tx) => {
+                            cell_label(ui, plain, palette.tx, "TX");
+                        }
+                        Some(Direction::Rx) => {
+                            cell_label(ui, plain, palette.rx, "RX");
+                        }
+                        None => {}
+                    });
+                }
+
+                if show_checksum {
+                    row_widget.col(|ui| match row.checksum {
+                        Some(true) => {
+                            cell_label(ui, plain, egui::Color32::from_rgb(100, 200, 120), "OK");
+                        }
+                        Some(false) => {
+                            cell_label(ui, plain, egui::Color32::from_rgb(255, 120, 60), "FAIL");
+                        }
+                        None => {}
+                    });
+                }
+
+                if num_cols > 0 {
+                    for &col_i in &shown {
+                        row_widget.col(|ui| {
+                            let raw = row.columns.get(col_i).map(String::as_str).unwrap_or("");
+                            let scaled =
+                                units::scale_of(&self.settings.column_scales, col_i).is_some();
+                            let typed = scaled
+                                || self.column_types.get(col_i).is_some_and(|t| t.is_numeric());
+                            // Scaled cells show the converted value
+                            let text = scaled.then(|| self.cell_text(row, col_i)).flatten();
+                            let val = text.as_deref().unwrap_or(raw);
+                            if let Some(spread) = &row.spread {
+                                // Window means line up like numbers
+                                let layout = egui::Layout::right_to_left(egui::Align::Center);
+                                ui.with_layout(layout, |ui| {
+                                    search_cell_label(ui, plain, color, val, search, current)
+                                        .on_hover_text(spread.describe(col_i));
+                                });
+                                return;
+                            }
+                            if !typed || !row.matched {
+                                search_cell_label(ui, plain, color, val, search, current);
+                                return;
+                            }
+                            // Numbers line up on the right; cells that don't
+                            // convert are shown red
+                            let valid = row.values.get(col_i).copied().flatten().is_some();
+                            let color = if valid || val.is_empty() {
+                                color
+                            } else {
+                                egui::Color32::from_rgb(230, 80, 80)
+                            };
+                            let layout = egui::Layout::right_to_left(egui::Align::Center);
+                            ui.with_layout(layout, |ui| {
+                                search_cell_label(ui, plain, color, val, search, current)
+                            });
+                        });
+                    }
+                } else {
+                    row_widget.col(|ui| {
+                        let val = row.columns.first().map(String::as_str).unwrap_or(&row.raw);
+                        search_cell_label(ui, plain, color, val, search, current);
+                    });
+                }
+
+                let response = row_widget.response();
+                response.context_menu(|ui| {
+                    // The whole selection if the row is part of it
+                    let n = if selection.contains(idx) { selection.len() } else { 1 };
+                    let rows = if n > 1 { format!("{} rows ", n) } else { String::new() };
+                    if ui.button(format!("Copy {}as text", rows)).clicked() {
+                        copy_row = Some((idx, false));
+                        ui.close_menu();
+                    }
+                    if ui.button(format!("Copy {}as CSV", rows)).clicked() {
+                        copy_row = Some((idx, true));
+                        ui.close_menu();
+                    }
+                    if row.line_no > 0 {
+                        let label = if bookmarked { "Remove bookmark" } else { "Bookmark" };
+                        if ui.button(label).clicked() {
+                            bookmark_row = Some(idx);
+                            ui.close_menu();
+                        }
+                    }
+                    if show_delta {
+                        ui.separator();
+                        if ui.button("Set as time reference").clicked() {
+                            reference_row = Some(idx);
+                            ui.close_menu();
+                        }
+                    }
+                });
+                if response.double_clicked() {
+                    inspect_row = Some(idx);
+                } else if response.clicked() {
+                    clicked_row = Some(idx);
+                }
+            });
+        });
+
+        if let Some(idx) = clicked_row {
+            let modifiers = ui.input(|i| i.modifiers);
+            self.selection.click(idx, modifiers, &self.table_view);
+        }
+        if let Some(idx) = inspect_row {
+            self.selection.select(idx);
+            self.inspected_row = self.rows.get(idx).cloned();
+        }
+        if let Some((idx, csv)) = copy_row {
+            if !self.selection.contains(idx) {
+                self.selection.select(idx);
+            }
+            self.copy_rows(ui.ctx(), &self.selection.rows(), csv);
+        }
+        if let Some(idx) = reference_row {
+            self.time_reference = self.rows.get(idx).map(|row| row.time);
+        }
+        if let Some(idx) = bookmark_row {
+            self.toggle_bookmarks(&[idx]);
+        }
+        if clear_reference {
+            self.time_reference = None;
+        }
+        if let Some(key) = sort_clicked {
+            self.table_view.toggle_sort(key);
+        }
+        if let Some(col) = plot_clicked {
+            toggle_plot_column(&mut self.settings.plots, col);
+            if self.settings.plots[0].columns.contains(&col) {
+                self.show_plots = true;
+            }
+            self.settings.save();
+        }
+    }
+
+    /// Label and value of each detail the row inspector lists
+    fn row_details(&self, row: &DataRow) -> Vec<(String, String)> {
+        let mut details = vec![
+            ("Received".to_string(), row.time.wall.format("%Y-%m-%d %H:%M:%S%.6f").to_string()),
+            ("Elapsed".to_string(), format!("{:.6} s", row.time.elapsed_secs())),
+        ];
+        if let Some(device) = row.time.device {
+            details.push(("Device time".to_string(), format!("{:.6}", device)));
+        }
+        let status = match (row.kind, row.matched) {
+            (RowKind::Marker, _) => "marker",
+            (RowKind::Sent, _) => "sent",
+            (RowKind::Dropped, _) => "dropped by the line script",
+            (RowKind::Data, true) => "matched",
+            (RowKind::Data, false) => "not matched",
+        };
+        details.push(("Status".to_string(), status.to_string()));
+        if let Some(dir) = row.dir {
+            details.push(("Direction".to_string(), dir.label().to_string()));
+        }
+        if let Some(level) = row.level {
+            details.push(("Level".to_string(), level.label().to_string()));
+        }
+        if self.is_bookmarked(row) {
+            details.push(("Bookmark".to_string(), "yes".to_string()));
+        }
+        if row.alert {
+            details.push(("Alert".to_string(), "breaks an alert rule".to_string()));
+        }
+        if row.line_error {
+            details.push(("Line error".to_string(), "framing/parity error".to_string()));
+        }
+        if let Some(spread) = &row.spread {
+            details.push(("Aggregated".to_string(), format!("{} samples", spread.count)));
+        }
+        if row.dropped > 0 {
+            details.push(("Dropped before".to_string(), format!("{} frames", row.dropped)));
+        }
+        if let Some(ok) = row.checksum {
+            details.push(("Checksum".to_string(), if ok { "OK" } else { "FAIL" }.to_string()));
+        }
+        if row.matched {
+            for (i, val) in row.columns.iter().enumerate() {
+                let text = match row.spread.as_ref().and_then(|s| s.range(i)) {
+                    Some((min, max)) => {
+                        format!("{}  ({} … {})", val, format_value(min), format_value(max))
+                    }
+                    None => val.clone(),
+                };
+                details.push((self.column_title(i), text));
             }
+        }
+        details
+    }
 
-            ui.separator();
-
-            // Auto-scroll toggle
-            ui.checkbox(&mut self.auto_scroll, "Auto-scroll");
+    fn render_row_inspector(&mut self, ctx: &egui::Context) {
+        let Some(row) = &self.inspected_row else {
+            return;
+        };
+        let details = self.row_details(row);
+        let mut open = true;
 
-            // Show raw toggle
-            ui.checkbox(&mut self.show_raw, "Raw view");
+        egui::Window::new("Row Inspector")
+            .open(&mut open)
+            .resizable(true)
+            .collapsible(false)
+            .default_width(420.0)
+            .show(ctx, |ui| {
+                egui::Grid::new("inspector_grid")
+                    .num_columns(2)
+                    .spacing([8.0, 4.0])
+                    .striped(true)
+                    .show(ui, |ui| {
+                        for (name, value) in &details {
+                            ui.label(format!("{}:", name));
+                            ui.label(RichText::new(value).monospace());
+                            ui.end_row();
+                        }
+                    });
+                ui.separator();
+                ui.label("Raw line:");
+                ui.label(RichText::new(&row.raw).monospace());
+                ui.horizontal(|ui| {
+                    if ui.button("Copy line").clicked() {
+                        ui.ctx().copy_text(row.raw.clone());
+                    }
+                    if ui
+                        .button("Copy all")
+                        .on_hover_text("The details above and the raw line as text")
+                        .clicked()
+                    {
+                        let mut text: String = details
+                            .iter()
+                            .map(|(name, value)| format!("{}: {}\n", name, value))
+                            .collect();
+                        text.push_str(&row.raw);
+                        ui.ctx().copy_text(text);
+                    }
+                });
+                ui.separator();
+                if row.bytes.is_empty() {
+                    ui.label("Received bytes: not available for this row");
+                } else {
+                    ui.label(format!("Received bytes ({}):", row.bytes.len()));
+                    egui::ScrollArea::vertical()
+                        .id_salt("inspector_bytes")
+                        .max_height(200.0)
+                        .show(ui, |ui| hex_dump::show_bytes(ui, &row.bytes));
+                }
+            });
 
-            ui.separator();
+        if !open {
+            self.inspected_row = None;
+        }
+    }
 
-            // Clear
-            if ui
-                .add_sized([60.0, 28.0], egui::Button::new("Clear"))
-                .clicked()
-            {
-                self.clear_data();
-            }
+    /// Merged view of this session and session B ordered by host timestamp
+    fn render_timeline(&mut self, ui: &mut egui::Ui) {
+        let palette = Palette::new(&self.settings.colors, ui.visuals());
+        let color_a = palette.matched;
+        let color_b = egui::Color32::from_rgb(230, 180, 90);
+        let plain = self.settings.lightweight;
+        let display = self.time_display();
 
-            // Export
-            if ui
-                .add_sized([90.0, 28.0], egui::Button::new("Export CSV"))
-                .clicked()
-            {
-                let path = format!("uart_export_{}.csv", Local::now().format("%Y%m%d_%H%M%S"));
-                self.export_csv();
-                self.set_status(format!("Exported to {}", path));
+        // Both sources are already in arrival order; merge them
+        let a = self.stream();
+        let b = &self.session_b.lines;
+        let mut merged = Vec::with_capacity(a.len() + b.len());
+        let (mut ia, mut ib) = (0, 0);
+        while ia < a.len() || ib < b.len() {
+            let take_a = match (a.get(ia), b.get(ib)) {
+                (Some(ra), Some(lb)) => ra.time <= lb.time,
+                (Some(_), None) => true,
+                _ => false,
+            };
+            if take_a {
+                if a[ia].kind != RowKind::Dropped {
+                    merged.push((false, a[ia].time, a[ia].raw.as_str()));
+                }
+                ia += 1;
+            } else {
+                merged.push((true, b[ib].time, b[ib].text.as_str()));
+                ib += 1;
             }
-        });
-    }
-
-    fn render_data_table(&mut self, ui: &mut egui::Ui) {
-        let show_ts = self.settings.show_timestamp;
-        let has_regex = self.compiled_regex.is_some();
-        let num_cols = self.num_columns;
-        let default_text_color = ui.visuals().text_color();
+        }
 
-        // Build column layout
         let mut builder = TableBuilder::new(ui)
-            .striped(true)
+            .striped(!plain)
             .resizable(true)
             .cell_layout(egui::Layout::left_to_right(egui::Align::Center))
+            .column(Column::initial(110.0).at_least(80.0))
+            .column(Column::initial(30.0).at_least(30.0))
+            .column(Column::initial(80.0).at_least(60.0))
+            .column(Column::remainder().at_least(100.0))
             .auto_shrink(false);
-
         if self.auto_scroll {
-            builder = builder.scroll_to_row(self.rows.len().saturating_sub(1), Some(egui::Align::BOTTOM));
-        }
-
-        // Timestamp column
-        if show_ts {
-            builder = builder.column(Column::initial(110.0).at_least(80.0).resizable(true));
-        }
-
-        if has_regex && num_cols > 0 {
-            for _ in 0..num_cols {
-                builder = builder.column(Column::initial(120.0).at_least(60.0).resizable(true));
-            }
-        } else {
-            // Raw data column
-            builder = builder.column(Column::remainder().at_least(100.0));
+            builder = builder.scroll_to_row(merged.len().saturating_sub(1), Some(egui::Align::BOTTOM));
         }
 
-        let table = builder.header(22.0, |mut header| {
-            if show_ts {
-                header.col(|ui| {
-                    ui.strong("Timestamp");
-                });
-            }
-            if has_regex && num_cols > 0 {
-                for i in 0..num_cols {
+        builder
+            .header(22.0, |mut header| {
+                for title in ["Timestamp", "Src", "Δt (ms)", "Data"] {
                     header.col(|ui| {
-                        ui.strong(self.column_header(i));
+                        ui.strong(title);
                     });
                 }
-            } else {
-                header.col(|ui| {
-                    ui.strong("Data");
+            })
+            .body(|body| {
+                body.rows(18.0, merged.len(), |mut row| {
+                    let idx = row.index();
+                    let (is_b, time, text) = merged[idx];
+                    let color = if is_b { color_b } else { color_a };
+                    row.col(|ui| {
+                        cell_label(ui, plain, palette.timestamp, &format_time(&time, &display));
+                    });
+                    row.col(|ui| {
+                        cell_label(ui, plain, color, if is_b { "B" } else { "A" });
+                    });
+                    row.col(|ui| {
+                        if idx > 0 {
+                            let dt = time.elapsed_secs() - merged[idx - 1].1.elapsed_secs();
+                            ui.label(format!("{:+.0}", dt * 1000.0));
+                        }
+                    });
+                    row.col(|ui| {
+                        cell_label(ui, plain, color, text);
+                    });
                 });
-            }
-        });
+            });
+    }
 
-        // Row count for borrow
-        let rows_len = self.rows.len();
+    fn render_raw_log(&mut self, ui: &mut egui::Ui) {
+        if let Some(zoom) = scrolled_zoom(ui, self.settings.raw_zoom) {
+            self.settings.raw_zoom = zoom;
+            self.settings.save();
+            self.set_status(format!("Raw log text size {:.0}%", zoom * 100.0));
+        }
+        let zoom = self.settings.raw_zoom;
+        let wrap = self.settings.raw_wrap;
+        let scroll = if wrap {
+            egui::ScrollArea::vertical()
+        } else {
+            egui::ScrollArea::both()
+        };
+        let scroll = scroll
+            .auto_shrink(false)
+            .stick_to_bottom(self.auto_scroll && self.paused.is_none());
+        let shown = self.raw_lines_shown();
+        let mut clicked = None;
+        let mut copy = None;
 
-        table.body(|body| {
-            body.rows(18.0, rows_len, |mut row_widget| {
-                let idx = row_widget.index();
-                if idx >= self.rows.len() {
-                    return;
+        scroll.show(ui, |ui| {
+            if !wrap {
+                ui.style_mut().wrap_mode = Some(egui::TextWrapMode::Extend);
+            }
+            let font_id = egui::FontId::monospace(12.0 * zoom);
+            let show_ts = self.settings.raw_timestamps;
+            let display = self.time_display();
+            let palette = Palette::new(&self.settings.colors, ui.visuals());
+            let current_match = self.search.current_pos();
+            let scroll_to = self.raw_scroll_to.take();
+            for (i, line) in self.raw_log.iter().take(shown).enumerate() {
+                let plain = ansi::strip(&line.text);
+                let level = self.levels.as_ref().and_then(|d| d.detect(&plain));
+                if level.is_some_and(|l| l < self.min_level) || !self.filter.matches(&plain) {
+                    continue;
                 }
-                let row = &self.rows[idx];
-                let color = if !row.matched {
-                    egui::Color32::from_rgb(160, 100, 100)
+                let text = if show_ts {
+                    format!("[{}] {}", format_time(&line.time, &display), line.text)
                 } else {
-                    default_text_color
+                    line.text.clone()
                 };
-
-                if show_ts {
-                    row_widget.col(|ui| {
-                        ui.colored_label(egui::Color32::from_rgb(140, 140, 200), &row.timestamp);
-                    });
+                let color = match line.dir {
+                    Some(Direction::Tx) => palette.tx,
+                    _ => level.and_then(|l| l.color()).unwrap_or(palette.raw_text),
+                };
+                let current = current_match == Some(i);
+                // Filled in below the text once its size is known
+                let background = ui.painter().add(egui::Shape::Noop);
+                // Search matches are shown on the text without its colors
+                let response = if let Some(job) = self.search.highlight(
+                    &ansi::strip(&text),
+                    &font_id,
+                    color,
+                    current,
+                ) {
+                    ui.label(job)
+                } else if matches!(plain, Cow::Owned(_)) {
+                    ui.label(ansi::layout(&text, &font_id, color))
+                } else {
+                    ui.label(RichText::new(text).font(font_id.clone()).color(color))
+                };
+                let response = response.interact(egui::Sense::click());
+                if self.raw_selected == Some(i) {
+                    let fill = ui.visuals().selection.bg_fill;
+                    let shape = egui::Shape::rect_filled(response.rect, 0.0, fill);
+                    ui.painter().set(background, shape);
                 }
-
-                if has_regex && num_cols > 0 {
-                    for col_i in 0..num_cols {
-                        row_widget.col(|ui| {
-                            let val = row.columns.get(col_i).map(String::as_str).unwrap_or("");
-                            ui.colored_label(color, val);
-                        });
+                response.context_menu(|ui| {
+                    if ui.button("Copy as text").clicked() {
+                        copy = Some((i, false));
+                        ui.close_menu();
                     }
-                } else {
-                    row_widget.col(|ui| {
-                        let val = row.columns.first().map(String::as_str).unwrap_or(&row.raw);
-                        ui.colored_label(color, val);
-                    });
+                    if ui.button("Copy as CSV").clicked() {
+                        copy = Some((i, true));
+                        ui.close_menu();
+                    }
+                });
+                if response.clicked() {
+                    clicked = Some(i);
+                }
+                if scroll_to == Some(i) {
+                    response.scroll_to_me(Some(egui::Align::Center));
                 }
+            }
+        });
+
+        if let Some(i) = clicked {
+            self.raw_selected = Some(i);
+        }
+        if let Some((i, csv)) = copy {
+            self.raw_selected = Some(i);
+            self.copy_raw_lines(ui.ctx(), &[i], csv);
+        }
+    }
+
+    /// The table next to or above the raw log, split by a draggable border
+    fn render_split(&mut self, ui: &mut egui::Ui) {
+        let size = self.settings.split_size;
+        let side_by_side = self.settings.view_layout == ViewLayout::SideBySide;
+        let panel = if side_by_side {
+            egui::SidePanel::right("raw_split_side")
+                .resizable(true)
+                .default_width(size)
+                .show_inside(ui, |ui| self.render_raw_log(ui))
+        } else {
+            egui::TopBottomPanel::bottom("raw_split_below")
+                .resizable(true)
+                .default_height(size)
+                .show_inside(ui, |ui| self.render_raw_log(ui))
+        };
+        self.render_data_table(ui);
+        // Keep the size the border was dragged to once it's let go
+        let rect = panel.response.rect;
+        let new_size = if side_by_side { rect.width() } else { rect.height() };
+        if (new_size - size).abs() >= 1.0 && !ui.input(|i| i.pointer.any_down()) {
+            self.settings.split_size = new_size;
+            self.settings.save();
+        }
+    }
+
+    /// Search bar of the table or raw log; finds the matches in the current
+    /// view and jumps to the one picked
+    fn show_search(&mut self, ui: &mut egui::Ui) {
+        if self.raw_only() {
+            let first = self.raw_log.first().map_or(0, |l| l.time.wall.timestamp_micros());
+            let shown = self.raw_lines_shown();
+            let key = (true, shown, first);
+            let lines = self.raw_log.iter().take(shown).enumerate().filter_map(|(i, line)| {
+                let plain = ansi::strip(&line.text);
+                let level = self.levels.as_ref().and_then(|d| d.detect(&plain));
+                let hidden = level.is_some_and(|l| l < self.min_level);
+                (!hidden && self.filter.matches(&plain)).then_some((i, plain))
             });
+            self.search.update(key, lines);
+        } else {
+            let key = (false, self.table_view.len(), 0);
+            let rows = &self.rows;
+            let view = &self.table_view;
+            let lines = (0..view.len())
+                .filter_map(|pos| Some((pos, Cow::Borrowed(rows[view.row(pos)?].raw.as_str()))));
+            self.search.update(key, lines);
+        }
+        let Some(pos) = self.search.show(ui) else {
+            return;
+        };
+        self.auto_scroll = false;
+        if self.raw_only() {
+            self.raw_scroll_to = Some(pos);
+        } else {
+            if let Some(row) = self.table_view.row(pos) {
+                self.selection.select(row);
+            }
+            self.scroll_to_selected = true;
+        }
+    }
+
+    /// Banner suggesting the baud scanner while the data looks garbled and
+    /// the parser matches next to nothing
+    fn show_mismatch_hint(&mut self, ui: &mut egui::Ui) {
+        let (matched, unmatched) = self.match_counts;
+        let match_rate = (self.parser.is_some() && matched + unmatched > 0)
+            .then(|| matched as f64 / (matched + unmatched) as f64);
+        if !self.mismatch_hint.is_due(match_rate) {
+            return;
+        }
+        ui.horizontal(|ui| {
+            ui.colored_label(
+                egui::Color32::YELLOW,
+                format!(
+                    "Data looks like a baud rate mismatch (mostly unreadable at {} baud) \
+                     — try auto-detect",
+                    self.settings.baud_rate
+                ),
+            );
+            if ui.button("Scan Baud").clicked() {
+                self.baud_scanner.is_open = true;
+            }
+            if ui.button("Dismiss").clicked() {
+                self.mismatch_hint.dismiss();
+            }
         });
+        ui.separator();
     }
 
-    fn render_raw_log(&mut self, ui: &mut egui::Ui) {
-        let scroll = egui::ScrollArea::vertical()
-            .auto_shrink(false)
-            .stick_to_bottom(self.auto_scroll);
+    /// The capture restored from the previous session, read-only
+    fn render_restored(&mut self, ui: &mut egui::Ui) {
+        let Some(capture) = &self.restored else {
+            return;
+        };
+        let mut close = false;
+        ui.horizontal(|ui| {
+            let started = capture.lines.first().map(|l| l.time.wall.format("%Y-%m-%d %H:%M:%S"));
+            ui.label(match started {
+                Some(started) => format!(
+                    "{}: {} lines from {} (read-only)",
+                    capture.name,
+                    capture.lines.len(),
+                    started
+                ),
+                None => format!("{}: empty", capture.name),
+            });
+            if ui.button("Close").clicked() {
+                close = true;
+            }
+        });
+        if !capture.device_info.is_empty() {
+            egui::CollapsingHeader::new("Device info")
+                .id_salt("restored_device_info")
+                .show(ui, |ui| info_grid(ui, "restored_device_info_grid", &capture.device_info));
+        }
+        ui.separator();
 
-        scroll.show(ui, |ui| {
-            let font_id = egui::FontId::monospace(12.0);
-            for line in &self.raw_log {
-                ui.label(RichText::new(line).font(font_id.clone()).color(
-                    egui::Color32::from_rgb(180, 220, 180),
-                ));
+        let font_id = egui::FontId::monospace(12.0);
+        let display = self.time_display();
+        let row_height = ui.text_style_height(&egui::TextStyle::Monospace);
+        egui::ScrollArea::vertical().auto_shrink(false).show_rows(
+            ui,
+            row_height,
+            capture.lines.len(),
+            |ui, range| {
+                for line in &capture.lines[range] {
+                    let text = format!("[{}] {}", format_time(&line.time, &display), line.text);
+                    ui.label(RichText::new(text).font(font_id.clone()).color(
+                        egui::Color32::from_rgb(180, 200, 220),
+                    ));
+                }
+            },
+        );
+
+        if close {
+            self.restored = None;
+            self.tab = DataTab::All;
+        }
+    }
+
+    fn render_macro_strip(&mut self, ui: &mut egui::Ui) {
+        let mut clicked = None;
+        ui.horizontal_wrapped(|ui| {
+            ui.label("Macros:");
+            for (i, m) in self.settings.macros.iter().enumerate() {
+                let label = match m.binding_label() {
+                    Some(binding) => format!("{}  {}", binding, m.name),
+                    None => m.name.clone(),
+                };
+                if ui.small_button(label).on_hover_text(&m.text).clicked() {
+                    clicked = Some(i);
+                }
             }
         });
+        if let Some(i) = clicked {
+            self.run_macro(i);
+        }
     }
 
     fn render_send_bar(&mut self, ui: &mut egui::Ui) {
+        if !self.settings.macros.is_empty() {
+            self.render_macro_strip(ui);
+        }
+        let flow_buttons = self.settings.flow_control == AppFlowControl::Software;
         ui.horizontal(|ui| {
             ui.label("Send:");
+            let valid = self.send_input_valid();
+            let mut reserved = if flow_buttons { 200.0 } else { 90.0 };
+            if !valid {
+                reserved += if self.tx_blocked { 120.0 } else { 24.0 };
+            }
             let resp = ui.add(
                 egui::TextEdit::singleline(&mut self.send_input)
-                    .desired_width(ui.available_width() - 90.0)
+                    .desired_width(ui.available_width() - reserved)
                     .hint_text("type data to send..."),
             );
+            if resp.changed() {
+                self.tx_blocked = false;
+            }
             if resp.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
-                self.send_input();
+                self.send_input(false);
+            }
+            if !valid {
+                ui.colored_label(egui::Color32::from_rgb(255, 170, 60), "⚠")
+                    .on_hover_text(format!(
+                        "Doesn't match the TX pattern {}",
+                        self.settings.tx_pattern
+                    ));
+            }
+            let anyway = RichText::new("Send anyway").color(egui::Color32::from_rgb(255, 100, 100));
+            if self.tx_blocked && ui.button(anyway).clicked() {
+                self.send_input(true);
             }
             if ui
                 .add_sized([80.0, 24.0], egui::Button::new("Send"))
                 .clicked()
             {
-                self.send_input();
+                self.send_input(false);
+            }
+            if flow_buttons {
+                let connected = self.serial.is_connected;
+                ui.add_enabled_ui(connected, |ui| {
+                    if ui
+                        .selectable_label(self.xoff_sent, "XOFF")
+                        .on_hover_text("Send XOFF (0x13) to pause the device")
+                        .clicked()
+                    {
+                        self.send_flow_control(true);
+                    }
+                    if ui
+                        .button("XON")
+                        .on_hover_text("Send XON (0x11) to resume the device")
+                        .clicked()
+                    {
+                        self.send_flow_control(false);
+                    }
+                });
             }
         });
     }
 
-    fn render_status_bar(&self, ui: &mut egui::Ui) {
+    fn render_status_bar(&mut self, ui: &mut egui::Ui) {
         ui.horizontal(|ui| {
             // Connection indicator
             let (dot_color, dot_label) = if self.serial.is_connected {
@@ -475,33 +3530,373 @@ impl UartConsoleApp {
             };
             ui.colored_label(msg_color, &self.status_msg);
 
+            if let Some(retry) = &self.retry {
+                ui.separator();
+                let left = retry.next_at.saturating_duration_since(Instant::now());
+                ui.label(format!(
+                    "Retry {}/{} in {:.1} s",
+                    retry.attempt,
+                    self.settings.connect_retries,
+                    left.as_secs_f32()
+                ));
+                if ui.small_button("Cancel").clicked() {
+                    self.retry = None;
+                    self.set_status("Connect retry cancelled");
+                }
+            }
+
+            self.update_view();
             ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
                 ui.label(format!("Rows: {}", self.rows.len()));
+                let serial = &self.serial;
+                let (rate, history) = if self.rate_in_lines {
+                    (serial.line_rate, &serial.line_rate_history)
+                } else {
+                    (serial.rx_rate, &serial.rate_history)
+                };
+                if sparkline(ui, history, self.rate_in_lines).clicked() {
+                    self.rate_in_lines = !self.rate_in_lines;
+                }
+                // Data stopped after flowing for a while
+                let stalled = serial.is_connected
+                    && history.len() > STALL_SECS
+                    && history.iter().rev().take(STALL_SECS).all(|&r| r == 0.0)
+                    && history.iter().any(|&r| r > 0.0);
+                if stalled {
+                    let stalled = format!("stalled {} s", STALL_SECS);
+                    ui.colored_label(egui::Color32::from_rgb(240, 200, 60), stalled);
+                } else {
+                    ui.label(format_rate(rate, self.rate_in_lines));
+                }
+                ui.separator();
+                if !self.bookmarks.is_empty() {
+                    if ui.small_button("▼").on_hover_text("Next bookmark (F2)").clicked() {
+                        self.jump_to_bookmark(true);
+                    }
+                    if ui
+                        .small_button("▲")
+                        .on_hover_text("Previous bookmark (Shift+F2)")
+                        .clicked()
+                    {
+                        self.jump_to_bookmark(false);
+                    }
+                    ui.colored_label(BOOKMARK_COLOR, format!("🔖 {}", self.bookmarks.len()));
+                    ui.separator();
+                }
+                if self.settings.seq_column.is_some() {
+                    let tracker = &self.seq_tracker;
+                    let text = format!("Dropped: {} in {} gaps", tracker.dropped, tracker.gaps);
+                    if tracker.dropped > 0 {
+                        ui.colored_label(egui::Color32::from_rgb(240, 200, 60), text);
+                    } else {
+                        ui.label(text);
+                    }
+                    ui.separator();
+                }
+                if !self.alerts.is_empty() || self.only_alerts {
+                    let text = RichText::new(format!("Alerts: {}", self.alert_hits));
+                    let text = if self.alert_hits > 0 {
+                        text.color(egui::Color32::from_rgb(255, 70, 70))
+                    } else {
+                        text
+                    };
+                    let hover = match &self.last_alert {
+                        Some(last) => format!("Last: {}\nClick to show only alert rows", last),
+                        None => "Click to show only alert rows".to_string(),
+                    };
+                    if ui
+                        .selectable_label(self.only_alerts, text)
+                        .on_hover_text(hover)
+                        .clicked()
+                    {
+                        self.only_alerts = !self.only_alerts;
+                        self.table_view.invalidate();
+                    }
+                    ui.separator();
+                }
+                if self.levels.is_some() {
+                    let mut min_level = self.min_level;
+                    egui::ComboBox::from_id_salt("min_level")
+                        .selected_text(format!("Level ≥ {}", min_level.label()))
+                        .show_ui(ui, |ui| {
+                            for level in LogLevel::all() {
+                                ui.selectable_value(&mut min_level, *level, level.label());
+                            }
+                        })
+                        .response
+                        .on_hover_text("Hide rows below this level; rows without one stay");
+                    if min_level != self.min_level {
+                        self.min_level = min_level;
+                        self.table_view.invalidate();
+                    }
+                    ui.separator();
+                }
+                if self.parser.is_some() || self.only_unmatched {
+                    let (matched, unmatched) = self.match_counts;
+                    let total = matched + unmatched;
+                    let percent = if total == 0 {
+                        100.0
+                    } else {
+                        matched as f64 * 100.0 / total as f64
+                    };
+                    let hidden = if self.settings.hide_unmatched && !self.only_unmatched {
+                        " hidden"
+                    } else {
+                        ""
+                    };
+                    let text = RichText::new(format!(
+                        "Matched: {}  Unmatched: {}{} ({:.1}%)",
+                        matched, unmatched, hidden, percent
+                    ));
+                    let text = if unmatched > 0 {
+                        text.color(egui::Color32::from_rgb(255, 100, 100))
+                    } else {
+                        text
+                    };
+                    if ui
+                        .selectable_label(self.only_unmatched, text)
+                        .on_hover_text(if self.only_unmatched {
+                            "Showing unmatched lines only; click to show all"
+                        } else {
+                            "Click to show only unmatched lines"
+                        })
+                        .clicked()
+                    {
+                        self.only_unmatched = !self.only_unmatched;
+                        self.table_view.invalidate();
+                    }
+                    if ui
+                        .checkbox(&mut self.settings.hide_unmatched, "Hide")
+                        .on_hover_text("Leave unmatched rows out of the table")
+                        .changed()
+                    {
+                        self.settings.save();
+                        self.table_view.invalidate();
+                    }
+                    ui.separator();
+                }
+                if ui
+                    .small_button("Reset")
+                    .on_hover_text("Reset byte, line and alert counters")
+                    .clicked()
+                {
+                    self.serial.reset_counters();
+                    self.alert_hits = 0;
+                    self.last_alert = None;
+                }
+                ui.label(format!(
+                    "RX: {}  TX: {}  Lines: {}",
+                    format_bytes(self.serial.rx_bytes() as f64),
+                    format_bytes(self.serial.tx_bytes() as f64),
+                    self.serial.lines
+                ));
             });
         });
     }
 }
 
 impl eframe::App for UartConsoleApp {
+    fn on_exit(&mut self, _gl: Option<&eframe::glow::Context>) {
+        // Saves the running session's capture and archive entry
+        self.finish_session();
+    }
+
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
         // Poll serial events every frame
         self.poll_serial_events();
+        if !self.serial.is_connected && self.session_stats.is_active() {
+            self.finish_session();
+        }
+        if self.serial.is_connected {
+            if let Some(command) = self.device_info.poll() {
+                self.send_text(&command);
+            }
+        }
+        self.trim_buffers();
+        let theme = self.settings.theme.preference();
+        if ctx.options(|o| o.theme_preference) != theme {
+            ctx.set_theme(theme);
+        }
+        let palette = Palette::new(&self.settings.colors, &ctx.style().visuals);
+        self.search.background = palette.highlight;
+        let committed = self.preview_backup.as_ref().unwrap_or(&self.settings);
+        self.session_b.poll(committed);
 
         // Request repaint while connected (for live data)
-        if self.serial.is_connected {
-            ctx.request_repaint_after(std::time::Duration::from_millis(30));
+        if self.serial.is_connected || self.session_b.serial.is_connected {
+            ctx.request_repaint_after(if self.settings.lightweight {
+                LIGHTWEIGHT_REPAINT_INTERVAL
+            } else {
+                REPAINT_INTERVAL
+            });
+        }
+
+        self.poll_retry(ctx);
+        self.poll_ports(ctx);
+        self.poll_alert(ctx);
+
+        // Keyboard shortcuts (macro bindings win over built-in keys)
+        if let Some(idx) = pressed_macro(ctx, &self.settings.macros) {
+            self.run_macro(idx);
+        }
+        if ctx.input(|i| i.key_pressed(egui::Key::F5)) && self.settings.has_target() {
+            self.reconnect();
+        }
+        let find = egui::KeyboardShortcut::new(egui::Modifiers::COMMAND, egui::Key::F);
+        if !self.show_terminal && ctx.input_mut(|i| i.consume_shortcut(&find)) {
+            self.search.open();
+        }
+        if ctx.input_mut(|i| i.consume_shortcut(&command_palette::SHORTCUT)) {
+            self.palette.toggle();
+        }
+        if self.palette.is_open {
+            let commands = self.palette_commands();
+            if let Some(command) = self.palette.show(ctx, &commands) {
+                self.run_command(command);
+            }
+        }
+        self.handle_table_keys(ctx);
+        self.render_row_inspector(ctx);
+        self.session_b.show(ctx);
+        self.session_summary.show(ctx);
+        if let Some((line, level)) = self.control_lines.show(ctx, self.serial.is_connected) {
+            self.serial.set_control_line(line, level);
+        }
+        let framing = self.settings.rx_framing.label();
+        if self
+            .decoder_stats
+            .show(ctx, framing, &self.serial.frame_stats())
+        {
+            self.serial.reset_frame_stats();
+        }
+        if self.histogram.is_open {
+            let col = self.settings.histogram.column;
+            // Full-rate rows, also while the table shows aggregated windows
+            let values: Vec<_> = self
+                .stream()
+                .iter()
+                .filter(|r| r.kind == RowKind::Data && r.matched)
+                .filter_map(|r| Some((r.time.wall, (*r.values.get(col)?)?)))
+                .filter(|(_, v)| v.is_finite())
+                .collect();
+            let times: Vec<_> = self
+                .selection
+                .rows()
+                .iter()
+                .filter_map(|&i| self.rows.get(i))
+                .map(|r| r.time.wall)
+                .collect();
+            let selected = (times.len() > 1)
+                .then(|| times.iter().min().copied().zip(times.iter().max().copied()))
+                .flatten();
+            let headers = self.column_headers();
+            let config = &mut self.settings.histogram;
+            if self.histogram.show(ctx, config, &headers, &values, selected) {
+                self.settings.save();
+            }
+        }
+        let connected = self.serial.is_connected;
+        if let Some(request) = self
+            .modbus
+            .show(ctx, connected)
+            .or_else(|| self.modbus.poll(connected))
+        {
+            self.echo_sent(to_hex(&request), request.clone());
+            self.serial.send(request);
         }
 
         // Handle settings window result
-        if let Some(new_settings) = self.settings_win.show(ctx) {
-            new_settings.save();
-            let needs_reconnect = self.serial.is_connected
-                && (new_settings.port_name != self.settings.port_name
-                    || new_settings.baud_rate != self.settings.baud_rate);
-            self.apply_settings(new_settings);
-            if needs_reconnect {
-                self.connect();
+        match self.settings_win.show(ctx) {
+            Some(SettingsAction::Apply(mut new_settings)) => {
+                let committed = self
+                    .preview_backup
+                    .take()
+                    .unwrap_or_else(|| self.settings.clone());
+                // edited outside the settings window
+                new_settings.dashboard = self.settings.dashboard.clone();
+                new_settings.plots = self.settings.plots.clone();
+                new_settings.histogram = self.settings.histogram.clone();
+                new_settings.table_zoom = self.settings.table_zoom;
+                new_settings.raw_zoom = self.settings.raw_zoom;
+                new_settings.view_layout = self.settings.view_layout;
+                new_settings.split_size = self.settings.split_size;
+                new_settings.save();
+                let needs_reconnect = self.serial.is_connected
+                    && (new_settings.connection != committed.connection
+                        || new_settings.port_name != committed.port_name
+                        || new_settings.sniffer_port != committed.sniffer_port
+                        || new_settings.low_latency != committed.low_latency
+                        || new_settings.rs485 != committed.rs485
+                        || new_settings.rs485_pre_delay_us != committed.rs485_pre_delay_us
+                        || new_settings.rs485_post_delay_us != committed.rs485_post_delay_us
+                        || new_settings.tx_char_delay_ms != committed.tx_char_delay_ms
+                        || new_settings.tx_line_delay_ms != committed.tx_line_delay_ms
+                        || new_settings.rx_framing != committed.rx_framing
+                        || new_settings.frame_header != committed.frame_header
+                        || new_settings.frame_length_offset != committed.frame_length_offset
+                        || new_settings.frame_length_size != committed.frame_length_size
+                        || new_settings.frame_big_endian != committed.frame_big_endian
+                        || new_settings.frame_trailer != committed.frame_trailer
+                        || new_settings.frame_crc != committed.frame_crc
+                        || new_settings.tlv_tag_size != committed.tlv_tag_size
+                        || new_settings.tlv_length_size != committed.tlv_length_size
+                        || new_settings.tlv_big_endian != committed.tlv_big_endian
+                        || new_settings.sim_mode != committed.sim_mode
+                        || new_settings.sim_rate_hz != committed.sim_rate_hz
+                        || new_settings.open_dtr != committed.open_dtr
+                        || new_settings.open_rts != committed.open_rts
+                        || new_settings.exclusive_lock != committed.exclusive_lock
+                        || new_settings.custom_divisor != committed.custom_divisor
+                        // Only set up on open, where the divisor is derived from it
+                        || (new_settings.custom_divisor
+                            && new_settings.baud_rate != committed.baud_rate));
+                // Applied to the open port so devices that reset on close keep running
+                let line_changed = new_settings.baud_rate != committed.baud_rate
+                    || new_settings.data_bits != committed.data_bits
+                    || new_settings.stop_bits != committed.stop_bits
+                    || new_settings.parity != committed.parity
+                    || new_settings.flow_control != committed.flow_control;
+                let rx_changed = new_settings.rx_line_ending != self.settings.rx_line_ending;
+                let encoding_changed = new_settings.text_encoding != self.settings.text_encoding;
+                self.apply_settings(new_settings);
+                if needs_reconnect {
+                    self.connect();
+                } else {
+                    if rx_changed {
+                        self.serial.set_rx_line_ending(self.settings.rx_line_ending.clone());
+                    }
+                    if encoding_changed {
+                        self.serial.set_encoding(self.settings.text_encoding.encoding());
+                    }
+                    if line_changed && self.serial.is_connected {
+                        self.serial.reconfigure(&self.settings);
+                        let target = self.settings.target_label();
+                        self.set_status(format!("Port reconfigured: {}", target));
+                    }
+                }
+            }
+            Some(SettingsAction::Preview(preview)) => {
+                if self.preview_backup.is_none() {
+                    self.preview_backup = Some(self.settings.clone());
+                }
+                self.preview_display(&preview);
+            }
+            Some(SettingsAction::Cancel) => {
+                if let Some(backup) = self.preview_backup.take() {
+                    self.preview_display(&backup);
+                }
             }
+            None => {}
+        }
+
+        // Handle baud scanner result
+        if let Some(baud) = self
+            .baud_scanner
+            .show(ctx, &self.settings, self.serial.is_connected)
+        {
+            self.settings.baud_rate = baud;
+            self.settings.save();
+            self.set_status(format!("Baud rate set to {}", baud));
         }
 
         // Top panel: toolbar
@@ -528,12 +3923,124 @@ impl eframe::App for UartConsoleApp {
                 ui.add_space(3.0);
             });
 
+        // Right panel: dashboard
+        if self.show_dashboard {
+            let headers = self.column_headers();
+            let latest = self.latest_values(headers.len());
+            let unit_names: Vec<String> = (0..headers.len())
+                .map(|i| {
+                    units::scale_of(&self.settings.column_scales, i)
+                        .map_or_else(String::new, |scale| scale.unit.clone())
+                })
+                .collect();
+            egui::SidePanel::right("dashboard")
+                .resizable(true)
+                .default_width(220.0)
+                .show(ctx, |ui| {
+                    let changed = self.dashboard.show(
+                        ui,
+                        &mut self.settings.dashboard,
+                        &headers,
+                        &unit_names,
+                        &latest,
+                    );
+                    if changed {
+                        self.settings.save();
+                    }
+                });
+        }
+
+        // Bottom panel: plots
+        if self.show_plots && !self.settings.lightweight {
+            let headers = self.column_headers();
+            egui::TopBottomPanel::bottom("plots")
+                .resizable(true)
+                .default_height(240.0)
+                .show(ctx, |ui| {
+                    let start = self.rows_excess();
+                    let samples: Vec<Sample> = self.rows[start..]
+                        .iter()
+                        .filter(|r| r.kind == RowKind::Data && r.matched)
+                        .map(|r| Sample {
+                            time: r.time.wall,
+                            values: &r.values,
+                            ranges: r.spread.as_ref().map_or(&[], |s| &s.ranges),
+                        })
+                        .collect();
+                    if show_plots(ui, &mut self.settings.plots, &headers, &samples) {
+                        self.settings.save();
+                    }
+                });
+        }
+
         // Central: data view
         egui::CentralPanel::default().show(ctx, |ui| {
-            if self.show_raw {
+            if !self.device_info.entries.is_empty() {
+                let can_identify = self.serial.is_connected;
+                if self.device_info.show(ui, can_identify) {
+                    self.identify_device();
+                }
+            }
+            self.show_mismatch_hint(ui);
+            let has_tabs = !self.router.tables.is_empty() || self.restored.is_some();
+            let single_view = !self.show_timeline
+                && self.settings.view_layout == ViewLayout::Table
+                && !self.show_hex
+                && !self.show_terminal;
+            if has_tabs && single_view {
+                ui.horizontal(|ui| {
+                    ui.selectable_value(&mut self.tab, DataTab::All, "All");
+                    for (i, table) in self.router.tables.iter().enumerate() {
+                        let title = format!("{} ({})", table.name, table.rows.len());
+                        ui.selectable_value(&mut self.tab, DataTab::Route(i), title);
+                    }
+                    if self.restored.is_some() {
+                        ui.selectable_value(&mut self.tab, DataTab::Restored, "Previous session");
+                    }
+                });
+                ui.separator();
+            }
+            let filterable = !self.show_timeline && !self.show_terminal && !self.show_hex;
+            let layout = self.settings.view_layout;
+            if filterable && (layout != ViewLayout::Table || self.tab == DataTab::All) {
+                self.update_view();
+                let counts = (!self.raw_only()).then(|| {
+                    let total = self.rows.len() - self.rows_excess();
+                    (self.table_view.len(), total)
+                });
+                if self.filter.show(ui, counts) {
+                    self.table_view.invalidate();
+                }
+                if self.search.is_open {
+                    self.show_search(ui);
+                }
+            }
+            if self.show_timeline {
+                self.render_timeline(ui);
+            } else if self.show_terminal {
+                let encoding = self.settings.text_encoding.encoding();
+                self.terminal.show(ui, encoding);
+                let keys = self.terminal.take_output();
+                if !keys.is_empty() {
+                    self.serial.send(keys);
+                }
+            } else if self.show_hex {
+                self.hex_dump.show(ui, self.auto_scroll);
+            } else if self.raw_only() {
                 self.render_raw_log(ui);
+            } else if layout.is_split() {
+                self.render_split(ui);
             } else {
-                self.render_data_table(ui);
+                match self.tab {
+                    DataTab::Route(i) if i < self.router.tables.len() => {
+                        let table = &self.router.tables[i];
+                        let display = self.settings.show_timestamp.then(|| self.time_display());
+                        let (density, zoom) = (self.settings.density, self.settings.table_zoom);
+                        show_route_table(ui, table, display, self.auto_scroll, density, zoom);
+                    }
+                    DataTab::Restored if self.restored.is_some() => self.render_restored(ui),
+                    _ => self.render_data_table(ui),
+                }
             }
         });
     }
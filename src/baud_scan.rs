@@ -0,0 +1,370 @@
+use std::io::{self, Read};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use encoding_rs::Encoding;
+
+use crate::settings::Settings;
+
+/// Baud rates tried by the scanner, most common first
+pub const SCAN_BAUD_RATES: &[u32] = &[
+    115200, 9600, 57600, 38400, 19200, 230400, 460800, 921600, 4800, 2400, 1200,
+];
+
+/// Minimum number of bytes for a rate to be considered a candidate
+const MIN_BYTES: usize = 16;
+
+#[derive(Debug, Clone)]
+pub struct ScanResult {
+    pub baud: u32,
+    pub bytes: usize,
+    /// Fraction of printable ASCII bytes (0.0 - 1.0)
+    pub score: f64,
+}
+
+enum ScanEvent {
+    Trying(u32),
+    Result(ScanResult),
+    Error(String),
+    Done,
+}
+
+/// Fraction of bytes that are printable ASCII or common whitespace
+pub fn printable_ratio(data: &[u8]) -> f64 {
+    if data.is_empty() {
+        return 0.0;
+    }
+    let printable = data
+        .iter()
+        .filter(|&&b| (0x20..0x7f).contains(&b) || matches!(b, b'\t' | b'\r' | b'\n'))
+        .count();
+    printable as f64 / data.len() as f64
+}
+
+/// Received bytes judged by the mismatch hint
+const HINT_WINDOW: usize = 512;
+
+/// Share of unreadable characters above which data looks garbled
+const GARBLED_RATIO: f64 = 0.1;
+
+/// Watches the received bytes for the noise a wrong baud rate produces:
+/// control characters and, in multi-byte encodings, invalid sequences. New
+/// users otherwise just see a table of replacement characters.
+#[derive(Default)]
+pub struct MismatchHint {
+    /// The latest `HINT_WINDOW` bytes
+    recent: Vec<u8>,
+    garbled: bool,
+    dismissed: bool,
+}
+
+impl MismatchHint {
+    /// Starts over, e.g. after reconnecting at another rate
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+
+    pub fn push(&mut self, data: &[u8], encoding: &'static Encoding) {
+        self.recent.extend_from_slice(data);
+        if self.recent.len() > HINT_WINDOW {
+            let excess = self.recent.len() - HINT_WINDOW;
+            self.recent.drain(..excess);
+        }
+        self.garbled = self.recent.len() == HINT_WINDOW
+            && garbled_ratio(&self.recent, encoding) > GARBLED_RATIO;
+    }
+
+    /// Whether to show the hint; `match_rate` is the share of rows the parser
+    /// matched, None without a parser
+    pub fn is_due(&self, match_rate: Option<f64>) -> bool {
+        self.garbled && !self.dismissed && match_rate.is_none_or(|r| r < 0.05)
+    }
+
+    pub fn dismiss(&mut self) {
+        self.dismissed = true;
+    }
+}
+
+/// Share of decoded characters that are replacement characters or control
+/// characters other than whitespace
+fn garbled_ratio(data: &[u8], encoding: &'static Encoding) -> f64 {
+    let (text, _) = encoding.decode_without_bom_handling(data);
+    let (mut total, mut bad) = (0, 0);
+    for c in text.chars() {
+        total += 1;
+        if c == char::REPLACEMENT_CHARACTER || (c.is_control() && !matches!(c, '\t' | '\r' | '\n'))
+        {
+            bad += 1;
+        }
+    }
+    if total == 0 {
+        return 0.0;
+    }
+    bad as f64 / total as f64
+}
+
+// Baud scanner window UI state
+pub struct BaudScanner {
+    pub is_open: bool,
+    pub dwell_secs: f64,
+    pub results: Vec<ScanResult>,
+    current: Option<u32>,
+    error: Option<String>,
+    event_rx: Option<mpsc::Receiver<ScanEvent>>,
+    cancel: Arc<AtomicBool>,
+}
+
+impl BaudScanner {
+    pub fn new() -> Self {
+        Self {
+            is_open: false,
+            dwell_secs: 2.0,
+            results: Vec::new(),
+            current: None,
+            error: None,
+            event_rx: None,
+            cancel: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.event_rx.is_some()
+    }
+
+    pub fn start(&mut self, settings: &Settings) {
+        if self.is_running() {
+            return;
+        }
+        self.results.clear();
+        self.error = None;
+        self.current = None;
+
+        if settings.port_name.is_empty() {
+            self.error = Some("No port selected".to_string());
+            return;
+        }
+
+        let (tx, rx) = mpsc::channel();
+        self.event_rx = Some(rx);
+        self.cancel = Arc::new(AtomicBool::new(false));
+
+        let cancel = self.cancel.clone();
+        let settings = settings.clone();
+        let dwell = Duration::from_secs_f64(self.dwell_secs);
+        thread::spawn(move || {
+            run_scan(&settings, dwell, &cancel, &tx);
+            let _ = tx.send(ScanEvent::Done);
+        });
+    }
+
+    pub fn cancel(&mut self) {
+        self.cancel.store(true, Ordering::Relaxed);
+    }
+
+    /// Best candidate so far: highest printable ratio among rates that produced data
+    pub fn best(&self) -> Option<&ScanResult> {
+        self.results
+            .iter()
+            .filter(|r| r.bytes >= MIN_BYTES)
+            .max_by(|a, b| {
+                a.score
+                    .partial_cmp(&b.score)
+                    .unwrap_or(std::cmp::Ordering::Equal)
+                    .then(a.bytes.cmp(&b.bytes))
+            })
+    }
+
+    fn poll(&mut self) {
+        let Some(rx) = &self.event_rx else {
+            return;
+        };
+        let mut done = false;
+        while let Ok(ev) = rx.try_recv() {
+            match ev {
+                ScanEvent::Trying(baud) => self.current = Some(baud),
+                ScanEvent::Result(res) => self.results.push(res),
+                ScanEvent::Error(e) => self.error = Some(e),
+                ScanEvent::Done => done = true,
+            }
+        }
+        if done {
+            self.event_rx = None;
+            self.current = None;
+        }
+    }
+
+    /// Renders the scanner window. Returns Some(baud) if the user picked a rate.
+    pub fn show(
+        &mut self,
+        ctx: &egui::Context,
+        settings: &Settings,
+        connected: bool,
+    ) -> Option<u32> {
+        self.poll();
+        if !self.is_open {
+            if self.is_running() {
+                self.cancel();
+            }
+            return None;
+        }
+        if self.is_running() {
+            ctx.request_repaint_after(Duration::from_millis(100));
+        }
+
+        let mut result = None;
+        let mut open = self.is_open;
+
+        egui::Window::new("Baud Rate Scanner")
+            .open(&mut open)
+            .resizable(false)
+            .collapsible(false)
+            .default_width(320.0)
+            .show(ctx, |ui| {
+                ui.label(format!(
+                    "Port: {}",
+                    if settings.port_name.is_empty() {
+                        "-- none --"
+                    } else {
+                        &settings.port_name
+                    }
+                ));
+                ui.horizontal(|ui| {
+                    ui.label("Time per rate (s):");
+                    ui.add_enabled(
+                        !self.is_running(),
+                        egui::DragValue::new(&mut self.dwell_secs)
+                            .range(0.5..=10.0)
+                            .speed(0.1),
+                    );
+                });
+
+                ui.horizontal(|ui| {
+                    if self.is_running() {
+                        if ui.button("Cancel").clicked() {
+                            self.cancel();
+                        }
+                        ui.spinner();
+                        if let Some(baud) = self.current {
+                            ui.label(format!("Trying {} baud...", baud));
+                        }
+                    } else if ui
+                        .add_enabled(!connected, egui::Button::new("Start scan"))
+                        .on_disabled_hover_text("Disconnect first")
+                        .clicked()
+                    {
+                        self.start(settings);
+                    }
+                });
+
+                if let Some(err) = &self.error {
+                    ui.colored_label(egui::Color32::RED, err);
+                }
+
+                if !self.results.is_empty() {
+                    ui.separator();
+                    let best_baud = self.best().map(|b| b.baud);
+                    egui::Grid::new("baud_scan_grid")
+                        .num_columns(3)
+                        .striped(true)
+                        .show(ui, |ui| {
+                            ui.strong("Baud");
+                            ui.strong("Bytes");
+                            ui.strong("Printable");
+                            ui.end_row();
+                            for res in &self.results {
+                                let text = res.baud.to_string();
+                                if Some(res.baud) == best_baud {
+                                    ui.colored_label(egui::Color32::GREEN, text);
+                                } else {
+                                    ui.label(text);
+                                }
+                                ui.label(res.bytes.to_string());
+                                ui.label(format!("{:.0}%", res.score * 100.0));
+                                ui.end_row();
+                            }
+                        });
+                }
+
+                if !self.is_running() && !self.results.is_empty() {
+                    ui.separator();
+                    match self.best() {
+                        Some(best) => {
+                            let baud = best.baud;
+                            ui.horizontal(|ui| {
+                                ui.label(format!("Best candidate: {} baud", baud));
+                                if ui.button("Use").clicked() {
+                                    result = Some(baud);
+                                }
+                            });
+                        }
+                        None => {
+                            ui.colored_label(egui::Color32::YELLOW, "No data received at any rate");
+                        }
+                    }
+                }
+            });
+
+        self.is_open = open;
+        result
+    }
+}
+
+fn run_scan(
+    settings: &Settings,
+    dwell: Duration,
+    cancel: &AtomicBool,
+    tx: &mpsc::Sender<ScanEvent>,
+) {
+    let mut port = match serialport::new(&settings.port_name, SCAN_BAUD_RATES[0])
+        .data_bits(settings.data_bits.to_serial())
+        .stop_bits(settings.stop_bits.to_serial())
+        .parity(settings.parity.to_serial())
+        .flow_control(settings.flow_control.to_serial())
+        .timeout(Duration::from_millis(50))
+        .open()
+    {
+        Ok(p) => p,
+        Err(e) => {
+            let _ = tx.send(ScanEvent::Error(format!(
+                "Failed to open {}: {}",
+                settings.port_name, e
+            )));
+            return;
+        }
+    };
+
+    let mut read_buf = [0u8; 256];
+    for &baud in SCAN_BAUD_RATES {
+        if cancel.load(Ordering::Relaxed) {
+            return;
+        }
+        let _ = tx.send(ScanEvent::Trying(baud));
+        if let Err(e) = port.set_baud_rate(baud) {
+            let _ = tx.send(ScanEvent::Error(format!("Cannot set {} baud: {}", baud, e)));
+            continue;
+        }
+        // Drop bytes received at the previous rate
+        let _ = port.clear(serialport::ClearBuffer::Input);
+
+        let mut data = Vec::new();
+        let start = Instant::now();
+        while start.elapsed() < dwell && !cancel.load(Ordering::Relaxed) {
+            match port.read(&mut read_buf) {
+                Ok(n) => data.extend_from_slice(&read_buf[..n]),
+                Err(ref e) if e.kind() == io::ErrorKind::TimedOut => {}
+                Err(e) => {
+                    let _ = tx.send(ScanEvent::Error(format!("Read error: {}", e)));
+                    return;
+                }
+            }
+        }
+
+        let _ = tx.send(ScanEvent::Result(ScanResult {
+            baud,
+            bytes: data.len(),
+            score: printable_ratio(&data),
+        }));
+    }
+}
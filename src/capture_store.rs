@@ -0,0 +1,129 @@
+// Saved session buffers: the lines received during each session are written
+// to `uart_console_captures/` next to the executable when the session ends
+// or the app closes, keeping the newest few. The newest capture can be
+// reopened read-only at startup, so closing the app doesn't lose it. The
+// device info responses captured for the session head the file.
+
+use std::io::{BufRead, Write};
+use std::path::PathBuf;
+
+use chrono::{DateTime, Local};
+
+use crate::device_info::InfoEntry;
+use crate::session::TimedLine;
+use crate::timestamp::Timestamp;
+
+/// A capture loaded back from disk
+pub struct SavedCapture {
+    /// File name without extension, e.g. "capture_20260301_091500"
+    pub name: String,
+    pub lines: Vec<TimedLine>,
+    pub device_info: Vec<InfoEntry>,
+}
+
+/// Starts a device info line: "#\t<command>\t<response line>"
+const INFO_PREFIX: &str = "#\t";
+
+fn capture_dir() -> PathBuf {
+    let mut path = std::env::current_exe().unwrap_or_default();
+    path.pop();
+    path.push("uart_console_captures");
+    path
+}
+
+/// Saved captures, oldest first
+fn list_captures() -> Vec<PathBuf> {
+    let Ok(entries) = std::fs::read_dir(capture_dir()) else {
+        return Vec::new();
+    };
+    let mut paths: Vec<PathBuf> = entries
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|p| {
+            p.file_name()
+                .and_then(|n| n.to_str())
+                .is_some_and(|n| n.starts_with("capture_") && n.ends_with(".log"))
+        })
+        .collect();
+    // The timestamped names sort chronologically
+    paths.sort();
+    paths
+}
+
+/// Writes `lines` as a new capture and deletes all but the newest `keep`.
+/// Each line is stored as "<RFC 3339 time>\t<text>", after one line per
+/// device info response line.
+pub fn save_capture(
+    lines: &[TimedLine],
+    device_info: &[InfoEntry],
+    keep: usize,
+) -> std::io::Result<PathBuf> {
+    let dir = capture_dir();
+    std::fs::create_dir_all(&dir)?;
+    let start = lines.first().map_or_else(Local::now, |l| l.time.wall);
+    let path = dir.join(format!("capture_{}.log", start.format("%Y%m%d_%H%M%S")));
+    let mut file = std::io::BufWriter::new(std::fs::File::create(&path)?);
+    for entry in device_info {
+        if entry.response.is_empty() {
+            writeln!(file, "{}{}\t", INFO_PREFIX, entry.command)?;
+        }
+        for response in &entry.response {
+            writeln!(file, "{}{}\t{}", INFO_PREFIX, entry.command, response)?;
+        }
+    }
+    for line in lines {
+        writeln!(file, "{}\t{}", line.time.wall.to_rfc3339(), line.text)?;
+    }
+    file.flush()?;
+
+    let captures = list_captures();
+    for old in &captures[..captures.len().saturating_sub(keep)] {
+        let _ = std::fs::remove_file(old);
+    }
+    Ok(path)
+}
+
+/// The newest saved capture, if any. Lines that don't parse are skipped.
+pub fn load_latest_capture() -> std::io::Result<Option<SavedCapture>> {
+    let Some(path) = list_captures().pop() else {
+        return Ok(None);
+    };
+    let file = std::io::BufReader::new(std::fs::File::open(&path)?);
+    let mut lines = Vec::new();
+    let mut device_info: Vec<InfoEntry> = Vec::new();
+    for line in file.lines() {
+        let line = line?;
+        if let Some(info) = line.strip_prefix(INFO_PREFIX) {
+            let (command, response) = info.split_once('\t').unwrap_or((info, ""));
+            let response = (!response.is_empty()).then(|| response.to_string());
+            // Consecutive lines of the same command are one response
+            match device_info.last_mut() {
+                Some(entry) if entry.command == command && response.is_some() => {
+                    entry.response.extend(response);
+                }
+                _ => device_info.push(InfoEntry {
+                    command: command.to_string(),
+                    response: response.into_iter().collect(),
+                }),
+            }
+            continue;
+        }
+        let Some((time, text)) = line.split_once('\t') else {
+            continue;
+        };
+        if let Ok(time) = DateTime::parse_from_rfc3339(time) {
+            lines.push(TimedLine {
+                time: Timestamp::from_wall(time.with_timezone(&Local)),
+                text: text.to_string(),
+                dir: None,
+            });
+        }
+    }
+    let name = path
+        .file_stem()
+        .map_or_else(String::new, |n| n.to_string_lossy().into_owned());
+    Ok(Some(SavedCapture {
+        name,
+        lines,
+        device_info,
+    }))
+}
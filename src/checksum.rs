@@ -0,0 +1,330 @@
+// Checksums used for the trailing CRC of binary frames and for the checksum
+// column that validates each received line or frame.
+
+use serde::{Deserialize, Serialize};
+
+use crate::framing::parse_hex;
+use crate::settings::{RxFraming, Settings};
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub enum Checksum {
+    #[default]
+    None,
+    /// XOR of all bytes, as in NMEA 0183
+    Xor,
+    Crc8,
+    Crc16Modbus,
+    Crc16Ccitt,
+    Crc32,
+}
+
+impl Checksum {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Checksum::None => "None",
+            Checksum::Xor => "XOR",
+            Checksum::Crc8 => "CRC-8",
+            Checksum::Crc16Modbus => "CRC-16/MODBUS (LE)",
+            Checksum::Crc16Ccitt => "CRC-16/CCITT (BE)",
+            Checksum::Crc32 => "CRC-32 (LE)",
+        }
+    }
+    pub fn all() -> &'static [Checksum] {
+        &[
+            Checksum::None,
+            Checksum::Xor,
+            Checksum::Crc8,
+            Checksum::Crc16Modbus,
+            Checksum::Crc16Ccitt,
+            Checksum::Crc32,
+        ]
+    }
+
+    /// Size of the checksum in bytes
+    pub fn size(&self) -> usize {
+        match self {
+            Checksum::None => 0,
+            Checksum::Xor | Checksum::Crc8 => 1,
+            Checksum::Crc16Modbus | Checksum::Crc16Ccitt => 2,
+            Checksum::Crc32 => 4,
+        }
+    }
+
+    pub fn compute(&self, data: &[u8]) -> u32 {
+        match self {
+            Checksum::None => 0,
+            Checksum::Xor => data.iter().fold(0, |acc, &b| acc ^ b) as u32,
+            Checksum::Crc8 => crc8(data) as u32,
+            Checksum::Crc16Modbus => crc16_modbus(data) as u32,
+            Checksum::Crc16Ccitt => crc16_ccitt(data) as u32,
+            Checksum::Crc32 => crc32(data),
+        }
+    }
+
+    /// True if `frame` ends with a valid checksum of the bytes before it.
+    /// CCITT is conventionally sent big-endian, the others little-endian.
+    pub fn matches_trailing(&self, frame: &[u8]) -> bool {
+        let size = self.size();
+        if size == 0 {
+            return true;
+        }
+        if frame.len() < size {
+            return false;
+        }
+        let (data, tail) = frame.split_at(frame.len() - size);
+        let fold = |acc: u32, &b: &u8| (acc << 8) | b as u32;
+        let received = if *self == Checksum::Crc16Ccitt {
+            tail.iter().fold(0, fold)
+        } else {
+            tail.iter().rev().fold(0, fold)
+        };
+        self.compute(data) == received
+    }
+}
+
+/// Where the checksum of a row is and which part of it is covered
+pub struct LineChecksum {
+    kind: Checksum,
+    /// Coverage starts after the first occurrence (empty = line start)
+    start: String,
+    /// The checksum follows the last occurrence as hex digits (empty = the
+    /// last hex digits of the line)
+    separator: String,
+    /// Rows are hex frames; the checksum is the trailing bytes
+    frames: bool,
+    /// Leading frame bytes not covered (e.g. a sync header)
+    skip: usize,
+}
+
+impl LineChecksum {
+    /// None when no checksum column is configured
+    pub fn from_settings(settings: &Settings) -> Option<Self> {
+        if settings.checksum == Checksum::None {
+            return None;
+        }
+        Some(Self {
+            kind: settings.checksum.clone(),
+            start: settings.checksum_start.clone(),
+            separator: settings.checksum_separator.clone(),
+            frames: settings.rx_framing != RxFraming::Lines,
+            skip: settings.checksum_skip,
+        })
+    }
+
+    /// Whether the row's checksum is valid; None if the row has no checksum
+    pub fn check(&self, line: &str) -> Option<bool> {
+        if self.frames {
+            let bytes = parse_hex(line).ok()?;
+            let covered = bytes.get(self.skip..).filter(|b| !b.is_empty())?;
+            return Some(self.kind.matches_trailing(covered));
+        }
+        let body = if self.start.is_empty() {
+            line
+        } else {
+            &line[line.find(&self.start)? + self.start.len()..]
+        };
+        let body = body.trim_end();
+        let (data, value) = if self.separator.is_empty() {
+            let split = body.len().checked_sub(self.kind.size() * 2)?;
+            if !body.is_char_boundary(split) {
+                return Some(false);
+            }
+            body.split_at(split)
+        } else {
+            let pos = body.rfind(&self.separator)?;
+            (&body[..pos], body[pos + self.separator.len()..].trim())
+        };
+        let value = u32::from_str_radix(value, 16).ok();
+        Some(value == Some(self.kind.compute(data.as_bytes())))
+    }
+}
+
+/// CRC-8 (poly 0x07, init 0x00)
+fn crc8(data: &[u8]) -> u8 {
+    let mut crc = 0u8;
+    for &b in data {
+        crc ^= b;
+        for _ in 0..8 {
+            crc = if crc & 0x80 != 0 {
+                (crc << 1) ^ 0x07
+            } else {
+                crc << 1
+            };
+        }
+    }
+    crc
+}
+
+/// CRC-16/MODBUS (reflected poly 0xA001, init 0xFFFF)
+fn crc16_modbus(data: &[u8]) -> u16 {
+    let mut crc = 0xFFFFu16;
+    for &b in data {
+        crc ^= b as u16;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ 0xA001
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    crc
+}
+
+/// CRC-16/CCITT-FALSE (poly 0x1021, init 0xFFFF)
+fn crc16_ccitt(data: &[u8]) -> u16 {
+    let mut crc = 0xFFFFu16;
+    for &b in data {
+        crc ^= (b as u16) << 8;
+        for _ in 0..8 {
+            crc = if crc & 0x8000 != 0 {
+                (crc << 1) ^ 0x1021
+            } else {
+                crc << 1
+            };
+        }
+    }
+    crc
+}
+
+/// CRC-32 as used by Ethernet and zlib
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = 0xFFFF_FFFFu32;
+    for &b in data {
+        crc ^= b as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ 0xEDB8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+/// Settings window section for the checksum column
+pub fn checksum_editor(ui: &mut egui::Ui, settings: &mut Settings) {
+    egui::Grid::new("checksum_grid")
+        .num_columns(2)
+        .spacing([8.0, 4.0])
+        .show(ui, |ui| {
+            ui.label("Algorithm:");
+            egui::ComboBox::from_id_salt("checksum_combo")
+                .selected_text(settings.checksum.label())
+                .width(170.0)
+                .show_ui(ui, |ui| {
+                    for c in Checksum::all() {
+                        ui.selectable_value(&mut settings.checksum, c.clone(), c.label());
+                    }
+                });
+            ui.end_row();
+
+            if settings.checksum == Checksum::None {
+                return;
+            }
+            if settings.rx_framing == RxFraming::Lines {
+                ui.label("Covered text starts after:");
+                ui.add(
+                    egui::TextEdit::singleline(&mut settings.checksum_start)
+                        .hint_text("e.g. $, empty = line start")
+                        .desired_width(140.0),
+                );
+                ui.end_row();
+
+                ui.label("Checksum (hex) follows:");
+                ui.add(
+                    egui::TextEdit::singleline(&mut settings.checksum_separator)
+                        .hint_text("e.g. *, empty = line end")
+                        .desired_width(140.0),
+                );
+                ui.end_row();
+            } else {
+                ui.label("Uncovered leading bytes:");
+                ui.add(
+                    egui::DragValue::new(&mut settings.checksum_skip)
+                        .range(0..=64)
+                        .suffix(" bytes"),
+                );
+                ui.end_row();
+            }
+        });
+    if settings.checksum != Checksum::None {
+        ui.label(if settings.rx_framing == RxFraming::Lines {
+            "The covered text ends where the checksum starts."
+        } else {
+            "The checksum is the last bytes of each frame."
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CHECK: &[u8] = b"123456789";
+
+    #[test]
+    fn check_values() {
+        // The "check" values of the CRC catalogue for "123456789"
+        assert_eq!(Checksum::Crc8.compute(CHECK), 0xF4);
+        assert_eq!(Checksum::Crc16Modbus.compute(CHECK), 0x4B37);
+        assert_eq!(Checksum::Crc16Ccitt.compute(CHECK), 0x29B1);
+        assert_eq!(Checksum::Crc32.compute(CHECK), 0xCBF4_3926);
+        assert_eq!(Checksum::Xor.compute(CHECK), 0x31);
+        assert_eq!(Checksum::None.compute(CHECK), 0);
+    }
+
+    #[test]
+    fn trailing_byte_order() {
+        // Modbus RTU "read 10 holding registers from slave 1", CRC low byte first
+        let request = [0x01, 0x03, 0x00, 0x00, 0x00, 0x0A, 0xC5, 0xCD];
+        assert!(Checksum::Crc16Modbus.matches_trailing(&request));
+        assert!(!Checksum::Crc16Modbus
+            .matches_trailing(&[0x01, 0x03, 0x00, 0x00, 0x00, 0x0A, 0xCD, 0xC5]));
+
+        let mut frame = CHECK.to_vec();
+        frame.extend([0x29, 0xB1]);
+        assert!(Checksum::Crc16Ccitt.matches_trailing(&frame));
+        let mut frame = CHECK.to_vec();
+        frame.extend([0x26, 0x39, 0xF4, 0xCB]);
+        assert!(Checksum::Crc32.matches_trailing(&frame));
+
+        assert!(!Checksum::Crc32.matches_trailing(&[0x01]));
+        assert!(Checksum::None.matches_trailing(&[]));
+    }
+
+    #[test]
+    fn nmea_style_line() {
+        let check = LineChecksum {
+            kind: Checksum::Xor,
+            start: "$".to_string(),
+            separator: "*".to_string(),
+            frames: false,
+            skip: 0,
+        };
+        assert_eq!(
+            check.check("$GPGLL,4916.45,N,12311.12,W,225444,A,*1D"),
+            Some(true)
+        );
+        assert_eq!(
+            check.check("$GPGLL,4916.45,N,12311.12,W,225444,A,*1E"),
+            Some(false)
+        );
+        assert_eq!(check.check("GPGLL without a start marker"), None);
+    }
+
+    #[test]
+    fn hex_frame_rows() {
+        let check = LineChecksum {
+            kind: Checksum::Crc16Modbus,
+            start: String::new(),
+            separator: String::new(),
+            frames: true,
+            skip: 0,
+        };
+        assert_eq!(check.check("01 03 00 00 00 0A C5 CD"), Some(true));
+        assert_eq!(check.check("01 03 00 00 00 0B C5 CD"), Some(false));
+        assert_eq!(check.check("not hex"), None);
+    }
+}
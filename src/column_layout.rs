@@ -0,0 +1,103 @@
+// Column layout: which parsed columns the table shows and in what order,
+// independent of the capture group order. Columns are referred to by their
+// parser index, so sorting, plots, alerts and exports are not affected.
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ColumnLayout {
+    /// Column indices in display order; columns not listed follow in parser
+    /// order
+    pub order: Vec<usize>,
+    /// Column indices not shown
+    pub hidden: Vec<usize>,
+}
+
+impl ColumnLayout {
+    /// All `num_cols` columns in display order, hidden ones included
+    fn ordered(&self, num_cols: usize) -> Vec<usize> {
+        let mut ordered: Vec<usize> = Vec::with_capacity(num_cols);
+        for &col in &self.order {
+            if col < num_cols && !ordered.contains(&col) {
+                ordered.push(col);
+            }
+        }
+        ordered.extend((0..num_cols).filter(|col| !self.order.contains(col)));
+        ordered
+    }
+
+    /// Columns shown, in display order; all of them if every one is hidden
+    pub fn visible(&self, num_cols: usize) -> Vec<usize> {
+        let ordered = self.ordered(num_cols);
+        let visible: Vec<usize> = ordered
+            .iter()
+            .copied()
+            .filter(|col| !self.hidden.contains(col))
+            .collect();
+        if visible.is_empty() {
+            ordered
+        } else {
+            visible
+        }
+    }
+
+    /// Checkbox per column, dragged by its handle to reorder; returns true
+    /// when the layout changed
+    pub fn show(&mut self, ui: &mut egui::Ui, headers: &[String]) -> bool {
+        let ordered = self.ordered(headers.len());
+        let shown = self.visible(headers.len()).len();
+        let mut changed = false;
+        let mut moved = None;
+        for (pos, &col) in ordered.iter().enumerate() {
+            let id = ui.id().with(("column_layout", col));
+            let row = ui.horizontal(|ui| {
+                ui.dnd_drag_source(id, pos, |ui| {
+                    ui.label("☰");
+                })
+                .response
+                .on_hover_text("Drag to move");
+                let mut visible = !self.hidden.contains(&col);
+                // The last visible column stays
+                let can_hide = !visible || shown > 1;
+                let toggle =
+                    ui.add_enabled(can_hide, egui::Checkbox::new(&mut visible, &headers[col]));
+                if toggle.changed() {
+                    if visible {
+                        self.hidden.retain(|&c| c != col);
+                    } else {
+                        self.hidden.push(col);
+                    }
+                    changed = true;
+                }
+            });
+            let response = row.response;
+            if let Some(from) = response.dnd_hover_payload::<usize>() {
+                // Line where the dragged column would go
+                let y = if *from < pos {
+                    response.rect.bottom()
+                } else {
+                    response.rect.top()
+                };
+                let stroke = ui.visuals().selection.stroke;
+                ui.painter().hline(response.rect.x_range(), y, stroke);
+            }
+            if let Some(from) = response.dnd_release_payload::<usize>() {
+                moved = Some((*from, pos));
+            }
+        }
+        if let Some((from, to)) = moved.filter(|(from, to)| from != to) {
+            let mut order = ordered;
+            let col = order.remove(from);
+            order.insert(to, col);
+            self.order = order;
+            changed = true;
+        }
+        ui.separator();
+        if ui.button("Reset").clicked() {
+            *self = ColumnLayout::default();
+            changed = true;
+        }
+        changed
+    }
+}
@@ -0,0 +1,77 @@
+// Column types: parsed columns marked int, float or hex are converted to
+// numbers once when the row is received. Typed cells are right-aligned,
+// plotted and checked against alarms by value and exported as plain numbers.
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ColumnType {
+    Text,
+    Int,
+    Float,
+    /// Hex digits with or without a 0x prefix
+    Hex,
+}
+
+impl ColumnType {
+    /// Parses a comma-separated type list such as "float, int, , hex"; empty
+    /// entries are text columns
+    pub fn parse_list(text: &str) -> Result<Vec<ColumnType>, String> {
+        if text.trim().is_empty() {
+            return Ok(Vec::new());
+        }
+        text.split(',')
+            .map(|name| match name.trim().to_ascii_lowercase().as_str() {
+                "" | "text" => Ok(ColumnType::Text),
+                "int" => Ok(ColumnType::Int),
+                "float" => Ok(ColumnType::Float),
+                "hex" => Ok(ColumnType::Hex),
+                other => Err(format!(
+                    "Unknown column type \"{}\" (use int, float, hex or text)",
+                    other
+                )),
+            })
+            .collect()
+    }
+
+    pub fn is_numeric(&self) -> bool {
+        *self != ColumnType::Text
+    }
+
+    /// The cell's value; None for text columns and cells that don't parse
+    pub fn convert(&self, cell: &str) -> Option<f64> {
+        let cell = cell.trim();
+        match self {
+            ColumnType::Text => None,
+            ColumnType::Int => cell.parse::<i64>().ok().map(|v| v as f64),
+            ColumnType::Float => cell.parse::<f64>().ok().filter(|v| v.is_finite()),
+            ColumnType::Hex => {
+                let digits = cell
+                    .strip_prefix("0x")
+                    .or_else(|| cell.strip_prefix("0X"))
+                    .unwrap_or(cell);
+                u64::from_str_radix(digits, 16).ok().map(|v| v as f64)
+            }
+        }
+    }
+
+    /// A converted value as written to exports (decimal, no quoting needed)
+    pub fn format(&self, value: f64) -> String {
+        match self {
+            ColumnType::Int => format!("{}", value as i64),
+            ColumnType::Hex => format!("{}", value as u64),
+            _ => value.to_string(),
+        }
+    }
+}
+
+/// Numeric value of every cell of a row. Typed columns are converted by
+/// their type; text columns count when they hold a plain decimal number.
+pub fn convert_row(types: &[ColumnType], columns: &[String]) -> Vec<Option<f64>> {
+    columns
+        .iter()
+        .enumerate()
+        .map(|(i, cell)| match types.get(i) {
+            Some(t) if t.is_numeric() => t.convert(cell),
+            _ => cell.trim().parse::<f64>().ok(),
+        })
+        .collect()
+}
@@ -0,0 +1,192 @@
+// Command palette (Ctrl+Shift+P): lists the toolbar actions, parser profiles,
+// export templates and macros by name and runs the one picked. Typing narrows
+// the list with a fuzzy match, so "expc" finds "Export CSV".
+
+use egui::{Key, Modifiers};
+
+use crate::export::ExportTemplate;
+use crate::settings::{Density, ViewLayout};
+
+pub const SHORTCUT: egui::KeyboardShortcut =
+    egui::KeyboardShortcut::new(Modifiers::COMMAND.plus(Modifiers::SHIFT), Key::P);
+
+/// Panels toggled on and off from the palette
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Panel {
+    HexDump,
+    Terminal,
+    Dashboard,
+    Plots,
+    Timeline,
+}
+
+/// What a palette entry does
+#[derive(Debug, Clone)]
+pub enum Command {
+    Connect,
+    Disconnect,
+    Reconnect,
+    FlushBuffers,
+    OpenSettings,
+    ScanBaud,
+    ControlLines,
+    DecoderStats,
+    /// Read the defmt ELF, protobuf schema and symbol file again
+    ReloadFiles,
+    Modbus,
+    Histogram,
+    SessionB,
+    Identify,
+    SelectProfile(String),
+    Export(ExportTemplate),
+    Clear,
+    TogglePause,
+    ToggleAutoScroll,
+    Find,
+    ToggleBookmarks,
+    /// Jump to the next bookmark, or the previous one if false
+    JumpToBookmark(bool),
+    SetLayout(ViewLayout),
+    SetDensity(Density),
+    ResetZoom,
+    TogglePanel(Panel),
+    RunMacro(usize),
+}
+
+#[derive(Default)]
+pub struct CommandPalette {
+    pub is_open: bool,
+    query: String,
+    /// Index into the matching entries
+    selected: usize,
+    /// Focus the query field on the next frame
+    focus: bool,
+}
+
+/// Score of `label` for a query typed as a subsequence of it, ignoring case
+/// and spaces; None when the query doesn't match. Consecutive characters and
+/// characters at the start of a word score higher.
+fn fuzzy_score(query: &str, label: &str) -> Option<i32> {
+    let label: Vec<char> = label.chars().flat_map(char::to_lowercase).collect();
+    let mut score = 0;
+    let mut pos = 0;
+    let mut prev: Option<usize> = None;
+    for q in query
+        .chars()
+        .flat_map(char::to_lowercase)
+        .filter(|c| !c.is_whitespace())
+    {
+        let found = pos + label[pos..].iter().position(|&c| c == q)?;
+        score += 1;
+        if prev.is_some_and(|p| p + 1 == found) {
+            score += 5;
+        }
+        if found == 0 || !label[found - 1].is_alphanumeric() {
+            score += 3;
+        }
+        score -= (found - pos).min(5) as i32;
+        prev = Some(found);
+        pos = found + 1;
+    }
+    Some(score)
+}
+
+impl CommandPalette {
+    pub fn toggle(&mut self) {
+        if self.is_open {
+            self.is_open = false;
+        } else {
+            self.is_open = true;
+            self.query.clear();
+            self.selected = 0;
+            self.focus = true;
+        }
+    }
+
+    /// Indices of the entries matching the query, best first
+    fn matches(&self, entries: &[(String, Command)]) -> Vec<usize> {
+        let mut scored: Vec<(i32, usize)> = entries
+            .iter()
+            .enumerate()
+            .filter_map(|(i, (label, _))| fuzzy_score(&self.query, label).map(|s| (s, i)))
+            .collect();
+        // Stable, so equal scores keep the listed order
+        scored.sort_by_key(|&(score, _)| std::cmp::Reverse(score));
+        scored.into_iter().map(|(_, i)| i).collect()
+    }
+
+    /// Palette window; returns the command picked with Enter or a click
+    pub fn show(&mut self, ctx: &egui::Context, entries: &[(String, Command)]) -> Option<Command> {
+        if !self.is_open {
+            return None;
+        }
+        let mut picked = None;
+        let mut close = false;
+        let window = egui::Window::new("Command palette")
+            .title_bar(false)
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_TOP, [0.0, 60.0])
+            .fixed_size([420.0, 0.0])
+            .show(ctx, |ui| {
+                // Taken before the field sees them so the cursor stays put
+                let (up, down, enter, escape) = ui.input_mut(|i| {
+                    (
+                        i.consume_key(Modifiers::NONE, Key::ArrowUp),
+                        i.consume_key(Modifiers::NONE, Key::ArrowDown),
+                        i.consume_key(Modifiers::NONE, Key::Enter),
+                        i.consume_key(Modifiers::NONE, Key::Escape),
+                    )
+                });
+                let field = ui.add(
+                    egui::TextEdit::singleline(&mut self.query)
+                        .hint_text("Type a command")
+                        .desired_width(f32::INFINITY),
+                );
+                if std::mem::take(&mut self.focus) {
+                    field.request_focus();
+                }
+                if field.changed() {
+                    self.selected = 0;
+                }
+                let matches = self.matches(entries);
+                let last = matches.len().saturating_sub(1);
+                if up {
+                    self.selected = self.selected.saturating_sub(1);
+                } else if down {
+                    self.selected = (self.selected + 1).min(last);
+                }
+                self.selected = self.selected.min(last);
+                if enter {
+                    picked = matches.get(self.selected).copied();
+                }
+                close = escape;
+
+                ui.separator();
+                if matches.is_empty() {
+                    ui.weak("No matching commands");
+                }
+                egui::ScrollArea::vertical()
+                    .max_height(320.0)
+                    .auto_shrink([false, true])
+                    .show(ui, |ui| {
+                        for (n, &i) in matches.iter().enumerate() {
+                            let item = ui.selectable_label(n == self.selected, &entries[i].0);
+                            if n == self.selected && (up || down) {
+                                item.scroll_to_me(None);
+                            }
+                            if item.clicked() {
+                                picked = Some(i);
+                            }
+                        }
+                    });
+            });
+        if window.is_some_and(|w| w.response.clicked_elsewhere()) {
+            close = true;
+        }
+        if picked.is_some() || close {
+            self.is_open = false;
+        }
+        picked.map(|i| entries[i].1.clone())
+    }
+}
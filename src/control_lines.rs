@@ -0,0 +1,238 @@
+use chrono::{DateTime, Local};
+use egui::Color32;
+
+/// Modem control and status lines
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ControlLine {
+    Cts,
+    Dsr,
+    Dcd,
+    Ri,
+    Rts,
+    Dtr,
+}
+
+impl ControlLine {
+    pub fn label(&self) -> &'static str {
+        match self {
+            ControlLine::Cts => "CTS",
+            ControlLine::Dsr => "DSR",
+            ControlLine::Dcd => "DCD",
+            ControlLine::Ri => "RI",
+            ControlLine::Rts => "RTS",
+            ControlLine::Dtr => "DTR",
+        }
+    }
+    pub fn all() -> &'static [ControlLine] {
+        &[
+            ControlLine::Cts,
+            ControlLine::Dsr,
+            ControlLine::Dcd,
+            ControlLine::Ri,
+            ControlLine::Rts,
+            ControlLine::Dtr,
+        ]
+    }
+    /// Lines driven by us rather than by the device
+    pub fn is_output(&self) -> bool {
+        matches!(self, ControlLine::Rts | ControlLine::Dtr)
+    }
+    fn index(&self) -> usize {
+        ControlLine::all()
+            .iter()
+            .position(|l| l == self)
+            .unwrap_or(0)
+    }
+}
+
+/// A recorded level change
+pub struct ControlEvent {
+    pub time: DateTime<Local>,
+    pub line: ControlLine,
+    pub level: bool,
+}
+
+/// Most transitions kept in the log
+const MAX_EVENTS: usize = 10_000;
+
+const HIGH_COLOR: Color32 = Color32::from_rgb(80, 200, 100);
+const LOW_COLOR: Color32 = Color32::from_rgb(110, 110, 110);
+
+/// Window with the current control line levels, RTS/DTR toggles and a
+/// timeline of transitions
+pub struct ControlLinesWindow {
+    pub is_open: bool,
+    pub events: Vec<ControlEvent>,
+    levels: [Option<bool>; 6],
+    /// Seconds of history shown in the timeline
+    window_secs: f64,
+}
+
+impl ControlLinesWindow {
+    pub fn new() -> Self {
+        Self {
+            is_open: false,
+            events: Vec::new(),
+            levels: [None; 6],
+            window_secs: 10.0,
+        }
+    }
+
+    /// Records a level reported by the I/O thread; unchanged levels are ignored
+    pub fn record(&mut self, line: ControlLine, level: bool) {
+        let slot = &mut self.levels[line.index()];
+        if *slot == Some(level) {
+            return;
+        }
+        *slot = Some(level);
+        self.events.push(ControlEvent {
+            time: Local::now(),
+            line,
+            level,
+        });
+        if self.events.len() > MAX_EVENTS {
+            let drain = self.events.len() - MAX_EVENTS;
+            self.events.drain(..drain);
+        }
+    }
+
+    /// Forgets the current levels, e.g. after disconnecting
+    pub fn reset_levels(&mut self) {
+        self.levels = [None; 6];
+    }
+
+    /// Renders the window. Returns an output line the user toggled and its new level.
+    pub fn show(&mut self, ctx: &egui::Context, connected: bool) -> Option<(ControlLine, bool)> {
+        if !self.is_open {
+            return None;
+        }
+        let mut open = self.is_open;
+        let mut toggled = None;
+
+        egui::Window::new("Control Lines")
+            .open(&mut open)
+            .default_width(520.0)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    for &line in ControlLine::all() {
+                        let level = self.levels[line.index()];
+                        let color = match level {
+                            Some(true) => HIGH_COLOR,
+                            _ => LOW_COLOR,
+                        };
+                        if line.is_output() {
+                            let text = egui::RichText::new(line.label()).color(color);
+                            if ui
+                                .add_enabled(connected, egui::Button::new(text))
+                                .on_hover_text(format!("Toggle {}", line.label()))
+                                .clicked()
+                            {
+                                toggled = Some((line, !level.unwrap_or(false)));
+                            }
+                        } else {
+                            ui.colored_label(color, line.label());
+                        }
+                    }
+                    ui.separator();
+                    ui.label("Window:");
+                    ui.add(
+                        egui::DragValue::new(&mut self.window_secs)
+                            .range(1.0..=3600.0)
+                            .suffix(" s"),
+                    );
+                    if ui.button("Clear").clicked() {
+                        self.events.clear();
+                    }
+                });
+                ui.separator();
+                self.draw_timeline(ui);
+                ui.separator();
+                egui::ScrollArea::vertical()
+                    .max_height(160.0)
+                    .stick_to_bottom(true)
+                    .show(ui, |ui| {
+                        for ev in &self.events {
+                            ui.monospace(format!(
+                                "{}  {:<3} {}",
+                                ev.time.format("%H:%M:%S%.3f"),
+                                ev.line.label(),
+                                if ev.level { "high" } else { "low" }
+                            ));
+                        }
+                    });
+            });
+
+        self.is_open = open;
+        toggled
+    }
+
+    /// One lane per line showing its level over the last `window_secs`
+    fn draw_timeline(&self, ui: &mut egui::Ui) {
+        const LANE: f32 = 18.0;
+        const LABEL_W: f32 = 36.0;
+        let lines = ControlLine::all();
+        let (rect, _) = ui.allocate_exact_size(
+            egui::vec2(ui.available_width(), LANE * lines.len() as f32),
+            egui::Sense::hover(),
+        );
+        let painter = ui.painter_at(rect);
+        painter.rect_filled(rect, 2.0, ui.visuals().extreme_bg_color);
+
+        let now = Local::now();
+        let plot_left = rect.left() + LABEL_W;
+        let plot_width = rect.width() - LABEL_W - 4.0;
+        let x_of = |t: DateTime<Local>| {
+            let age = (now - t).num_milliseconds() as f64 / 1000.0;
+            plot_left + plot_width * (1.0 - (age / self.window_secs).clamp(0.0, 1.0)) as f32
+        };
+
+        for (lane, &line) in lines.iter().enumerate() {
+            let top = rect.top() + lane as f32 * LANE;
+            let y_high = top + 4.0;
+            let y_low = top + LANE - 4.0;
+            painter.text(
+                egui::pos2(rect.left() + 4.0, top + LANE / 2.0),
+                egui::Align2::LEFT_CENTER,
+                line.label(),
+                egui::FontId::monospace(11.0),
+                ui.visuals().text_color(),
+            );
+
+            // Level at the left edge is the last change before the window
+            let cutoff = now - chrono::Duration::milliseconds((self.window_secs * 1000.0) as i64);
+            let mut level = self
+                .events
+                .iter()
+                .rev()
+                .find(|e| e.line == line && e.time < cutoff)
+                .map(|e| e.level);
+            let mut points = Vec::new();
+            let mut x = plot_left;
+            for ev in self
+                .events
+                .iter()
+                .filter(|e| e.line == line && e.time >= cutoff)
+            {
+                let ex = x_of(ev.time);
+                if let Some(l) = level {
+                    let y = if l { y_high } else { y_low };
+                    points.push(egui::pos2(x, y));
+                    points.push(egui::pos2(ex, y));
+                }
+                level = Some(ev.level);
+                x = ex;
+            }
+            if let Some(l) = level {
+                let y = if l { y_high } else { y_low };
+                points.push(egui::pos2(x, y));
+                points.push(egui::pos2(plot_left + plot_width, y));
+            }
+            let color = if line.is_output() {
+                Color32::from_rgb(230, 160, 80)
+            } else {
+                Color32::from_rgb(90, 170, 250)
+            };
+            painter.add(egui::Shape::line(points, egui::Stroke::new(1.5, color)));
+        }
+    }
+}
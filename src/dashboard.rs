@@ -0,0 +1,382 @@
+use egui::{Color32, RichText};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum WidgetKind {
+    Readout,
+    Gauge,
+    Led,
+    Bar,
+}
+
+impl WidgetKind {
+    pub fn label(&self) -> &'static str {
+        match self {
+            WidgetKind::Readout => "Readout",
+            WidgetKind::Gauge => "Gauge",
+            WidgetKind::Led => "LED",
+            WidgetKind::Bar => "Bar",
+        }
+    }
+    pub fn all() -> &'static [WidgetKind] {
+        &[
+            WidgetKind::Readout,
+            WidgetKind::Gauge,
+            WidgetKind::Led,
+            WidgetKind::Bar,
+        ]
+    }
+
+    /// Draws the value on a min..max scale
+    fn has_range(&self) -> bool {
+        matches!(self, WidgetKind::Gauge | WidgetKind::Bar)
+    }
+}
+
+/// One dashboard widget bound to a parsed column
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DashboardWidget {
+    pub kind: WidgetKind,
+    /// Index of the parsed column (0-based)
+    pub column: usize,
+    /// Text size of a readout's value
+    pub size: f32,
+    /// Gauge and bar scale
+    pub min: f64,
+    pub max: f64,
+    /// Values below `low` or above `high` are shown as an alarm
+    pub low: Option<f64>,
+    pub high: Option<f64>,
+}
+
+impl Default for DashboardWidget {
+    fn default() -> Self {
+        Self {
+            kind: WidgetKind::Readout,
+            column: 0,
+            size: 28.0,
+            min: 0.0,
+            max: 100.0,
+            low: None,
+            high: None,
+        }
+    }
+}
+
+impl DashboardWidget {
+    pub fn in_alarm(&self, value: f64) -> bool {
+        self.low.is_some_and(|low| value < low) || self.high.is_some_and(|high| value > high)
+    }
+}
+
+const OK_COLOR: Color32 = Color32::from_rgb(60, 200, 60);
+const ALARM_COLOR: Color32 = Color32::from_rgb(230, 60, 60);
+const IDLE_COLOR: Color32 = Color32::from_rgb(90, 90, 90);
+
+/// Interprets a cell as a boolean for LED widgets
+fn is_truthy(value: &str) -> bool {
+    match value.trim().to_ascii_lowercase().as_str() {
+        "1" | "true" | "on" | "high" | "ok" | "yes" => true,
+        other => other.parse::<f64>().is_ok_and(|v| v != 0.0),
+    }
+}
+
+// Dashboard panel UI state
+pub struct DashboardPanel {
+    pub editing: bool,
+}
+
+impl DashboardPanel {
+    pub fn new() -> Self {
+        Self { editing: false }
+    }
+
+    /// Renders the dashboard. `latest` holds the most recent value of each
+    /// column and `units` their units, empty if none. Returns true if the
+    /// widget configuration was changed.
+    pub fn show(
+        &mut self,
+        ui: &mut egui::Ui,
+        widgets: &mut Vec<DashboardWidget>,
+        headers: &[String],
+        units: &[String],
+        latest: &[Option<String>],
+    ) -> bool {
+        let mut changed = false;
+
+        ui.horizontal(|ui| {
+            ui.heading("Dashboard");
+            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                ui.toggle_value(&mut self.editing, "Edit");
+            });
+        });
+        ui.separator();
+
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            let mut remove = None;
+            let mut moved = None;
+            let count = widgets.len();
+            for (i, widget) in widgets.iter_mut().enumerate() {
+                let title = headers
+                    .get(widget.column)
+                    .cloned()
+                    .unwrap_or_else(|| format!("Col {}", widget.column + 1));
+                let value = latest.get(widget.column).cloned().flatten();
+                let unit = units.get(widget.column).map_or("", String::as_str);
+
+                egui::Frame::group(ui.style()).show(ui, |ui| {
+                    ui.set_width(ui.available_width());
+                    ui.label(RichText::new(&title).strong());
+                    match widget.kind {
+                        WidgetKind::Readout => render_readout(ui, widget, value.as_deref(), unit),
+                        WidgetKind::Gauge => render_gauge(ui, widget, value.as_deref()),
+                        WidgetKind::Led => render_led(ui, value.as_deref()),
+                        WidgetKind::Bar => render_bar(ui, widget, value.as_deref()),
+                    }
+
+                    if self.editing {
+                        ui.separator();
+                        changed |= render_widget_editor(ui, i, widget, headers);
+                        ui.horizontal(|ui| {
+                            if ui
+                                .add_enabled(i > 0, egui::Button::new("▲").small())
+                                .on_hover_text("Move up")
+                                .clicked()
+                            {
+                                moved = Some((i, i - 1));
+                            }
+                            if ui
+                                .add_enabled(i + 1 < count, egui::Button::new("▼").small())
+                                .on_hover_text("Move down")
+                                .clicked()
+                            {
+                                moved = Some((i, i + 1));
+                            }
+                            if ui.small_button("Remove").clicked() {
+                                remove = Some(i);
+                            }
+                        });
+                    }
+                });
+                ui.add_space(4.0);
+            }
+
+            if let Some(i) = remove {
+                widgets.remove(i);
+                changed = true;
+            }
+            if let Some((from, to)) = moved {
+                widgets.swap(from, to);
+                changed = true;
+            }
+
+            if self.editing && ui.button("Add widget").clicked() {
+                widgets.push(DashboardWidget::default());
+                changed = true;
+            }
+
+            if widgets.is_empty() && !self.editing {
+                ui.label("No widgets. Click Edit to add one.");
+            }
+        });
+
+        changed
+    }
+}
+
+fn render_readout(ui: &mut egui::Ui, widget: &DashboardWidget, value: Option<&str>, unit: &str) {
+    let text = value.unwrap_or("--");
+    let color = match value.and_then(|v| v.trim().parse::<f64>().ok()) {
+        Some(v) if widget.in_alarm(v) => ALARM_COLOR,
+        _ => ui.visuals().strong_text_color(),
+    };
+    ui.horizontal(|ui| {
+        ui.label(RichText::new(text).size(widget.size).monospace().color(color));
+        // The unit sits on the value's baseline at half its size
+        if value.is_some() && !unit.is_empty() {
+            ui.with_layout(egui::Layout::left_to_right(egui::Align::Max), |ui| {
+                ui.label(RichText::new(unit).size(widget.size * 0.5).color(color));
+            });
+        }
+    });
+}
+
+fn render_gauge(ui: &mut egui::Ui, widget: &DashboardWidget, value: Option<&str>) {
+    let number = value.and_then(|v| v.trim().parse::<f64>().ok());
+    let (rect, _) = ui.allocate_exact_size(egui::vec2(160.0, 90.0), egui::Sense::hover());
+    let painter = ui.painter_at(rect);
+
+    let center = egui::pos2(rect.center().x, rect.bottom() - 12.0);
+    let radius = 64.0;
+    let arc = |from: f64, to: f64| -> Vec<egui::Pos2> {
+        let steps = 32;
+        (0..=steps)
+            .map(|i| {
+                let t = from + (to - from) * i as f64 / steps as f64;
+                let angle = std::f64::consts::PI * (1.0 - t);
+                egui::pos2(
+                    center.x + (radius * angle.cos()) as f32,
+                    center.y - (radius * angle.sin()) as f32,
+                )
+            })
+            .collect()
+    };
+
+    painter.add(egui::Shape::line(arc(0.0, 1.0), egui::Stroke::new(10.0, IDLE_COLOR)));
+
+    if let Some(v) = number {
+        let span = widget.max - widget.min;
+        let frac = if span > 0.0 {
+            ((v - widget.min) / span).clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+        let color = if widget.in_alarm(v) { ALARM_COLOR } else { OK_COLOR };
+        painter.add(egui::Shape::line(arc(0.0, frac), egui::Stroke::new(10.0, color)));
+    }
+
+    painter.text(
+        center,
+        egui::Align2::CENTER_BOTTOM,
+        value.unwrap_or("--"),
+        egui::FontId::monospace(18.0),
+        ui.visuals().strong_text_color(),
+    );
+}
+
+fn render_bar(ui: &mut egui::Ui, widget: &DashboardWidget, value: Option<&str>) {
+    let number = value.and_then(|v| v.trim().parse::<f64>().ok());
+    let width = ui.available_width();
+    let (rect, _) = ui.allocate_exact_size(egui::vec2(width, 22.0), egui::Sense::hover());
+    let painter = ui.painter_at(rect);
+    painter.rect_filled(rect, 3.0, IDLE_COLOR);
+
+    if let Some(v) = number {
+        let span = widget.max - widget.min;
+        let frac = if span > 0.0 {
+            ((v - widget.min) / span).clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+        let color = if widget.in_alarm(v) { ALARM_COLOR } else { OK_COLOR };
+        let mut filled = rect;
+        filled.set_width(rect.width() * frac as f32);
+        painter.rect_filled(filled, 3.0, color);
+    }
+
+    painter.text(
+        rect.center(),
+        egui::Align2::CENTER_CENTER,
+        value.unwrap_or("--"),
+        egui::FontId::monospace(14.0),
+        Color32::WHITE,
+    );
+    ui.horizontal(|ui| {
+        ui.small(widget.min.to_string());
+        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+            ui.small(widget.max.to_string());
+        });
+    });
+}
+
+fn render_led(ui: &mut egui::Ui, value: Option<&str>) {
+    let lit = value.is_some_and(is_truthy);
+    ui.horizontal(|ui| {
+        let (rect, _) = ui.allocate_exact_size(egui::vec2(22.0, 22.0), egui::Sense::hover());
+        let color = match value {
+            None => IDLE_COLOR,
+            Some(_) if lit => OK_COLOR,
+            Some(_) => ALARM_COLOR,
+        };
+        ui.painter().circle_filled(rect.center(), 10.0, color);
+        ui.label(value.unwrap_or("--"));
+    });
+}
+
+fn render_widget_editor(
+    ui: &mut egui::Ui,
+    idx: usize,
+    widget: &mut DashboardWidget,
+    headers: &[String],
+) -> bool {
+    let mut changed = false;
+
+    egui::Grid::new(("dash_widget_grid", idx))
+        .num_columns(2)
+        .spacing([6.0, 4.0])
+        .show(ui, |ui| {
+            ui.label("Column:");
+            let selected = headers
+                .get(widget.column)
+                .cloned()
+                .unwrap_or_else(|| format!("Col {}", widget.column + 1));
+            egui::ComboBox::from_id_salt(("dash_col", idx))
+                .selected_text(selected)
+                .show_ui(ui, |ui| {
+                    for (col, name) in headers.iter().enumerate() {
+                        changed |= ui
+                            .selectable_value(&mut widget.column, col, name)
+                            .changed();
+                    }
+                });
+            ui.end_row();
+
+            ui.label("Type:");
+            egui::ComboBox::from_id_salt(("dash_kind", idx))
+                .selected_text(widget.kind.label())
+                .show_ui(ui, |ui| {
+                    for kind in WidgetKind::all() {
+                        changed |= ui
+                            .selectable_value(&mut widget.kind, kind.clone(), kind.label())
+                            .changed();
+                    }
+                });
+            ui.end_row();
+
+            if widget.kind == WidgetKind::Readout {
+                ui.label("Text size:");
+                changed |= ui
+                    .add(egui::DragValue::new(&mut widget.size).range(12.0..=160.0))
+                    .changed();
+                ui.end_row();
+            }
+
+            if widget.kind.has_range() {
+                ui.label("Range:");
+                ui.horizontal(|ui| {
+                    changed |= ui.add(egui::DragValue::new(&mut widget.min)).changed();
+                    ui.label("to");
+                    changed |= ui.add(egui::DragValue::new(&mut widget.max)).changed();
+                });
+                ui.end_row();
+            }
+
+            if widget.kind != WidgetKind::Led {
+                ui.label("Alarm below:");
+                changed |= optional_value(ui, &mut widget.low, widget.min);
+                ui.end_row();
+
+                ui.label("Alarm above:");
+                changed |= optional_value(ui, &mut widget.high, widget.max);
+                ui.end_row();
+            }
+        });
+
+    changed
+}
+
+fn optional_value(ui: &mut egui::Ui, value: &mut Option<f64>, initial: f64) -> bool {
+    let mut changed = false;
+    ui.horizontal(|ui| {
+        let mut enabled = value.is_some();
+        if ui.checkbox(&mut enabled, "").changed() {
+            *value = if enabled { Some(initial) } else { None };
+            changed = true;
+        }
+        if let Some(v) = value {
+            changed |= ui.add(egui::DragValue::new(v)).changed();
+        }
+    });
+    changed
+}
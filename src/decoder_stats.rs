@@ -0,0 +1,96 @@
+// Link quality of the binary frame decoders (length-prefixed, SLIP, COBS,
+// Modbus RTU): frames received, decode/CRC failures, resynchronisations and
+// the average frame size.
+
+/// Snapshot of the frame counters kept by the I/O thread
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FrameStats {
+    pub frames: u64,
+    /// Frames that failed to decode or whose checksum didn't match
+    pub errors: u64,
+    /// Decoded bytes of all frames
+    pub bytes: u64,
+    /// Times bytes were discarded to find the next frame
+    pub resyncs: u64,
+}
+
+impl FrameStats {
+    /// Share of frames with errors in percent
+    pub fn error_rate(&self) -> f64 {
+        if self.frames == 0 {
+            0.0
+        } else {
+            self.errors as f64 * 100.0 / self.frames as f64
+        }
+    }
+
+    pub fn average_size(&self) -> f64 {
+        if self.frames == 0 {
+            0.0
+        } else {
+            self.bytes as f64 / self.frames as f64
+        }
+    }
+}
+
+pub struct DecoderStatsWindow {
+    pub is_open: bool,
+}
+
+impl DecoderStatsWindow {
+    pub fn new() -> Self {
+        Self { is_open: false }
+    }
+
+    /// Renders the window. Returns true when the counters should be reset.
+    pub fn show(&mut self, ctx: &egui::Context, framing: &str, stats: &FrameStats) -> bool {
+        if !self.is_open {
+            return false;
+        }
+        let mut open = self.is_open;
+        let mut reset = false;
+
+        egui::Window::new("Decoder Status")
+            .open(&mut open)
+            .resizable(false)
+            .show(ctx, |ui| {
+                egui::Grid::new("decoder_stats_grid")
+                    .num_columns(2)
+                    .spacing([16.0, 4.0])
+                    .show(ui, |ui| {
+                        ui.label("Decoder:");
+                        ui.label(framing);
+                        ui.end_row();
+
+                        ui.label("Frames received:");
+                        ui.label(stats.frames.to_string());
+                        ui.end_row();
+
+                        ui.label("CRC/decode failures:");
+                        let text = format!("{} ({:.2} %)", stats.errors, stats.error_rate());
+                        if stats.errors > 0 {
+                            ui.colored_label(egui::Color32::from_rgb(210, 110, 230), text);
+                        } else {
+                            ui.label(text);
+                        }
+                        ui.end_row();
+
+                        ui.label("Resync events:");
+                        ui.label(stats.resyncs.to_string())
+                            .on_hover_text("Noise or broken frames skipped to find the next frame");
+                        ui.end_row();
+
+                        ui.label("Average frame size:");
+                        ui.label(format!("{:.1} bytes", stats.average_size()));
+                        ui.end_row();
+                    });
+                ui.add_space(6.0);
+                if ui.button("Reset").clicked() {
+                    reset = true;
+                }
+            });
+
+        self.is_open = open;
+        reset
+    }
+}
@@ -0,0 +1,378 @@
+// defmt log decoding: defmt firmware sends only an index into a table of
+// format strings plus the raw argument bytes. The table is read from the
+// `.defmt` section of the firmware ELF, where each symbol's name is a JSON
+// description (tag and format string) and its address is the index. Frames
+// arrive rzCOBS-encoded and 0x00-delimited (see `framing`); each decoded
+// frame becomes a log line such as "0.001234 INFO  boot ok, vbat=3.31".
+
+use std::collections::{BTreeMap, HashMap};
+
+use defmt_parser::{DisplayHint, Fragment, ParserMode, TimePrecision, Type};
+use object::{Object, ObjectSection, ObjectSymbol};
+use serde::Deserialize;
+
+/// Nested `{=?}` arguments deeper than this are taken as a corrupt frame
+const MAX_DEPTH: usize = 16;
+
+/// The JSON in a `.defmt` symbol name
+#[derive(Deserialize)]
+struct SymbolInfo {
+    tag: String,
+    data: String,
+}
+
+struct Entry {
+    tag: String,
+    format: String,
+}
+
+/// Format strings of one firmware build
+pub struct DefmtTable {
+    entries: HashMap<u16, Entry>,
+    /// Format of the timestamp following each message index, if the firmware
+    /// defines one
+    timestamp: Option<String>,
+}
+
+impl DefmtTable {
+    pub fn load(path: &str) -> Result<Self, String> {
+        let data = std::fs::read(path).map_err(|e| format!("Cannot read {}: {}", path, e))?;
+        let file = object::File::parse(&*data).map_err(|e| format!("{}: {}", path, e))?;
+        let section = file
+            .section_by_name(".defmt")
+            .ok_or_else(|| format!("{} has no .defmt section", path))?
+            .index();
+
+        let mut entries = HashMap::new();
+        let mut timestamp = None;
+        for symbol in file.symbols() {
+            let Ok(name) = symbol.name() else {
+                continue;
+            };
+            if let Some(encoding) = name.strip_prefix("_defmt_encoding_ = ") {
+                if encoding != "rzcobs" {
+                    return Err(format!(
+                        "The firmware uses the {} defmt encoding; only rzcobs can be framed \
+                         on a UART",
+                        encoding
+                    ));
+                }
+                continue;
+            }
+            if symbol.section_index() != Some(section) {
+                continue;
+            }
+            let Ok(info) = serde_json::from_str::<SymbolInfo>(name) else {
+                continue;
+            };
+            if info.tag == "defmt_timestamp" {
+                timestamp = Some(info.data);
+            } else {
+                let entry = Entry {
+                    tag: info.tag,
+                    format: info.data,
+                };
+                entries.insert(symbol.address() as u16, entry);
+            }
+        }
+        if entries.is_empty() {
+            return Err(format!("{} has no defmt format strings", path));
+        }
+        Ok(Self { entries, timestamp })
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Turns a decoded frame into a log line: timestamp, level and message
+    pub fn decode(&self, frame: &[u8]) -> Result<String, String> {
+        let mut reader = Reader {
+            bytes: frame,
+            pos: 0,
+        };
+        let entry = self.entry(reader.u16()?)?;
+        let mut line = String::new();
+        if let Some(format) = &self.timestamp {
+            line.push_str(&self.format(format, &mut reader, 0)?);
+            line.push(' ');
+        }
+        if let Some(level) = entry.tag.strip_prefix("defmt_") {
+            if matches!(level, "trace" | "debug" | "info" | "warn" | "error") {
+                line.push_str(&format!("{:<5} ", level.to_ascii_uppercase()));
+            }
+        }
+        line.push_str(&self.format(&entry.format, &mut reader, 0)?);
+        Ok(line)
+    }
+
+    fn entry(&self, index: u16) -> Result<&Entry, String> {
+        self.entries
+            .get(&index)
+            .ok_or_else(|| format!("unknown format string {:#06x} (stale ELF?)", index))
+    }
+
+    /// Reads the arguments of `format` and renders it
+    fn format(&self, format: &str, reader: &mut Reader, depth: usize) -> Result<String, String> {
+        if depth > MAX_DEPTH {
+            return Err("format nesting too deep".to_string());
+        }
+        let fragments = defmt_parser::parse(format, ParserMode::ForwardsCompatible)
+            .map_err(|e| format!("bad format string \"{}\": {}", format, e))?;
+
+        // Arguments are sent once each, in index order; bitfields of one
+        // argument share an integer wide enough for the highest bit
+        let mut types: BTreeMap<usize, Type> = BTreeMap::new();
+        for fragment in &fragments {
+            if let Fragment::Parameter(param) = fragment {
+                let ty = types.entry(param.index).or_insert_with(|| param.ty.clone());
+                if let (Type::BitField(have), Type::BitField(new)) = (&mut *ty, &param.ty) {
+                    have.end = have.end.max(new.end);
+                }
+            }
+        }
+        let mut args = HashMap::new();
+        for (index, ty) in types {
+            args.insert(index, self.read_arg(&ty, reader, depth)?);
+        }
+
+        let mut out = String::new();
+        for fragment in &fragments {
+            match fragment {
+                Fragment::Literal(text) => out.push_str(text),
+                Fragment::Parameter(param) => {
+                    if let Some(arg) = args.get(&param.index) {
+                        out.push_str(&render(arg, &param.ty, param.hint.as_ref()));
+                    }
+                }
+            }
+        }
+        Ok(out)
+    }
+
+    fn read_arg(&self, ty: &Type, reader: &mut Reader, depth: usize) -> Result<Arg, String> {
+        Ok(match ty {
+            Type::U8 => Arg::Uint(reader.uint(1)?),
+            Type::U16 => Arg::Uint(reader.uint(2)?),
+            Type::U32 | Type::Usize => Arg::Uint(reader.uint(4)?),
+            Type::U64 => Arg::Uint(reader.uint(8)?),
+            Type::U128 => Arg::Uint(reader.uint(16)?),
+            Type::I8 => Arg::Int(reader.uint(1)? as i8 as i128),
+            Type::I16 => Arg::Int(reader.uint(2)? as i16 as i128),
+            Type::I32 | Type::Isize => Arg::Int(reader.uint(4)? as i32 as i128),
+            Type::I64 => Arg::Int(reader.uint(8)? as i64 as i128),
+            Type::I128 => Arg::Int(reader.uint(16)? as i128),
+            Type::BitField(range) => {
+                let size = match range.end {
+                    0..=8 => 1,
+                    9..=16 => 2,
+                    17..=32 => 4,
+                    33..=64 => 8,
+                    _ => 16,
+                };
+                Arg::Uint(reader.uint(size)?)
+            }
+            Type::F32 => Arg::Text(f32::from_bits(reader.uint(4)? as u32).to_string()),
+            Type::F64 => Arg::Text(f64::from_bits(reader.uint(8)? as u64).to_string()),
+            Type::Bool => Arg::Bool(reader.uint(1)? != 0),
+            Type::Char => {
+                let c =
+                    char::from_u32(reader.uint(4)? as u32).unwrap_or(char::REPLACEMENT_CHARACTER);
+                Arg::Text(c.to_string())
+            }
+            Type::Str => {
+                let len = reader.uint(4)? as usize;
+                Arg::Text(String::from_utf8_lossy(reader.take(len)?).into_owned())
+            }
+            Type::IStr => Arg::Text(self.entry(reader.u16()?)?.format.clone()),
+            Type::U8Slice => {
+                let len = reader.uint(4)? as usize;
+                Arg::Bytes(reader.take(len)?.to_vec())
+            }
+            Type::U8Array(len) => Arg::Bytes(reader.take(*len)?.to_vec()),
+            Type::Debug | Type::Display => {
+                let end = reader.rest().iter().position(|&b| b == 0xFF);
+                let text = reader.take(end.ok_or("unterminated Debug/Display argument")?)?;
+                let text = String::from_utf8_lossy(text).into_owned();
+                reader.take(1)?;
+                Arg::Text(text)
+            }
+            Type::Format => {
+                let entry = self.entry(reader.u16()?)?;
+                Arg::Text(self.format(&entry.format, reader, depth + 1)?)
+            }
+            Type::FormatArray(len) => {
+                let entry = self.entry(reader.u16()?)?;
+                self.read_list(&entry.format, *len, reader, depth)?
+            }
+            Type::FormatSlice => {
+                let len = reader.uint(4)? as usize;
+                let entry = self.entry(reader.u16()?)?;
+                self.read_list(&entry.format, len, reader, depth)?
+            }
+            Type::FormatSequence => {
+                // Formats follow one after another until a zero index
+                let mut text = String::new();
+                loop {
+                    let index = reader.u16()?;
+                    if index == 0 {
+                        break;
+                    }
+                    let entry = self.entry(index)?;
+                    text.push_str(&self.format(&entry.format, reader, depth + 1)?);
+                }
+                Arg::Text(text)
+            }
+        })
+    }
+
+    /// `len` values of the same format, shown as a list
+    fn read_list(
+        &self,
+        format: &str,
+        len: usize,
+        reader: &mut Reader,
+        depth: usize,
+    ) -> Result<Arg, String> {
+        if len > reader.rest().len() {
+            return Err("list longer than the frame".to_string());
+        }
+        let items = (0..len)
+            .map(|_| self.format(format, reader, depth + 1))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Arg::Text(format!("[{}]", items.join(", "))))
+    }
+}
+
+enum Arg {
+    Uint(u128),
+    Int(i128),
+    Bool(bool),
+    Text(String),
+    Bytes(Vec<u8>),
+}
+
+fn render(arg: &Arg, ty: &Type, hint: Option<&DisplayHint>) -> String {
+    match arg {
+        Arg::Uint(value) => {
+            let value = match ty {
+                Type::BitField(range) => {
+                    let bits = range.end.saturating_sub(range.start) as u32;
+                    let mask = 1u128.checked_shl(bits).map_or(u128::MAX, |m| m - 1);
+                    (value >> range.start) & mask
+                }
+                _ => *value,
+            };
+            render_uint(value, hint)
+        }
+        Arg::Int(value) => match hint {
+            Some(DisplayHint::Hexadecimal { .. } | DisplayHint::Binary { .. }) => {
+                // Two's complement at the type's width, as Rust shows it
+                let bits = match ty {
+                    Type::I8 => 8,
+                    Type::I16 => 16,
+                    Type::I32 | Type::Isize => 32,
+                    Type::I64 => 64,
+                    _ => 128,
+                };
+                let mask = 1u128.checked_shl(bits).map_or(u128::MAX, |m| m - 1);
+                render_uint(*value as u128 & mask, hint)
+            }
+            _ => value.to_string(),
+        },
+        Arg::Bool(value) => value.to_string(),
+        Arg::Text(text) => match (ty, hint) {
+            (Type::Str | Type::IStr, Some(DisplayHint::Debug)) => format!("{:?}", text),
+            _ => text.clone(),
+        },
+        Arg::Bytes(bytes) => match hint {
+            Some(DisplayHint::Ascii) => {
+                let text: String = bytes
+                    .iter()
+                    .flat_map(|b| b.escape_ascii())
+                    .map(char::from)
+                    .collect();
+                format!("b\"{}\"", text)
+            }
+            _ => {
+                let items: Vec<String> = bytes
+                    .iter()
+                    .map(|&b| render_uint(b as u128, hint))
+                    .collect();
+                format!("[{}]", items.join(", "))
+            }
+        },
+    }
+}
+
+fn render_uint(value: u128, hint: Option<&DisplayHint>) -> String {
+    match hint {
+        // Like Rust's own `{:#04x}`, the width includes the prefix
+        Some(DisplayHint::Hexadecimal {
+            alternate,
+            uppercase,
+            zero_pad,
+        }) => match (*alternate, *uppercase) {
+            (true, true) => format!("{:#01$X}", value, *zero_pad),
+            (true, false) => format!("{:#01$x}", value, *zero_pad),
+            (false, true) => format!("{:01$X}", value, *zero_pad),
+            (false, false) => format!("{:01$x}", value, *zero_pad),
+        },
+        Some(DisplayHint::Binary {
+            alternate,
+            zero_pad,
+        }) => match alternate {
+            true => format!("{:#01$b}", value, *zero_pad),
+            false => format!("{:01$b}", value, *zero_pad),
+        },
+        Some(DisplayHint::Octal {
+            alternate,
+            zero_pad,
+        }) => match alternate {
+            true => format!("{:#01$o}", value, *zero_pad),
+            false => format!("{:01$o}", value, *zero_pad),
+        },
+        Some(DisplayHint::NoHint { zero_pad }) => format!("{:01$}", value, *zero_pad),
+        Some(
+            DisplayHint::Seconds(precision)
+            | DisplayHint::Time(precision)
+            | DisplayHint::ISO8601(precision),
+        ) => match precision {
+            TimePrecision::Micros => format!("{}.{:06}", value / 1_000_000, value % 1_000_000),
+            TimePrecision::Millis => format!("{}.{:03}", value / 1_000, value % 1_000),
+            TimePrecision::Seconds => value.to_string(),
+        },
+        _ => value.to_string(),
+    }
+}
+
+/// Cursor over a frame's argument bytes
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn rest(&self) -> &'a [u8] {
+        &self.bytes[self.pos..]
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8], String> {
+        let rest = self.rest();
+        if rest.len() < len {
+            return Err("frame too short".to_string());
+        }
+        self.pos += len;
+        Ok(&rest[..len])
+    }
+
+    fn u16(&mut self) -> Result<u16, String> {
+        Ok(self.uint(2)? as u16)
+    }
+
+    /// Little-endian unsigned integer of `size` bytes
+    fn uint(&mut self, size: usize) -> Result<u128, String> {
+        let bytes = self.take(size)?;
+        Ok(bytes.iter().rev().fold(0, |acc, &b| (acc << 8) | b as u128))
+    }
+}
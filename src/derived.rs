@@ -0,0 +1,111 @@
+// Derived columns: one `name = expression` per line, evaluated with Rhai on
+// every parsed row and appended after the parsed columns, e.g.
+//
+//     power = volts * amps
+//     temp_f = temp_c * 1.8 + 32
+//
+// Columns are referred to by their header (lower-cased, other characters
+// replaced by `_`) or as c1, c2, ...; later lines can use earlier results.
+
+use rhai::{Dynamic, Engine, Scope, AST};
+
+use crate::settings::Settings;
+
+/// Bounds a runaway expression so the UI can't hang
+const MAX_OPERATIONS: u64 = 10_000;
+
+pub struct DerivedColumns {
+    engine: Engine,
+    columns: Vec<(String, AST)>,
+}
+
+impl DerivedColumns {
+    /// None when no derived columns are configured
+    pub fn from_settings(settings: &Settings) -> Result<Option<Self>, String> {
+        let mut engine = Engine::new();
+        engine.set_max_operations(MAX_OPERATIONS);
+        let mut columns = Vec::new();
+        for (n, line) in settings.derived_columns.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with("//") {
+                continue;
+            }
+            let error = |e: String| format!("Derived column line {}: {}", n + 1, e);
+            let Some((name, expression)) = line.split_once('=') else {
+                return Err(error("expected name = expression".to_string()));
+            };
+            let name = name.trim();
+            if name.is_empty() {
+                return Err(error("missing column name".to_string()));
+            }
+            let ast = engine
+                .compile_expression(expression.trim())
+                .map_err(|e| error(e.to_string()))?;
+            columns.push((name.to_string(), ast));
+        }
+        if columns.is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(Self { engine, columns }))
+    }
+
+    pub fn len(&self) -> usize {
+        self.columns.len()
+    }
+
+    pub fn name(&self, idx: usize) -> Option<&str> {
+        self.columns.get(idx).map(|(name, _)| name.as_str())
+    }
+
+    /// Appends the derived values to a row. `headers` name the parsed
+    /// columns and `values` holds their numeric values, if any. Expressions
+    /// that fail (e.g. on a missing value) leave their cell empty.
+    pub fn evaluate(&self, headers: &[String], values: &[Option<f64>], columns: &mut Vec<String>) {
+        let mut scope = Scope::new();
+        for (i, header) in headers.iter().enumerate() {
+            let value = match values.get(i).copied().flatten() {
+                Some(v) => Dynamic::from_float(v),
+                None => Dynamic::from(columns.get(i).cloned().unwrap_or_default()),
+            };
+            scope.push_dynamic(variable_name(header), value.clone());
+            scope.push_dynamic(format!("c{}", i + 1), value);
+        }
+        for (name, ast) in &self.columns {
+            let value = self
+                .engine
+                .eval_ast_with_scope::<Dynamic>(&mut scope, ast)
+                .unwrap_or(Dynamic::UNIT);
+            columns.push(format_value(&value));
+            scope.push_dynamic(variable_name(name), value);
+        }
+    }
+}
+
+/// The variable a column is known by in expressions, e.g. "Temp C" -> temp_c
+pub fn variable_name(header: &str) -> String {
+    header
+        .trim()
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_lowercase()
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+/// Floats are rounded to 6 decimals without trailing zeros
+fn format_value(value: &Dynamic) -> String {
+    if value.is_unit() {
+        return String::new();
+    }
+    match value.as_float() {
+        Ok(v) if v.is_finite() => {
+            let text = format!("{:.6}", v);
+            text.trim_end_matches('0').trim_end_matches('.').to_string()
+        }
+        _ => value.to_string(),
+    }
+}
@@ -0,0 +1,134 @@
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Local};
+
+/// A command's response is complete after this long without a new line
+const RESPONSE_WINDOW: Duration = Duration::from_millis(500);
+
+/// One identification command and the lines it produced
+#[derive(Clone)]
+pub struct InfoEntry {
+    pub command: String,
+    pub response: Vec<String>,
+}
+
+impl InfoEntry {
+    /// The response lines, or a note that there was none
+    pub fn response_text(&self) -> String {
+        if self.response.is_empty() {
+            "(no response)".to_string()
+        } else {
+            self.response.join("\n")
+        }
+    }
+}
+
+/// Identification commands sent on connect and their captured responses
+pub struct DeviceInfo {
+    pub entries: Vec<InfoEntry>,
+    /// When the current capture was started
+    pub captured_at: Option<DateTime<Local>>,
+    pending: VecDeque<String>,
+    /// Entry currently collecting response lines, and when it closes
+    current: Option<(usize, Instant)>,
+}
+
+impl DeviceInfo {
+    pub fn new() -> Self {
+        Self {
+            entries: Vec::new(),
+            captured_at: None,
+            pending: VecDeque::new(),
+            current: None,
+        }
+    }
+
+    /// Starts a new capture, discarding the previous one
+    pub fn start(&mut self, commands: Vec<String>) {
+        self.entries.clear();
+        self.pending = commands.into();
+        self.current = None;
+        self.captured_at = Some(Local::now());
+    }
+
+    pub fn is_capturing(&self) -> bool {
+        self.current.is_some() || !self.pending.is_empty()
+    }
+
+    /// Returns the next command to send once the previous response is done
+    pub fn poll(&mut self) -> Option<String> {
+        if let Some((_, until)) = self.current {
+            if Instant::now() < until {
+                return None;
+            }
+            self.current = None;
+        }
+        let command = self.pending.pop_front()?;
+        self.entries.push(InfoEntry {
+            command: command.clone(),
+            response: Vec::new(),
+        });
+        self.current = Some((self.entries.len() - 1, Instant::now() + RESPONSE_WINDOW));
+        Some(command)
+    }
+
+    /// Records a received line as part of the current response
+    pub fn capture(&mut self, line: &str) {
+        let Some((idx, until)) = &mut self.current else {
+            return;
+        };
+        let entry = &mut self.entries[*idx];
+        // Skip the device echoing the command back
+        if entry.response.is_empty() && line.trim() == entry.command.trim() {
+            return;
+        }
+        entry.response.push(line.to_string());
+        *until = Instant::now() + RESPONSE_WINDOW;
+    }
+
+    /// Renders the collapsible banner. Returns true if a re-capture was requested.
+    pub fn show(&mut self, ui: &mut egui::Ui, can_identify: bool) -> bool {
+        let mut identify = false;
+        let title = match self.captured_at {
+            Some(t) => format!("Device info (captured {})", t.format("%H:%M:%S")),
+            None => "Device info".to_string(),
+        };
+        egui::CollapsingHeader::new(title)
+            .id_salt("device_info_banner")
+            .default_open(true)
+            .show(ui, |ui| {
+                info_grid(ui, "device_info_grid", &self.entries);
+                ui.horizontal(|ui| {
+                    if ui
+                        .add_enabled(
+                            can_identify && !self.is_capturing(),
+                            egui::Button::new("Identify again"),
+                        )
+                        .clicked()
+                    {
+                        identify = true;
+                    }
+                    if self.is_capturing() {
+                        ui.spinner();
+                    }
+                });
+            });
+        ui.separator();
+        identify
+    }
+}
+
+/// Commands and their responses side by side
+pub fn info_grid(ui: &mut egui::Ui, id: &str, entries: &[InfoEntry]) {
+    egui::Grid::new(id)
+        .num_columns(2)
+        .spacing([12.0, 2.0])
+        .show(ui, |ui| {
+            for entry in entries {
+                ui.strong(&entry.command);
+                ui.monospace(entry.response_text());
+                ui.end_row();
+            }
+        });
+}
@@ -0,0 +1,145 @@
+// Per-OS serial driver workarounds from the Advanced settings section: the
+// RTS/DTR levels right after opening (boards that reset or enter their
+// bootloader on a DTR/RTS edge, mostly an issue with Windows drivers), plus
+// on Linux the exclusive TIOCEXCL lock and the legacy custom baud divisor for
+// drivers that ignore arbitrary rates.
+
+use serde::{Deserialize, Serialize};
+
+use crate::settings::Settings;
+
+/// Level of a control line right after the port opens
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub enum PinState {
+    /// Whatever the driver does
+    #[default]
+    Driver,
+    High,
+    Low,
+}
+
+impl PinState {
+    pub fn label(&self) -> &'static str {
+        match self {
+            PinState::Driver => "Driver default",
+            PinState::High => "Asserted",
+            PinState::Low => "Released",
+        }
+    }
+    pub fn all() -> &'static [PinState] {
+        &[PinState::Driver, PinState::High, PinState::Low]
+    }
+
+    pub fn level(&self) -> Option<bool> {
+        match self {
+            PinState::Driver => None,
+            PinState::High => Some(true),
+            PinState::Low => Some(false),
+        }
+    }
+}
+
+/// Sets DTR as part of opening, so it doesn't pulse where the driver allows
+pub fn configure_builder(
+    builder: serialport::SerialPortBuilder,
+    settings: &Settings,
+) -> serialport::SerialPortBuilder {
+    match settings.open_dtr.level() {
+        Some(level) => builder.dtr_on_open(level),
+        None => builder,
+    }
+}
+
+/// Applies the quirks that need an open port. Returns the ones that failed.
+pub fn apply(port: &mut dyn serialport::SerialPort, settings: &Settings) -> Vec<String> {
+    let mut warnings = Vec::new();
+    if let Some(level) = settings.open_dtr.level() {
+        // dtr_on_open is only a request on some platforms; set it for sure
+        if let Err(e) = port.write_data_terminal_ready(level) {
+            warnings.push(format!("Cannot set DTR: {}", e));
+        }
+    }
+    if let Some(level) = settings.open_rts.level() {
+        if let Err(e) = port.write_request_to_send(level) {
+            warnings.push(format!("Cannot set RTS: {}", e));
+        }
+    }
+    warnings
+}
+
+/// Applies the Linux-only quirks. Returns the ones that failed.
+#[cfg(target_os = "linux")]
+pub fn apply_linux(port: &mut serialport::TTYPort, settings: &Settings) -> Vec<String> {
+    let mut warnings = Vec::new();
+    // serialport takes the lock by default
+    if !settings.exclusive_lock {
+        if let Err(e) = port.set_exclusive(false) {
+            warnings.push(format!("Cannot release the exclusive lock: {}", e));
+        }
+    }
+    if settings.custom_divisor {
+        match crate::low_latency::set_custom_divisor(port, settings.baud_rate) {
+            Ok(actual) if actual != settings.baud_rate => warnings.push(format!(
+                "Custom divisor gives {} baud instead of {}",
+                actual, settings.baud_rate
+            )),
+            Ok(_) => {}
+            Err(e) => warnings.push(format!("Custom divisor: {}", e)),
+        }
+    }
+    warnings
+}
+
+/// Settings window section for the driver workarounds
+pub fn quirks_editor(ui: &mut egui::Ui, settings: &mut Settings) {
+    let linux = cfg!(target_os = "linux");
+    egui::Grid::new("quirks_grid")
+        .num_columns(2)
+        .spacing([16.0, 6.0])
+        .show(ui, |ui| {
+            for (label, state) in [
+                ("DTR on open:", &mut settings.open_dtr),
+                ("RTS on open:", &mut settings.open_rts),
+            ] {
+                ui.label(label);
+                egui::ComboBox::from_id_salt(label)
+                    .selected_text(state.label())
+                    .width(140.0)
+                    .show_ui(ui, |ui| {
+                        for s in PinState::all() {
+                            ui.selectable_value(state, *s, s.label());
+                        }
+                    })
+                    .response
+                    .on_hover_text(
+                        "Level right after opening; release DTR/RTS for boards that reset \
+                         or enter their bootloader when the port opens",
+                    );
+                ui.end_row();
+            }
+
+            ui.label("Low latency:");
+            ui.checkbox(&mut settings.low_latency, "").on_hover_text(
+                "Shorter read timeout; on Linux also sets ASYNC_LOW_LATENCY and the FTDI \
+                 latency timer",
+            );
+            ui.end_row();
+
+            ui.label("Exclusive lock (Linux):");
+            ui.add_enabled(linux, egui::Checkbox::new(&mut settings.exclusive_lock, ""))
+                .on_hover_text(
+                    "TIOCEXCL: other programs can't open the port while connected. Turn \
+                     off to share the port with another tool.",
+                );
+            ui.end_row();
+
+            ui.label("Custom divisor (Linux):");
+            ui.add_enabled(linux, egui::Checkbox::new(&mut settings.custom_divisor, ""))
+                .on_hover_text(
+                    "Set the baud rate as base clock / divisor (ASYNC_SPD_CUST at 38400) \
+                     for drivers that reject non-standard rates",
+                );
+            ui.end_row();
+        });
+    ui.label("Changes apply on the next connect.");
+}
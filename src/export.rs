@@ -0,0 +1,163 @@
+use chrono::format::{Item, StrftimeItems};
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+
+/// Saved CSV export layout
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ExportTemplate {
+    pub name: String,
+    /// Column numbers (1-based) in output order, comma-separated; empty = all
+    pub columns: String,
+    /// Header overrides, comma-separated; empty = table headers
+    pub headers: String,
+    /// chrono format of the timestamp column; empty = no timestamp
+    pub timestamp_format: String,
+    pub delimiter: String,
+    /// File name with `{date}`, `{time}` and `{profile}` placeholders
+    pub filename: String,
+}
+
+impl Default for ExportTemplate {
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            columns: String::new(),
+            headers: String::new(),
+            timestamp_format: "%H:%M:%S%.3f".to_string(),
+            delimiter: ",".to_string(),
+            filename: "uart_export_{date}_{time}.csv".to_string(),
+        }
+    }
+}
+
+impl ExportTemplate {
+    /// Zero-based indices of the exported columns, skipping numbers outside
+    /// `1..=available`
+    pub fn column_indices(&self, available: usize) -> Vec<usize> {
+        if self.columns.trim().is_empty() {
+            return (0..available).collect();
+        }
+        self.columns
+            .split(',')
+            .filter_map(|c| c.trim().parse::<usize>().ok())
+            .filter(|&c| c >= 1 && c <= available)
+            .map(|c| c - 1)
+            .collect()
+    }
+
+    /// Header override for the n-th exported column
+    pub fn header(&self, n: usize) -> Option<String> {
+        self.headers
+            .split(',')
+            .nth(n)
+            .map(str::trim)
+            .filter(|h| !h.is_empty())
+            .map(str::to_string)
+    }
+
+    /// True if the timestamp format has no unknown specifiers (formatting
+    /// with one would fail halfway through the export)
+    pub fn timestamp_format_valid(&self) -> bool {
+        !StrftimeItems::new(&self.timestamp_format).any(|i| matches!(i, Item::Error))
+    }
+
+    pub fn delimiter(&self) -> &str {
+        match self.delimiter.as_str() {
+            "" => ",",
+            "\\t" => "\t",
+            d => d,
+        }
+    }
+
+    /// The file name with placeholders filled in
+    pub fn file_name(&self, profile: &str) -> String {
+        let now = Local::now();
+        let profile = if profile.is_empty() {
+            "default"
+        } else {
+            profile
+        };
+        let profile: String = profile
+            .chars()
+            .map(|c| {
+                if c.is_alphanumeric() || c == '-' {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect();
+        self.filename
+            .replace("{date}", &now.format("%Y%m%d").to_string())
+            .replace("{time}", &now.format("%H%M%S").to_string())
+            .replace("{profile}", &profile)
+    }
+}
+
+/// Quotes a field that contains the delimiter, a quote or a line break
+pub fn escape_field(field: &str, delimiter: &str) -> String {
+    if field.contains(delimiter) || field.contains('"') || field.contains(['\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// Table editor for the export templates in the settings window
+pub fn template_editor(ui: &mut egui::Ui, templates: &mut Vec<ExportTemplate>) {
+    let mut remove = None;
+
+    egui::Grid::new("export_template_grid")
+        .num_columns(7)
+        .spacing([6.0, 4.0])
+        .striped(true)
+        .show(ui, |ui| {
+            ui.strong("Name");
+            ui.strong("Columns");
+            ui.strong("Headers");
+            ui.strong("Timestamp");
+            ui.strong("Delim.");
+            ui.strong("File name");
+            ui.end_row();
+
+            for (i, t) in templates.iter_mut().enumerate() {
+                ui.add(egui::TextEdit::singleline(&mut t.name).desired_width(70.0));
+                ui.add(
+                    egui::TextEdit::singleline(&mut t.columns)
+                        .hint_text("all")
+                        .desired_width(60.0),
+                );
+                ui.add(
+                    egui::TextEdit::singleline(&mut t.headers)
+                        .hint_text("table headers")
+                        .desired_width(100.0),
+                );
+                ui.add(
+                    egui::TextEdit::singleline(&mut t.timestamp_format)
+                        .hint_text("none")
+                        .desired_width(80.0),
+                );
+                ui.add(egui::TextEdit::singleline(&mut t.delimiter).desired_width(24.0));
+                ui.add(egui::TextEdit::singleline(&mut t.filename).desired_width(160.0));
+                if ui.small_button("Remove").clicked() {
+                    remove = Some(i);
+                }
+                ui.end_row();
+            }
+        });
+
+    if let Some(i) = remove {
+        templates.remove(i);
+    }
+    if ui.button("Add template").clicked() {
+        templates.push(ExportTemplate {
+            name: format!("Template {}", templates.len() + 1),
+            ..ExportTemplate::default()
+        });
+    }
+    ui.label(
+        "Columns are 1-based numbers in output order (e.g. 3,1). Timestamp uses chrono \
+         format codes. File name placeholders: {date} {time} {profile}. Use \\t for tab.",
+    );
+}
@@ -0,0 +1,93 @@
+// Field extraction from binary frames: a byte-level regex is matched against
+// each raw frame of the length-prefixed, SLIP and COBS framings, so fields of
+// a binary protocol can be picked out (e.g. `\x02(..)(....)\x03`) without the
+// payload having to be text. A matching frame becomes a JSON object with one
+// entry per capture group, named after the group or numbered from 1, holding
+// the captured bytes as hex digits in frame order; the `hex` column type reads
+// them as big-endian numbers. Frames that don't match stay hex rows.
+
+use regex::bytes::Regex;
+use serde_json::{Map, Value};
+
+use crate::regex_guard;
+use crate::settings::{ParserMode, RxFraming, Settings};
+
+pub struct FramePattern {
+    regex: Regex,
+    /// Key of each capture group, without the implicit whole-match group
+    names: Vec<String>,
+}
+
+impl FramePattern {
+    /// None unless a pattern is set and frames are shown as hex rows
+    pub fn from_settings(settings: &Settings) -> Result<Option<Self>, String> {
+        let framed = matches!(
+            settings.rx_framing,
+            RxFraming::LengthPrefixed | RxFraming::Slip | RxFraming::Cobs
+        );
+        if !framed || settings.frame_pattern.is_empty() {
+            return Ok(None);
+        }
+        let regex = regex_guard::compile_bytes(&settings.frame_pattern)
+            .map_err(|e| format!("Frame pattern: {}", e))?;
+        let names = regex
+            .capture_names()
+            .enumerate()
+            .skip(1)
+            .map(|(i, name)| name.map_or_else(|| i.to_string(), str::to_string))
+            .collect();
+        Ok(Some(Self { regex, names }))
+    }
+
+    /// The captured fields as a JSON object line; None if the frame doesn't
+    /// match
+    pub fn extract(&self, frame: &[u8]) -> Option<String> {
+        let caps = self.regex.captures(frame)?;
+        let fields: Map<String, Value> = self
+            .names
+            .iter()
+            .enumerate()
+            .map(|(i, name)| {
+                let hex = caps.get(i + 1).map_or_else(String::new, |m| {
+                    m.as_bytes().iter().map(|b| format!("{:02X}", b)).collect()
+                });
+                (name.clone(), Value::String(hex))
+            })
+            .collect();
+        Some(Value::Object(fields).to_string())
+    }
+}
+
+/// RX Framing section for the frame pattern, shown with the hex-row framings
+pub fn frame_pattern_editor(ui: &mut egui::Ui, settings: &mut Settings) {
+    ui.horizontal(|ui| {
+        ui.label("Field pattern:");
+        ui.add(
+            egui::TextEdit::singleline(&mut settings.frame_pattern)
+                .hint_text(r"e.g. \x02(?P<cmd>.)(?P<value>....)\x03, empty = off")
+                .font(egui::TextStyle::Monospace)
+                .desired_width(f32::INFINITY),
+        );
+    });
+    if settings.frame_pattern.is_empty() {
+        return;
+    }
+    match FramePattern::from_settings(settings) {
+        Ok(_) => {
+            ui.label(
+                "Matched against the raw frame bytes: \\xNN is a byte and . any byte. Each \
+                 capture group becomes a column holding its bytes in hex (use the hex column \
+                 type for numbers); frames that don't match are shown as hex.",
+            );
+            if settings.parser_mode != ParserMode::Json {
+                ui.colored_label(
+                    egui::Color32::YELLOW,
+                    "Set the parser mode to JSON Lines for one column per group.",
+                );
+            }
+        }
+        Err(e) => {
+            ui.colored_label(egui::Color32::RED, e);
+        }
+    }
+}
@@ -0,0 +1,683 @@
+// Binary framing of received data, as an alternative to splitting lines.
+// Length-prefixed frames have a fixed header, a length field at a fixed
+// offset from the start of the frame, the payload and an optional trailer
+// (e.g. a checksum) of fixed size. SLIP frames (RFC 1055) end with 0xC0 and
+// escape that byte inside the payload. COBS frames end with 0x00, which the
+// encoding removes from the payload; defmt frames are 0x00-delimited too,
+// with the rzCOBS encoding. Protobuf frames are messages preceded by their
+// length as a varint (the delimited format); TLV records are length-prefixed
+// frames whose tag stands in for the header. Modbus RTU frames end with 3.5
+// character times of silence and carry a CRC-16/MODBUS.
+// Any frame type may end with a checksum that is verified against the rest
+// of it.
+
+use std::time::Duration;
+
+use crate::checksum::Checksum;
+use crate::settings::{RxFraming, Settings};
+
+/// Longer frames are taken as corrupt (bad length field, missing delimiter)
+const MAX_FRAME_LEN: usize = 64 * 1024;
+
+const SLIP_END: u8 = 0xC0;
+const SLIP_ESC: u8 = 0xDB;
+const SLIP_ESC_END: u8 = 0xDC;
+const SLIP_ESC_ESC: u8 = 0xDD;
+
+const COBS_END: u8 = 0x00;
+
+/// A decoded frame; `error` is set when it failed to decode or its CRC
+/// doesn't match
+pub struct Frame {
+    pub bytes: Vec<u8>,
+    pub error: Option<&'static str>,
+}
+
+enum FrameKind {
+    LengthPrefixed(FrameLayout),
+    Slip,
+    Cobs,
+    /// defmt's reverse zero-compressing COBS, 0x00-delimited
+    Rzcobs,
+    /// Preceded by a varint length
+    Varint,
+    /// Frames end when the line is idle for `gap`
+    Idle {
+        gap: Duration,
+    },
+}
+
+/// Assembles frames from the received byte stream
+pub struct Framer {
+    kind: FrameKind,
+    crc: Checksum,
+    /// Times bytes were discarded to find the next frame, since the last
+    /// `take_resyncs`
+    resyncs: u64,
+}
+
+impl Framer {
+    /// None when data is split into lines
+    pub fn from_settings(settings: &Settings) -> Result<Option<Self>, String> {
+        let kind = match settings.rx_framing {
+            RxFraming::Lines => return Ok(None),
+            RxFraming::LengthPrefixed => {
+                FrameKind::LengthPrefixed(FrameLayout::from_settings(settings)?)
+            }
+            RxFraming::Slip => FrameKind::Slip,
+            RxFraming::Cobs => FrameKind::Cobs,
+            RxFraming::Defmt | RxFraming::Protobuf | RxFraming::Tlv => {
+                // None of these carry a checksum
+                let kind = match settings.rx_framing {
+                    RxFraming::Defmt => FrameKind::Rzcobs,
+                    RxFraming::Protobuf => FrameKind::Varint,
+                    _ => FrameKind::LengthPrefixed(FrameLayout::for_tlv(settings)),
+                };
+                return Ok(Some(Self {
+                    kind,
+                    crc: Checksum::None,
+                    resyncs: 0,
+                }));
+            }
+            RxFraming::ModbusRtu => {
+                return Ok(Some(Self {
+                    kind: FrameKind::Idle {
+                        gap: modbus_gap(settings.baud_rate),
+                    },
+                    crc: Checksum::Crc16Modbus,
+                    resyncs: 0,
+                }))
+            }
+        };
+        Ok(Some(Self {
+            kind,
+            crc: settings.frame_crc.clone(),
+            resyncs: 0,
+        }))
+    }
+
+    /// Silence that ends a frame, for framings delimited by time
+    pub fn idle_gap(&self) -> Option<Duration> {
+        match self.kind {
+            FrameKind::Idle { gap } => Some(gap),
+            _ => None,
+        }
+    }
+
+    /// Recomputes the frame gap of time-delimited framings
+    pub fn set_baud_rate(&mut self, baud_rate: u32) {
+        if let FrameKind::Idle { gap } = &mut self.kind {
+            *gap = modbus_gap(baud_rate);
+        }
+    }
+
+    /// Takes everything in `buf` as one frame once the line has been idle
+    /// for the frame gap
+    pub fn take_idle_frame(&self, buf: &mut Vec<u8>, idle: Duration) -> Option<Frame> {
+        let gap = self.idle_gap()?;
+        if buf.is_empty() || idle < gap {
+            return None;
+        }
+        Some(self.check_crc(Frame::ok(std::mem::take(buf))))
+    }
+
+    /// Removes all complete frames from `buf` and returns them decoded
+    pub fn take_frames(&mut self, buf: &mut Vec<u8>) -> Vec<Frame> {
+        let resyncs = &mut self.resyncs;
+        let frames: Vec<Frame> = match &self.kind {
+            FrameKind::LengthPrefixed(layout) => layout
+                .take_frames(buf, resyncs)
+                .into_iter()
+                .map(Frame::ok)
+                .collect(),
+            FrameKind::Slip => take_delimited(buf, SLIP_END, resyncs)
+                .map(|raw| Frame::ok(slip_unescape(&raw)))
+                .collect(),
+            FrameKind::Cobs => take_delimited(buf, COBS_END, resyncs)
+                .map(|raw| match cobs_decode(&raw) {
+                    Some(bytes) => Frame::ok(bytes),
+                    None => Frame {
+                        bytes: raw,
+                        error: Some("COBS decode error"),
+                    },
+                })
+                .collect(),
+            FrameKind::Rzcobs => take_delimited(buf, COBS_END, resyncs)
+                .map(|raw| match rzcobs_decode(&raw) {
+                    Some(bytes) => Frame::ok(bytes),
+                    None => Frame {
+                        bytes: raw,
+                        error: Some("rzCOBS decode error"),
+                    },
+                })
+                .collect(),
+            FrameKind::Varint => take_varint_prefixed(buf, resyncs)
+                .into_iter()
+                .map(Frame::ok)
+                .collect(),
+            FrameKind::Idle { .. } => {
+                if buf.len() > MAX_FRAME_LEN {
+                    buf.clear();
+                    *resyncs += 1;
+                }
+                Vec::new()
+            }
+        };
+        frames.into_iter().map(|f| self.check_crc(f)).collect()
+    }
+
+    /// Resynchronisations since the last call
+    pub fn take_resyncs(&mut self) -> u64 {
+        std::mem::take(&mut self.resyncs)
+    }
+
+    fn check_crc(&self, mut frame: Frame) -> Frame {
+        if frame.error.is_none() && !self.crc.matches_trailing(&frame.bytes) {
+            frame.error = Some("Frame CRC mismatch");
+        }
+        frame
+    }
+}
+
+impl Frame {
+    fn ok(bytes: Vec<u8>) -> Self {
+        Self { bytes, error: None }
+    }
+}
+
+/// 3.5 character times of 11 bits; fixed at 1.75 ms above 19200 baud as the
+/// Modbus spec recommends
+pub fn modbus_gap(baud_rate: u32) -> Duration {
+    if baud_rate > 19200 {
+        Duration::from_micros(1750)
+    } else {
+        Duration::from_secs_f64(3.5 * 11.0 / baud_rate.max(1) as f64)
+    }
+}
+
+/// Drains all frames ending with `end` from `buf`, without the delimiter.
+/// Senders often put the delimiter before a frame too, so empty frames are
+/// skipped. A missing delimiter discards the data and counts a resync.
+fn take_delimited(buf: &mut Vec<u8>, end: u8, resyncs: &mut u64) -> impl Iterator<Item = Vec<u8>> {
+    let mut frames = Vec::new();
+    while let Some(pos) = buf.iter().position(|&b| b == end) {
+        let mut raw: Vec<u8> = buf.drain(..=pos).collect();
+        raw.pop();
+        if !raw.is_empty() {
+            frames.push(raw);
+        }
+    }
+    if buf.len() > MAX_FRAME_LEN {
+        buf.clear();
+        *resyncs += 1;
+    }
+    frames.into_iter()
+}
+
+/// Drains all frames preceded by a varint length from `buf`, without the
+/// length. A length over `MAX_FRAME_LEN` can't be real: one byte is dropped
+/// and a resync counted.
+fn take_varint_prefixed(buf: &mut Vec<u8>, resyncs: &mut u64) -> Vec<Vec<u8>> {
+    let mut frames = Vec::new();
+    loop {
+        let mut len = 0usize;
+        let mut size = 0;
+        loop {
+            let Some(&b) = buf.get(size) else {
+                return frames;
+            };
+            len |= ((b & 0x7F) as usize) << (7 * size);
+            size += 1;
+            if b & 0x80 == 0 {
+                break;
+            }
+            if size == 4 {
+                // 28 bits are far beyond MAX_FRAME_LEN already
+                len = usize::MAX;
+                break;
+            }
+        }
+        if len > MAX_FRAME_LEN {
+            buf.drain(..1);
+            *resyncs += 1;
+            continue;
+        }
+        if buf.len() < size + len {
+            return frames;
+        }
+        frames.push(buf[size..size + len].to_vec());
+        buf.drain(..size + len);
+    }
+}
+
+/// Undoes SLIP escaping; an invalid escape keeps the byte as is (RFC 1055)
+fn slip_unescape(raw: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(raw.len());
+    let mut escaped = false;
+    for &b in raw {
+        if escaped {
+            out.push(match b {
+                SLIP_ESC_END => SLIP_END,
+                SLIP_ESC_ESC => SLIP_ESC,
+                other => other,
+            });
+            escaped = false;
+        } else if b == SLIP_ESC {
+            escaped = true;
+        } else {
+            out.push(b);
+        }
+    }
+    out
+}
+
+/// Undoes COBS encoding; None if a code byte points past the end of the frame
+fn cobs_decode(raw: &[u8]) -> Option<Vec<u8>> {
+    let mut out = Vec::with_capacity(raw.len());
+    let mut i = 0;
+    while i < raw.len() {
+        let code = raw[i] as usize;
+        let end = i + code;
+        if code == 0 || end > raw.len() {
+            return None;
+        }
+        out.extend_from_slice(&raw[i + 1..end]);
+        i = end;
+        // A full block (0xFF) carries no zero after it, nor does the last one
+        if code < 0xFF && i < raw.len() {
+            out.push(0);
+        }
+    }
+    Some(out)
+}
+
+/// Undoes defmt's rzCOBS encoding, which is read backwards from the end of
+/// the frame. A code byte 0xxxxxxx covers 7 positions (bit set = zero byte),
+/// 1nnnnnnn covers n + 7 bytes and a zero, 0xFF covers 134 bytes. The
+/// encoder pads the last group with zeros, which the message decoder
+/// ignores. None if a code byte points past the start of the frame.
+fn rzcobs_decode(raw: &[u8]) -> Option<Vec<u8>> {
+    let mut out = Vec::with_capacity(raw.len() * 8 / 7 + 8);
+    let mut bytes = raw.iter().rev().copied();
+    while let Some(code) = bytes.next() {
+        match code {
+            0x00 => return None,
+            0x01..=0x7F => {
+                for bit in (0..7).rev() {
+                    if code & (1 << bit) == 0 {
+                        out.push(bytes.next()?);
+                    } else {
+                        out.push(0);
+                    }
+                }
+            }
+            0x80..=0xFE => {
+                out.push(0);
+                for _ in 0..(code & 0x7F) + 7 {
+                    out.push(bytes.next()?);
+                }
+            }
+            0xFF => {
+                for _ in 0..134 {
+                    out.push(bytes.next()?);
+                }
+            }
+        }
+    }
+    out.reverse();
+    Some(out)
+}
+
+pub struct FrameLayout {
+    /// Bytes every frame starts with (may be empty)
+    header: Vec<u8>,
+    /// Offset of the length field from the start of the frame
+    length_offset: usize,
+    /// Size of the length field in bytes (1, 2 or 4)
+    length_size: usize,
+    big_endian: bool,
+    /// Bytes after the payload not counted by the length field
+    trailer: usize,
+}
+
+impl FrameLayout {
+    pub fn from_settings(settings: &Settings) -> Result<Self, String> {
+        let header = parse_hex(&settings.frame_header)?;
+        if settings.frame_length_offset < header.len() {
+            return Err("Length field overlaps the frame header".to_string());
+        }
+        Ok(Self {
+            header,
+            length_offset: settings.frame_length_offset,
+            length_size: settings.frame_length_size.clamp(1, 4),
+            big_endian: settings.frame_big_endian,
+            trailer: settings.frame_trailer,
+        })
+    }
+
+    /// TLV records: the tag takes the place of a header, the length counts
+    /// the value only
+    pub fn for_tlv(settings: &Settings) -> Self {
+        let tag_size = settings.tlv_tag_size.clamp(1, 4);
+        Self {
+            header: Vec::new(),
+            length_offset: tag_size,
+            length_size: settings.tlv_length_size.clamp(1, 4),
+            big_endian: settings.tlv_big_endian,
+            trailer: 0,
+        }
+    }
+
+    /// Removes all complete frames from `buf`. Bytes before a header are
+    /// dropped so the parser resynchronises after line noise; each such
+    /// skip is counted in `resyncs`.
+    pub fn take_frames(&self, buf: &mut Vec<u8>, resyncs: &mut u64) -> Vec<Vec<u8>> {
+        let mut frames = Vec::new();
+        loop {
+            if !self.header.is_empty() {
+                match find(buf, &self.header) {
+                    Some(0) => {}
+                    Some(start) => {
+                        buf.drain(..start);
+                        *resyncs += 1;
+                    }
+                    None => {
+                        // Keep a possible partial header at the end
+                        let keep = (self.header.len() - 1).min(buf.len());
+                        if buf.len() > keep {
+                            buf.drain(..buf.len() - keep);
+                            *resyncs += 1;
+                        }
+                        return frames;
+                    }
+                }
+            }
+            let len_end = self.length_offset + self.length_size;
+            if buf.len() < len_end {
+                return frames;
+            }
+            let payload = self.read_length(&buf[self.length_offset..len_end]);
+            let total = len_end + payload + self.trailer;
+            if total > MAX_FRAME_LEN {
+                // Not a real frame; skip this header and look for the next one
+                buf.drain(..1);
+                *resyncs += 1;
+                continue;
+            }
+            if buf.len() < total {
+                return frames;
+            }
+            frames.push(buf.drain(..total).collect());
+        }
+    }
+
+    fn read_length(&self, field: &[u8]) -> usize {
+        let fold = |acc: usize, &b: &u8| (acc << 8) | b as usize;
+        if self.big_endian {
+            field.iter().fold(0, fold)
+        } else {
+            field.iter().rev().fold(0, fold)
+        }
+    }
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack.windows(needle.len()).position(|w| w == needle)
+}
+
+/// Parses hex bytes like "AA 55" or "aa55"
+pub fn parse_hex(text: &str) -> Result<Vec<u8>, String> {
+    let digits: String = text.chars().filter(|c| !c.is_whitespace()).collect();
+    if let Some(c) = digits.chars().find(|c| !c.is_ascii_hexdigit()) {
+        return Err(format!("Invalid hex digit '{}'", c));
+    }
+    if !digits.len().is_multiple_of(2) {
+        return Err(format!("Odd number of hex digits in \"{}\"", text));
+    }
+    (0..digits.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&digits[i..i + 2], 16).map_err(|e| e.to_string()))
+        .collect()
+}
+
+/// Formats a frame for display, e.g. "AA 55 03 01 02 03"
+pub fn to_hex(frame: &[u8]) -> String {
+    frame
+        .iter()
+        .map(|b| format!("{:02X}", b))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Reference COBS encoder for the round trips
+    fn cobs_encode(data: &[u8]) -> Vec<u8> {
+        let mut out = vec![0];
+        let mut code_at = 0;
+        for &b in data {
+            if b != 0 {
+                out.push(b);
+            }
+            if b == 0 || out.len() - code_at == 0xFF {
+                out[code_at] = (out.len() - code_at) as u8;
+                code_at = out.len();
+                out.push(0);
+            }
+        }
+        out[code_at] = (out.len() - code_at) as u8;
+        out
+    }
+
+    #[test]
+    fn cobs_known_answers() {
+        // Examples from Cheshire & Baker's COBS paper / the COBS article
+        let cases: [(&[u8], &[u8]); 6] = [
+            (&[0x00], &[0x01, 0x01]),
+            (&[0x00, 0x00], &[0x01, 0x01, 0x01]),
+            (&[0x00, 0x11, 0x00], &[0x01, 0x02, 0x11, 0x01]),
+            (&[0x11, 0x22, 0x00, 0x33], &[0x03, 0x11, 0x22, 0x02, 0x33]),
+            (&[0x11, 0x22, 0x33, 0x44], &[0x05, 0x11, 0x22, 0x33, 0x44]),
+            (&[0x11, 0x00, 0x00, 0x00], &[0x02, 0x11, 0x01, 0x01, 0x01]),
+        ];
+        for (decoded, encoded) in cases {
+            assert_eq!(cobs_encode(decoded), encoded);
+            assert_eq!(cobs_decode(encoded).as_deref(), Some(decoded));
+        }
+    }
+
+    #[test]
+    fn cobs_full_blocks() {
+        let ramp: Vec<u8> = (0x01..=0xFE).collect();
+        let mut encoded = vec![0xFF];
+        encoded.extend(&ramp);
+        assert_eq!(cobs_decode(&encoded), Some(ramp.clone()));
+
+        let mut data = vec![0x00];
+        data.extend(&ramp);
+        let mut encoded = vec![0x01, 0xFF];
+        encoded.extend(&ramp);
+        assert_eq!(cobs_decode(&encoded), Some(data));
+
+        let mut data = ramp.clone();
+        data.push(0xFF);
+        let mut encoded = vec![0xFF];
+        encoded.extend(&ramp);
+        encoded.extend([0x02, 0xFF]);
+        assert_eq!(cobs_decode(&encoded), Some(data));
+    }
+
+    #[test]
+    fn cobs_round_trips() {
+        let inputs: [Vec<u8>; 4] = [
+            Vec::new(),
+            vec![0; 300],
+            (0..=255).cycle().take(1000).collect(),
+            b"hello\0world\0".to_vec(),
+        ];
+        for data in inputs {
+            let encoded = cobs_encode(&data);
+            assert!(!encoded.contains(&0));
+            assert_eq!(cobs_decode(&encoded), Some(data));
+        }
+    }
+
+    #[test]
+    fn cobs_rejects_malformed_frames() {
+        // Zero code byte, and a code pointing past the end
+        assert_eq!(cobs_decode(&[0x00, 0x11]), None);
+        assert_eq!(cobs_decode(&[0x03, 0x11]), None);
+        assert_eq!(cobs_decode(&[0x02, 0x11, 0x05, 0x22]), None);
+    }
+
+    #[test]
+    fn rzcobs_groups() {
+        // 0x1F: two literal bytes, then five zeros (read from the end)
+        assert_eq!(
+            rzcobs_decode(&[0x11, 0x22, 0x1F]),
+            Some(vec![0, 0, 0, 0, 0, 0x11, 0x22])
+        );
+        // 0x80: seven literal bytes followed by a zero
+        assert_eq!(
+            rzcobs_decode(&[1, 2, 3, 4, 5, 6, 7, 0x80]),
+            Some(vec![1, 2, 3, 4, 5, 6, 7, 0])
+        );
+        // 0x7F: seven zeros
+        assert_eq!(rzcobs_decode(&[0x7F]), Some(vec![0; 7]));
+        // 0xFF: 134 literal bytes, no zero
+        let run: Vec<u8> = (1..=134).collect();
+        let mut encoded = run.clone();
+        encoded.push(0xFF);
+        assert_eq!(rzcobs_decode(&encoded), Some(run));
+    }
+
+    #[test]
+    fn rzcobs_rejects_malformed_frames() {
+        assert_eq!(rzcobs_decode(&[0x00]), None);
+        // Codes needing more bytes than the frame has
+        assert_eq!(rzcobs_decode(&[0x05]), None);
+        assert_eq!(rzcobs_decode(&[1, 2, 0x81]), None);
+        assert_eq!(rzcobs_decode(&[1, 0xFF]), None);
+    }
+
+    #[test]
+    fn slip_unescapes() {
+        assert_eq!(slip_unescape(&[0x01, 0xDB, 0xDC, 0x02]), [0x01, 0xC0, 0x02]);
+        assert_eq!(slip_unescape(&[0xDB, 0xDD]), [0xDB]);
+        // An invalid escape keeps the byte
+        assert_eq!(slip_unescape(&[0xDB, 0x41]), [0x41]);
+    }
+
+    #[test]
+    fn delimited_frames_skip_empty_ones() {
+        let mut buf = vec![0xC0, 0x01, 0x02, 0xC0, 0xC0, 0x03, 0xC0, 0x04];
+        let mut resyncs = 0;
+        let frames: Vec<Vec<u8>> = take_delimited(&mut buf, SLIP_END, &mut resyncs).collect();
+        assert_eq!(frames, [vec![0x01, 0x02], vec![0x03]]);
+        assert_eq!(buf, [0x04]);
+        assert_eq!(resyncs, 0);
+
+        let mut buf = vec![0x55; MAX_FRAME_LEN + 1];
+        assert_eq!(take_delimited(&mut buf, SLIP_END, &mut resyncs).count(), 0);
+        assert!(buf.is_empty());
+        assert_eq!(resyncs, 1);
+    }
+
+    #[test]
+    fn varint_prefixed_frames() {
+        let mut buf = vec![0x03, 0xA, 0xB, 0xC, 0x00, 0x01, 0xD, 0x02, 0xE];
+        let mut resyncs = 0;
+        let frames = take_varint_prefixed(&mut buf, &mut resyncs);
+        assert_eq!(frames, [vec![0xA, 0xB, 0xC], vec![], vec![0xD]]);
+        // The last frame waits for its second byte
+        assert_eq!(buf, [0x02, 0xE]);
+
+        // 300 = 0xAC 0x02
+        let mut buf = vec![0xAC, 0x02];
+        buf.extend(vec![0x42; 300]);
+        let frames = take_varint_prefixed(&mut buf, &mut resyncs);
+        assert_eq!(frames, [vec![0x42; 300]]);
+        assert!(buf.is_empty());
+        assert_eq!(resyncs, 0);
+    }
+
+    #[test]
+    fn varint_length_too_long_resyncs() {
+        // 0xFF 0xFF 0xFF 0x7F and 0xFF 0xFF 0x7F are far over MAX_FRAME_LEN
+        // and their first bytes are dropped; 0xFF 0x7F (16383) then waits
+        // for its payload
+        let mut buf = vec![0xFF, 0xFF, 0xFF, 0x7F, 0x01];
+        let mut resyncs = 0;
+        assert!(take_varint_prefixed(&mut buf, &mut resyncs).is_empty());
+        assert_eq!(resyncs, 2);
+        assert_eq!(buf, [0xFF, 0x7F, 0x01]);
+    }
+
+    #[test]
+    fn modbus_gap_follows_the_baud_rate() {
+        assert_eq!(modbus_gap(9600), Duration::from_secs_f64(38.5 / 9600.0));
+        assert_eq!(modbus_gap(115200), Duration::from_micros(1750));
+    }
+
+    #[test]
+    fn tlv_layout_reads_tag_and_length_fields() {
+        let settings = Settings {
+            rx_framing: RxFraming::Tlv,
+            tlv_tag_size: 2,
+            tlv_length_size: 1,
+            tlv_big_endian: true,
+            ..Settings::default()
+        };
+        let layout = FrameLayout::for_tlv(&settings);
+        let mut buf = vec![
+            0x00, 0x01, 0x02, 0xAA, 0xBB, 0x01, 0x00, 0x00, 0x12, 0x34, 0x03, 0x01,
+        ];
+        let mut resyncs = 0;
+        let frames = layout.take_frames(&mut buf, &mut resyncs);
+        assert_eq!(
+            frames,
+            [vec![0x00, 0x01, 0x02, 0xAA, 0xBB], vec![0x01, 0x00, 0x00]]
+        );
+        // The third record waits for the rest of its value
+        assert_eq!(buf, [0x12, 0x34, 0x03, 0x01]);
+        assert_eq!(resyncs, 0);
+
+        // 2-byte length, little endian: 0x0002
+        let settings = Settings {
+            tlv_tag_size: 1,
+            tlv_length_size: 2,
+            tlv_big_endian: false,
+            ..settings
+        };
+        let layout = FrameLayout::for_tlv(&settings);
+        let mut buf = vec![0x07, 0x02, 0x00, 0x0C, 0x0D];
+        let frames = layout.take_frames(&mut buf, &mut resyncs);
+        assert_eq!(frames, [vec![0x07, 0x02, 0x00, 0x0C, 0x0D]]);
+        assert!(buf.is_empty());
+    }
+
+    #[test]
+    fn tlv_framer_skips_the_checksum() {
+        let settings = Settings {
+            rx_framing: RxFraming::Tlv,
+            frame_crc: Checksum::Crc16Modbus,
+            ..Settings::default()
+        };
+        let mut framer = Framer::from_settings(&settings).unwrap().unwrap();
+        let mut buf = vec![0x05, 0x01, 0x42];
+        let frames = framer.take_frames(&mut buf);
+        assert_eq!(frames.len(), 1);
+        assert_eq!(frames[0].bytes, [0x05, 0x01, 0x42]);
+        assert_eq!(frames[0].error, None);
+    }
+
+    #[test]
+    fn hex_round_trips() {
+        assert_eq!(parse_hex("AA 55 0f"), Ok(vec![0xAA, 0x55, 0x0F]));
+        assert_eq!(to_hex(&[0xAA, 0x55, 0x0F]), "AA 55 0F");
+        assert!(parse_hex("ABC").is_err());
+        assert!(parse_hex("GG").is_err());
+    }
+}
@@ -0,0 +1,126 @@
+// Hex dump view: the received bytes exactly as read from the port, before
+// line splitting, framing or UTF-8 decoding, shown 16 per row as offset, hex
+// and ASCII like `hexdump -C`. The oldest rows are dropped past
+// `MAX_DUMP_BYTES`; offsets keep counting from the start of the capture.
+
+use std::collections::VecDeque;
+
+use egui::RichText;
+
+const BYTES_PER_ROW: usize = 16;
+
+/// Bytes kept for the view; whole rows are dropped so offsets stay aligned
+const MAX_DUMP_BYTES: usize = 4 * 1024 * 1024;
+
+#[derive(Default)]
+pub struct HexDump {
+    bytes: VecDeque<u8>,
+    /// Stream offset of `bytes[0]`
+    start: u64,
+}
+
+impl HexDump {
+    pub fn push(&mut self, data: &[u8]) {
+        self.bytes.extend(data);
+        if self.bytes.len() > MAX_DUMP_BYTES {
+            let excess = self.bytes.len() - MAX_DUMP_BYTES;
+            let drop = excess.div_ceil(BYTES_PER_ROW) * BYTES_PER_ROW;
+            self.bytes.drain(..drop.min(self.bytes.len()));
+            self.start += drop as u64;
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.bytes.clear();
+        self.start = 0;
+    }
+
+    pub fn show(&self, ui: &mut egui::Ui, auto_scroll: bool) {
+        if self.bytes.is_empty() {
+            ui.label(
+                "No bytes received yet. The dump shows data read from the serial port; the \
+                 simulator and sniffer mode deliver lines only.",
+            );
+            return;
+        }
+        let rows = self.bytes.len().div_ceil(BYTES_PER_ROW);
+        let font_id = egui::FontId::monospace(12.0);
+        let row_height = ui.text_style_height(&egui::TextStyle::Monospace);
+        egui::ScrollArea::vertical()
+            .auto_shrink(false)
+            .stick_to_bottom(auto_scroll)
+            .show_rows(ui, row_height, rows, |ui, range| {
+                for row in range {
+                    ui.horizontal(|ui| {
+                        let (offset, hex, ascii) = self.row(row);
+                        ui.label(
+                            RichText::new(offset)
+                                .font(font_id.clone())
+                                .color(egui::Color32::GRAY),
+                        );
+                        ui.label(RichText::new(hex).font(font_id.clone()));
+                        ui.label(
+                            RichText::new(ascii)
+                                .font(font_id.clone())
+                                .color(egui::Color32::from_rgb(180, 220, 180)),
+                        );
+                    });
+                }
+            });
+    }
+
+    /// Offset, hex and ASCII columns of one row
+    fn row(&self, row: usize) -> (String, String, String) {
+        let first = row * BYTES_PER_ROW;
+        let last = (first + BYTES_PER_ROW).min(self.bytes.len());
+        let bytes: Vec<u8> = self.bytes.range(first..last).copied().collect();
+        dump_row(self.start + first as u64, &bytes)
+    }
+}
+
+/// Offset, hex and ASCII columns of up to 16 bytes; the hex column is padded
+/// so the ASCII column lines up on a short last row
+pub fn dump_row(offset: u64, bytes: &[u8]) -> (String, String, String) {
+    let mut hex = String::with_capacity(BYTES_PER_ROW * 3 + 1);
+    let mut ascii = String::with_capacity(BYTES_PER_ROW + 2);
+    ascii.push('|');
+    for i in 0..BYTES_PER_ROW {
+        if i == BYTES_PER_ROW / 2 {
+            hex.push(' ');
+        }
+        match bytes.get(i) {
+            Some(&b) => {
+                hex.push_str(&format!("{:02x} ", b));
+                ascii.push(if b.is_ascii_graphic() || b == b' ' {
+                    b as char
+                } else {
+                    '.'
+                });
+            }
+            None => hex.push_str("   "),
+        }
+    }
+    ascii.push('|');
+    (format!("{:08x}", offset), hex, ascii)
+}
+
+/// Bytes of a single line or frame, 16 per row, for the row inspector
+pub fn show_bytes(ui: &mut egui::Ui, bytes: &[u8]) {
+    let font_id = egui::FontId::monospace(12.0);
+    for (i, chunk) in bytes.chunks(BYTES_PER_ROW).enumerate() {
+        let (offset, hex, ascii) = dump_row((i * BYTES_PER_ROW) as u64, chunk);
+        ui.horizontal(|ui| {
+            ui.label(
+                RichText::new(&offset[4..])
+                    .font(font_id.clone())
+                    .color(egui::Color32::GRAY),
+            );
+            ui.label(RichText::new(hex).font(font_id.clone()));
+            ui.label(
+                RichText::new(ascii)
+                    .font(font_id.clone())
+                    .color(egui::Color32::from_rgb(180, 220, 180)),
+            );
+        });
+    }
+}
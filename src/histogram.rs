@@ -0,0 +1,296 @@
+// Histogram of a numeric column: how its values spread over equal-width bins,
+// over the whole buffer, the last N seconds or the time span of the rows
+// selected in the table. Handy for response latencies and ADC noise, where
+// the shape matters more than the sequence.
+
+use chrono::{DateTime, Local};
+use egui::Color32;
+use serde::{Deserialize, Serialize};
+
+const BAR_COLOR: Color32 = Color32::from_rgb(90, 170, 250);
+
+/// Which rows the histogram counts
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum HistogramRange {
+    /// Everything still in the buffer
+    Buffer,
+    /// Last `window_secs` seconds
+    Seconds,
+    /// From the first to the last selected row
+    Selection,
+}
+
+impl HistogramRange {
+    pub fn label(&self) -> &'static str {
+        match self {
+            HistogramRange::Buffer => "Entire buffer",
+            HistogramRange::Seconds => "Last seconds",
+            HistogramRange::Selection => "Selected rows",
+        }
+    }
+    pub fn all() -> &'static [HistogramRange] {
+        &[
+            HistogramRange::Buffer,
+            HistogramRange::Seconds,
+            HistogramRange::Selection,
+        ]
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HistogramConfig {
+    /// Index of the parsed column (0-based)
+    pub column: usize,
+    pub bins: usize,
+    pub range: HistogramRange,
+    pub window_secs: f64,
+}
+
+impl Default for HistogramConfig {
+    fn default() -> Self {
+        Self {
+            column: 0,
+            bins: 20,
+            range: HistogramRange::Buffer,
+            window_secs: 60.0,
+        }
+    }
+}
+
+/// Counts per bin; bin `i` covers `lo + i * width` up to the next one, the
+/// last one includes the maximum
+struct Bins {
+    lo: f64,
+    width: f64,
+    counts: Vec<usize>,
+}
+
+impl Bins {
+    fn new(values: &[f64], bins: usize) -> Option<Self> {
+        let lo = values.iter().copied().fold(f64::INFINITY, f64::min);
+        let hi = values.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+        if !lo.is_finite() {
+            return None;
+        }
+        // All values equal: one bin's width around them
+        let (lo, hi) = if hi - lo < 1e-12 {
+            (lo - 0.5, hi + 0.5)
+        } else {
+            (lo, hi)
+        };
+        let width = (hi - lo) / bins as f64;
+        let mut counts = vec![0; bins];
+        for v in values {
+            let i = ((v - lo) / width) as usize;
+            counts[i.min(bins - 1)] += 1;
+        }
+        Some(Self { lo, width, counts })
+    }
+
+    fn bounds(&self, i: usize) -> (f64, f64) {
+        let from = self.lo + i as f64 * self.width;
+        (from, from + self.width)
+    }
+}
+
+pub struct HistogramWindow {
+    pub is_open: bool,
+}
+
+impl HistogramWindow {
+    pub fn new() -> Self {
+        Self { is_open: false }
+    }
+
+    /// Renders the window. `values` holds the column's numeric values with
+    /// their receive time, oldest first, and `selected` the time span of the
+    /// rows selected in the table. Returns true if the configuration changed.
+    pub fn show(
+        &mut self,
+        ctx: &egui::Context,
+        config: &mut HistogramConfig,
+        headers: &[String],
+        values: &[(DateTime<Local>, f64)],
+        selected: Option<(DateTime<Local>, DateTime<Local>)>,
+    ) -> bool {
+        if !self.is_open {
+            return false;
+        }
+        let mut open = self.is_open;
+        let mut changed = false;
+
+        egui::Window::new("Histogram")
+            .open(&mut open)
+            .default_size([480.0, 300.0])
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    changed |= toolbar(ui, config, headers);
+                });
+                ui.separator();
+
+                let values: Vec<f64> = match config.range {
+                    HistogramRange::Buffer => values.iter().map(|&(_, v)| v).collect(),
+                    HistogramRange::Seconds => {
+                        let Some(&(last, _)) = values.last() else {
+                            ui.weak("No numeric values in this column yet");
+                            return;
+                        };
+                        let span = (config.window_secs * 1000.0) as i64;
+                        let cutoff = last - chrono::Duration::milliseconds(span);
+                        values
+                            .iter()
+                            .filter(|(t, _)| *t >= cutoff)
+                            .map(|&(_, v)| v)
+                            .collect()
+                    }
+                    HistogramRange::Selection => {
+                        let Some((from, to)) = selected else {
+                            ui.weak("Select two or more rows in the table to set the time range");
+                            return;
+                        };
+                        values
+                            .iter()
+                            .filter(|(t, _)| (from..=to).contains(t))
+                            .map(|&(_, v)| v)
+                            .collect()
+                    }
+                };
+                let Some(bins) = Bins::new(&values, config.bins.max(1)) else {
+                    ui.weak("No numeric values in this column yet");
+                    return;
+                };
+                summary(ui, &values);
+                draw(ui, &bins);
+            });
+
+        self.is_open = open;
+        changed
+    }
+}
+
+/// Column, bin count and range controls
+fn toolbar(ui: &mut egui::Ui, config: &mut HistogramConfig, headers: &[String]) -> bool {
+    let mut changed = false;
+    let selected = headers
+        .get(config.column)
+        .cloned()
+        .unwrap_or_else(|| format!("Col {}", config.column + 1));
+    egui::ComboBox::from_id_salt("histogram_column")
+        .selected_text(selected)
+        .show_ui(ui, |ui| {
+            for (col, name) in headers.iter().enumerate() {
+                changed |= ui.selectable_value(&mut config.column, col, name).changed();
+            }
+        });
+    changed |= ui
+        .add(
+            egui::DragValue::new(&mut config.bins)
+                .range(2..=200)
+                .suffix(" bins"),
+        )
+        .changed();
+    let before = config.range;
+    egui::ComboBox::from_id_salt("histogram_range")
+        .selected_text(config.range.label())
+        .width(110.0)
+        .show_ui(ui, |ui| {
+            for range in HistogramRange::all() {
+                ui.selectable_value(&mut config.range, *range, range.label());
+            }
+        });
+    changed |= config.range != before;
+    if config.range == HistogramRange::Seconds {
+        changed |= ui
+            .add(
+                egui::DragValue::new(&mut config.window_secs)
+                    .range(1.0..=86400.0)
+                    .suffix(" s"),
+            )
+            .changed();
+    }
+    changed
+}
+
+/// Count, extremes, mean and standard deviation
+fn summary(ui: &mut egui::Ui, values: &[f64]) {
+    let n = values.len() as f64;
+    let mean = values.iter().sum::<f64>() / n;
+    let variance = values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / n;
+    let lo = values.iter().copied().fold(f64::INFINITY, f64::min);
+    let hi = values.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    ui.label(format!(
+        "n = {}   min {:.4}   max {:.4}   mean {:.4}   σ {:.4}",
+        values.len(),
+        lo,
+        hi,
+        mean,
+        variance.sqrt()
+    ));
+}
+
+fn draw(ui: &mut egui::Ui, bins: &Bins) {
+    let height = ui.available_height().max(120.0);
+    let (rect, response) = ui.allocate_exact_size(
+        egui::vec2(ui.available_width(), height),
+        egui::Sense::hover(),
+    );
+    let painter = ui.painter_at(rect);
+    painter.rect_filled(rect, 2.0, ui.visuals().extreme_bg_color);
+
+    // Room for the value labels below the bars
+    let plot = rect
+        .shrink2(egui::vec2(4.0, 0.0))
+        .with_max_y(rect.bottom() - 16.0);
+    let max = bins.counts.iter().copied().max().unwrap_or(0).max(1);
+    let bar_width = plot.width() / bins.counts.len() as f32;
+    let hovered = response
+        .hover_pos()
+        .filter(|pos| plot.x_range().contains(pos.x))
+        .map(|pos| (((pos.x - plot.left()) / bar_width) as usize).min(bins.counts.len() - 1));
+    for (i, &count) in bins.counts.iter().enumerate() {
+        let top = plot.bottom() - count as f32 / max as f32 * (plot.height() - 4.0);
+        let left = plot.left() + i as f32 * bar_width;
+        let bar = egui::Rect::from_min_max(
+            egui::pos2(left + 0.5, top),
+            egui::pos2(left + bar_width - 0.5, plot.bottom()),
+        );
+        let color = if hovered == Some(i) {
+            BAR_COLOR
+        } else {
+            BAR_COLOR.gamma_multiply(0.7)
+        };
+        painter.rect_filled(bar, 0.0, color);
+    }
+
+    let weak = ui.visuals().weak_text_color();
+    let small = egui::FontId::proportional(11.0);
+    let (lo, _) = bins.bounds(0);
+    let (_, hi) = bins.bounds(bins.counts.len() - 1);
+    painter.text(
+        rect.left_bottom() + egui::vec2(4.0, -2.0),
+        egui::Align2::LEFT_BOTTOM,
+        format!("{:.4}", lo),
+        small.clone(),
+        weak,
+    );
+    painter.text(
+        rect.right_bottom() + egui::vec2(-4.0, -2.0),
+        egui::Align2::RIGHT_BOTTOM,
+        format!("{:.4}", hi),
+        small.clone(),
+        weak,
+    );
+    painter.text(
+        rect.left_top() + egui::vec2(4.0, 2.0),
+        egui::Align2::LEFT_TOP,
+        max.to_string(),
+        small,
+        weak,
+    );
+
+    if let Some(i) = hovered {
+        let (from, to) = bins.bounds(i);
+        response.on_hover_text_at_pointer(format!("{:.4} … {:.4}: {}", from, to, bins.counts[i]));
+    }
+}
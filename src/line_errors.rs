@@ -0,0 +1,99 @@
+// UART line error reporting. On Linux the tty driver keeps framing, parity,
+// overrun and break counters (TIOCGICOUNT); other platforms don't expose them
+// through serialport, so no counter is available there.
+
+/// Error counters of a UART
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+struct Counts {
+    frame: i64,
+    parity: i64,
+    overrun: i64,
+    brk: i64,
+}
+
+impl Counts {
+    /// Describes what increased since `prev`, e.g. "3 framing, 1 parity"
+    fn describe_since(&self, prev: &Counts) -> Option<String> {
+        let parts: Vec<String> = [
+            (self.frame - prev.frame, "framing"),
+            (self.parity - prev.parity, "parity"),
+            (self.overrun - prev.overrun, "overrun"),
+            (self.brk - prev.brk, "break"),
+        ]
+        .iter()
+        .filter(|(n, _)| *n > 0)
+        .map(|(n, what)| format!("{} {}", n, what))
+        .collect();
+        if parts.is_empty() {
+            None
+        } else {
+            Some(parts.join(", "))
+        }
+    }
+}
+
+/// Polls the line error counters of an open port
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+pub struct ErrorCounter {
+    #[cfg(target_os = "linux")]
+    fd: std::os::unix::io::RawFd,
+    last: Counts,
+}
+
+#[cfg(target_os = "linux")]
+mod linux {
+    use super::Counts;
+    use std::os::unix::io::RawFd;
+
+    /// `struct serial_icounter_struct` from `<linux/serial.h>`
+    #[repr(C)]
+    #[derive(Default)]
+    struct SerialIcounter {
+        cts: libc::c_int,
+        dsr: libc::c_int,
+        rng: libc::c_int,
+        dcd: libc::c_int,
+        rx: libc::c_int,
+        tx: libc::c_int,
+        frame: libc::c_int,
+        overrun: libc::c_int,
+        parity: libc::c_int,
+        brk: libc::c_int,
+        buf_overrun: libc::c_int,
+        reserved: [libc::c_int; 9],
+    }
+
+    pub fn read_counts(fd: RawFd) -> Option<Counts> {
+        let mut ic = SerialIcounter::default();
+        // SAFETY: serial_icounter_struct is plain old data filled by the ioctl.
+        if unsafe { libc::ioctl(fd, libc::TIOCGICOUNT, &mut ic) } != 0 {
+            return None;
+        }
+        Some(Counts {
+            frame: ic.frame as i64,
+            parity: ic.parity as i64,
+            overrun: (ic.overrun + ic.buf_overrun) as i64,
+            brk: ic.brk as i64,
+        })
+    }
+}
+
+impl ErrorCounter {
+    /// Returns None if the driver doesn't keep error counters (e.g. ptys)
+    #[cfg(target_os = "linux")]
+    pub fn new(fd: std::os::unix::io::RawFd) -> Option<Self> {
+        let last = linux::read_counts(fd)?;
+        Some(Self { fd, last })
+    }
+
+    /// Returns a description of the errors seen since the last poll
+    pub fn poll(&mut self) -> Option<String> {
+        #[cfg(target_os = "linux")]
+        let now = linux::read_counts(self.fd)?;
+        #[cfg(not(target_os = "linux"))]
+        let now = self.last;
+        let description = now.describe_since(&self.last);
+        self.last = now;
+        description
+    }
+}
@@ -0,0 +1,79 @@
+// Live filter: hides table rows and raw log lines that don't contain a text,
+// or match a regex, while typing. Only the view is filtered; rows keep
+// arriving and clearing the filter shows everything again.
+
+use regex::Regex;
+
+use crate::regex_guard;
+
+#[derive(Default)]
+pub struct LineFilter {
+    text: String,
+    /// `text` is a regex rather than plain text
+    is_regex: bool,
+    /// None while the filter is empty or doesn't compile
+    matcher: Option<Regex>,
+    error: Option<String>,
+}
+
+impl LineFilter {
+    pub fn is_active(&self) -> bool {
+        self.matcher.is_some()
+    }
+
+    /// Whether a line passes; everything does while the filter is off
+    pub fn matches(&self, line: &str) -> bool {
+        self.matcher.as_ref().is_none_or(|re| re.is_match(line))
+    }
+
+    /// Plain text matches case-insensitively anywhere in the line
+    fn compile(&mut self) {
+        self.matcher = None;
+        self.error = None;
+        if self.text.is_empty() {
+            return;
+        }
+        let pattern = if self.is_regex {
+            self.text.clone()
+        } else {
+            format!("(?i){}", regex::escape(&self.text))
+        };
+        match regex_guard::compile(&pattern) {
+            Ok(re) => self.matcher = Some(re),
+            Err(e) => self.error = Some(e),
+        }
+    }
+
+    /// Filter bar above the table and raw log; `counts` is the number of
+    /// rows shown and received. Returns true when the filter changed.
+    pub fn show(&mut self, ui: &mut egui::Ui, counts: Option<(usize, usize)>) -> bool {
+        let mut changed = false;
+        ui.horizontal(|ui| {
+            ui.label("Filter:");
+            changed |= ui
+                .add(
+                    egui::TextEdit::singleline(&mut self.text)
+                        .hint_text(if self.is_regex { "regex" } else { "text" })
+                        .desired_width(260.0),
+                )
+                .changed();
+            changed |= ui
+                .checkbox(&mut self.is_regex, "Regex")
+                .on_hover_text("Match the filter as a regex instead of plain text")
+                .changed();
+            if !self.text.is_empty() && ui.small_button("✖").on_hover_text("Clear").clicked() {
+                self.text.clear();
+                changed = true;
+            }
+            if changed {
+                self.compile();
+            }
+            if let Some(e) = &self.error {
+                ui.colored_label(egui::Color32::RED, e);
+            } else if let Some((shown, total)) = counts.filter(|_| self.is_active()) {
+                ui.label(format!("{} of {} rows", shown, total));
+            }
+        });
+        changed
+    }
+}
@@ -0,0 +1,104 @@
+// Rhai scripts that transform each received line before it is parsed, for
+// streams that mix log text and telemetry. The line is in the `line`
+// variable and the script's value decides what happens to it:
+//
+//     if line.starts_with("DBG") { return false; }     // drop the line
+//     if line.starts_with("T:") {
+//         return line.sub_string(2).split(",");       // use as columns
+//     }
+//     line.to_upper()                                   // parse this text
+//
+// Nothing (or true) keeps the line unchanged for the parser.
+
+use rhai::{Dynamic, Engine, Scope, AST};
+
+use crate::settings::Settings;
+
+/// Bounds a runaway script (e.g. an endless loop) so the UI can't hang
+const MAX_OPERATIONS: u64 = 100_000;
+
+/// What the script decided for a line
+pub enum ScriptOutput {
+    /// Parse the line as usual
+    Keep,
+    /// Parse this text instead of the line
+    Text(String),
+    /// Use these values as the row's columns, bypassing the parser
+    Columns(Vec<String>),
+    /// Leave the line out of the table
+    Drop,
+}
+
+pub struct LineScript {
+    engine: Engine,
+    ast: AST,
+}
+
+impl LineScript {
+    /// None when no script is configured
+    pub fn from_settings(settings: &Settings) -> Result<Option<Self>, String> {
+        if settings.line_script.trim().is_empty() {
+            return Ok(None);
+        }
+        let mut engine = Engine::new();
+        engine.set_max_operations(MAX_OPERATIONS);
+        let ast = engine
+            .compile(&settings.line_script)
+            .map_err(|e| format!("Line script error: {}", e))?;
+        Ok(Some(Self { engine, ast }))
+    }
+
+    /// Runs the script on one line. Each line starts with a fresh scope.
+    pub fn run(&self, line: &str) -> Result<ScriptOutput, String> {
+        let mut scope = Scope::new();
+        scope.push("line", line.to_string());
+        let value: Dynamic = self
+            .engine
+            .eval_ast_with_scope(&mut scope, &self.ast)
+            .map_err(|e| e.to_string())?;
+        if value.is_unit() {
+            return Ok(ScriptOutput::Keep);
+        }
+        if let Ok(keep) = value.as_bool() {
+            return Ok(if keep {
+                ScriptOutput::Keep
+            } else {
+                ScriptOutput::Drop
+            });
+        }
+        if value.is_string() {
+            return Ok(ScriptOutput::Text(value.to_string()));
+        }
+        if value.is_array() {
+            let columns = value
+                .into_typed_array::<Dynamic>()?
+                .iter()
+                .map(Dynamic::to_string)
+                .collect();
+            return Ok(ScriptOutput::Columns(columns));
+        }
+        Err(format!(
+            "script returned {}; expected a string, an array, true or false",
+            value.type_name()
+        ))
+    }
+}
+
+/// Settings window section for the line script
+pub fn script_editor(ui: &mut egui::Ui, settings: &mut Settings) {
+    ui.label(
+        "A Rhai script run on every received line (in `line`) before parsing. \
+         Return a string to parse instead, an array to use as the columns, \
+         or false to drop the line.",
+    );
+    ui.add(
+        egui::TextEdit::multiline(&mut settings.line_script)
+            .code_editor()
+            .hint_text("if line.starts_with(\"DBG\") { return false; }")
+            .desired_rows(4)
+            .desired_width(f32::INFINITY),
+    );
+    if let Err(e) = LineScript::from_settings(settings) {
+        ui.colored_label(egui::Color32::RED, e);
+    }
+}
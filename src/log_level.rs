@@ -0,0 +1,127 @@
+// Log levels: most firmware logs mark each line with a severity ("[ERROR]",
+// "<wrn>", ESP-IDF's "W (1234) wifi:"). The marker is found once on receipt,
+// by a built-in pattern or a configurable regex, and the row is colored by
+// it; the status bar filters rows below a minimum level. Lines without a
+// marker are never hidden, so continuation lines and data rows stay visible.
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::regex_guard;
+use crate::settings::Settings;
+
+/// ESP-IDF / Zephyr style single letters at the line start, or a level word
+/// anywhere in the line
+const DEFAULT_PATTERN: &str = r"^([EWIDV]) \(\d+\)|(?i:\b(trace|verbose|debug|dbg|info|inf|notice|warn|warning|wrn|error|err|fatal|critical|crit)\b)";
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum LogLevel {
+    #[default]
+    Trace,
+    Debug,
+    Info,
+    Warn,
+    Error,
+}
+
+impl LogLevel {
+    pub fn label(&self) -> &'static str {
+        match self {
+            LogLevel::Trace => "Trace",
+            LogLevel::Debug => "Debug",
+            LogLevel::Info => "Info",
+            LogLevel::Warn => "Warn",
+            LogLevel::Error => "Error",
+        }
+    }
+    pub fn all() -> &'static [LogLevel] {
+        &[
+            LogLevel::Trace,
+            LogLevel::Debug,
+            LogLevel::Info,
+            LogLevel::Warn,
+            LogLevel::Error,
+        ]
+    }
+
+    /// Text color of rows at this level; None keeps the default color
+    pub fn color(&self) -> Option<egui::Color32> {
+        match self {
+            LogLevel::Trace => Some(egui::Color32::from_rgb(110, 110, 110)),
+            LogLevel::Debug => Some(egui::Color32::from_rgb(145, 145, 145)),
+            LogLevel::Info => None,
+            LogLevel::Warn => Some(egui::Color32::from_rgb(230, 180, 60)),
+            LogLevel::Error => Some(egui::Color32::from_rgb(235, 90, 90)),
+        }
+    }
+
+    /// Level of a marker by its first letter, so "E", "err" and "ERROR" all
+    /// read as errors
+    fn from_marker(marker: &str) -> Option<Self> {
+        match marker.chars().next()?.to_ascii_lowercase() {
+            't' | 'v' => Some(LogLevel::Trace),
+            'd' => Some(LogLevel::Debug),
+            'i' | 'n' => Some(LogLevel::Info),
+            'w' => Some(LogLevel::Warn),
+            'e' | 'f' | 'c' => Some(LogLevel::Error),
+            _ => None,
+        }
+    }
+}
+
+pub struct LevelDetector {
+    regex: Regex,
+}
+
+impl LevelDetector {
+    /// None unless level detection is on
+    pub fn from_settings(settings: &Settings) -> Result<Option<Self>, String> {
+        if !settings.log_levels {
+            return Ok(None);
+        }
+        let pattern = match settings.level_pattern.as_str() {
+            "" => DEFAULT_PATTERN,
+            pattern => pattern,
+        };
+        let regex =
+            regex_guard::compile(pattern).map_err(|e| format!("Log level pattern: {}", e))?;
+        Ok(Some(Self { regex }))
+    }
+
+    /// Level of the line, read from the first capture group that matched (or
+    /// the whole match of a pattern without groups)
+    pub fn detect(&self, line: &str) -> Option<LogLevel> {
+        let caps = self.regex.captures(line)?;
+        let marker = caps
+            .iter()
+            .skip(1)
+            .flatten()
+            .next()
+            .or_else(|| caps.get(0))?;
+        LogLevel::from_marker(marker.as_str())
+    }
+}
+
+/// Settings section for level detection
+pub fn level_editor(ui: &mut egui::Ui, settings: &mut Settings) {
+    ui.checkbox(&mut settings.log_levels, "Detect log levels and color rows");
+    ui.add_enabled_ui(settings.log_levels, |ui| {
+        ui.horizontal(|ui| {
+            ui.label("Level pattern:");
+            ui.add(
+                egui::TextEdit::singleline(&mut settings.level_pattern)
+                    .hint_text(r"e.g. \[(\w+)\], empty = built-in markers")
+                    .font(egui::TextStyle::Monospace)
+                    .desired_width(f32::INFINITY),
+            );
+        });
+    });
+    if let Err(e) = LevelDetector::from_settings(settings) {
+        ui.colored_label(egui::Color32::RED, e);
+    }
+    ui.label(
+        "The built-in markers are level words such as ERROR, warn or <dbg> and ESP-IDF's \
+         `E (1234)` prefix. A custom pattern's first capture group is read by its first \
+         letter: T/V trace, D debug, I/N info, W warn, E/F/C error.",
+    );
+}
@@ -0,0 +1,134 @@
+// Low-latency tweaks for USB serial adapters. On Linux this sets
+// ASYNC_LOW_LATENCY on the tty (ftdi_sio then drops its latency timer to 1 ms)
+// and sets the FTDI latency timer via sysfs where writable. Other platforms
+// only get the shorter read timeout. The same serial_struct ioctls set the
+// legacy custom baud divisor (see `driver_quirks`).
+
+use std::time::Duration;
+
+/// Read timeout used in low-latency mode
+pub const LOW_LATENCY_TIMEOUT: Duration = Duration::from_millis(5);
+/// Read timeout used otherwise
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_millis(50);
+
+#[cfg(target_os = "linux")]
+mod linux {
+    use std::os::unix::io::RawFd;
+
+    const ASYNC_LOW_LATENCY: libc::c_int = 1 << 13;
+    const ASYNC_SPD_MASK: libc::c_int = 0x1030;
+    const ASYNC_SPD_CUST: libc::c_int = 0x0030;
+
+    /// `struct serial_struct` from `<linux/serial.h>`
+    #[repr(C)]
+    struct SerialStruct {
+        type_: libc::c_int,
+        line: libc::c_int,
+        port: libc::c_uint,
+        irq: libc::c_int,
+        flags: libc::c_int,
+        xmit_fifo_size: libc::c_int,
+        custom_divisor: libc::c_int,
+        baud_base: libc::c_int,
+        close_delay: libc::c_ushort,
+        io_type: libc::c_char,
+        reserved_char: [libc::c_char; 1],
+        hub6: libc::c_int,
+        closing_wait: libc::c_ushort,
+        closing_wait2: libc::c_ushort,
+        iomem_base: *mut libc::c_uchar,
+        iomem_reg_shift: libc::c_ushort,
+        port_high: libc::c_uint,
+        iomap_base: libc::c_ulong,
+    }
+
+    /// Reads the tty's serial_struct, lets `change` edit it and writes it back
+    fn update_serial(fd: RawFd, change: impl FnOnce(&mut SerialStruct)) -> Result<(), String> {
+        // SAFETY: serial_struct is plain old data; the ioctls only read/write it.
+        unsafe {
+            let mut ss: SerialStruct = std::mem::zeroed();
+            if libc::ioctl(fd, libc::TIOCGSERIAL, &mut ss) != 0 {
+                return Err(format!(
+                    "TIOCGSERIAL failed: {}",
+                    std::io::Error::last_os_error()
+                ));
+            }
+            change(&mut ss);
+            if libc::ioctl(fd, libc::TIOCSSERIAL, &ss) != 0 {
+                return Err(format!(
+                    "TIOCSSERIAL failed: {}",
+                    std::io::Error::last_os_error()
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn set_async_low_latency(fd: RawFd) -> Result<(), String> {
+        update_serial(fd, |ss| ss.flags |= ASYNC_LOW_LATENCY)
+    }
+
+    /// Makes 38400 baud mean `baud_base / divisor` with the divisor closest to
+    /// `baud`. Returns the resulting baud rate.
+    pub fn set_custom_divisor(fd: RawFd, baud: u32) -> Result<u32, String> {
+        let mut actual = Err("the driver reports no base clock".to_string());
+        update_serial(fd, |ss| {
+            if ss.baud_base <= 0 || baud == 0 {
+                return;
+            }
+            let divisor = ((ss.baud_base as f64 / baud as f64).round() as libc::c_int).max(1);
+            ss.flags = (ss.flags & !ASYNC_SPD_MASK) | ASYNC_SPD_CUST;
+            ss.custom_divisor = divisor;
+            actual = Ok((ss.baud_base / divisor) as u32);
+        })?;
+        actual
+    }
+
+    /// Sets the FTDI latency timer to 1 ms via sysfs, if the device is an FTDI
+    /// adapter. Returns Ok(false) when there is no latency timer to set.
+    pub fn set_ftdi_latency_timer(port_name: &str) -> Result<bool, String> {
+        let Some(tty) = port_name.rsplit('/').next() else {
+            return Ok(false);
+        };
+        let path = format!("/sys/bus/usb-serial/devices/{}/latency_timer", tty);
+        if !std::path::Path::new(&path).exists() {
+            return Ok(false);
+        }
+        std::fs::write(&path, "1")
+            .map(|_| true)
+            .map_err(|e| format!("Cannot set {}: {}", path, e))
+    }
+}
+
+/// Applies the platform low-latency tweaks to an open port.
+/// Returns a warning message if something could not be applied.
+#[cfg(target_os = "linux")]
+pub fn enable(port_name: &str, port: &serialport::TTYPort) -> Option<String> {
+    use std::os::unix::io::AsRawFd;
+
+    let mut warnings = Vec::new();
+    if let Err(e) = linux::set_async_low_latency(port.as_raw_fd()) {
+        warnings.push(e);
+    }
+    if let Err(e) = linux::set_ftdi_latency_timer(port_name) {
+        warnings.push(e);
+    }
+    if warnings.is_empty() {
+        None
+    } else {
+        Some(format!("Low-latency mode: {}", warnings.join("; ")))
+    }
+}
+
+/// Sets the custom divisor closest to `baud` and switches the port to the
+/// 38400 baud alias. Returns the baud rate actually set.
+#[cfg(target_os = "linux")]
+pub fn set_custom_divisor(port: &mut serialport::TTYPort, baud: u32) -> Result<u32, String> {
+    use serialport::SerialPort;
+    use std::os::unix::io::AsRawFd;
+
+    let actual = linux::set_custom_divisor(port.as_raw_fd(), baud)?;
+    port.set_baud_rate(38400)
+        .map_err(|e| format!("Cannot select the 38400 alias: {}", e))?;
+    Ok(actual)
+}
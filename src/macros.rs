@@ -0,0 +1,171 @@
+use egui::{Key, KeyboardShortcut, Modifiers};
+use serde::{Deserialize, Serialize};
+
+/// Keys a macro can be bound to. Letters and digits need a modifier so they
+/// don't fire while typing.
+const BINDABLE_KEYS: &[Key] = &[
+    Key::F1,
+    Key::F2,
+    Key::F3,
+    Key::F4,
+    Key::F5,
+    Key::F6,
+    Key::F7,
+    Key::F8,
+    Key::F9,
+    Key::F10,
+    Key::F11,
+    Key::F12,
+    Key::Num0,
+    Key::Num1,
+    Key::Num2,
+    Key::Num3,
+    Key::Num4,
+    Key::Num5,
+    Key::Num6,
+    Key::Num7,
+    Key::Num8,
+    Key::Num9,
+    Key::A,
+    Key::B,
+    Key::C,
+    Key::D,
+    Key::E,
+    Key::F,
+    Key::G,
+    Key::H,
+    Key::I,
+    Key::J,
+    Key::K,
+    Key::L,
+    Key::M,
+    Key::N,
+    Key::O,
+    Key::P,
+    Key::Q,
+    Key::R,
+    Key::S,
+    Key::T,
+    Key::U,
+    Key::V,
+    Key::W,
+    Key::X,
+    Key::Y,
+    Key::Z,
+];
+
+/// A named string sent to the port on click or key press
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TxMacro {
+    pub name: String,
+    pub text: String,
+    /// egui key name (e.g. "F1"), empty = unbound
+    pub key: String,
+    pub ctrl: bool,
+    pub shift: bool,
+    pub alt: bool,
+}
+
+impl TxMacro {
+    fn modifiers(&self) -> Modifiers {
+        Modifiers {
+            alt: self.alt,
+            ctrl: self.ctrl,
+            shift: self.shift,
+            ..Default::default()
+        }
+    }
+
+    pub fn shortcut(&self) -> Option<KeyboardShortcut> {
+        let key = Key::from_name(&self.key)?;
+        let is_function_key = BINDABLE_KEYS[..12].contains(&key);
+        if !is_function_key && !self.ctrl && !self.alt {
+            return None;
+        }
+        Some(KeyboardShortcut::new(self.modifiers(), key))
+    }
+
+    /// Human readable binding, e.g. "Ctrl+Shift+F3"
+    pub fn binding_label(&self) -> Option<String> {
+        let shortcut = self.shortcut()?;
+        let mut parts = Vec::new();
+        if self.ctrl {
+            parts.push("Ctrl");
+        }
+        if self.alt {
+            parts.push("Alt");
+        }
+        if self.shift {
+            parts.push("Shift");
+        }
+        parts.push(shortcut.logical_key.name());
+        Some(parts.join("+"))
+    }
+
+    /// Number of modifiers; more specific bindings are checked first
+    pub fn specificity(&self) -> usize {
+        [self.ctrl, self.alt, self.shift].iter().filter(|&&m| m).count()
+    }
+}
+
+/// Returns the index of the macro whose shortcut was pressed this frame
+pub fn pressed_macro(ctx: &egui::Context, macros: &[TxMacro]) -> Option<usize> {
+    let mut order: Vec<usize> = (0..macros.len()).collect();
+    order.sort_by_key(|&i| std::cmp::Reverse(macros[i].specificity()));
+    ctx.input_mut(|input| {
+        order.into_iter().find(|&i| {
+            macros[i]
+                .shortcut()
+                .is_some_and(|shortcut| input.consume_shortcut(&shortcut))
+        })
+    })
+}
+
+/// Settings editor for the macro list
+pub fn macro_editor(ui: &mut egui::Ui, macros: &mut Vec<TxMacro>) {
+    let mut remove = None;
+
+    egui::Grid::new("macro_grid")
+        .num_columns(5)
+        .spacing([6.0, 4.0])
+        .striped(true)
+        .show(ui, |ui| {
+            ui.strong("Name");
+            ui.strong("Text");
+            ui.strong("Key");
+            ui.strong("Modifiers");
+            ui.end_row();
+
+            for (i, m) in macros.iter_mut().enumerate() {
+                ui.add(egui::TextEdit::singleline(&mut m.name).desired_width(80.0));
+                ui.add(egui::TextEdit::singleline(&mut m.text).desired_width(140.0));
+                egui::ComboBox::from_id_salt(("macro_key", i))
+                    .selected_text(if m.key.is_empty() { "None" } else { &m.key })
+                    .width(60.0)
+                    .show_ui(ui, |ui| {
+                        ui.selectable_value(&mut m.key, String::new(), "None");
+                        for key in BINDABLE_KEYS {
+                            ui.selectable_value(&mut m.key, key.name().to_string(), key.name());
+                        }
+                    });
+                ui.horizontal(|ui| {
+                    ui.checkbox(&mut m.ctrl, "Ctrl");
+                    ui.checkbox(&mut m.alt, "Alt");
+                    ui.checkbox(&mut m.shift, "Shift");
+                });
+                if ui.small_button("Remove").clicked() {
+                    remove = Some(i);
+                }
+                ui.end_row();
+            }
+        });
+
+    if let Some(i) = remove {
+        macros.remove(i);
+    }
+    if ui.button("Add macro").clicked() {
+        macros.push(TxMacro::default());
+    }
+    ui.label("Letter and digit keys need Ctrl or Alt.");
+}
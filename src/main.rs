@@ -1,10 +1,70 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
+mod aggregate;
+mod alerts;
+mod ansi;
 mod app;
+mod baud_scan;
+mod capture_store;
+mod checksum;
+mod column_layout;
+mod column_types;
+mod command_palette;
+mod control_lines;
+mod dashboard;
+mod decoder_stats;
+mod defmt;
+mod derived;
+mod device_info;
+mod driver_quirks;
+mod export;
+mod frame_fields;
+mod framing;
+mod hex_dump;
+mod histogram;
+mod line_errors;
+mod line_filter;
+mod line_script;
+mod log_level;
+mod low_latency;
+mod macros;
+mod modbus;
+mod nmea;
+mod parser;
+mod plot;
+mod port_watch;
+mod proto_schema;
+mod records;
+mod regex_guard;
+mod routing;
+mod script;
+mod search;
+mod selection;
+mod sequence;
 mod serial_port;
+mod session;
+mod session_summary;
 mod settings;
+mod simulator;
+mod sniffer;
+mod symbols;
+mod table_view;
+mod terminal;
+mod theme;
+mod timestamp;
+mod tlv;
+mod tx_map;
+mod units;
 
 fn main() -> eframe::Result<()> {
+    // uart_console --headless <script> [--port <name>] [--baud <rate>]
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if let [flag, script, rest @ ..] = args.as_slice() {
+        if flag == "--headless" {
+            std::process::exit(script::run_headless(script, rest));
+        }
+    }
+
     let native_options = eframe::NativeOptions {
         viewport: egui::ViewportBuilder::default()
             .with_title("UART Console")
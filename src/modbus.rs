@@ -0,0 +1,512 @@
+use std::time::{Duration, Instant};
+
+use crate::checksum::Checksum;
+use crate::framing::to_hex;
+
+/// A slave that hasn't answered within this time is reported as timed out
+const RESPONSE_TIMEOUT: Duration = Duration::from_millis(1000);
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ModbusFunction {
+    ReadCoils,
+    ReadDiscreteInputs,
+    ReadHoldingRegisters,
+    ReadInputRegisters,
+    WriteSingleCoil,
+    WriteSingleRegister,
+    WriteMultipleRegisters,
+}
+
+impl ModbusFunction {
+    pub fn label(&self) -> &'static str {
+        match self {
+            ModbusFunction::ReadCoils => "01 Read coils",
+            ModbusFunction::ReadDiscreteInputs => "02 Read discrete inputs",
+            ModbusFunction::ReadHoldingRegisters => "03 Read holding registers",
+            ModbusFunction::ReadInputRegisters => "04 Read input registers",
+            ModbusFunction::WriteSingleCoil => "05 Write single coil",
+            ModbusFunction::WriteSingleRegister => "06 Write single register",
+            ModbusFunction::WriteMultipleRegisters => "16 Write multiple registers",
+        }
+    }
+    pub fn all() -> &'static [ModbusFunction] {
+        &[
+            ModbusFunction::ReadCoils,
+            ModbusFunction::ReadDiscreteInputs,
+            ModbusFunction::ReadHoldingRegisters,
+            ModbusFunction::ReadInputRegisters,
+            ModbusFunction::WriteSingleCoil,
+            ModbusFunction::WriteSingleRegister,
+            ModbusFunction::WriteMultipleRegisters,
+        ]
+    }
+
+    pub fn code(&self) -> u8 {
+        match self {
+            ModbusFunction::ReadCoils => 1,
+            ModbusFunction::ReadDiscreteInputs => 2,
+            ModbusFunction::ReadHoldingRegisters => 3,
+            ModbusFunction::ReadInputRegisters => 4,
+            ModbusFunction::WriteSingleCoil => 5,
+            ModbusFunction::WriteSingleRegister => 6,
+            ModbusFunction::WriteMultipleRegisters => 16,
+        }
+    }
+
+    fn reads_bits(&self) -> bool {
+        matches!(
+            self,
+            ModbusFunction::ReadCoils | ModbusFunction::ReadDiscreteInputs
+        )
+    }
+
+    fn is_write(&self) -> bool {
+        matches!(
+            self,
+            ModbusFunction::WriteSingleCoil
+                | ModbusFunction::WriteSingleRegister
+                | ModbusFunction::WriteMultipleRegisters
+        )
+    }
+
+    /// Largest quantity one request may read or write
+    fn max_count(&self) -> u16 {
+        match self {
+            ModbusFunction::ReadCoils | ModbusFunction::ReadDiscreteInputs => 2000,
+            ModbusFunction::WriteMultipleRegisters => 123,
+            _ => 125,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ModbusRequest {
+    pub slave: u8,
+    pub function: ModbusFunction,
+    pub address: u16,
+    /// Coils/registers to read
+    pub count: u16,
+    /// Values to write
+    pub values: Vec<u16>,
+}
+
+impl ModbusRequest {
+    /// The request ADU including its CRC
+    pub fn encode(&self) -> Vec<u8> {
+        let mut frame = vec![self.slave, self.function.code()];
+        frame.extend_from_slice(&self.address.to_be_bytes());
+        match self.function {
+            ModbusFunction::WriteSingleCoil => {
+                let on = self.values.first().is_some_and(|&v| v != 0);
+                frame.extend_from_slice(if on { &[0xFF, 0x00] } else { &[0x00, 0x00] });
+            }
+            ModbusFunction::WriteSingleRegister => {
+                let value = self.values.first().copied().unwrap_or(0);
+                frame.extend_from_slice(&value.to_be_bytes());
+            }
+            ModbusFunction::WriteMultipleRegisters => {
+                frame.extend_from_slice(&(self.values.len() as u16).to_be_bytes());
+                frame.push((self.values.len() * 2) as u8);
+                for v in &self.values {
+                    frame.extend_from_slice(&v.to_be_bytes());
+                }
+            }
+            _ => frame.extend_from_slice(&self.count.to_be_bytes()),
+        }
+        let crc = Checksum::Crc16Modbus.compute(&frame) as u16;
+        frame.extend_from_slice(&crc.to_le_bytes());
+        frame
+    }
+
+    /// Decodes the response to this request as a `key=value` line, e.g.
+    /// "slave=1 fn=3 r100=42 r101=7". None if the frame isn't a response
+    /// from this slave to this function or its CRC is bad.
+    pub fn decode(&self, frame: &[u8]) -> Option<String> {
+        // Slave, function and CRC at least
+        if frame.len() < 5 || frame[0] != self.slave {
+            return None;
+        }
+        if !Checksum::Crc16Modbus.matches_trailing(frame) {
+            return None;
+        }
+        let code = self.function.code();
+        let pdu = &frame[2..frame.len() - 2];
+        let prefix = format!("slave={} fn={}", self.slave, code);
+        if frame[1] == code | 0x80 {
+            let exception = pdu[0];
+            return Some(format!(
+                "{} exception={} ({})",
+                prefix,
+                exception,
+                exception_label(exception)
+            ));
+        }
+        if frame[1] != code {
+            return None;
+        }
+        if self.function.is_write() {
+            return Some(format!("{} written addr={}", prefix, self.address));
+        }
+        let data = pdu.get(1..1 + *pdu.first()? as usize)?;
+        let values: Vec<String> = if self.function.reads_bits() {
+            (0..self.count as usize)
+                .filter(|i| i / 8 < data.len())
+                .map(|i| {
+                    let bit = (data[i / 8] >> (i % 8)) & 1;
+                    format!("c{}={}", self.address as usize + i, bit)
+                })
+                .collect()
+        } else {
+            data.chunks_exact(2)
+                .enumerate()
+                .map(|(i, pair)| {
+                    let value = u16::from_be_bytes([pair[0], pair[1]]);
+                    format!("r{}={}", self.address as usize + i, value)
+                })
+                .collect()
+        };
+        Some(format!("{} {}", prefix, values.join(" ")))
+    }
+}
+
+fn exception_label(code: u8) -> &'static str {
+    match code {
+        1 => "illegal function",
+        2 => "illegal data address",
+        3 => "illegal data value",
+        4 => "slave device failure",
+        5 => "acknowledge",
+        6 => "slave device busy",
+        8 => "memory parity error",
+        10 => "gateway path unavailable",
+        11 => "gateway target failed to respond",
+        _ => "unknown",
+    }
+}
+
+/// Parses comma-separated values, decimal or 0x-prefixed hex
+fn parse_values(text: &str) -> Result<Vec<u16>, String> {
+    text.split(',')
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+        .map(|v| {
+            let parsed = match v.strip_prefix("0x").or_else(|| v.strip_prefix("0X")) {
+                Some(hex) => u16::from_str_radix(hex, 16),
+                None => v.parse::<u16>(),
+            };
+            parsed.map_err(|_| format!("Invalid value \"{}\"", v))
+        })
+        .collect()
+}
+
+/// Request form with interval polling; responses are matched to the
+/// outstanding request
+pub struct ModbusWindow {
+    pub is_open: bool,
+    slave: u8,
+    function: ModbusFunction,
+    address: u16,
+    count: u16,
+    /// Values to write, comma-separated
+    values: String,
+    poll: bool,
+    poll_interval_ms: u64,
+    last_sent: Option<Instant>,
+    /// Request awaiting its response
+    pending: Option<ModbusRequest>,
+    status: String,
+}
+
+impl ModbusWindow {
+    pub fn new() -> Self {
+        Self {
+            is_open: false,
+            slave: 1,
+            function: ModbusFunction::ReadHoldingRegisters,
+            address: 0,
+            count: 1,
+            values: String::new(),
+            poll: false,
+            poll_interval_ms: 1000,
+            last_sent: None,
+            pending: None,
+            status: String::new(),
+        }
+    }
+
+    fn request(&self) -> Result<ModbusRequest, String> {
+        let values = if self.function.is_write() {
+            let values = parse_values(&self.values)?;
+            if values.is_empty() {
+                return Err("No value to write".to_string());
+            }
+            if values.len() > self.function.max_count() as usize {
+                return Err(format!("At most {} values", self.function.max_count()));
+            }
+            values
+        } else {
+            Vec::new()
+        };
+        Ok(ModbusRequest {
+            slave: self.slave,
+            function: self.function,
+            address: self.address,
+            count: self.count.clamp(1, self.function.max_count()),
+            values,
+        })
+    }
+
+    /// Builds the request to send and marks it outstanding
+    fn send(&mut self) -> Option<Vec<u8>> {
+        match self.request() {
+            Ok(request) => {
+                let frame = request.encode();
+                self.status = format!("Sent {}", to_hex(&frame));
+                self.pending = Some(request);
+                self.last_sent = Some(Instant::now());
+                Some(frame)
+            }
+            Err(e) => {
+                self.status = e;
+                self.poll = false;
+                None
+            }
+        }
+    }
+
+    /// Reports a timed-out request and returns the next poll request when due
+    pub fn poll(&mut self, connected: bool) -> Option<Vec<u8>> {
+        let since = self.last_sent.map(|t| t.elapsed());
+        if self.pending.is_some() && since.is_some_and(|s| s >= RESPONSE_TIMEOUT) {
+            self.status = format!("No response from slave {}", self.slave);
+            self.pending = None;
+        }
+        let interval = Duration::from_millis(self.poll_interval_ms);
+        if !self.poll || !connected || self.pending.is_some() || since.is_some_and(|s| s < interval)
+        {
+            return None;
+        }
+        self.send()
+    }
+
+    /// Matches a received frame to the outstanding request. Returns the
+    /// decoded line, or None if the frame isn't its response.
+    pub fn response(&mut self, frame: &[u8]) -> Option<String> {
+        let line = self.pending.as_ref()?.decode(frame)?;
+        self.pending = None;
+        self.status = match self.last_sent {
+            Some(t) => format!("Response after {} ms", t.elapsed().as_millis()),
+            None => String::new(),
+        };
+        Some(line)
+    }
+
+    /// Renders the window. Returns a request frame to send.
+    pub fn show(&mut self, ctx: &egui::Context, connected: bool) -> Option<Vec<u8>> {
+        if !self.is_open {
+            return None;
+        }
+        let mut open = self.is_open;
+        let mut send = false;
+
+        egui::Window::new("Modbus RTU")
+            .open(&mut open)
+            .resizable(false)
+            .show(ctx, |ui| {
+                egui::Grid::new("modbus_grid")
+                    .num_columns(2)
+                    .spacing([8.0, 4.0])
+                    .show(ui, |ui| {
+                        ui.label("Slave ID:");
+                        ui.add(egui::DragValue::new(&mut self.slave).range(1..=247));
+                        ui.end_row();
+
+                        ui.label("Function:");
+                        egui::ComboBox::from_id_salt("modbus_function")
+                            .selected_text(self.function.label())
+                            .width(200.0)
+                            .show_ui(ui, |ui| {
+                                for f in ModbusFunction::all() {
+                                    ui.selectable_value(&mut self.function, *f, f.label());
+                                }
+                            });
+                        ui.end_row();
+
+                        ui.label("Address:");
+                        ui.add(egui::DragValue::new(&mut self.address));
+                        ui.end_row();
+
+                        if self.function.is_write() {
+                            ui.label("Values:");
+                            ui.add(
+                                egui::TextEdit::singleline(&mut self.values)
+                                    .hint_text("e.g. 100, 0x1F")
+                                    .desired_width(160.0),
+                            );
+                        } else {
+                            ui.label("Count:");
+                            ui.add(
+                                egui::DragValue::new(&mut self.count)
+                                    .range(1..=self.function.max_count()),
+                            );
+                        }
+                        ui.end_row();
+                    });
+                ui.add_space(6.0);
+                ui.horizontal(|ui| {
+                    if ui
+                        .add_enabled(connected, egui::Button::new("Send"))
+                        .clicked()
+                    {
+                        send = true;
+                    }
+                    ui.checkbox(&mut self.poll, "Poll every");
+                    ui.add(
+                        egui::DragValue::new(&mut self.poll_interval_ms)
+                            .range(50..=60000)
+                            .suffix(" ms"),
+                    );
+                });
+                if !self.status.is_empty() {
+                    ui.label(&self.status);
+                }
+                ui.weak("Responses appear in the table as key=value lines.");
+            });
+
+        self.is_open = open;
+        if !open {
+            self.poll = false;
+        }
+        if send {
+            self.send()
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(slave: u8, function: ModbusFunction, address: u16, count: u16) -> ModbusRequest {
+        ModbusRequest {
+            slave,
+            function,
+            address,
+            count,
+            values: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn encodes_requests() {
+        // Request examples from the Modbus RTU references (Simply Modbus)
+        let read = request(0x11, ModbusFunction::ReadHoldingRegisters, 0x006B, 3);
+        assert_eq!(
+            read.encode(),
+            [0x11, 0x03, 0x00, 0x6B, 0x00, 0x03, 0x76, 0x87]
+        );
+
+        let read = request(1, ModbusFunction::ReadHoldingRegisters, 0, 10);
+        assert_eq!(
+            read.encode(),
+            [0x01, 0x03, 0x00, 0x00, 0x00, 0x0A, 0xC5, 0xCD]
+        );
+
+        let coil = ModbusRequest {
+            values: vec![1],
+            ..request(0x11, ModbusFunction::WriteSingleCoil, 0x00AC, 0)
+        };
+        assert_eq!(
+            coil.encode(),
+            [0x11, 0x05, 0x00, 0xAC, 0xFF, 0x00, 0x4E, 0x8B]
+        );
+
+        let register = ModbusRequest {
+            values: vec![3],
+            ..request(1, ModbusFunction::WriteSingleRegister, 1, 0)
+        };
+        assert_eq!(
+            register.encode(),
+            [0x01, 0x06, 0x00, 0x01, 0x00, 0x03, 0x98, 0x0B]
+        );
+
+        let registers = ModbusRequest {
+            values: vec![0x000A, 0x0102],
+            ..request(0x11, ModbusFunction::WriteMultipleRegisters, 1, 0)
+        };
+        assert_eq!(
+            registers.encode(),
+            [0x11, 0x10, 0x00, 0x01, 0x00, 0x02, 0x04, 0x00, 0x0A, 0x01, 0x02, 0xC6, 0xF0]
+        );
+    }
+
+    #[test]
+    fn decodes_register_responses() {
+        let read = request(0x11, ModbusFunction::ReadHoldingRegisters, 0x006B, 3);
+        let response = [
+            0x11, 0x03, 0x06, 0xAE, 0x41, 0x56, 0x52, 0x43, 0x40, 0x49, 0xAD,
+        ];
+        assert_eq!(
+            read.decode(&response).as_deref(),
+            Some("slave=17 fn=3 r107=44609 r108=22098 r109=17216")
+        );
+    }
+
+    #[test]
+    fn decodes_coil_responses() {
+        let read = request(1, ModbusFunction::ReadCoils, 20, 10);
+        let response = [0x01, 0x01, 0x02, 0xCD, 0x01, 0x2C, 0xAC];
+        assert_eq!(
+            read.decode(&response).as_deref(),
+            Some("slave=1 fn=1 c20=1 c21=0 c22=1 c23=1 c24=0 c25=0 c26=1 c27=1 c28=1 c29=0")
+        );
+    }
+
+    #[test]
+    fn decodes_write_echoes_and_exceptions() {
+        let write = ModbusRequest {
+            values: vec![0x000A, 0x0102],
+            ..request(0x11, ModbusFunction::WriteMultipleRegisters, 1, 0)
+        };
+        let echo = [0x11, 0x10, 0x00, 0x01, 0x00, 0x02, 0x12, 0x98];
+        assert_eq!(
+            write.decode(&echo).as_deref(),
+            Some("slave=17 fn=16 written addr=1")
+        );
+
+        let read = request(0x11, ModbusFunction::ReadHoldingRegisters, 0x006B, 3);
+        let exception = [0x11, 0x83, 0x02, 0xC1, 0x34];
+        assert_eq!(
+            read.decode(&exception).as_deref(),
+            Some("slave=17 fn=3 exception=2 (illegal data address)")
+        );
+    }
+
+    #[test]
+    fn ignores_other_frames() {
+        let read = request(0x11, ModbusFunction::ReadHoldingRegisters, 0x006B, 3);
+        // Bad CRC, another slave, another function, too short
+        let mut response = vec![
+            0x11, 0x03, 0x06, 0xAE, 0x41, 0x56, 0x52, 0x43, 0x40, 0x49, 0xAD,
+        ];
+        response[4] ^= 1;
+        assert_eq!(read.decode(&response), None);
+        let other_slave = request(0x12, ModbusFunction::ReadHoldingRegisters, 0x006B, 3);
+        assert_eq!(other_slave.decode(&[0x11, 0x83, 0x02, 0xC1, 0x34]), None);
+        let coils = request(1, ModbusFunction::ReadCoils, 0, 1);
+        assert_eq!(
+            coils.decode(&[0x01, 0x03, 0x00, 0x00, 0x00, 0x0A, 0xC5, 0xCD]),
+            None
+        );
+        assert_eq!(read.decode(&[0x11, 0x03]), None);
+    }
+
+    #[test]
+    fn parses_values() {
+        assert_eq!(parse_values("1, 0x0A,0XFF ,"), Ok(vec![1, 10, 255]));
+        assert!(parse_values("70000").is_err());
+        assert!(parse_values("0xZZ").is_err());
+    }
+}
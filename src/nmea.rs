@@ -0,0 +1,136 @@
+// NMEA 0183 sentences from GPS receivers: `$GPGGA,...*hh`. The checksum is
+// the XOR of the characters between `$` and `*`. GGA, RMC and GSV are decoded
+// into named columns; other sentences are split into numbered fields.
+
+/// Named fields of a sentence, or None if it isn't NMEA or the checksum is bad
+pub fn nmea_fields(line: &str) -> Option<Vec<(String, String)>> {
+    let line = line.trim();
+    let body = line.strip_prefix('$').or_else(|| line.strip_prefix('!'))?;
+    let body = match body.rsplit_once('*') {
+        Some((body, checksum)) => {
+            let expected = u8::from_str_radix(checksum, 16).ok()?;
+            if body.bytes().fold(0, |acc, b| acc ^ b) != expected {
+                return None;
+            }
+            body
+        }
+        // The checksum is optional for most sentences
+        None => body,
+    };
+    let mut parts = body.split(',');
+    let address = parts.next()?;
+    if address.len() < 5 || !address.chars().all(|c| c.is_ascii_alphanumeric()) {
+        return None;
+    }
+    let fields: Vec<&str> = parts.collect();
+    let get = |i: usize| fields.get(i).copied().unwrap_or("");
+
+    let mut out = vec![("Sentence".to_string(), address.to_string())];
+    let mut push = |key: &str, value: String| out.push((key.to_string(), value));
+    // The last three letters name the sentence; the rest is the talker
+    match &address[address.len() - 3..] {
+        "GGA" => {
+            push("Time", time(get(0)));
+            push("Lat", coordinate(get(1), get(2)));
+            push("Lon", coordinate(get(3), get(4)));
+            push("Fix", fix_quality(get(5)).to_string());
+            push("Sats", get(6).to_string());
+            push("HDOP", get(7).to_string());
+            push("Alt (m)", get(8).to_string());
+            push("Geoid (m)", get(10).to_string());
+        }
+        "RMC" => {
+            push("Time", time(get(0)));
+            let status = match get(1) {
+                "A" => "valid",
+                "V" => "warning",
+                other => other,
+            };
+            push("Status", status.to_string());
+            push("Lat", coordinate(get(2), get(3)));
+            push("Lon", coordinate(get(4), get(5)));
+            push("Speed (kn)", get(6).to_string());
+            push("Course", get(7).to_string());
+            push("Date", date(get(8)));
+        }
+        "GSV" => {
+            push("Msg", format!("{}/{}", get(1), get(0)));
+            push("In view", get(2).to_string());
+            // Up to four satellites per message: PRN, elevation, azimuth, SNR
+            let satellites: Vec<String> = fields
+                .get(3..)
+                .unwrap_or_default()
+                .chunks(4)
+                .filter(|sat| !sat[0].is_empty())
+                .map(|sat| {
+                    let snr = sat.get(3).copied().unwrap_or("");
+                    if snr.is_empty() {
+                        format!("PRN {} (not tracked)", sat[0])
+                    } else {
+                        format!("PRN {} ({} dB)", sat[0], snr)
+                    }
+                })
+                .collect();
+            push("Satellites", satellites.join(", "));
+        }
+        _ => {
+            for (i, f) in fields.iter().enumerate() {
+                push(&format!("Field {}", i + 1), f.to_string());
+            }
+        }
+    }
+    Some(out)
+}
+
+/// "123519.00" -> "12:35:19.00"
+fn time(field: &str) -> String {
+    if field.len() < 6 || !field.is_char_boundary(6) {
+        return field.to_string();
+    }
+    format!("{}:{}:{}", &field[0..2], &field[2..4], &field[4..])
+}
+
+/// "010326" -> "2026-03-01"
+fn date(field: &str) -> String {
+    if field.len() != 6 || !field.bytes().all(|b| b.is_ascii_digit()) {
+        return field.to_string();
+    }
+    format!("20{}-{}-{}", &field[4..6], &field[2..4], &field[0..2])
+}
+
+/// "4807.038", "N" -> "48.117300"; south and west are negative
+fn coordinate(value: &str, hemisphere: &str) -> String {
+    let Some(dot) = value.find('.') else {
+        return String::new();
+    };
+    // Degrees are everything before the two minute digits
+    let Some(split) = dot.checked_sub(2).filter(|&i| value.is_char_boundary(i)) else {
+        return String::new();
+    };
+    let (Ok(degrees), Ok(minutes)) = (value[..split].parse::<f64>(), value[split..].parse::<f64>())
+    else {
+        return String::new();
+    };
+    let decimal = degrees + minutes / 60.0;
+    let sign = if matches!(hemisphere, "S" | "W") {
+        -1.0
+    } else {
+        1.0
+    };
+    format!("{:.6}", sign * decimal)
+}
+
+fn fix_quality(field: &str) -> &str {
+    match field {
+        "0" => "none",
+        "1" => "GPS",
+        "2" => "DGPS",
+        "3" => "PPS",
+        "4" => "RTK",
+        "5" => "float RTK",
+        "6" => "estimated",
+        "7" => "manual",
+        "8" => "simulation",
+        other => other,
+    }
+}
@@ -0,0 +1,146 @@
+use std::sync::OnceLock;
+
+use regex::Regex;
+use serde_json::Value;
+
+use crate::nmea::nmea_fields;
+
+/// Splits received lines into table columns
+pub enum LineParser {
+    /// One column per capture group
+    Regex(Regex),
+    /// One column per (flattened) object key, in order of first appearance
+    Json(KeyedColumns),
+    /// One column per `key=value` key, in order of first appearance
+    KeyValue(KeyedColumns),
+    /// Fields split at a delimiter character; `widest` is the most fields seen
+    Delimiter { delimiter: char, widest: usize },
+    /// NMEA 0183 sentences with a valid checksum, decoded to named columns
+    Nmea(KeyedColumns),
+}
+
+impl LineParser {
+    /// Returns the columns of `line`, or None if it doesn't match
+    pub fn parse(&mut self, line: &str) -> Option<Vec<String>> {
+        match self {
+            LineParser::Regex(re) => {
+                let caps = re.captures(line)?;
+                Some(
+                    (1..caps.len())
+                        .map(|i| caps.get(i).map_or("", |m| m.as_str()).to_string())
+                        .collect(),
+                )
+            }
+            LineParser::Json(keyed) => Some(keyed.place(json_fields(line)?)),
+            LineParser::KeyValue(keyed) => Some(keyed.place(key_value_fields(line)?)),
+            LineParser::Nmea(keyed) => Some(keyed.place(nmea_fields(line)?)),
+            LineParser::Delimiter { delimiter, widest } => {
+                if line.trim().is_empty() {
+                    return None;
+                }
+                let fields: Vec<String> = line
+                    .split(*delimiter)
+                    .map(|f| f.trim().to_string())
+                    .collect();
+                *widest = (*widest).max(fields.len());
+                Some(fields)
+            }
+        }
+    }
+
+    pub fn num_columns(&self) -> usize {
+        match self {
+            LineParser::Regex(re) => re.captures_len().saturating_sub(1),
+            LineParser::Json(keyed) | LineParser::KeyValue(keyed) | LineParser::Nmea(keyed) => {
+                keyed.keys.len()
+            }
+            LineParser::Delimiter { widest, .. } => *widest,
+        }
+    }
+
+    /// Name of a column from the pattern itself (named group or key)
+    pub fn column_name(&self, idx: usize) -> Option<&str> {
+        match self {
+            LineParser::Regex(re) => re.capture_names().nth(idx + 1).flatten(),
+            LineParser::Json(keyed) | LineParser::KeyValue(keyed) | LineParser::Nmea(keyed) => {
+                keyed.keys.get(idx).map(String::as_str)
+            }
+            LineParser::Delimiter { .. } => None,
+        }
+    }
+}
+
+/// Column layout learned from the keys seen so far
+#[derive(Default)]
+pub struct KeyedColumns {
+    pub keys: Vec<String>,
+}
+
+impl KeyedColumns {
+    /// Puts each value in its key's column; new keys are appended as columns
+    /// and keys missing from this line leave their column empty
+    fn place(&mut self, fields: Vec<(String, String)>) -> Vec<String> {
+        let mut columns = vec![String::new(); self.keys.len()];
+        for (key, value) in fields {
+            match self.keys.iter().position(|k| *k == key) {
+                Some(i) => columns[i] = value,
+                None => {
+                    self.keys.push(key);
+                    columns.push(value);
+                }
+            }
+        }
+        columns
+    }
+}
+
+/// Fields of a JSON object line. Nested objects and arrays are flattened to
+/// `a.b` and `a.0` keys.
+fn json_fields(line: &str) -> Option<Vec<(String, String)>> {
+    let Value::Object(map) = serde_json::from_str::<Value>(line.trim()).ok()? else {
+        return None;
+    };
+    let mut fields = Vec::new();
+    for (key, value) in &map {
+        flatten(key.clone(), value, &mut fields);
+    }
+    Some(fields)
+}
+
+/// `key=value` pairs of a line, separated by spaces, commas or semicolons.
+/// Values may be double-quoted to contain separators.
+fn key_value_fields(line: &str) -> Option<Vec<(String, String)>> {
+    static PAIR: OnceLock<Regex> = OnceLock::new();
+    let pair = PAIR.get_or_init(|| {
+        Regex::new(r#"([A-Za-z_][\w.\-]*)\s*=\s*(?:"([^"]*)"|([^\s,;]*))"#).expect("valid pattern")
+    });
+    let fields: Vec<(String, String)> = pair
+        .captures_iter(line)
+        .map(|caps| {
+            let value = caps
+                .get(2)
+                .or_else(|| caps.get(3))
+                .map_or("", |m| m.as_str());
+            (caps[1].to_string(), value.to_string())
+        })
+        .collect();
+    (!fields.is_empty()).then_some(fields)
+}
+
+fn flatten(key: String, value: &Value, out: &mut Vec<(String, String)>) {
+    match value {
+        Value::Object(map) => {
+            for (k, v) in map {
+                flatten(format!("{}.{}", key, k), v, out);
+            }
+        }
+        Value::Array(items) => {
+            for (i, v) in items.iter().enumerate() {
+                flatten(format!("{}.{}", key, i), v, out);
+            }
+        }
+        Value::String(s) => out.push((key, s.clone())),
+        Value::Null => out.push((key, String::new())),
+        other => out.push((key, other.to_string())),
+    }
+}
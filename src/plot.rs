@@ -0,0 +1,424 @@
+use chrono::{DateTime, Local};
+use egui::Color32;
+use serde::{Deserialize, Serialize};
+
+const SERIES_COLORS: &[Color32] = &[
+    Color32::from_rgb(90, 170, 250),
+    Color32::from_rgb(250, 150, 60),
+    Color32::from_rgb(110, 210, 110),
+    Color32::from_rgb(230, 90, 110),
+    Color32::from_rgb(190, 130, 240),
+    Color32::from_rgb(230, 210, 80),
+];
+
+/// Which part of the capture a plot shows
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum PlotWindow {
+    /// Last `window_secs` seconds
+    Seconds,
+    /// Last `window_points` rows
+    Points,
+    /// Everything still in the buffer
+    All,
+}
+
+impl PlotWindow {
+    pub fn label(&self) -> &'static str {
+        match self {
+            PlotWindow::Seconds => "Last seconds",
+            PlotWindow::Points => "Last points",
+            PlotWindow::All => "Entire capture",
+        }
+    }
+    pub fn all() -> &'static [PlotWindow] {
+        &[PlotWindow::Seconds, PlotWindow::Points, PlotWindow::All]
+    }
+}
+
+/// A line chart of one or more parsed columns over time
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PlotConfig {
+    pub columns: Vec<usize>,
+    pub window: PlotWindow,
+    pub window_secs: f64,
+    pub window_points: usize,
+    /// Label the X axis with wall-clock time instead of elapsed seconds
+    pub time_of_day: bool,
+    /// Shrink pauses longer than `gap_secs` to `gap_secs`
+    pub compress_gaps: bool,
+    pub gap_secs: f64,
+}
+
+impl Default for PlotConfig {
+    fn default() -> Self {
+        Self {
+            columns: vec![0],
+            window: PlotWindow::Seconds,
+            window_secs: 30.0,
+            window_points: 500,
+            time_of_day: false,
+            compress_gaps: false,
+            gap_secs: 5.0,
+        }
+    }
+}
+
+/// One received row as seen by the plots
+pub struct Sample<'a> {
+    pub time: DateTime<Local>,
+    /// Numeric value of each column
+    pub values: &'a [Option<f64>],
+    /// Minimum and maximum of each column when the sample is an aggregated
+    /// time window, drawn as a band around the line; empty otherwise
+    pub ranges: &'a [Option<(f64, f64)>],
+}
+
+impl PlotConfig {
+    /// Adds the column to the chart, or removes it if already charted
+    pub fn toggle_column(&mut self, col: usize) {
+        if self.columns.contains(&col) {
+            self.columns.retain(|&c| c != col);
+        } else {
+            self.columns.push(col);
+            self.columns.sort_unstable();
+        }
+    }
+
+    /// The samples inside this plot's window
+    fn visible<'s, 'a>(&self, samples: &'s [Sample<'a>]) -> &'s [Sample<'a>] {
+        let start = match self.window {
+            PlotWindow::All => 0,
+            PlotWindow::Points => samples.len().saturating_sub(self.window_points),
+            PlotWindow::Seconds => {
+                let Some(last) = samples.last() else {
+                    return samples;
+                };
+                let span = chrono::Duration::milliseconds((self.window_secs * 1000.0) as i64);
+                let cutoff = last.time - span;
+                samples.partition_point(|s| s.time < cutoff)
+            }
+        };
+        &samples[start..]
+    }
+}
+
+/// Renders all plots stacked vertically. Returns true if the configuration changed.
+pub fn show_plots(
+    ui: &mut egui::Ui,
+    plots: &mut Vec<PlotConfig>,
+    headers: &[String],
+    samples: &[Sample],
+) -> bool {
+    let mut changed = false;
+    let mut remove = None;
+
+    ui.horizontal(|ui| {
+        ui.heading("Plots");
+        if ui.button("Add plot").clicked() {
+            plots.push(PlotConfig::default());
+            changed = true;
+        }
+    });
+
+    if plots.is_empty() {
+        ui.weak("No plots. Click \"Add plot\" to chart a numeric column.");
+        return changed;
+    }
+
+    let plot_height = ((ui.available_height() - 8.0) / plots.len() as f32 - 30.0).max(60.0);
+    egui::ScrollArea::vertical().show(ui, |ui| {
+        for (i, plot) in plots.iter_mut().enumerate() {
+            ui.horizontal(|ui| {
+                changed |= plot_toolbar(ui, i, plot, headers);
+                if ui.small_button("Remove").clicked() {
+                    remove = Some(i);
+                }
+            });
+            draw_plot(ui, plot, headers, plot.visible(samples), plot_height);
+            ui.add_space(4.0);
+        }
+    });
+
+    if let Some(i) = remove {
+        plots.remove(i);
+        changed = true;
+    }
+    changed
+}
+
+/// Toggles a column on the first plot, the one the table header icons
+/// control. A plot is created if there is none yet.
+pub fn toggle_plot_column(plots: &mut Vec<PlotConfig>, col: usize) {
+    match plots.first_mut() {
+        Some(plot) => plot.toggle_column(col),
+        None => plots.push(PlotConfig {
+            columns: vec![col],
+            ..PlotConfig::default()
+        }),
+    }
+}
+
+/// Column selection and window controls above a plot
+fn plot_toolbar(ui: &mut egui::Ui, idx: usize, plot: &mut PlotConfig, headers: &[String]) -> bool {
+    let mut changed = false;
+
+    ui.menu_button(format!("Columns ({})", plot.columns.len()), |ui| {
+        for (col, header) in headers.iter().enumerate() {
+            let mut on = plot.columns.contains(&col);
+            if ui.checkbox(&mut on, header).changed() {
+                plot.toggle_column(col);
+                changed = true;
+            }
+        }
+    });
+
+    let before = plot.window.clone();
+    egui::ComboBox::from_id_salt(("plot_window", idx))
+        .selected_text(plot.window.label())
+        .width(110.0)
+        .show_ui(ui, |ui| {
+            for w in PlotWindow::all() {
+                ui.selectable_value(&mut plot.window, w.clone(), w.label());
+            }
+        });
+    changed |= plot.window != before;
+
+    match plot.window {
+        PlotWindow::Seconds => {
+            changed |= ui
+                .add(
+                    egui::DragValue::new(&mut plot.window_secs)
+                        .range(1.0..=86400.0)
+                        .suffix(" s"),
+                )
+                .changed();
+        }
+        PlotWindow::Points => {
+            changed |= ui
+                .add(
+                    egui::DragValue::new(&mut plot.window_points)
+                        .range(10..=1_000_000)
+                        .suffix(" pts"),
+                )
+                .changed();
+        }
+        PlotWindow::All => {}
+    }
+
+    changed |= ui
+        .checkbox(&mut plot.time_of_day, "Clock")
+        .on_hover_text("Label the time axis with the time of day")
+        .changed();
+    changed |= ui
+        .checkbox(&mut plot.compress_gaps, "Compress gaps")
+        .on_hover_text("Shrink idle periods so sparse bursts stay readable")
+        .changed();
+    if plot.compress_gaps {
+        changed |= ui
+            .add(
+                egui::DragValue::new(&mut plot.gap_secs)
+                    .range(0.1..=3600.0)
+                    .prefix("> ")
+                    .suffix(" s"),
+            )
+            .changed();
+    }
+    changed
+}
+
+/// X position of every sample in seconds from the first one, with pauses
+/// longer than `gap_secs` shortened to `gap_secs` when compressing. Also
+/// returns the positions where a pause was cut.
+fn sample_offsets(plot: &PlotConfig, samples: &[Sample]) -> (Vec<f64>, Vec<f64>) {
+    let mut offsets = Vec::with_capacity(samples.len());
+    let mut cuts = Vec::new();
+    let mut x = 0.0;
+    for (i, s) in samples.iter().enumerate() {
+        if i > 0 {
+            let dt = (s.time - samples[i - 1].time).num_milliseconds() as f64 / 1000.0;
+            if plot.compress_gaps && dt > plot.gap_secs {
+                x += plot.gap_secs;
+                cuts.push(x - plot.gap_secs / 2.0);
+            } else {
+                x += dt;
+            }
+        }
+        offsets.push(x);
+    }
+    (offsets, cuts)
+}
+
+fn draw_plot(
+    ui: &mut egui::Ui,
+    plot: &PlotConfig,
+    headers: &[String],
+    samples: &[Sample],
+    height: f32,
+) {
+    let (rect, _) = ui.allocate_exact_size(
+        egui::vec2(ui.available_width(), height),
+        egui::Sense::hover(),
+    );
+    let painter = ui.painter_at(rect);
+    painter.rect_filled(rect, 2.0, ui.visuals().extreme_bg_color);
+
+    let Some(last) = samples.last() else {
+        return;
+    };
+    let (offsets, cuts) = sample_offsets(plot, samples);
+    let end = offsets.last().copied().unwrap_or(0.0);
+    // A seconds window keeps a fixed width so the live plot scrolls steadily;
+    // with compressed gaps the real time span no longer maps to the width
+    let (x0, span) = match plot.window {
+        PlotWindow::Seconds if !plot.compress_gaps => {
+            let first = samples[0].time;
+            let start =
+                last.time - chrono::Duration::milliseconds((plot.window_secs * 1000.0) as i64);
+            let lead = (first - start).num_milliseconds() as f64 / 1000.0;
+            (-lead, plot.window_secs)
+        }
+        _ => (0.0, end.max(1e-3)),
+    };
+
+    // Parse the series and find the value range
+    type Point = (f64, f64, Option<(f64, f64)>);
+    let series: Vec<Vec<Point>> = plot
+        .columns
+        .iter()
+        .map(|&col| {
+            samples
+                .iter()
+                .zip(&offsets)
+                .filter_map(|(s, &x)| {
+                    let v = (*s.values.get(col)?)?;
+                    let range = s.ranges.get(col).copied().flatten();
+                    v.is_finite().then_some((x - x0, v, range))
+                })
+                .collect()
+        })
+        .collect();
+    let (mut lo, mut hi) = (f64::INFINITY, f64::NEG_INFINITY);
+    for &(_, v, range) in series.iter().flatten() {
+        let (min, max) = range.unwrap_or((v, v));
+        lo = lo.min(v).min(min);
+        hi = hi.max(v).max(max);
+    }
+    if !lo.is_finite() {
+        return;
+    }
+    if hi - lo < 1e-9 {
+        lo -= 0.5;
+        hi += 0.5;
+    }
+
+    let x_of = |t: f64| rect.left() + (t / span) as f32 * rect.width();
+    let y_of = |v: f64| rect.bottom() - ((v - lo) / (hi - lo)) as f32 * (rect.height() - 4.0) - 2.0;
+    // Plot at most two points per pixel
+    let max_points = (rect.width() * 2.0) as usize;
+
+    // Dashed markers where idle time was cut out
+    let weak = ui.visuals().weak_text_color();
+    for &cut in &cuts {
+        let x = x_of(cut - x0);
+        painter.add(egui::Shape::dashed_line(
+            &[egui::pos2(x, rect.top()), egui::pos2(x, rect.bottom())],
+            egui::Stroke::new(1.0, weak),
+            4.0,
+            3.0,
+        ));
+    }
+
+    for (i, points) in series.iter().enumerate() {
+        let color = SERIES_COLORS[i % SERIES_COLORS.len()];
+        let step = (points.len() / max_points.max(1)).max(1);
+        // Min/max band of aggregated windows, one quad between neighbours
+        let band: Vec<(f32, f64, f64)> = points
+            .iter()
+            .step_by(step)
+            .filter_map(|&(t, _, range)| range.map(|(min, max)| (x_of(t), min, max)))
+            .collect();
+        for pair in band.windows(2) {
+            let [(x1, min1, max1), (x2, min2, max2)] = [pair[0], pair[1]];
+            painter.add(egui::Shape::convex_polygon(
+                vec![
+                    egui::pos2(x1, y_of(max1)),
+                    egui::pos2(x2, y_of(max2)),
+                    egui::pos2(x2, y_of(min2)),
+                    egui::pos2(x1, y_of(min1)),
+                ],
+                color.gamma_multiply(0.25),
+                egui::Stroke::NONE,
+            ));
+        }
+        let line: Vec<egui::Pos2> = points
+            .iter()
+            .step_by(step)
+            .map(|&(t, v, _)| egui::pos2(x_of(t), y_of(v)))
+            .collect();
+        painter.add(egui::Shape::line(line, egui::Stroke::new(1.5, color)));
+    }
+
+    // Axis labels and legend
+    let small = egui::FontId::proportional(11.0);
+    painter.text(
+        rect.left_top() + egui::vec2(4.0, 2.0),
+        egui::Align2::LEFT_TOP,
+        format!("{:.3}", hi),
+        small.clone(),
+        weak,
+    );
+    painter.text(
+        rect.left_bottom() + egui::vec2(4.0, -2.0),
+        egui::Align2::LEFT_BOTTOM,
+        format!("{:.3}", lo),
+        small.clone(),
+        weak,
+    );
+    if plot.time_of_day {
+        // Clock time at evenly spaced ticks, counted from the last sample
+        // before each tick so compressed gaps don't skew the labels
+        const TICKS: usize = 4;
+        for tick in 1..=TICKS {
+            let x = x0 + span * tick as f64 / TICKS as f64;
+            let idx = offsets.partition_point(|&o| o <= x).saturating_sub(1);
+            let time = samples[idx].time
+                + chrono::Duration::milliseconds(((x - offsets[idx]) * 1000.0) as i64);
+            let align = if tick == TICKS {
+                egui::Align2::RIGHT_BOTTOM
+            } else {
+                egui::Align2::CENTER_BOTTOM
+            };
+            painter.text(
+                egui::pos2(
+                    x_of(x - x0) - if tick == TICKS { 4.0 } else { 0.0 },
+                    rect.bottom() - 2.0,
+                ),
+                align,
+                time.format("%H:%M:%S").to_string(),
+                small.clone(),
+                weak,
+            );
+        }
+    } else {
+        painter.text(
+            rect.right_bottom() + egui::vec2(-4.0, -2.0),
+            egui::Align2::RIGHT_BOTTOM,
+            format!("{:.1} s", span),
+            small.clone(),
+            weak,
+        );
+    }
+    let mut legend_pos = rect.right_top() + egui::vec2(-4.0, 2.0);
+    for (i, &col) in plot.columns.iter().enumerate().rev() {
+        let name = headers
+            .get(col)
+            .cloned()
+            .unwrap_or_else(|| format!("Col {}", col + 1));
+        let color = SERIES_COLORS[i % SERIES_COLORS.len()];
+        let galley = painter.layout_no_wrap(name, small.clone(), color);
+        legend_pos.x -= galley.size().x;
+        painter.galley(legend_pos, galley, color);
+        legend_pos.x -= 10.0;
+    }
+}
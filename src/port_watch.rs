@@ -0,0 +1,76 @@
+use std::time::{Duration, Instant};
+
+use crate::serial_port::{list_ports, PortEntry};
+
+/// How often the port list is re-enumerated to notice plugged devices
+pub const RESCAN_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Port list of the toolbar picker, kept current while disconnected
+pub struct PortWatcher {
+    pub ports: Vec<PortEntry>,
+    last_scan: Instant,
+}
+
+/// Ports that appeared or disappeared since the previous scan
+pub struct PortChanges {
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+}
+
+impl PortWatcher {
+    pub fn new() -> Self {
+        Self {
+            ports: list_ports(),
+            last_scan: Instant::now(),
+        }
+    }
+
+    /// Re-enumerates the ports now and reports what changed
+    pub fn refresh(&mut self) -> PortChanges {
+        let ports = list_ports();
+        let known = |list: &[PortEntry], name: &str| list.iter().any(|p| p.name == name);
+        let changes = PortChanges {
+            added: ports
+                .iter()
+                .filter(|p| !known(&self.ports, &p.name))
+                .map(|p| p.name.clone())
+                .collect(),
+            removed: self
+                .ports
+                .iter()
+                .filter(|p| !known(&ports, &p.name))
+                .map(|p| p.name.clone())
+                .collect(),
+        };
+        self.ports = ports;
+        self.last_scan = Instant::now();
+        changes
+    }
+
+    /// Rescans once `RESCAN_INTERVAL` has passed; None if it is not due yet
+    pub fn poll(&mut self) -> Option<PortChanges> {
+        (self.last_scan.elapsed() >= RESCAN_INTERVAL).then(|| self.refresh())
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.ports.iter().any(|p| p.name == name)
+    }
+}
+
+impl PortChanges {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty()
+    }
+
+    /// Status bar message, e.g. "Port added: /dev/ttyUSB0"
+    pub fn describe(&self) -> String {
+        let mut parts = Vec::new();
+        if !self.added.is_empty() {
+            parts.push(format!("Port added: {}", self.added.join(", ")));
+        }
+        if !self.removed.is_empty() {
+            parts.push(format!("Port removed: {}", self.removed.join(", ")));
+        }
+        parts.join("; ")
+    }
+}
@@ -0,0 +1,153 @@
+// Protobuf frame decoding: with protobuf framing each frame is one message
+// (see `framing`), decoded at run time with a schema instead of generated
+// code. The schema is a .proto file, parsed in-process with its imports
+// looked up next to it, or a descriptor set compiled by protoc. Each message
+// is turned into a JSON object line, so the JSON Lines parser gives one
+// column per field (nested messages as `a.b`, repeated fields as `a.0`).
+
+use std::path::Path;
+
+use protobuf::descriptor::{FileDescriptorProto, FileDescriptorSet};
+use protobuf::reflect::{
+    FileDescriptor, MessageDescriptor, ReflectFieldRef, ReflectValueRef, RuntimeFieldType,
+};
+use protobuf::{Message, MessageDyn};
+use serde_json::{Map, Number, Value};
+
+use crate::framing::to_hex;
+
+/// The message type frames are decoded as
+pub struct ProtoSchema {
+    message: MessageDescriptor,
+}
+
+impl ProtoSchema {
+    /// Loads `path` and looks up `message` by its full or package-relative
+    /// name; an empty name takes the first message of the file
+    pub fn load(path: &str, message: &str) -> Result<Self, String> {
+        let (protos, main) = if path.ends_with(".proto") {
+            parse_proto(path)?
+        } else {
+            let data = std::fs::read(path).map_err(|e| format!("Cannot read {}: {}", path, e))?;
+            let set = FileDescriptorSet::parse_from_bytes(&data)
+                .map_err(|e| format!("{} is not a descriptor set: {}", path, e))?;
+            // protoc lists the input file after its imports
+            let main = set.file.last().map(|f| f.name().to_string());
+            (set.file, main.unwrap_or_default())
+        };
+        let files =
+            FileDescriptor::new_dynamic_fds(protos, &[]).map_err(|e| format!("{}: {}", path, e))?;
+
+        let name = message.trim().trim_start_matches('.');
+        let found = if name.is_empty() {
+            files
+                .iter()
+                .find(|f| f.proto().name() == main)
+                .and_then(|f| f.messages().next())
+        } else {
+            // Full names are looked up with a leading dot
+            let full_name = format!(".{}", name);
+            files.iter().find_map(|f| {
+                f.message_by_full_name(&full_name)
+                    .or_else(|| f.message_by_package_relative_name(name))
+            })
+        };
+        let message = found.ok_or_else(|| match name {
+            "" => format!("{} defines no messages", path),
+            _ => format!("{} has no message {}", path, name),
+        })?;
+        Ok(Self { message })
+    }
+
+    /// Full name of the message type
+    pub fn name(&self) -> &str {
+        self.message.full_name()
+    }
+
+    /// Decodes a frame into a JSON object line
+    pub fn decode(&self, frame: &[u8]) -> Result<String, String> {
+        let message = self
+            .message
+            .parse_from_bytes(frame)
+            .map_err(|e| e.to_string())?;
+        Ok(message_json(&*message).to_string())
+    }
+}
+
+/// The descriptors of a .proto file and its imports, and the file's name
+/// among them
+fn parse_proto(path: &str) -> Result<(Vec<FileDescriptorProto>, String), String> {
+    let file = Path::new(path);
+    let dir = file.parent().unwrap_or(Path::new("."));
+    let parsed = protobuf_parse::Parser::new()
+        .pure()
+        .include(dir)
+        .input(file)
+        .parse_and_typecheck()
+        .map_err(|e| format!("{}: {:#}", path, e))?;
+    let main = parsed
+        .relative_paths
+        .first()
+        .map(|p| p.to_string())
+        .unwrap_or_default();
+    Ok((parsed.file_descriptors, main))
+}
+
+/// All fields, set or not, so every message gives the same columns
+fn message_json(message: &dyn MessageDyn) -> Value {
+    let mut object = Map::new();
+    for field in message.descriptor_dyn().fields() {
+        let value = match field.runtime_field_type() {
+            RuntimeFieldType::Singular(_) => {
+                value_json(field.get_singular_field_or_default(message))
+            }
+            _ => match field.get_reflect(message) {
+                ReflectFieldRef::Repeated(items) => {
+                    Value::Array(items.into_iter().map(value_json).collect())
+                }
+                ReflectFieldRef::Map(map) => {
+                    let entries = (&map)
+                        .into_iter()
+                        .map(|(k, v)| (key_text(k), value_json(v)))
+                        .collect();
+                    Value::Object(entries)
+                }
+                ReflectFieldRef::Optional(value) => value.value().map_or(Value::Null, value_json),
+            },
+        };
+        object.insert(field.name().to_string(), value);
+    }
+    Value::Object(object)
+}
+
+fn value_json(value: ReflectValueRef) -> Value {
+    match value {
+        ReflectValueRef::U32(v) => v.into(),
+        ReflectValueRef::U64(v) => v.into(),
+        ReflectValueRef::I32(v) => v.into(),
+        ReflectValueRef::I64(v) => v.into(),
+        // Via the shortest text, so 0.1f32 isn't shown as 0.10000000149
+        ReflectValueRef::F32(v) => float_json(v.to_string().parse().unwrap_or(f64::NAN)),
+        ReflectValueRef::F64(v) => float_json(v),
+        ReflectValueRef::Bool(v) => v.into(),
+        ReflectValueRef::String(v) => v.into(),
+        ReflectValueRef::Bytes(v) => to_hex(v).into(),
+        ReflectValueRef::Enum(descriptor, number) => match descriptor.value_by_number(number) {
+            Some(value) => value.name().into(),
+            None => number.into(),
+        },
+        ReflectValueRef::Message(message) => message_json(&*message),
+    }
+}
+
+/// JSON has no NaN or infinity; those are kept as text
+fn float_json(v: f64) -> Value {
+    Number::from_f64(v).map_or_else(|| Value::String(v.to_string()), Value::Number)
+}
+
+fn key_text(key: ReflectValueRef) -> String {
+    match key {
+        ReflectValueRef::String(s) => s.to_string(),
+        other => other.to_string(),
+    }
+}
@@ -0,0 +1,216 @@
+// Multi-line records: stack traces, status dumps and similar output that
+// spans several lines are joined (with '\n') into one record before the line
+// script and parser see it. A record starts at a start marker, ends at an end
+// marker, or is a fixed number of lines.
+
+use std::time::{Duration, Instant};
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::regex_guard;
+use crate::settings::Settings;
+use crate::timestamp::Timestamp;
+
+/// A record longer than this is passed on as it is
+const MAX_RECORD_LINES: usize = 1000;
+
+/// A pending record is passed on after this long without a new line, so the
+/// last record doesn't wait for the next one to start
+pub const RECORD_IDLE: Duration = Duration::from_millis(500);
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub enum RecordMode {
+    /// Every line is a record
+    #[default]
+    Off,
+    /// A line matching the marker begins a record; the lines up to the next
+    /// marker belong to it
+    StartMarker,
+    /// Lines are collected until one matches the marker
+    EndMarker,
+    /// Every `record_lines` lines form a record
+    LineCount,
+}
+
+impl RecordMode {
+    pub fn label(&self) -> &'static str {
+        match self {
+            RecordMode::Off => "Off (one line per row)",
+            RecordMode::StartMarker => "Start marker",
+            RecordMode::EndMarker => "End marker",
+            RecordMode::LineCount => "Fixed line count",
+        }
+    }
+    pub fn all() -> &'static [RecordMode] {
+        &[
+            RecordMode::Off,
+            RecordMode::StartMarker,
+            RecordMode::EndMarker,
+            RecordMode::LineCount,
+        ]
+    }
+}
+
+/// A complete record and the time its first line arrived
+pub struct Record {
+    pub time: Timestamp,
+    pub text: String,
+}
+
+pub struct RecordAssembler {
+    mode: RecordMode,
+    marker: Option<Regex>,
+    count: usize,
+    lines: Vec<String>,
+    started: Timestamp,
+    last_line: Instant,
+}
+
+impl RecordAssembler {
+    /// None when records are off
+    pub fn from_settings(settings: &Settings) -> Result<Option<Self>, String> {
+        let marker = match settings.record_mode {
+            RecordMode::Off => return Ok(None),
+            RecordMode::LineCount => None,
+            RecordMode::StartMarker | RecordMode::EndMarker => {
+                if settings.record_marker.is_empty() {
+                    return Err("Record marker is empty".to_string());
+                }
+                let re = regex_guard::compile(&settings.record_marker)
+                    .map_err(|e| format!("Record marker error: {}", e))?;
+                Some(re)
+            }
+        };
+        Ok(Some(Self {
+            mode: settings.record_mode.clone(),
+            marker,
+            count: settings.record_lines.max(1),
+            lines: Vec::new(),
+            started: Timestamp::now(),
+            last_line: Instant::now(),
+        }))
+    }
+
+    fn is_marker(&self, line: &str) -> bool {
+        line.len() <= regex_guard::MAX_MATCH_LEN
+            && self.marker.as_ref().is_some_and(|re| re.is_match(line))
+    }
+
+    /// Adds a line; returns the records it completed (at most two: the
+    /// previous one and, for a lone line outside a record, the line itself)
+    pub fn push(&mut self, line: String, time: Timestamp) -> Vec<Record> {
+        self.last_line = Instant::now();
+        let mut done = Vec::new();
+        match self.mode {
+            RecordMode::Off => done.push(Record { time, text: line }),
+            RecordMode::StartMarker => {
+                if self.is_marker(&line) {
+                    done.extend(self.flush());
+                    self.start(line, time);
+                } else if self.lines.is_empty() {
+                    // Output between records stays one row per line
+                    done.push(Record { time, text: line });
+                } else {
+                    self.lines.push(line);
+                }
+            }
+            RecordMode::EndMarker => {
+                let end = self.is_marker(&line);
+                self.start_or_add(line, time);
+                if end {
+                    done.extend(self.flush());
+                }
+            }
+            RecordMode::LineCount => {
+                self.start_or_add(line, time);
+                if self.lines.len() >= self.count {
+                    done.extend(self.flush());
+                }
+            }
+        }
+        if self.lines.len() >= MAX_RECORD_LINES {
+            done.extend(self.flush());
+        }
+        done
+    }
+
+    fn start(&mut self, line: String, time: Timestamp) {
+        self.started = time;
+        self.lines.push(line);
+    }
+
+    fn start_or_add(&mut self, line: String, time: Timestamp) {
+        if self.lines.is_empty() {
+            self.start(line, time);
+        } else {
+            self.lines.push(line);
+        }
+    }
+
+    /// Passes on the pending lines as a record, complete or not
+    pub fn flush(&mut self) -> Option<Record> {
+        if self.lines.is_empty() {
+            return None;
+        }
+        let text = std::mem::take(&mut self.lines).join("\n");
+        Some(Record {
+            time: self.started,
+            text,
+        })
+    }
+
+    /// The pending record once no line arrived for `RECORD_IDLE`
+    pub fn flush_idle(&mut self) -> Option<Record> {
+        if self.last_line.elapsed() < RECORD_IDLE {
+            return None;
+        }
+        self.flush()
+    }
+}
+
+/// Settings window section for multi-line records
+pub fn record_editor(ui: &mut egui::Ui, settings: &mut Settings) {
+    ui.horizontal(|ui| {
+        ui.label("Records:");
+        egui::ComboBox::from_id_salt("record_mode_combo")
+            .selected_text(settings.record_mode.label())
+            .width(170.0)
+            .show_ui(ui, |ui| {
+                for mode in RecordMode::all() {
+                    ui.selectable_value(&mut settings.record_mode, mode.clone(), mode.label());
+                }
+            });
+    });
+    match settings.record_mode {
+        RecordMode::Off => {}
+        RecordMode::StartMarker | RecordMode::EndMarker => {
+            ui.label("Marker (regex):");
+            ui.add(
+                egui::TextEdit::singleline(&mut settings.record_marker)
+                    .hint_text(if settings.record_mode == RecordMode::StartMarker {
+                        "e.g. ^Traceback|^=== STATUS"
+                    } else {
+                        "e.g. ^=== END"
+                    })
+                    .desired_width(f32::INFINITY),
+            );
+            if let Err(e) = RecordAssembler::from_settings(settings) {
+                ui.colored_label(egui::Color32::RED, e);
+            }
+        }
+        RecordMode::LineCount => {
+            ui.horizontal(|ui| {
+                ui.label("Lines per record:");
+                ui.add(
+                    egui::DragValue::new(&mut settings.record_lines).range(1..=MAX_RECORD_LINES),
+                );
+            });
+        }
+    }
+    ui.label(
+        "The lines of a record are joined with a newline before the line script and parser \
+         run; use (?s) or (?m) in the regex to match across them. A record is also closed \
+         after half a second without new lines.",
+    );
+}
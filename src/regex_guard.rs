@@ -0,0 +1,46 @@
+// Limits for user-supplied regexes. The regex crate matches in linear time, so
+// there is no catastrophic backtracking, but a pattern can still compile to a
+// huge program or be slow on very long lines.
+
+use std::time::Duration;
+
+use regex::{bytes, Regex, RegexBuilder};
+
+/// Limit for the compiled program of a user pattern
+const SIZE_LIMIT: usize = 1 << 20;
+/// Limit for the lazy DFA cache of a user pattern
+const DFA_SIZE_LIMIT: usize = 2 << 20;
+/// Lines longer than this are not run through the parser regex
+pub const MAX_MATCH_LEN: usize = 16 * 1024;
+/// Matching a single line slower than this is reported
+pub const MATCH_BUDGET: Duration = Duration::from_millis(5);
+
+/// Compiles a user pattern within the size limits
+pub fn compile(pattern: &str) -> Result<Regex, String> {
+    RegexBuilder::new(pattern)
+        .size_limit(SIZE_LIMIT)
+        .dfa_size_limit(DFA_SIZE_LIMIT)
+        .build()
+        .map_err(describe)
+}
+
+/// Compiles a user pattern for binary data within the size limits. Unicode
+/// is off, so `\xNN` and `.` match single bytes; `.` also matches `\n`.
+pub fn compile_bytes(pattern: &str) -> Result<bytes::Regex, String> {
+    bytes::RegexBuilder::new(pattern)
+        .unicode(false)
+        .dot_matches_new_line(true)
+        .size_limit(SIZE_LIMIT)
+        .dfa_size_limit(DFA_SIZE_LIMIT)
+        .build()
+        .map_err(describe)
+}
+
+fn describe(e: regex::Error) -> String {
+    match e {
+        regex::Error::CompiledTooBig(limit) => {
+            format!("pattern too complex (compiled size exceeds {} KiB)", limit / 1024)
+        }
+        e => e.to_string(),
+    }
+}
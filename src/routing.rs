@@ -0,0 +1,222 @@
+// Routing of interleaved telemetry into separate tables: each route has a
+// regex (e.g. `^IMU: (\S+),(\S+),(\S+)`) whose capture groups are the
+// columns of its own table. A line goes to the first route it matches and
+// still appears in the main table.
+
+use egui_extras::{Column, TableBuilder};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::regex_guard;
+use crate::settings::{ByteTotal, Density, Settings};
+use crate::timestamp::{TimeDisplay, Timestamp};
+
+/// A named pattern whose matches get a table of their own
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Route {
+    pub name: String,
+    pub pattern: String,
+    /// Comma-separated header overrides
+    pub column_names: String,
+}
+
+pub struct RouteRow {
+    pub time: Timestamp,
+    pub columns: Vec<String>,
+}
+
+pub struct RouteTable {
+    pub name: String,
+    regex: Regex,
+    pub headers: Vec<String>,
+    pub rows: Vec<RouteRow>,
+    /// Size of `rows` for the size retention policy
+    bytes: ByteTotal,
+}
+
+impl RouteRow {
+    fn size(&self) -> usize {
+        self.columns.iter().map(String::len).sum()
+    }
+}
+
+/// The route tables, filled as lines arrive
+#[derive(Default)]
+pub struct Router {
+    pub tables: Vec<RouteTable>,
+}
+
+impl Router {
+    pub fn from_settings(settings: &Settings) -> Result<Self, String> {
+        let mut tables = Vec::new();
+        for route in &settings.routes {
+            if route.pattern.is_empty() {
+                continue;
+            }
+            let regex = regex_guard::compile(&route.pattern)
+                .map_err(|e| format!("Route \"{}\": {}", route.name, e))?;
+            let names: Vec<&str> = route
+                .column_names
+                .split(',')
+                .map(str::trim)
+                .filter(|n| !n.is_empty())
+                .collect();
+            let headers = (1..regex.captures_len())
+                .map(|i| match names.get(i - 1) {
+                    Some(name) => name.to_string(),
+                    None => match regex.capture_names().nth(i).flatten() {
+                        Some(name) => name.to_string(),
+                        None => format!("Col {}", i),
+                    },
+                })
+                .collect();
+            tables.push(RouteTable {
+                name: route.name.clone(),
+                regex,
+                headers,
+                rows: Vec::new(),
+                bytes: ByteTotal::default(),
+            });
+        }
+        Ok(Self { tables })
+    }
+
+    /// Adds the line to the table of the first route it matches
+    pub fn route(&mut self, line: &str, time: Timestamp) {
+        if line.len() > regex_guard::MAX_MATCH_LEN {
+            return;
+        }
+        for table in &mut self.tables {
+            if let Some(caps) = table.regex.captures(line) {
+                let columns = (1..caps.len())
+                    .map(|i| caps.get(i).map_or("", |m| m.as_str()).to_string())
+                    .collect();
+                table.rows.push(RouteRow { time, columns });
+                return;
+            }
+        }
+    }
+
+    /// Drops rows outside the retention policy
+    pub fn trim(&mut self, settings: &Settings) {
+        for table in &mut self.tables {
+            let drain = settings.retention_excess(
+                &table.rows,
+                &table.bytes,
+                |r| r.time.wall,
+                RouteRow::size,
+            );
+            table.bytes.remove(&table.rows[..drain], RouteRow::size);
+            table.rows.drain(..drain);
+        }
+    }
+
+    pub fn clear(&mut self) {
+        for table in &mut self.tables {
+            table.rows.clear();
+            table.bytes.reset();
+        }
+    }
+}
+
+/// Renders a route table, following the newest row when `auto_scroll` is on.
+/// Timestamps are shown as `time_display` asks unless it is None.
+pub fn show_route_table(
+    ui: &mut egui::Ui,
+    table: &RouteTable,
+    time_display: Option<TimeDisplay>,
+    auto_scroll: bool,
+    density: Density,
+    zoom: f32,
+) {
+    let show_ts = time_display.is_some();
+    density.apply(ui, zoom);
+    let row_height = density.row_height(zoom);
+    let mut builder = TableBuilder::new(ui)
+        .striped(true)
+        .resizable(true)
+        .cell_layout(egui::Layout::left_to_right(egui::Align::Center))
+        .auto_shrink(false);
+    if auto_scroll && !table.rows.is_empty() {
+        builder = builder.scroll_to_row(table.rows.len() - 1, Some(egui::Align::BOTTOM));
+    }
+    if show_ts {
+        builder = builder.column(Column::initial(110.0).at_least(80.0).resizable(true));
+    }
+    for _ in &table.headers {
+        builder = builder.column(Column::initial(120.0).at_least(60.0).resizable(true));
+    }
+    builder
+        .header(density.header_height(zoom), |mut header| {
+            if show_ts {
+                header.col(|ui| {
+                    ui.strong("Timestamp");
+                });
+            }
+            for title in &table.headers {
+                header.col(|ui| {
+                    ui.strong(title);
+                });
+            }
+        })
+        .body(|body| {
+            body.rows(row_height, table.rows.len(), |mut row_widget| {
+                let row = &table.rows[row_widget.index()];
+                if let Some(display) = &time_display {
+                    row_widget.col(|ui| {
+                        let ts_color = egui::Color32::from_rgb(140, 140, 200);
+                        ui.colored_label(ts_color, row.time.format(display, "%H:%M:%S%.3f"));
+                    });
+                }
+                for value in &row.columns {
+                    row_widget.col(|ui| {
+                        ui.label(value);
+                    });
+                }
+            });
+        });
+}
+
+/// Settings editor for the route list
+pub fn route_editor(ui: &mut egui::Ui, routes: &mut Vec<Route>) {
+    let mut remove = None;
+
+    egui::Grid::new("route_grid")
+        .num_columns(4)
+        .spacing([6.0, 4.0])
+        .striped(true)
+        .show(ui, |ui| {
+            ui.strong("Tab");
+            ui.strong("Pattern");
+            ui.strong("Column names");
+            ui.end_row();
+
+            for (i, route) in routes.iter_mut().enumerate() {
+                ui.add(egui::TextEdit::singleline(&mut route.name).desired_width(70.0));
+                ui.add(
+                    egui::TextEdit::singleline(&mut route.pattern)
+                        .hint_text(r"^IMU: (\S+),(\S+)")
+                        .desired_width(180.0),
+                );
+                ui.add(egui::TextEdit::singleline(&mut route.column_names).desired_width(120.0));
+                if ui.small_button("Remove").clicked() {
+                    remove = Some(i);
+                }
+                ui.end_row();
+            }
+        });
+
+    if let Some(i) = remove {
+        routes.remove(i);
+    }
+    if ui.button("Add route").clicked() {
+        routes.push(Route::default());
+    }
+    for route in routes.iter().filter(|r| !r.pattern.is_empty()) {
+        if let Err(e) = regex_guard::compile(&route.pattern) {
+            ui.colored_label(egui::Color32::RED, format!("{}: {}", route.name, e));
+        }
+    }
+    ui.label("Each line goes to the first route it matches; capture groups become columns.");
+}
@@ -0,0 +1,277 @@
+// Headless test scripts: send commands to the device and assert on what it
+// answers, for hardware smoke tests in CI. One step per line:
+//
+//     # comment
+//     send AT+RST
+//     expect ^READY within 5s
+//     wait 500ms
+//
+// `expect` fails if no received line matches the regex in time (5 s by
+// default). The run ends with a pass/fail summary and exit code.
+
+use std::collections::VecDeque;
+use std::io::Write;
+use std::time::{Duration, Instant};
+
+use chrono::Local;
+use regex::Regex;
+
+use crate::framing::to_hex;
+use crate::regex_guard;
+use crate::serial_port::{SerialEvent, SerialPortManager};
+use crate::settings::Settings;
+use crate::tx_map;
+
+const DEFAULT_EXPECT_TIMEOUT: Duration = Duration::from_secs(5);
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Exit codes of a headless run
+pub const EXIT_PASS: i32 = 0;
+pub const EXIT_FAIL: i32 = 1;
+/// The script or connection couldn't be set up
+pub const EXIT_ERROR: i32 = 2;
+
+pub enum Step {
+    Send(String),
+    Wait(Duration),
+    Expect { pattern: Regex, within: Duration },
+}
+
+/// Parses a script; errors name the offending line
+pub fn parse_script(text: &str) -> Result<Vec<Step>, String> {
+    let mut steps = Vec::new();
+    for (n, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let (command, arg) = line.split_once(' ').unwrap_or((line, ""));
+        let arg = arg.trim();
+        let step = match command {
+            "send" => Ok(Step::Send(arg.to_string())),
+            "wait" => parse_duration(arg).map(Step::Wait),
+            "expect" => parse_expect(arg),
+            other => Err(format!("unknown command \"{}\"", other)),
+        };
+        steps.push(step.map_err(|e| format!("line {}: {}", n + 1, e))?);
+    }
+    Ok(steps)
+}
+
+fn parse_expect(arg: &str) -> Result<Step, String> {
+    let (pattern, within) = match arg.rsplit_once(" within ") {
+        Some((pattern, time)) => (pattern.trim(), parse_duration(time)?),
+        None => (arg, DEFAULT_EXPECT_TIMEOUT),
+    };
+    if pattern.is_empty() {
+        return Err("expect needs a pattern".to_string());
+    }
+    let pattern = regex_guard::compile(pattern)?;
+    Ok(Step::Expect { pattern, within })
+}
+
+/// "500ms", "2s" or "1.5s"
+fn parse_duration(text: &str) -> Result<Duration, String> {
+    let text = text.trim();
+    let parsed = if let Some(ms) = text.strip_suffix("ms") {
+        ms.trim().parse::<u64>().ok().map(Duration::from_millis)
+    } else if let Some(s) = text.strip_suffix('s') {
+        s.trim()
+            .parse::<f64>()
+            .ok()
+            .filter(|s| s.is_finite() && *s >= 0.0)
+            .map(Duration::from_secs_f64)
+    } else {
+        None
+    };
+    parsed.ok_or_else(|| format!("invalid duration \"{}\" (use e.g. 500ms or 2s)", text))
+}
+
+/// Runs a script against the saved connection settings and returns the
+/// process exit code. `--port` and `--baud` in `args` override the settings.
+pub fn run_headless(path: &str, args: &[String]) -> i32 {
+    let steps = match std::fs::read_to_string(path)
+        .map_err(|e| e.to_string())
+        .and_then(|text| parse_script(&text))
+    {
+        Ok(steps) => steps,
+        Err(e) => {
+            eprintln!("{}: {}", path, e);
+            return EXIT_ERROR;
+        }
+    };
+    let mut settings = Settings::load();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match (arg.as_str(), args.next()) {
+            ("--port", Some(port)) => settings.port_name = port.clone(),
+            ("--baud", Some(baud)) => match baud.parse() {
+                Ok(baud) => settings.baud_rate = baud,
+                Err(_) => {
+                    eprintln!("Invalid baud rate \"{}\"", baud);
+                    return EXIT_ERROR;
+                }
+            },
+            _ => {
+                eprintln!("Unknown argument \"{}\"", arg);
+                return EXIT_ERROR;
+            }
+        }
+    }
+
+    let mut serial = SerialPortManager::new();
+    if let Err(e) = serial.connect(&settings) {
+        eprintln!("{}", e);
+        return EXIT_ERROR;
+    }
+    println!("Connected to {}", settings.target_label());
+
+    let mut runner = Runner {
+        serial,
+        settings,
+        passed: 0,
+        failed: 0,
+        queued: VecDeque::new(),
+        disconnected: false,
+    };
+    for step in &steps {
+        runner.run(step);
+    }
+    runner.serial.disconnect();
+
+    let total = runner.passed + runner.failed;
+    println!(
+        "{}: {} of {} assertions passed",
+        if runner.failed == 0 { "PASS" } else { "FAIL" },
+        runner.passed,
+        total
+    );
+    let _ = std::io::stdout().flush();
+    if runner.failed == 0 {
+        EXIT_PASS
+    } else {
+        EXIT_FAIL
+    }
+}
+
+struct Runner {
+    serial: SerialPortManager,
+    settings: Settings,
+    passed: usize,
+    failed: usize,
+    /// Received lines not yet looked at by a step, oldest first
+    queued: VecDeque<String>,
+    /// The port went away; remaining assertions fail without waiting
+    disconnected: bool,
+}
+
+impl Runner {
+    fn run(&mut self, step: &Step) {
+        match step {
+            Step::Send(text) => {
+                println!("> {}", text);
+                let mut data = self.settings.text_encoding.encode(text);
+                data.extend_from_slice(self.settings.tx_line_ending.as_bytes());
+                self.serial
+                    .send(tx_map::apply(&self.settings.tx_char_map, &data));
+            }
+            Step::Wait(duration) => {
+                self.receive_until(Instant::now() + *duration, None);
+            }
+            Step::Expect { pattern, within } => {
+                let deadline = Instant::now() + *within;
+                if self.receive_until(deadline, Some(pattern)) {
+                    self.passed += 1;
+                    println!("PASS expect {}", pattern.as_str());
+                } else {
+                    self.failed += 1;
+                    println!(
+                        "FAIL expect {} (nothing matched within {:.1} s)",
+                        pattern.as_str(),
+                        within.as_secs_f64()
+                    );
+                }
+            }
+        }
+    }
+
+    /// Prints received lines until `deadline`, or until one matches
+    /// `pattern`. Returns true if a line matched; lines after it stay queued
+    /// for the next step.
+    fn receive_until(&mut self, deadline: Instant, pattern: Option<&Regex>) -> bool {
+        loop {
+            while let Some(line) = self.queued.pop_front() {
+                println!("{} < {}", Local::now().format("%H:%M:%S%.3f"), line);
+                if pattern.is_some_and(|re| re.is_match(&line)) {
+                    return true;
+                }
+            }
+            if self.disconnected || Instant::now() >= deadline {
+                return false;
+            }
+            self.poll();
+            if self.queued.is_empty() {
+                std::thread::sleep(POLL_INTERVAL);
+            }
+        }
+    }
+
+    /// Queues the received lines and reports errors and disconnects
+    fn poll(&mut self) {
+        for ev in self.serial.poll_events() {
+            let line = match ev {
+                SerialEvent::Data(line) | SerialEvent::Traffic(_, line) => line,
+                SerialEvent::Frame(frame) => to_hex(&frame),
+                SerialEvent::Error(e) | SerialEvent::Warning(e) | SerialEvent::LineError(e) => {
+                    eprintln!("{}", e);
+                    continue;
+                }
+                SerialEvent::Disconnected => {
+                    eprintln!("Port disconnected");
+                    self.disconnected = true;
+                    continue;
+                }
+                SerialEvent::Connected
+                | SerialEvent::ControlLine(..)
+                | SerialEvent::RawBytes(_)
+                | SerialEvent::LineBytes(_)
+                | SerialEvent::LineEndingDetected(_) => continue,
+            };
+            self.queued.push_back(line);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_steps() {
+        let script = "# smoke test\n\n  send AT+RST\nexpect ^READY within 2s\nwait 500ms\n\
+                      expect OK\nwait 1.5s\nsend\n";
+        let steps = parse_script(script).unwrap();
+        assert_eq!(steps.len(), 6);
+        assert!(matches!(&steps[0], Step::Send(s) if s == "AT+RST"));
+        assert!(matches!(&steps[1], Step::Expect { pattern, within }
+            if pattern.as_str() == "^READY" && *within == Duration::from_secs(2)));
+        assert!(matches!(&steps[2], Step::Wait(d) if *d == Duration::from_millis(500)));
+        assert!(matches!(&steps[3], Step::Expect { pattern, within }
+            if pattern.as_str() == "OK" && *within == DEFAULT_EXPECT_TIMEOUT));
+        assert!(matches!(&steps[4], Step::Wait(d) if *d == Duration::from_millis(1500)));
+        assert!(matches!(&steps[5], Step::Send(s) if s.is_empty()));
+    }
+
+    #[test]
+    fn errors_name_the_line() {
+        let error = |script: &str| parse_script(script).err().unwrap();
+        assert_eq!(
+            error("send a\nreboot now"),
+            "line 2: unknown command \"reboot\""
+        );
+        assert!(error("# x\nwait soon").starts_with("line 2: invalid duration \"soon\""));
+        assert!(error("wait -1s").starts_with("line 1: invalid duration"));
+        assert!(error("expect OK within 5").starts_with("line 1: invalid duration \"5\""));
+        assert_eq!(error("\nexpect"), "line 2: expect needs a pattern");
+    }
+}
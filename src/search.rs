@@ -0,0 +1,203 @@
+// Search (Ctrl+F): finds a text or regex in the table or the raw log,
+// highlights the matches, counts them and jumps between them with Enter /
+// Shift+Enter or F3 / Shift+F3. Unlike the filter bar nothing is hidden. The
+// matches are found again whenever the view changes.
+
+use std::borrow::Cow;
+
+use egui::text::{LayoutJob, TextFormat};
+use egui::{Color32, FontId};
+use regex::Regex;
+
+use crate::regex_guard;
+
+const CURRENT: Color32 = Color32::from_rgb(200, 120, 20);
+
+/// Which view the matches belong to and how long it was
+pub type ViewKey = (bool, usize, i64);
+
+#[derive(Default)]
+pub struct Search {
+    pub is_open: bool,
+    text: String,
+    is_regex: bool,
+    /// None while the search is empty or doesn't compile
+    matcher: Option<Regex>,
+    error: Option<String>,
+    /// Positions of the matching rows or lines in the searched view
+    matches: Vec<usize>,
+    /// View the matches were found in; None when they need finding again
+    searched: Option<ViewKey>,
+    /// Index into `matches` of the match jumped to last
+    current: Option<usize>,
+    /// Focus the search field on the next frame
+    focus: bool,
+    /// Background of the matches other than the current one, from the theme
+    pub background: Color32,
+}
+
+impl Search {
+    pub fn open(&mut self) {
+        self.is_open = true;
+        self.focus = true;
+    }
+
+    /// The matches have to be found again, e.g. after rows were added
+    pub fn invalidate(&mut self) {
+        self.searched = None;
+    }
+
+    /// Finds the matches in a view unless they're up to date; `key`
+    /// identifies the view, `lines` yields the searchable lines with their
+    /// positions
+    pub fn update<'a>(&mut self, key: ViewKey, lines: impl Iterator<Item = (usize, Cow<'a, str>)>) {
+        if self.searched == Some(key) {
+            return;
+        }
+        self.searched = Some(key);
+        let Some(re) = self.matcher.as_ref().filter(|_| self.is_open) else {
+            self.matches.clear();
+            return;
+        };
+        // Stay on the same position where it still matches
+        let current = self.current_pos();
+        self.matches = lines
+            .filter(|(_, line)| re.is_match(line))
+            .map(|(pos, _)| pos)
+            .collect();
+        self.current = current.and_then(|pos| self.matches.iter().position(|&m| m == pos));
+    }
+
+    /// Position of the match jumped to last
+    pub fn current_pos(&self) -> Option<usize> {
+        self.current.and_then(|i| self.matches.get(i).copied())
+    }
+
+    /// Moves to the next or previous match, wrapping around; returns its
+    /// position
+    fn step(&mut self, forward: bool) -> Option<usize> {
+        let n = self.matches.len();
+        if n == 0 {
+            return None;
+        }
+        let next = match (self.current, forward) {
+            (None, true) => 0,
+            (None, false) => n - 1,
+            (Some(i), true) => (i + 1) % n,
+            (Some(i), false) => (i + n - 1) % n,
+        };
+        self.current = Some(next);
+        self.matches.get(next).copied()
+    }
+
+    fn compile(&mut self) {
+        self.matcher = None;
+        self.error = None;
+        self.current = None;
+        self.searched = None;
+        if self.text.is_empty() {
+            return;
+        }
+        let pattern = if self.is_regex {
+            self.text.clone()
+        } else {
+            format!("(?i){}", regex::escape(&self.text))
+        };
+        match regex_guard::compile(&pattern) {
+            Ok(re) => self.matcher = Some(re),
+            Err(e) => self.error = Some(e),
+        }
+    }
+
+    /// Search bar; returns the position to jump to when the user moved to
+    /// another match
+    pub fn show(&mut self, ui: &mut egui::Ui) -> Option<usize> {
+        let mut jump = None;
+        ui.horizontal(|ui| {
+            ui.label("Find:");
+            let field = ui.add(
+                egui::TextEdit::singleline(&mut self.text)
+                    .hint_text(if self.is_regex { "regex" } else { "text" })
+                    .desired_width(260.0),
+            );
+            if std::mem::take(&mut self.focus) {
+                field.request_focus();
+            }
+            let mut changed = field.changed();
+            changed |= ui.checkbox(&mut self.is_regex, "Regex").changed();
+            if changed {
+                self.compile();
+            }
+            let (enter, shift, f3) = ui.input(|i| {
+                (
+                    i.key_pressed(egui::Key::Enter),
+                    i.modifiers.shift,
+                    i.key_pressed(egui::Key::F3),
+                )
+            });
+            // Enter leaves the single-line field, so take focus back
+            if field.lost_focus() && enter {
+                jump = self.step(!shift);
+                field.request_focus();
+            }
+            if f3 {
+                jump = self.step(!shift);
+            }
+            if ui
+                .small_button("▲")
+                .on_hover_text("Previous (Shift+Enter)")
+                .clicked()
+            {
+                jump = self.step(false);
+            }
+            if ui.small_button("▼").on_hover_text("Next (Enter)").clicked() {
+                jump = self.step(true);
+            }
+            if ui.small_button("✖").on_hover_text("Close (Esc)").clicked()
+                || (field.has_focus() || field.lost_focus())
+                    && ui.input(|i| i.key_pressed(egui::Key::Escape))
+            {
+                self.is_open = false;
+                self.matches.clear();
+                self.searched = None;
+            }
+            if let Some(e) = &self.error {
+                ui.colored_label(Color32::RED, e);
+            } else if self.matcher.is_some() {
+                ui.label(match (self.current, self.matches.len()) {
+                    (_, 0) => "No matches".to_string(),
+                    (Some(i), n) => format!("{} of {}", i + 1, n),
+                    (None, n) => format!("{} matches", n),
+                });
+            }
+        });
+        jump
+    }
+
+    /// The text with its matches highlighted; None if it has none
+    pub fn highlight(
+        &self,
+        text: &str,
+        font_id: &FontId,
+        color: Color32,
+        current: bool,
+    ) -> Option<LayoutJob> {
+        let re = self.matcher.as_ref().filter(|_| self.is_open)?;
+        let mut found = re.find_iter(text).filter(|m| !m.is_empty()).peekable();
+        found.peek()?;
+        let plain = TextFormat::simple(font_id.clone(), color);
+        let marked = TextFormat {
+            background: if current { CURRENT } else { self.background },
+            ..plain.clone()
+        };
+        let mut job = LayoutJob::default();
+        let mut last = 0;
+        for m in found {
+            job.append(&text[last..m.start()], 0.0, plain.clone());
+            job.append(m.as_str(), 0.0, marked.clone());
+            last = m.end();
+        }
+        job.append(&text[last..], 0.0, plain);
+        Some(job)
+    }
+}
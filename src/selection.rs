@@ -0,0 +1,104 @@
+// Row selection of the data table: click selects one row, Ctrl+click (Cmd on
+// macOS) adds or removes a row and Shift+click selects the rows between the
+// anchor and the clicked one as they are displayed. Rows are kept as indices
+// into the row buffer, so sorting or filtering doesn't change what's selected.
+
+use std::collections::BTreeSet;
+
+use crate::table_view::TableView;
+
+#[derive(Default)]
+pub struct Selection {
+    rows: BTreeSet<usize>,
+    /// Row a Shift+click range starts from
+    anchor: Option<usize>,
+    /// Row clicked or moved to last; the keyboard moves on from it
+    cursor: Option<usize>,
+}
+
+impl Selection {
+    pub fn cursor(&self) -> Option<usize> {
+        self.cursor
+    }
+
+    pub fn contains(&self, row: usize) -> bool {
+        self.rows.contains(&row)
+    }
+
+    pub fn len(&self) -> usize {
+        self.rows.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+
+    /// Selected rows in arrival order
+    pub fn rows(&self) -> Vec<usize> {
+        self.rows.iter().copied().collect()
+    }
+
+    pub fn clear(&mut self) {
+        *self = Selection::default();
+    }
+
+    /// Selects only `row`
+    pub fn select(&mut self, row: usize) {
+        self.rows.clear();
+        self.rows.insert(row);
+        self.anchor = Some(row);
+        self.cursor = Some(row);
+    }
+
+    /// Adds `row` to the selection or removes it
+    pub fn toggle(&mut self, row: usize) {
+        if !self.rows.remove(&row) {
+            self.rows.insert(row);
+        }
+        self.anchor = Some(row);
+        self.cursor = Some(row);
+    }
+
+    /// Selects the rows displayed from the anchor to `row`
+    pub fn extend(&mut self, row: usize, view: &TableView) {
+        let range = self
+            .anchor
+            .and_then(|anchor| view.pos_of(anchor))
+            .zip(view.pos_of(row));
+        let Some((from, to)) = range else {
+            self.select(row);
+            return;
+        };
+        self.rows = (from.min(to)..=from.max(to))
+            .filter_map(|pos| view.row(pos))
+            .collect();
+        self.cursor = Some(row);
+    }
+
+    /// Selects every row in the view
+    pub fn select_all(&mut self, view: &TableView) {
+        self.rows = (0..view.len()).filter_map(|pos| view.row(pos)).collect();
+    }
+
+    /// Applies a click on `row` with the modifiers held
+    pub fn click(&mut self, row: usize, modifiers: egui::Modifiers, view: &TableView) {
+        if modifiers.shift {
+            self.extend(row, view);
+        } else if modifiers.command {
+            self.toggle(row);
+        } else {
+            self.select(row);
+        }
+    }
+
+    /// Follows the rows after the oldest `drained` were removed
+    pub fn drain(&mut self, drained: usize) {
+        self.rows = self
+            .rows
+            .iter()
+            .filter_map(|row| row.checked_sub(drained))
+            .collect();
+        self.anchor = self.anchor.and_then(|row| row.checked_sub(drained));
+        self.cursor = self.cursor.and_then(|row| row.checked_sub(drained));
+    }
+}
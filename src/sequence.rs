@@ -0,0 +1,34 @@
+/// Detects dropped frames from a column holding an increasing sequence counter
+#[derive(Default)]
+pub struct SequenceTracker {
+    last: Option<u64>,
+    /// Frames missing across all gaps
+    pub dropped: u64,
+    /// Number of places where frames went missing
+    pub gaps: u64,
+}
+
+impl SequenceTracker {
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+
+    /// Feeds the next counter value and returns how many frames are missing
+    /// before it. A value that doesn't advance is taken as a counter reset or
+    /// wrap-around rather than loss; non-numeric values are ignored.
+    pub fn check(&mut self, value: &str) -> u64 {
+        let Ok(value) = value.trim().parse::<u64>() else {
+            return 0;
+        };
+        let missing = match self.last {
+            Some(last) if value > last => value - last - 1,
+            _ => 0,
+        };
+        self.last = Some(value);
+        if missing > 0 {
+            self.dropped += missing;
+            self.gaps += 1;
+        }
+        missing
+    }
+}
@@ -1,27 +1,380 @@
-use std::io::{self, Read, Write};
-use std::sync::mpsc;
+use std::collections::VecDeque;
+use std::io::{self, Read};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{mpsc, Arc};
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
-use crate::settings::Settings;
+use encoding_rs::{Encoding, UTF_8};
+
+use crate::control_lines::ControlLine;
+use crate::decoder_stats::FrameStats;
+use crate::driver_quirks;
+use crate::framing::{Frame, Framer};
+use crate::line_errors::ErrorCounter;
+use crate::low_latency;
+use crate::settings::{ConnectionKind, LineEnding, Settings};
+use crate::simulator::run_simulator_thread;
+use crate::sniffer::run_sniffer_thread;
+
+/// Software flow control characters
+pub const XON: u8 = 0x11;
+pub const XOFF: u8 = 0x13;
 
 pub enum SerialCommand {
     Send(Vec<u8>),
+    SetRxLineEnding(LineEnding),
+    /// Character set received lines are decoded from
+    SetEncoding(&'static Encoding),
+    /// Discard data queued in the OS input and output buffers
+    FlushBuffers,
+    SetRts(bool),
+    SetDtr(bool),
+    /// Change the line settings of the open port without closing it
+    Reconfigure(LineConfig),
     Disconnect,
 }
 
+/// Line settings that can be changed while the port is open
+pub struct LineConfig {
+    pub baud_rate: u32,
+    pub data_bits: serialport::DataBits,
+    pub stop_bits: serialport::StopBits,
+    pub parity: serialport::Parity,
+    pub flow_control: serialport::FlowControl,
+}
+
+impl LineConfig {
+    pub fn from_settings(settings: &Settings) -> Self {
+        Self {
+            baud_rate: settings.baud_rate,
+            data_bits: settings.data_bits.to_serial(),
+            stop_bits: settings.stop_bits.to_serial(),
+            parity: settings.parity.to_serial(),
+            flow_control: settings.flow_control.to_serial(),
+        }
+    }
+
+    /// Applies the settings through the port's setters; DTR/RTS are left as they are
+    pub fn apply(&self, port: &mut dyn serialport::SerialPort) -> serialport::Result<()> {
+        port.set_baud_rate(self.baud_rate)?;
+        port.set_data_bits(self.data_bits)?;
+        port.set_stop_bits(self.stop_bits)?;
+        port.set_parity(self.parity)?;
+        port.set_flow_control(self.flow_control)
+    }
+}
+
+/// Direction of a line seen in sniffer mode
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Direction {
+    /// Host to device
+    Tx,
+    /// Device to host
+    Rx,
+}
+
+impl Direction {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Direction::Tx => "TX",
+            Direction::Rx => "RX",
+        }
+    }
+}
+
 pub enum SerialEvent {
     Data(String),
+    /// Line forwarded between the two ports in sniffer mode
+    Traffic(Direction, String),
     Connected,
     Disconnected,
     Error(String),
+    /// Non-fatal problem; the connection stays up
+    Warning(String),
+    /// Framing/parity/overrun/break errors reported by the UART since the
+    /// last read; the lines that follow contain the affected data
+    LineError(String),
+    /// Binary frame assembled in frame mode
+    Frame(Vec<u8>),
+    /// Bytes as read from the port, before line splitting or framing
+    RawBytes(Vec<u8>),
+    /// A modem control line changed level (or was first read)
+    ControlLine(ControlLine, bool),
+    /// Bytes of the next `Data` line as received, line ending included
+    LineBytes(Vec<u8>),
+    /// Line ending found in the first lines with the `Auto` RX line ending
+    LineEndingDetected(LineEnding),
 }
 
+/// RS-485 half-duplex direction control: RTS enables the transmitter
+pub struct Rs485Direction {
+    /// Delay between asserting RTS and the first byte
+    pub pre_delay: Duration,
+    /// Delay between the last byte leaving the UART and releasing RTS
+    pub post_delay: Duration,
+}
+
+impl Rs485Direction {
+    pub fn from_settings(settings: &Settings) -> Option<Self> {
+        settings.rs485.then(|| Self {
+            pre_delay: Duration::from_micros(settings.rs485_pre_delay_us),
+            post_delay: Duration::from_micros(settings.rs485_post_delay_us),
+        })
+    }
+}
+
+/// How outgoing data is written to the port
+pub struct TxConfig {
+    pub rs485: Option<Rs485Direction>,
+    /// Pause after every byte
+    pub char_delay: Duration,
+    /// Extra pause after every line terminator
+    pub line_delay: Duration,
+}
+
+impl TxConfig {
+    pub fn from_settings(settings: &Settings) -> Self {
+        Self {
+            rs485: Rs485Direction::from_settings(settings),
+            char_delay: Duration::from_millis(settings.tx_char_delay_ms),
+            line_delay: Duration::from_millis(settings.tx_line_delay_ms),
+        }
+    }
+
+    fn is_paced(&self) -> bool {
+        !self.char_delay.is_zero() || !self.line_delay.is_zero()
+    }
+}
+
+/// A paced send in progress. The I/O thread writes one chunk per pass once
+/// it is due, so reads and commands are still serviced during a paste.
+struct PacedSend {
+    data: Vec<u8>,
+    /// Start of the next chunk
+    pos: usize,
+    /// When the next chunk may be written
+    due: Instant,
+}
+
+impl PacedSend {
+    /// Starts sending `data` at `due`, keying the RS-485 transmitter first
+    fn start(
+        port: &mut dyn serialport::SerialPort,
+        data: Vec<u8>,
+        due: Instant,
+        tx: &TxConfig,
+    ) -> io::Result<Self> {
+        if let Some(rs485) = &tx.rs485 {
+            port.write_request_to_send(true)?;
+            thread::sleep(rs485.pre_delay);
+        }
+        Ok(Self { data, pos: 0, due })
+    }
+
+    /// Writes the next chunk if it is due: up to the end of the line, or a
+    /// single byte with a character delay. A line ends at LF, or at a CR that
+    /// isn't followed by LF. Returns true once everything has been written.
+    fn poll(
+        &mut self,
+        port: &mut dyn serialport::SerialPort,
+        tx: &TxConfig,
+        counters: &IoCounters,
+    ) -> io::Result<bool> {
+        if Instant::now() < self.due {
+            return Ok(false);
+        }
+        let data = &self.data;
+        let mut end = self.pos;
+        let line_end = loop {
+            let b = data[end];
+            end += 1;
+            let line_end = b == b'\n' || (b == b'\r' && data.get(end) != Some(&b'\n'));
+            if line_end || !tx.char_delay.is_zero() || end == data.len() {
+                break line_end;
+            }
+        };
+        port.write_all(&data[self.pos..end])?;
+        // Let the chunk leave the UART before pausing
+        port.flush()?;
+        counters.add_tx(end - self.pos);
+        self.pos = end;
+        let pause = if line_end {
+            tx.char_delay + tx.line_delay
+        } else {
+            tx.char_delay
+        };
+        self.due = Instant::now() + pause;
+        if self.pos < self.data.len() {
+            return Ok(false);
+        }
+        self.finish(port, tx)?;
+        Ok(true)
+    }
+
+    /// Releases the RS-485 transmitter after the last chunk, or when the
+    /// send is cancelled
+    fn finish(&self, port: &mut dyn serialport::SerialPort, tx: &TxConfig) -> io::Result<()> {
+        if let Some(rs485) = &tx.rs485 {
+            thread::sleep(rs485.post_delay);
+            port.write_request_to_send(false)?;
+        }
+        Ok(())
+    }
+}
+
+/// How received data is split up
+pub struct RxConfig {
+    pub line_ending: LineEnding,
+    pub encoding: &'static Encoding,
+    /// Binary frames instead of lines
+    pub framer: Option<Framer>,
+    /// Ending found by the `Auto` line ending; lines are split at it from
+    /// then on
+    detected: Option<LineEnding>,
+}
+
+impl RxConfig {
+    pub fn from_settings(settings: &Settings) -> Result<Self, String> {
+        Ok(Self {
+            line_ending: settings.rx_line_ending.clone(),
+            encoding: settings.text_encoding.encoding(),
+            framer: Framer::from_settings(settings)?,
+            detected: None,
+        })
+    }
+
+    /// Emits every complete line or frame in `buf`
+    fn split(
+        &mut self,
+        buf: &mut Vec<u8>,
+        event_tx: &mpsc::SyncSender<SerialEvent>,
+        counters: &IoCounters,
+    ) {
+        match &mut self.framer {
+            Some(framer) => {
+                for frame in framer.take_frames(buf) {
+                    send_frame(frame, event_tx, counters);
+                }
+                counters.add_resyncs(framer.take_resyncs());
+            }
+            None => {
+                if self.line_ending == LineEnding::Auto && self.detected.is_none() {
+                    if let Some(le) = detect_line_ending(buf) {
+                        let _ = event_tx.send(SerialEvent::LineEndingDetected(le.clone()));
+                        self.detected = Some(le);
+                    }
+                }
+                let line_ending = self.detected.as_ref().unwrap_or(&self.line_ending);
+                extract_lines(buf, line_ending, self.encoding, event_tx);
+            }
+        }
+    }
+
+    /// Emits the frame in `buf` of a time-delimited framing once the line
+    /// has been idle long enough
+    fn split_idle(
+        &self,
+        buf: &mut Vec<u8>,
+        idle: Duration,
+        event_tx: &mpsc::SyncSender<SerialEvent>,
+        counters: &IoCounters,
+    ) {
+        if let Some(frame) = self
+            .framer
+            .as_ref()
+            .and_then(|f| f.take_idle_frame(buf, idle))
+        {
+            send_frame(frame, event_tx, counters);
+        }
+    }
+
+    /// Silence kept before transmitting, for time-delimited framings
+    fn idle_gap(&self) -> Option<Duration> {
+        self.framer.as_ref().and_then(Framer::idle_gap)
+    }
+
+    /// Emits what is left in `buf` when the port closes. A partial frame is
+    /// dropped; a partial line is still shown, even ending in a cut-off
+    /// character.
+    fn flush(&self, buf: &mut Vec<u8>, event_tx: &mpsc::SyncSender<SerialEvent>) {
+        if self.framer.is_none() && !buf.is_empty() {
+            let line = decode(buf, self.encoding);
+            let _ = event_tx.send(SerialEvent::LineBytes(std::mem::take(buf)));
+            let _ = event_tx.send(SerialEvent::Data(line));
+        }
+    }
+}
+
+fn send_frame(frame: Frame, event_tx: &mpsc::SyncSender<SerialEvent>, counters: &IoCounters) {
+    counters.add_frame(frame.bytes.len(), frame.error.is_some());
+    if let Some(e) = frame.error {
+        // Marks the frame's row like a UART error
+        let _ = event_tx.send(SerialEvent::LineError(e.to_string()));
+    }
+    let _ = event_tx.send(SerialEvent::Frame(frame.bytes));
+}
+
+/// Byte and frame counters shared with the I/O thread
+#[derive(Default)]
+pub struct IoCounters {
+    pub rx_bytes: AtomicU64,
+    pub tx_bytes: AtomicU64,
+    /// Frames assembled by a binary framing
+    pub frames: AtomicU64,
+    /// Frames that failed to decode or whose checksum didn't match
+    pub frame_errors: AtomicU64,
+    pub frame_bytes: AtomicU64,
+    /// Times the framer discarded bytes to find the next frame
+    pub resyncs: AtomicU64,
+}
+
+impl IoCounters {
+    pub fn add_rx(&self, n: usize) {
+        self.rx_bytes.fetch_add(n as u64, Ordering::Relaxed);
+    }
+    pub fn add_tx(&self, n: usize) {
+        self.tx_bytes.fetch_add(n as u64, Ordering::Relaxed);
+    }
+    pub fn add_frame(&self, len: usize, error: bool) {
+        self.frames.fetch_add(1, Ordering::Relaxed);
+        self.frame_bytes.fetch_add(len as u64, Ordering::Relaxed);
+        if error {
+            self.frame_errors.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+    pub fn add_resyncs(&self, n: u64) {
+        self.resyncs.fetch_add(n, Ordering::Relaxed);
+    }
+}
+
+/// How often the RX rate is recomputed
+const RATE_INTERVAL: Duration = Duration::from_secs(1);
+/// Number of rate samples kept for the status bar sparkline
+const RATE_HISTORY: usize = 60;
+
 pub struct SerialPortManager {
     pub cmd_tx: Option<mpsc::Sender<SerialCommand>>,
     pub event_rx: mpsc::Receiver<SerialEvent>,
     event_tx: mpsc::SyncSender<SerialEvent>,
+    thread: Option<thread::JoinHandle<()>>,
+    /// Events drained while waiting for the I/O thread to exit
+    pending: Vec<SerialEvent>,
     pub is_connected: bool,
+    pub counters: Arc<IoCounters>,
+    /// Lines received since the last counter reset
+    pub lines: u64,
+    /// Received bytes per second over the last `RATE_INTERVAL`
+    pub rx_rate: f64,
+    /// Recent `rx_rate` samples, oldest first
+    pub rate_history: VecDeque<f64>,
+    /// Received lines per second over the last `RATE_INTERVAL`
+    pub line_rate: f64,
+    /// Recent `line_rate` samples, oldest first
+    pub line_rate_history: VecDeque<f64>,
+    rate_at: Instant,
+    rate_rx: u64,
+    rate_lines: u64,
 }
 
 impl SerialPortManager {
@@ -31,27 +384,114 @@ impl SerialPortManager {
             cmd_tx: None,
             event_rx,
             event_tx,
+            thread: None,
+            pending: Vec::new(),
             is_connected: false,
+            counters: Arc::new(IoCounters::default()),
+            lines: 0,
+            rx_rate: 0.0,
+            rate_history: VecDeque::with_capacity(RATE_HISTORY),
+            line_rate: 0.0,
+            line_rate_history: VecDeque::with_capacity(RATE_HISTORY),
+            rate_at: Instant::now(),
+            rate_rx: 0,
+            rate_lines: 0,
         }
     }
 
+    pub fn rx_bytes(&self) -> u64 {
+        self.counters.rx_bytes.load(Ordering::Relaxed)
+    }
+
+    pub fn tx_bytes(&self) -> u64 {
+        self.counters.tx_bytes.load(Ordering::Relaxed)
+    }
+
+    /// Frame counters of the binary framing
+    pub fn frame_stats(&self) -> FrameStats {
+        let c = &self.counters;
+        FrameStats {
+            frames: c.frames.load(Ordering::Relaxed),
+            errors: c.frame_errors.load(Ordering::Relaxed),
+            bytes: c.frame_bytes.load(Ordering::Relaxed),
+            resyncs: c.resyncs.load(Ordering::Relaxed),
+        }
+    }
+
+    pub fn reset_frame_stats(&self) {
+        let c = &self.counters;
+        for counter in [&c.frames, &c.frame_errors, &c.frame_bytes, &c.resyncs] {
+            counter.store(0, Ordering::Relaxed);
+        }
+    }
+
+    pub fn reset_counters(&mut self) {
+        self.counters.rx_bytes.store(0, Ordering::Relaxed);
+        self.counters.tx_bytes.store(0, Ordering::Relaxed);
+        self.reset_frame_stats();
+        self.lines = 0;
+        self.rx_rate = 0.0;
+        self.rate_history.clear();
+        self.line_rate = 0.0;
+        self.line_rate_history.clear();
+        self.rate_at = Instant::now();
+        self.rate_rx = 0;
+        self.rate_lines = 0;
+    }
+
+    fn update_rate(&mut self) {
+        let elapsed = self.rate_at.elapsed();
+        if elapsed < RATE_INTERVAL {
+            return;
+        }
+        let rx = self.rx_bytes();
+        self.rx_rate = rx.saturating_sub(self.rate_rx) as f64 / elapsed.as_secs_f64();
+        self.line_rate = self.lines.saturating_sub(self.rate_lines) as f64 / elapsed.as_secs_f64();
+        for (history, rate) in [
+            (&mut self.rate_history, self.rx_rate),
+            (&mut self.line_rate_history, self.line_rate),
+        ] {
+            if history.len() == RATE_HISTORY {
+                history.pop_front();
+            }
+            history.push_back(rate);
+        }
+        self.rate_rx = rx;
+        self.rate_lines = self.lines;
+        self.rate_at = Instant::now();
+    }
+
     pub fn connect(&mut self, settings: &Settings) -> Result<(), String> {
         if self.is_connected {
             self.disconnect();
         }
 
+        if settings.connection == ConnectionKind::Simulator {
+            let (cmd_tx, cmd_rx) = mpsc::channel::<SerialCommand>();
+            self.cmd_tx = Some(cmd_tx);
+            self.is_connected = true;
+
+            let event_tx = self.event_tx.clone();
+            let counters = self.counters.clone();
+            let mode = settings.sim_mode.clone();
+            let rate = settings.sim_rate_hz;
+            self.thread = Some(thread::spawn(move || {
+                run_simulator_thread(mode, rate, cmd_rx, event_tx, counters);
+            }));
+            return Ok(());
+        }
+
         let port_name = settings.port_name.clone();
         if port_name.is_empty() {
             return Err("No port selected".to_string());
         }
 
-        let port = serialport::new(&port_name, settings.baud_rate)
-            .data_bits(settings.data_bits.to_serial())
-            .stop_bits(settings.stop_bits.to_serial())
-            .parity(settings.parity.to_serial())
-            .flow_control(settings.flow_control.to_serial())
-            .timeout(Duration::from_millis(50))
-            .open()
+        if settings.connection == ConnectionKind::Sniffer {
+            return self.connect_sniffer(settings);
+        }
+
+        let rx_config = RxConfig::from_settings(settings)?;
+        let opened = open_port(&port_name, settings)
             .map_err(|e| format!("Failed to open {}: {}", port_name, e))?;
 
         let (cmd_tx, cmd_rx) = mpsc::channel::<SerialCommand>();
@@ -59,11 +499,65 @@ impl SerialPortManager {
         self.is_connected = true;
 
         let event_tx = self.event_tx.clone();
+        let counters = self.counters.clone();
+        let tx_config = TxConfig::from_settings(settings);
+
+        self.thread = Some(thread::spawn(move || {
+            let _ = event_tx.send(SerialEvent::Connected);
+            if let Some(warning) = opened.warning {
+                let _ = event_tx.send(SerialEvent::Warning(warning));
+            }
+            run_serial_thread(
+                opened.port,
+                cmd_rx,
+                event_tx,
+                counters,
+                rx_config,
+                tx_config,
+                opened.errors,
+            );
+        }));
+
+        Ok(())
+    }
+
+    /// Opens the host and device ports and forwards traffic between them
+    fn connect_sniffer(&mut self, settings: &Settings) -> Result<(), String> {
+        if settings.sniffer_port.is_empty() {
+            return Err("No device port selected".to_string());
+        }
+        if settings.sniffer_port == settings.port_name {
+            return Err("Host and device port must differ".to_string());
+        }
+        let host = open_port(&settings.port_name, settings)
+            .map_err(|e| format!("Failed to open {}: {}", settings.port_name, e))?;
+        let device = open_port(&settings.sniffer_port, settings)
+            .map_err(|e| format!("Failed to open {}: {}", settings.sniffer_port, e))?;
+
+        let (cmd_tx, cmd_rx) = mpsc::channel::<SerialCommand>();
+        self.cmd_tx = Some(cmd_tx);
+        self.is_connected = true;
+
+        let event_tx = self.event_tx.clone();
+        let counters = self.counters.clone();
         let rx_line_ending = settings.rx_line_ending.clone();
+        let encoding = settings.text_encoding.encoding();
 
-        thread::spawn(move || {
-            run_serial_thread(port, cmd_rx, event_tx, rx_line_ending);
-        });
+        self.thread = Some(thread::spawn(move || {
+            let _ = event_tx.send(SerialEvent::Connected);
+            for warning in [host.warning, device.warning].into_iter().flatten() {
+                let _ = event_tx.send(SerialEvent::Warning(warning));
+            }
+            run_sniffer_thread(
+                host.port,
+                device.port,
+                cmd_rx,
+                event_tx,
+                counters,
+                rx_line_ending,
+                encoding,
+            );
+        }));
 
         Ok(())
     }
@@ -72,6 +566,16 @@ impl SerialPortManager {
         if let Some(tx) = self.cmd_tx.take() {
             let _ = tx.send(SerialCommand::Disconnect);
         }
+        // Wait for the thread to release the port so it can be reopened right
+        // away. Keep draining its events meanwhile: with the channel full it
+        // would block in send and never see the command.
+        if let Some(handle) = self.thread.take() {
+            while !handle.is_finished() {
+                self.drain_events();
+                thread::sleep(Duration::from_millis(1));
+            }
+            let _ = handle.join();
+        }
         self.is_connected = false;
     }
 
@@ -81,41 +585,284 @@ impl SerialPortManager {
         }
     }
 
-    /// Drain all pending events; returns them as a vec.
-    pub fn poll_events(&self) -> Vec<SerialEvent> {
-        let mut events = Vec::new();
-        loop {
-            match self.event_rx.try_recv() {
-                Ok(ev) => events.push(ev),
-                Err(_) => break,
+    /// Changes how incoming data is split into lines without reconnecting
+    pub fn set_rx_line_ending(&self, line_ending: LineEnding) {
+        if let Some(tx) = &self.cmd_tx {
+            let _ = tx.send(SerialCommand::SetRxLineEnding(line_ending));
+        }
+    }
+
+    /// Changes the character set of received lines without reconnecting
+    pub fn set_encoding(&self, encoding: &'static Encoding) {
+        if let Some(tx) = &self.cmd_tx {
+            let _ = tx.send(SerialCommand::SetEncoding(encoding));
+        }
+    }
+
+    /// Discards stale data queued in the port's OS buffers
+    pub fn flush_buffers(&self) {
+        if let Some(tx) = &self.cmd_tx {
+            let _ = tx.send(SerialCommand::FlushBuffers);
+        }
+    }
+
+    /// Applies changed baud rate, framing and flow control to the open port
+    pub fn reconfigure(&self, settings: &Settings) {
+        if let Some(tx) = &self.cmd_tx {
+            let _ = tx.send(SerialCommand::Reconfigure(LineConfig::from_settings(
+                settings,
+            )));
+        }
+    }
+
+    /// Drives RTS or DTR; other lines are inputs and are ignored
+    pub fn set_control_line(&self, line: ControlLine, level: bool) {
+        let cmd = match line {
+            ControlLine::Rts => SerialCommand::SetRts(level),
+            ControlLine::Dtr => SerialCommand::SetDtr(level),
+            _ => return,
+        };
+        if let Some(tx) = &self.cmd_tx {
+            let _ = tx.send(cmd);
+        }
+    }
+
+    /// Moves the queued events into `pending`
+    fn drain_events(&mut self) {
+        while let Ok(ev) = self.event_rx.try_recv() {
+            if matches!(
+                ev,
+                SerialEvent::Data(_) | SerialEvent::Traffic(..) | SerialEvent::Frame(_)
+            ) {
+                self.lines += 1;
             }
+            self.pending.push(ev);
         }
-        events
+    }
+
+    /// Drain all pending events; returns them as a vec.
+    pub fn poll_events(&mut self) -> Vec<SerialEvent> {
+        self.drain_events();
+        self.update_rate();
+        std::mem::take(&mut self.pending)
+    }
+}
+
+/// A port offered in the port pickers
+#[derive(Clone)]
+pub struct PortEntry {
+    pub name: String,
+    /// Name plus port kind, e.g. "/dev/rfcomm0 (Bluetooth SPP)"
+    pub label: String,
+}
+
+/// Lists the available ports. Bluetooth SPP links show up here too: the OS
+/// exposes a paired SPP device as an ordinary serial port (rfcomm / COM).
+/// BLE Nordic UART devices are not listed: they are GATT services, not ports,
+/// and reach the console only through a bridge that creates a virtual port.
+pub fn list_ports() -> Vec<PortEntry> {
+    serialport::available_ports()
+        .unwrap_or_default()
+        .into_iter()
+        .map(|p| {
+            let kind = match &p.port_type {
+                serialport::SerialPortType::UsbPort(usb) => {
+                    usb.product.clone().unwrap_or_else(|| "USB".to_string())
+                }
+                serialport::SerialPortType::BluetoothPort => "Bluetooth SPP".to_string(),
+                serialport::SerialPortType::PciPort => "PCI".to_string(),
+                serialport::SerialPortType::Unknown => String::new(),
+            };
+            let label = if kind.is_empty() {
+                p.port_name.clone()
+            } else {
+                format!("{} ({})", p.port_name, kind)
+            };
+            PortEntry {
+                name: p.port_name,
+                label,
+            }
+        })
+        .collect()
+}
+
+fn port_builder(port_name: &str, settings: &Settings) -> serialport::SerialPortBuilder {
+    let timeout = if settings.low_latency {
+        low_latency::LOW_LATENCY_TIMEOUT
+    } else {
+        low_latency::DEFAULT_TIMEOUT
+    };
+    let builder = serialport::new(port_name, settings.baud_rate)
+        .data_bits(settings.data_bits.to_serial())
+        .stop_bits(settings.stop_bits.to_serial())
+        .parity(settings.parity.to_serial())
+        .flow_control(settings.flow_control.to_serial())
+        .timeout(timeout);
+    driver_quirks::configure_builder(builder, settings)
+}
+
+/// A freshly opened port
+struct OpenedPort {
+    port: Box<dyn serialport::SerialPort>,
+    /// Non-fatal problem while setting the port up
+    warning: Option<String>,
+    /// Line error counters, where the platform exposes them
+    errors: Option<ErrorCounter>,
+}
+
+/// Joins the setup problems into one warning
+fn join_warnings(warnings: Vec<String>) -> Option<String> {
+    if warnings.is_empty() {
+        None
+    } else {
+        Some(warnings.join("; "))
     }
 }
 
+/// Opens the port, applying low-latency tweaks and driver quirks if enabled
+#[cfg(target_os = "linux")]
+fn open_port(port_name: &str, settings: &Settings) -> serialport::Result<OpenedPort> {
+    use std::os::unix::io::AsRawFd;
+
+    let mut port = port_builder(port_name, settings).open_native()?;
+    let mut warnings = driver_quirks::apply_linux(&mut port, settings);
+    warnings.extend(driver_quirks::apply(&mut port, settings));
+    if settings.low_latency {
+        warnings.extend(low_latency::enable(port_name, &port));
+    }
+    let errors = ErrorCounter::new(port.as_raw_fd());
+    Ok(OpenedPort {
+        port: Box::new(port),
+        warning: join_warnings(warnings),
+        errors,
+    })
+}
+
+#[cfg(not(target_os = "linux"))]
+fn open_port(port_name: &str, settings: &Settings) -> serialport::Result<OpenedPort> {
+    let mut port = port_builder(port_name, settings).open()?;
+    let warnings = driver_quirks::apply(port.as_mut(), settings);
+    Ok(OpenedPort {
+        port,
+        warning: join_warnings(warnings),
+        errors: None,
+    })
+}
+
 fn run_serial_thread(
     mut port: Box<dyn serialport::SerialPort>,
     cmd_rx: mpsc::Receiver<SerialCommand>,
     event_tx: mpsc::SyncSender<SerialEvent>,
-    rx_line_ending: crate::settings::LineEnding,
+    counters: Arc<IoCounters>,
+    mut rx_config: RxConfig,
+    tx_config: TxConfig,
+    mut errors: Option<ErrorCounter>,
 ) {
-    let _ = event_tx.send(SerialEvent::Connected);
+    if tx_config.rs485.is_some() {
+        // Start in receive mode
+        if let Err(e) = port.write_request_to_send(false) {
+            let _ = event_tx.send(SerialEvent::Warning(format!("Cannot release RTS: {}", e)));
+        }
+    }
 
     let mut rx_buf = Vec::<u8>::with_capacity(4096);
     let mut read_buf = [0u8; 256];
+    // Last modem status; polling stops for good if the port can't report it
+    let mut inputs: Option<[bool; 4]> = None;
+    let mut poll_inputs = true;
+    // End of the last received chunk, for time-delimited frames
+    let mut last_rx = Instant::now();
+    let read_timeout = port.timeout();
+    let mut paced: Option<PacedSend> = None;
+    // Earliest start of the next paced send, after the last one's final pause
+    let mut tx_ready = Instant::now();
 
     loop {
         // Check for commands (non-blocking)
         loop {
             match cmd_rx.try_recv() {
                 Ok(SerialCommand::Disconnect) => {
+                    if let Some(send) = paced.take() {
+                        let _ = send.finish(&mut *port, &tx_config);
+                    }
                     let _ = event_tx.send(SerialEvent::Disconnected);
                     return;
                 }
+                Ok(SerialCommand::SetRts(level)) => match port.write_request_to_send(level) {
+                    Ok(()) => {
+                        let _ = event_tx.send(SerialEvent::ControlLine(ControlLine::Rts, level));
+                    }
+                    Err(e) => {
+                        let _ =
+                            event_tx.send(SerialEvent::Warning(format!("Cannot set RTS: {}", e)));
+                    }
+                },
+                Ok(SerialCommand::Reconfigure(config)) => {
+                    if let Some(framer) = rx_config.framer.as_mut() {
+                        framer.set_baud_rate(config.baud_rate);
+                    }
+                    if let Err(e) = config.apply(&mut *port) {
+                        let _ = event_tx
+                            .send(SerialEvent::Warning(format!("Reconfigure failed: {}", e)));
+                    }
+                }
+                Ok(SerialCommand::SetDtr(level)) => match port.write_data_terminal_ready(level) {
+                    Ok(()) => {
+                        let _ = event_tx.send(SerialEvent::ControlLine(ControlLine::Dtr, level));
+                    }
+                    Err(e) => {
+                        let _ =
+                            event_tx.send(SerialEvent::Warning(format!("Cannot set DTR: {}", e)));
+                    }
+                },
                 Ok(SerialCommand::Send(data)) => {
-                    if let Err(e) = port.write_all(&data) {
-                        let _ = event_tx.send(SerialEvent::Error(format!("Write error: {}", e)));
+                    if let Some(gap) = rx_config.idle_gap() {
+                        // Don't let the request run into a frame still arriving
+                        thread::sleep(gap.saturating_sub(last_rx.elapsed()));
+                    }
+                    // A new send replaces a paste still in progress
+                    if let Some(send) = paced.take() {
+                        tx_ready = Instant::now();
+                        if let Err(e) = send.finish(&mut *port, &tx_config) {
+                            let _ = event_tx
+                                .send(SerialEvent::Warning(format!("Cannot release RTS: {}", e)));
+                        }
+                    }
+                    let result = if tx_config.is_paced() && !data.is_empty() {
+                        let due = tx_ready.max(Instant::now());
+                        PacedSend::start(&mut *port, data, due, &tx_config)
+                            .map(|send| paced = Some(send))
+                    } else {
+                        write_data(&mut *port, &data, &tx_config)
+                            .map(|()| counters.add_tx(data.len()))
+                    };
+                    match result {
+                        Ok(()) => {}
+                        Err(ref e) if is_peer_closed(e) => {
+                            rx_config.flush(&mut rx_buf, &event_tx);
+                            let _ = event_tx.send(SerialEvent::Disconnected);
+                            return;
+                        }
+                        Err(e) => {
+                            let _ =
+                                event_tx.send(SerialEvent::Error(format!("Write error: {}", e)));
+                        }
+                    }
+                }
+                Ok(SerialCommand::SetRxLineEnding(le)) => {
+                    rx_config.line_ending = le;
+                    rx_config.detected = None;
+                    rx_config.split(&mut rx_buf, &event_tx, &counters);
+                }
+                Ok(SerialCommand::SetEncoding(encoding)) => {
+                    rx_config.encoding = encoding;
+                    rx_config.split(&mut rx_buf, &event_tx, &counters);
+                }
+                Ok(SerialCommand::FlushBuffers) => {
+                    // Drop the partial line too, it belongs to the stale data
+                    rx_buf.clear();
+                    if let Err(e) = port.clear(serialport::ClearBuffer::All) {
+                        let _ = event_tx.send(SerialEvent::Warning(format!("Flush failed: {}", e)));
                     }
                 }
                 Err(mpsc::TryRecvError::Empty) => break,
@@ -126,16 +873,79 @@ fn run_serial_thread(
             }
         }
 
+        if let Some(send) = paced.as_mut() {
+            match send.poll(&mut *port, &tx_config, &counters) {
+                Ok(false) => {}
+                Ok(true) => {
+                    tx_ready = send.due;
+                    paced = None;
+                }
+                Err(ref e) if is_peer_closed(e) => {
+                    rx_config.flush(&mut rx_buf, &event_tx);
+                    let _ = event_tx.send(SerialEvent::Disconnected);
+                    return;
+                }
+                Err(e) => {
+                    let _ = send.finish(&mut *port, &tx_config);
+                    paced = None;
+                    let _ = event_tx.send(SerialEvent::Error(format!("Write error: {}", e)));
+                }
+            }
+        }
+        // Don't let a quiet line hold up the next paced chunk
+        let timeout = paced.as_ref().map_or(read_timeout, |send| {
+            send.due
+                .saturating_duration_since(Instant::now())
+                .clamp(Duration::from_millis(1), read_timeout)
+        });
+        if port.timeout() != timeout {
+            let _ = port.set_timeout(timeout);
+        }
+
+        if poll_inputs {
+            match read_inputs(&mut *port) {
+                Ok(now) => {
+                    for (i, &line) in INPUT_LINES.iter().enumerate() {
+                        if inputs.is_none_or(|prev| prev[i] != now[i]) {
+                            let _ = event_tx.send(SerialEvent::ControlLine(line, now[i]));
+                        }
+                    }
+                    inputs = Some(now);
+                }
+                // ptys and some adapters don't support TIOCMGET
+                Err(_) => poll_inputs = false,
+            }
+        }
+
         // Read from port
         match port.read(&mut read_buf) {
-            Ok(0) => {}
+            Ok(0) => {
+                // EOF: the other end of a PTY / virtual port went away
+                rx_config.flush(&mut rx_buf, &event_tx);
+                let _ = event_tx.send(SerialEvent::Disconnected);
+                return;
+            }
             Ok(n) => {
+                counters.add_rx(n);
+                // A gap before this chunk ends the previous frame
+                rx_config.split_idle(&mut rx_buf, last_rx.elapsed(), &event_tx, &counters);
+                if let Some(seen) = errors.as_mut().and_then(ErrorCounter::poll) {
+                    let _ = event_tx.send(SerialEvent::LineError(format!("UART errors: {}", seen)));
+                }
+                let _ = event_tx.send(SerialEvent::RawBytes(read_buf[..n].to_vec()));
                 rx_buf.extend_from_slice(&read_buf[..n]);
-                // Extract complete lines
-                extract_lines(&mut rx_buf, &rx_line_ending, &event_tx);
+                last_rx = Instant::now();
+                // Extract complete lines or frames
+                rx_config.split(&mut rx_buf, &event_tx, &counters);
             }
             Err(ref e) if e.kind() == io::ErrorKind::TimedOut => {
-                // Normal timeout - continue
+                // Normal timeout; the line went quiet
+                rx_config.split_idle(&mut rx_buf, last_rx.elapsed(), &event_tx, &counters);
+            }
+            Err(ref e) if is_peer_closed(e) => {
+                rx_config.flush(&mut rx_buf, &event_tx);
+                let _ = event_tx.send(SerialEvent::Disconnected);
+                return;
             }
             Err(e) => {
                 let _ = event_tx.send(SerialEvent::Error(format!("Read error: {}", e)));
@@ -146,71 +956,327 @@ fn run_serial_thread(
     }
 }
 
+/// Modem status lines in the order returned by `read_inputs`
+const INPUT_LINES: [ControlLine; 4] = [
+    ControlLine::Cts,
+    ControlLine::Dsr,
+    ControlLine::Dcd,
+    ControlLine::Ri,
+];
+
+fn read_inputs(port: &mut dyn serialport::SerialPort) -> serialport::Result<[bool; 4]> {
+    Ok([
+        port.read_clear_to_send()?,
+        port.read_data_set_ready()?,
+        port.read_carrier_detect()?,
+        port.read_ring_indicator()?,
+    ])
+}
+
+/// True if a read or write error means the peer closed the port: a broken pipe, or
+/// EIO, which is what reading a PTY whose master side was closed (socat, QEMU
+/// exiting) fails with on Linux.
+fn is_peer_closed(e: &io::Error) -> bool {
+    e.kind() == io::ErrorKind::BrokenPipe || is_pty_hangup(e)
+}
+
+#[cfg(target_os = "linux")]
+fn is_pty_hangup(e: &io::Error) -> bool {
+    e.raw_os_error() == Some(libc::EIO)
+}
+
+#[cfg(not(target_os = "linux"))]
+fn is_pty_hangup(_e: &io::Error) -> bool {
+    false
+}
+
+/// Writes `data` in one go, keying the RS-485 transmitter with RTS when
+/// configured
+fn write_data(port: &mut dyn serialport::SerialPort, data: &[u8], tx: &TxConfig) -> io::Result<()> {
+    let Some(rs485) = &tx.rs485 else {
+        return port.write_all(data);
+    };
+    port.write_request_to_send(true)?;
+    thread::sleep(rs485.pre_delay);
+    // flush() waits until the last byte has left the UART (tcdrain)
+    let result = port.write_all(data).and_then(|_| port.flush());
+    thread::sleep(rs485.post_delay);
+    port.write_request_to_send(false)?;
+    result
+}
+
 fn extract_lines(
     buf: &mut Vec<u8>,
-    line_ending: &crate::settings::LineEnding,
+    line_ending: &LineEnding,
+    encoding: &'static Encoding,
     event_tx: &mpsc::SyncSender<SerialEvent>,
 ) {
-    use crate::settings::LineEnding;
+    for (line, bytes) in take_raw_lines(buf, line_ending, encoding) {
+        let _ = event_tx.send(SerialEvent::LineBytes(bytes));
+        let _ = event_tx.send(SerialEvent::Data(line));
+    }
+}
 
+/// Removes all complete lines from `buf`; with `LineEnding::None` everything
+/// buffered is one line, except a multi-byte character whose remaining bytes
+/// haven't been read yet
+pub fn take_lines(
+    buf: &mut Vec<u8>,
+    line_ending: &LineEnding,
+    encoding: &'static Encoding,
+) -> Vec<String> {
+    take_raw_lines(buf, line_ending, encoding)
+        .into_iter()
+        .map(|(line, _)| line)
+        .collect()
+}
+
+/// Like `take_lines`, with the bytes each line was decoded from, line ending
+/// included
+fn take_raw_lines(
+    buf: &mut Vec<u8>,
+    line_ending: &LineEnding,
+    encoding: &'static Encoding,
+) -> Vec<(String, Vec<u8>)> {
+    let mut lines = Vec::new();
     match line_ending {
         LineEnding::None => {
-            // Send everything as-is
-            if !buf.is_empty() {
-                if let Ok(s) = String::from_utf8(buf.clone()) {
-                    let _ = event_tx.send(SerialEvent::Data(s));
-                } else {
-                    // Send as lossy UTF-8
-                    let s = String::from_utf8_lossy(buf).to_string();
-                    let _ = event_tx.send(SerialEvent::Data(s));
-                }
-                buf.clear();
+            let complete = complete_len(buf, encoding);
+            if complete > 0 {
+                let line_bytes: Vec<u8> = buf.drain(..complete).collect();
+                lines.push((decode(&line_bytes, encoding), line_bytes));
             }
         }
-        LineEnding::LF => {
-            extract_by_delimiter(buf, b'\n', event_tx);
-        }
-        LineEnding::CR => {
-            extract_by_delimiter(buf, b'\r', event_tx);
+        // A CRLF is taken as one ending when both bytes are buffered; blank
+        // lines are dropped, so one whose LF arrives in the next read still
+        // yields a single line
+        LineEnding::Auto => take_by_delimiter(buf, b"\r\n", encoding, &mut lines),
+        LineEnding::LF => take_by_delimiter(buf, b"\n", encoding, &mut lines),
+        LineEnding::CR => take_by_delimiter(buf, b"\r", encoding, &mut lines),
+        LineEnding::CrLf => take_by_crlf(buf, encoding, &mut lines),
+    }
+    lines
+}
+
+/// Received bytes as text; invalid sequences become U+FFFD
+fn decode(bytes: &[u8], encoding: &'static Encoding) -> String {
+    encoding.decode_without_bom_handling(bytes).0.into_owned()
+}
+
+/// Length of `buf` without a multi-byte character cut off at its end. Lines
+/// split at a delimiter never cut one: in every supported encoding CR and LF
+/// only occur as themselves.
+fn complete_len(buf: &[u8], encoding: &'static Encoding) -> usize {
+    if encoding == UTF_8 {
+        return complete_utf8_len(buf);
+    }
+    if encoding.is_single_byte() {
+        return buf.len();
+    }
+    // A streaming decoder holds back the bytes of a cut-off character; the
+    // complete part is the longest prefix that decodes to the same text
+    let mut decoder = encoding.new_decoder_without_bom_handling();
+    let capacity = decoder.max_utf8_buffer_length(buf.len()).unwrap_or(0);
+    let mut text = String::with_capacity(capacity);
+    let _ = decoder.decode_to_string(buf, &mut text, false);
+    (0..=buf.len().min(3))
+        .map(|back| buf.len() - back)
+        .find(|&len| encoding.decode_without_bom_handling(&buf[..len]).0 == text)
+        .unwrap_or(buf.len())
+}
+
+/// Length of `buf` without a multi-byte UTF-8 sequence cut off at its end
+fn complete_utf8_len(buf: &[u8]) -> usize {
+    // The lead byte of a cut-off sequence is among the last three bytes
+    for back in 1..=buf.len().min(3) {
+        let b = buf[buf.len() - back];
+        if b & 0xC0 == 0x80 {
+            continue; // continuation byte
         }
-        LineEnding::CrLf => {
-            extract_by_crlf(buf, event_tx);
+        let needed = match b {
+            0xC2..=0xDF => 2,
+            0xE0..=0xEF => 3,
+            0xF0..=0xF4 => 4,
+            _ => 1,
+        };
+        return if needed > back {
+            buf.len() - back
+        } else {
+            buf.len()
+        };
+    }
+    buf.len()
+}
+
+/// The line ending used most in `buf`; None until a line has ended. A CR at
+/// the end of `buf` may be the first half of a CRLF and isn't counted.
+pub fn detect_line_ending(buf: &[u8]) -> Option<LineEnding> {
+    let (mut cr, mut lf, mut crlf) = (0, 0, 0);
+    let mut i = 0;
+    while i < buf.len() {
+        match (buf[i], buf.get(i + 1)) {
+            (b'\r', Some(b'\n')) => {
+                crlf += 1;
+                i += 1;
+            }
+            (b'\r', Some(_)) => cr += 1,
+            (b'\n', _) => lf += 1,
+            _ => {}
         }
+        i += 1;
     }
+    // Ties go to the later entry
+    [
+        (cr, LineEnding::CR),
+        (lf, LineEnding::LF),
+        (crlf, LineEnding::CrLf),
+    ]
+    .into_iter()
+    .filter(|&(n, _)| n > 0)
+    .max_by_key(|&(n, _)| n)
+    .map(|(_, le)| le)
 }
 
-fn extract_by_delimiter(
+/// Splits at any of the `delims` bytes
+fn take_by_delimiter(
     buf: &mut Vec<u8>,
-    delim: u8,
-    event_tx: &mpsc::SyncSender<SerialEvent>,
+    delims: &[u8],
+    encoding: &'static Encoding,
+    lines: &mut Vec<(String, Vec<u8>)>,
 ) {
-    loop {
-        if let Some(pos) = buf.iter().position(|&b| b == delim) {
-            let line_bytes = buf.drain(..=pos).collect::<Vec<u8>>();
-            let line = String::from_utf8_lossy(&line_bytes)
-                .trim_end_matches(|c: char| c == '\r' || c == '\n')
-                .to_string();
-            if !line.is_empty() {
-                let _ = event_tx.send(SerialEvent::Data(line));
-            }
-        } else {
-            break;
+    while let Some(mut pos) = buf.iter().position(|b| delims.contains(b)) {
+        if delims == b"\r\n" && buf[pos] == b'\r' && buf.get(pos + 1) == Some(&b'\n') {
+            pos += 1;
         }
+        let line_bytes = buf.drain(..=pos).collect::<Vec<u8>>();
+        push_line(line_bytes, encoding, lines);
     }
 }
 
-fn extract_by_crlf(buf: &mut Vec<u8>, event_tx: &mpsc::SyncSender<SerialEvent>) {
-    loop {
-        if let Some(pos) = buf.windows(2).position(|w| w == b"\r\n") {
-            let line_bytes: Vec<u8> = buf.drain(..pos + 2).collect();
-            let line = String::from_utf8_lossy(&line_bytes)
-                .trim_end_matches(|c: char| c == '\r' || c == '\n')
-                .to_string();
-            if !line.is_empty() {
-                let _ = event_tx.send(SerialEvent::Data(line));
-            }
-        } else {
-            break;
+fn take_by_crlf(
+    buf: &mut Vec<u8>,
+    encoding: &'static Encoding,
+    lines: &mut Vec<(String, Vec<u8>)>,
+) {
+    while let Some(pos) = buf.windows(2).position(|w| w == b"\r\n") {
+        let line_bytes: Vec<u8> = buf.drain(..pos + 2).collect();
+        push_line(line_bytes, encoding, lines);
+    }
+}
+
+/// Adds a line without its ending; blank lines are dropped
+fn push_line(line_bytes: Vec<u8>, encoding: &'static Encoding, lines: &mut Vec<(String, Vec<u8>)>) {
+    let line = decode(&line_bytes, encoding)
+        .trim_end_matches(['\r', '\n'])
+        .to_string();
+    if !line.is_empty() {
+        lines.push((line, line_bytes));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn auto_config() -> RxConfig {
+        RxConfig {
+            line_ending: LineEnding::Auto,
+            encoding: UTF_8,
+            framer: None,
+            detected: None,
+        }
+    }
+
+    /// Lines split from `input` added to `buf`
+    fn split(config: &mut RxConfig, buf: &mut Vec<u8>, input: &[u8]) -> Vec<String> {
+        let (event_tx, event_rx) = mpsc::sync_channel(64);
+        buf.extend_from_slice(input);
+        config.split(buf, &event_tx, &IoCounters::default());
+        event_rx
+            .try_iter()
+            .filter_map(|e| match e {
+                SerialEvent::Data(line) => Some(line),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn complete_utf8_len_holds_back_cut_off_characters() {
+        assert_eq!(complete_utf8_len(b""), 0);
+        assert_eq!(complete_utf8_len(b"ab"), 2);
+        // é, € and 😀 cut after each of their bytes
+        assert_eq!(complete_utf8_len(&[b'a', 0xC3]), 1);
+        assert_eq!(complete_utf8_len(&[b'a', 0xC3, 0xA9]), 3);
+        assert_eq!(complete_utf8_len(&[0xE2]), 0);
+        assert_eq!(complete_utf8_len(&[0xE2, 0x82]), 0);
+        assert_eq!(complete_utf8_len(&[0xE2, 0x82, 0xAC]), 3);
+        assert_eq!(complete_utf8_len(&[b'a', 0xF0, 0x9F, 0x98]), 1);
+        assert_eq!(complete_utf8_len(&[b'a', 0xF0, 0x9F, 0x98, 0x80]), 5);
+        // Invalid bytes aren't waited for
+        assert_eq!(complete_utf8_len(&[b'a', 0xFF]), 2);
+        assert_eq!(complete_utf8_len(&[0x80, 0x80, 0x80]), 3);
+    }
+
+    #[test]
+    fn characters_split_across_reads_stay_intact() {
+        let mut buf = Vec::new();
+        let mut lines = Vec::new();
+        for read in [
+            &b"1\xE2"[..],
+            &[0x82],
+            &[0xAC, b'2', 0xF0, 0x9F],
+            &[0x98, 0x80],
+        ] {
+            buf.extend_from_slice(read);
+            lines.extend(take_lines(&mut buf, &LineEnding::None, UTF_8));
         }
+        assert_eq!(lines, ["1", "€2", "😀"]);
+        assert!(buf.is_empty());
+    }
+
+    #[test]
+    fn detects_the_most_used_ending() {
+        assert_eq!(detect_line_ending(b"a\r\nb\r\n"), Some(LineEnding::CrLf));
+        assert_eq!(detect_line_ending(b"a\nb\nc\r\n"), Some(LineEnding::LF));
+        assert_eq!(detect_line_ending(b"a\rb\r"), Some(LineEnding::CR));
+        // Ties go to CRLF
+        assert_eq!(detect_line_ending(b"a\nb\r\n"), Some(LineEnding::CrLf));
+        // A trailing CR may be half a CRLF
+        assert_eq!(detect_line_ending(b"abc\r"), None);
+        assert_eq!(detect_line_ending(b"abc"), None);
+    }
+
+    #[test]
+    fn auto_splits_at_the_detected_ending() {
+        let mut config = auto_config();
+        let mut buf = Vec::new();
+        assert_eq!(
+            split(&mut config, &mut buf, b"one\r\ntwo\r\nthr"),
+            ["one", "two"]
+        );
+        assert_eq!(config.detected, Some(LineEnding::CrLf));
+        // A lone CR no longer ends a line
+        assert_eq!(
+            split(&mut config, &mut buf, b"ee\rfour\r\n"),
+            ["three\rfour"]
+        );
+        assert!(buf.is_empty());
+    }
+
+    #[test]
+    fn splits_at_any_delimiter() {
+        let mut buf = b"a\r\nb\rc\n\r\nd".to_vec();
+        let mut lines = Vec::new();
+        take_by_delimiter(&mut buf, b"\r\n", UTF_8, &mut lines);
+        let text: Vec<&str> = lines.iter().map(|(line, _)| line.as_str()).collect();
+        assert_eq!(text, ["a", "b", "c"]);
+        assert_eq!(lines[0].1, b"a\r\n");
+        assert_eq!(buf, b"d");
+
+        let mut buf = b"x\ny\n".to_vec();
+        let mut lines = Vec::new();
+        take_by_delimiter(&mut buf, b"\r", UTF_8, &mut lines);
+        assert!(lines.is_empty());
     }
 }
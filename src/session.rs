@@ -0,0 +1,211 @@
+use crate::framing::to_hex;
+use crate::serial_port::{list_ports, Direction, PortEntry, SerialEvent, SerialPortManager};
+use crate::settings::{port_picker, ByteTotal, ConnectionKind, Settings, BAUD_RATES};
+use crate::timestamp::Timestamp;
+
+/// A received line with its host arrival time
+#[derive(Clone)]
+pub struct TimedLine {
+    pub time: Timestamp,
+    pub text: String,
+    /// Direction of sniffed traffic and of sent data echoed into the log
+    pub dir: Option<Direction>,
+}
+
+/// Second device session shown alongside the main one in the merged timeline
+pub struct SecondarySession {
+    pub settings: Settings,
+    pub serial: SerialPortManager,
+    pub lines: Vec<TimedLine>,
+    /// Size of `lines` for the size retention policy
+    line_bytes: ByteTotal,
+    pub status: String,
+    pub is_open: bool,
+    available_ports: Vec<PortEntry>,
+}
+
+impl SecondarySession {
+    pub fn new(settings: &Settings) -> Self {
+        Self {
+            settings: Settings {
+                connection: ConnectionKind::Serial,
+                port_name: String::new(),
+                ..settings.clone()
+            },
+            serial: SerialPortManager::new(),
+            lines: Vec::new(),
+            line_bytes: ByteTotal::default(),
+            status: "Disconnected".to_string(),
+            is_open: false,
+            available_ports: Vec::new(),
+        }
+    }
+
+    pub fn open(&mut self) {
+        self.is_open = true;
+        self.refresh_ports();
+    }
+
+    fn refresh_ports(&mut self) {
+        self.available_ports = list_ports();
+    }
+
+    pub fn connect(&mut self) {
+        match self.serial.connect(&self.settings) {
+            Ok(()) => self.status = "Connecting...".to_string(),
+            Err(e) => self.status = e,
+        }
+    }
+
+    pub fn disconnect(&mut self) {
+        self.serial.disconnect();
+        self.status = "Disconnected".to_string();
+    }
+
+    /// Drains serial events into the line buffer, trimming it by the
+    /// retention policy of `retention`
+    pub fn poll(&mut self, retention: &Settings) {
+        for ev in self.serial.poll_events() {
+            match ev {
+                SerialEvent::Connected => {
+                    self.serial.is_connected = true;
+                    self.status = format!("Connected to {}", self.settings.target_label());
+                }
+                SerialEvent::Disconnected => {
+                    self.serial.is_connected = false;
+                    self.status = "Disconnected".to_string();
+                }
+                SerialEvent::Data(text) => {
+                    self.lines.push(TimedLine {
+                        time: Timestamp::now(),
+                        text,
+                        dir: None,
+                    });
+                }
+                SerialEvent::Traffic(dir, text) => {
+                    self.lines.push(TimedLine {
+                        time: Timestamp::now(),
+                        text,
+                        dir: Some(dir),
+                    });
+                }
+                SerialEvent::Error(e) => {
+                    self.serial.is_connected = false;
+                    self.status = e;
+                }
+                SerialEvent::Warning(w) | SerialEvent::LineError(w) => {
+                    self.status = w;
+                }
+                SerialEvent::Frame(frame) => {
+                    self.lines.push(TimedLine {
+                        time: Timestamp::now(),
+                        text: to_hex(&frame),
+                        dir: None,
+                    });
+                }
+                SerialEvent::LineEndingDetected(le) => {
+                    self.status = format!("RX line ending detected: {}", le.label());
+                }
+                SerialEvent::ControlLine(..)
+                | SerialEvent::RawBytes(_)
+                | SerialEvent::LineBytes(_) => {}
+            }
+        }
+        let size = |l: &TimedLine| l.text.len();
+        let drain =
+            retention.retention_excess(&self.lines, &self.line_bytes, |l| l.time.wall, size);
+        self.line_bytes.remove(&self.lines[..drain], size);
+        self.lines.drain(..drain);
+    }
+
+    /// Renders the session window
+    pub fn show(&mut self, ctx: &egui::Context) {
+        if !self.is_open {
+            return;
+        }
+        let mut open = self.is_open;
+
+        egui::Window::new("Session B")
+            .open(&mut open)
+            .resizable(false)
+            .collapsible(false)
+            .default_width(320.0)
+            .show(ctx, |ui| {
+                let connected = self.serial.is_connected;
+                egui::Grid::new("session_b_grid")
+                    .num_columns(2)
+                    .spacing([8.0, 6.0])
+                    .show(ui, |ui| {
+                        ui.label("Type:");
+                        ui.add_enabled_ui(!connected, |ui| {
+                            egui::ComboBox::from_id_salt("session_b_kind")
+                                .selected_text(self.settings.connection.label())
+                                .width(140.0)
+                                .show_ui(ui, |ui| {
+                                    for kind in ConnectionKind::all() {
+                                        ui.selectable_value(
+                                            &mut self.settings.connection,
+                                            kind.clone(),
+                                            kind.label(),
+                                        );
+                                    }
+                                });
+                        });
+                        ui.end_row();
+
+                        ui.label("Port:");
+                        ui.add_enabled_ui(!connected, |ui| {
+                            ui.horizontal(|ui| {
+                                port_picker(
+                                    ui,
+                                    &mut self.settings.port_name,
+                                    &self.available_ports,
+                                );
+                                if ui.button("Refresh").clicked() {
+                                    self.refresh_ports();
+                                }
+                            });
+                        });
+                        ui.end_row();
+
+                        ui.label("Baud Rate:");
+                        ui.add_enabled_ui(!connected, |ui| {
+                            egui::ComboBox::from_id_salt("session_b_baud")
+                                .selected_text(self.settings.baud_rate.to_string())
+                                .width(140.0)
+                                .show_ui(ui, |ui| {
+                                    for &baud in BAUD_RATES {
+                                        ui.selectable_value(
+                                            &mut self.settings.baud_rate,
+                                            baud,
+                                            baud.to_string(),
+                                        );
+                                    }
+                                });
+                        });
+                        ui.end_row();
+                    });
+
+                ui.separator();
+                ui.horizontal(|ui| {
+                    if connected {
+                        if ui.button("Disconnect").clicked() {
+                            self.disconnect();
+                        }
+                    } else if ui
+                        .add_enabled(self.settings.has_target(), egui::Button::new("Connect"))
+                        .clicked()
+                    {
+                        self.connect();
+                    }
+                    if ui.button("Clear").clicked() {
+                        self.lines.clear();
+                    }
+                });
+                ui.label(&self.status);
+                ui.label(format!("Lines: {}", self.lines.len()));
+            });
+
+        self.is_open = open;
+    }
+}
@@ -0,0 +1,250 @@
+use std::io::Write;
+use std::path::PathBuf;
+
+use chrono::{DateTime, Local};
+
+/// Statistics of one connection, from connect to disconnect
+#[derive(Clone)]
+pub struct SessionSummary {
+    pub target: String,
+    pub started: DateTime<Local>,
+    pub ended: Option<DateTime<Local>>,
+    pub rx_bytes: u64,
+    pub tx_bytes: u64,
+    pub lines: u64,
+    pub matched: u64,
+    pub unmatched: u64,
+    /// UART framing/parity errors and undecodable frames
+    pub line_errors: u64,
+    /// Warnings and errors shown in the status bar
+    pub warnings: u64,
+    /// Frames missing according to the sequence column
+    pub dropped: u64,
+    pub triggers: u64,
+    /// Times a dashboard widget went into alarm
+    pub alarms: u64,
+}
+
+impl SessionSummary {
+    fn new(target: String) -> Self {
+        Self {
+            target,
+            started: Local::now(),
+            ended: None,
+            rx_bytes: 0,
+            tx_bytes: 0,
+            lines: 0,
+            matched: 0,
+            unmatched: 0,
+            line_errors: 0,
+            warnings: 0,
+            dropped: 0,
+            triggers: 0,
+            alarms: 0,
+        }
+    }
+
+    pub fn duration(&self) -> chrono::Duration {
+        self.ended.unwrap_or_else(Local::now) - self.started
+    }
+
+    /// Percentage of parsed lines the parser matched
+    pub fn match_rate(&self) -> Option<f64> {
+        let total = self.matched + self.unmatched;
+        (total > 0).then(|| self.matched as f64 * 100.0 / total as f64)
+    }
+
+    /// Label/value pairs in display order
+    pub fn fields(&self) -> Vec<(&'static str, String)> {
+        let secs = self.duration().num_seconds().max(0);
+        let mut fields = vec![
+            ("Target", self.target.clone()),
+            (
+                "Started",
+                self.started.format("%Y-%m-%d %H:%M:%S").to_string(),
+            ),
+            (
+                "Duration",
+                format!("{:02}:{:02}:{:02}", secs / 3600, secs / 60 % 60, secs % 60),
+            ),
+            ("Bytes RX", self.rx_bytes.to_string()),
+            ("Bytes TX", self.tx_bytes.to_string()),
+            ("Lines", self.lines.to_string()),
+        ];
+        if let Some(rate) = self.match_rate() {
+            fields.push((
+                "Match rate",
+                format!("{:.1}% ({} unmatched)", rate, self.unmatched),
+            ));
+        }
+        fields.extend([
+            ("Line errors", self.line_errors.to_string()),
+            ("Warnings", self.warnings.to_string()),
+            ("Dropped frames", self.dropped.to_string()),
+            ("Triggers fired", self.triggers.to_string()),
+            ("Dashboard alarms", self.alarms.to_string()),
+        ]);
+        fields
+    }
+
+    /// Plain text report, one field per line
+    pub fn to_text(&self) -> String {
+        self.fields()
+            .iter()
+            .map(|(label, value)| format!("{}: {}\n", label, value))
+            .collect()
+    }
+
+    /// Appends the report to the session archive next to the executable
+    pub fn append_to_archive(&self) -> std::io::Result<PathBuf> {
+        let path = archive_path();
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)?;
+        writeln!(file, "=== Session ===")?;
+        write!(file, "{}", self.to_text())?;
+        writeln!(file)?;
+        Ok(path)
+    }
+}
+
+fn archive_path() -> PathBuf {
+    let mut path = std::env::current_exe().unwrap_or_default();
+    path.pop();
+    path.push("uart_console_sessions.log");
+    path
+}
+
+/// Collects statistics while connected. Byte and line counts are taken as
+/// differences of the manager's counters, which the user may reset.
+#[derive(Default)]
+pub struct SessionRecorder {
+    current: Option<SessionSummary>,
+    /// Counter values (RX bytes, TX bytes, lines) when the session started
+    base: (u64, u64, u64),
+    /// Alarm state of each dashboard widget, to count alarm onsets
+    in_alarm: Vec<bool>,
+}
+
+impl SessionRecorder {
+    pub fn is_active(&self) -> bool {
+        self.current.is_some()
+    }
+
+    pub fn start(&mut self, target: String, counters: (u64, u64, u64)) {
+        self.current = Some(SessionSummary::new(target));
+        self.base = counters;
+        self.in_alarm.clear();
+    }
+
+    /// Ends the session and returns its summary
+    pub fn finish(&mut self, counters: (u64, u64, u64)) -> Option<SessionSummary> {
+        let mut summary = self.current.take()?;
+        summary.ended = Some(Local::now());
+        summary.rx_bytes = counters.0.saturating_sub(self.base.0);
+        summary.tx_bytes = counters.1.saturating_sub(self.base.1);
+        summary.lines = counters.2.saturating_sub(self.base.2);
+        Some(summary)
+    }
+
+    /// The running session's counters, for updating in place
+    pub fn current(&mut self) -> Option<&mut SessionSummary> {
+        self.current.as_mut()
+    }
+
+    /// Counts widgets whose alarm state went from off to on
+    pub fn record_alarms(&mut self, alarms: impl Iterator<Item = bool>) {
+        let Some(summary) = &mut self.current else {
+            return;
+        };
+        for (i, alarm) in alarms.enumerate() {
+            if i >= self.in_alarm.len() {
+                self.in_alarm.push(false);
+            }
+            if alarm && !self.in_alarm[i] {
+                summary.alarms += 1;
+            }
+            self.in_alarm[i] = alarm;
+        }
+    }
+}
+
+/// Window with the summary of the last session
+pub struct SessionSummaryWindow {
+    pub is_open: bool,
+    pub summary: Option<SessionSummary>,
+    /// Result of the last archive write
+    archive_msg: Option<String>,
+}
+
+impl SessionSummaryWindow {
+    pub fn new() -> Self {
+        Self {
+            is_open: false,
+            summary: None,
+            archive_msg: None,
+        }
+    }
+
+    /// Shows `summary`, appending it to the archive first if `archive` is set
+    pub fn open(&mut self, summary: SessionSummary, archive: bool) {
+        self.archive_msg = archive.then(|| archive_result(&summary));
+        self.summary = Some(summary);
+        self.is_open = true;
+    }
+
+    pub fn show(&mut self, ctx: &egui::Context) {
+        if !self.is_open {
+            return;
+        }
+        let Some(summary) = &self.summary else {
+            return;
+        };
+        let mut open = self.is_open;
+        let mut archive = false;
+        egui::Window::new("Session Summary")
+            .open(&mut open)
+            .resizable(false)
+            .show(ctx, |ui| {
+                egui::Grid::new("session_summary_grid")
+                    .num_columns(2)
+                    .spacing([16.0, 4.0])
+                    .striped(true)
+                    .show(ui, |ui| {
+                        for (label, value) in summary.fields() {
+                            ui.strong(label);
+                            ui.label(value);
+                            ui.end_row();
+                        }
+                    });
+                ui.add_space(6.0);
+                ui.horizontal(|ui| {
+                    if ui.button("Copy").clicked() {
+                        ui.ctx().copy_text(summary.to_text());
+                    }
+                    if ui
+                        .add_enabled(self.archive_msg.is_none(), egui::Button::new("Archive"))
+                        .on_hover_text("Append to uart_console_sessions.log")
+                        .clicked()
+                    {
+                        archive = true;
+                    }
+                });
+                if let Some(msg) = &self.archive_msg {
+                    ui.label(msg);
+                }
+            });
+        if archive {
+            self.archive_msg = Some(archive_result(summary));
+        }
+        self.is_open = open;
+    }
+}
+
+fn archive_result(summary: &SessionSummary) -> String {
+    match summary.append_to_archive() {
+        Ok(path) => format!("Appended to {}", path.display()),
+        Err(e) => format!("Archive failed: {}", e),
+    }
+}
@@ -1,5 +1,62 @@
+use std::cell::Cell;
+
+use chrono::{DateTime, Local};
+use encoding_rs::{EncoderResult, Encoding};
 use serde::{Deserialize, Serialize};
 
+use crate::alerts::{alert_editor, AlertRule};
+use crate::checksum::{checksum_editor, Checksum};
+use crate::column_layout::ColumnLayout;
+use crate::column_types::ColumnType;
+use crate::dashboard::DashboardWidget;
+use crate::derived::DerivedColumns;
+use crate::driver_quirks::{quirks_editor, PinState};
+use crate::export::{template_editor, ExportTemplate};
+use crate::frame_fields::frame_pattern_editor;
+use crate::framing::Framer;
+use crate::histogram::HistogramConfig;
+use crate::line_script::script_editor;
+use crate::log_level::level_editor;
+use crate::macros::{macro_editor, TxMacro};
+use crate::parser::{KeyedColumns, LineParser};
+use crate::plot::PlotConfig;
+use crate::records::{record_editor, RecordMode};
+use crate::regex_guard;
+use crate::routing::{route_editor, Route};
+use crate::serial_port::{list_ports, PortEntry};
+use crate::simulator::SimMode;
+use crate::symbols::symbol_editor;
+use crate::theme::{theme_editor, Colors, Theme};
+use crate::timestamp::{DeviceTimeFormat, TimeSource};
+use crate::tlv::{tlv_editor, TlvLabel};
+use crate::tx_map::{char_map_editor, CharMapping};
+use crate::units::{scale_editor, ColumnScale};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ConnectionKind {
+    Serial,
+    Simulator,
+    /// Host port and device port bridged, traffic shown in both directions
+    Sniffer,
+}
+
+impl ConnectionKind {
+    pub fn label(&self) -> &'static str {
+        match self {
+            ConnectionKind::Serial => "Serial port",
+            ConnectionKind::Simulator => "Demo / Simulator",
+            ConnectionKind::Sniffer => "Sniffer (two ports)",
+        }
+    }
+    pub fn all() -> &'static [ConnectionKind] {
+        &[
+            ConnectionKind::Serial,
+            ConnectionKind::Simulator,
+            ConnectionKind::Sniffer,
+        ]
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum AppDataBits {
     Five,
@@ -110,6 +167,9 @@ impl AppFlowControl {
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum LineEnding {
+    /// RX only: lines end at CR, LF or CRLF, whichever the device sends most
+    /// in its first lines
+    Auto,
     None,
     CR,
     LF,
@@ -119,7 +179,7 @@ pub enum LineEnding {
 impl LineEnding {
     pub fn as_bytes(&self) -> &'static [u8] {
         match self {
-            LineEnding::None => b"",
+            LineEnding::Auto | LineEnding::None => b"",
             LineEnding::CR => b"\r",
             LineEnding::LF => b"\n",
             LineEnding::CrLf => b"\r\n",
@@ -127,15 +187,393 @@ impl LineEnding {
     }
     pub fn label(&self) -> &'static str {
         match self {
+            LineEnding::Auto => "Auto",
             LineEnding::None => "None",
             LineEnding::CR => "CR (\\r)",
             LineEnding::LF => "LF (\\n)",
             LineEnding::CrLf => "CRLF (\\r\\n)",
         }
     }
+    /// Endings to send; `Auto` only applies to received data
     pub fn all() -> &'static [LineEnding] {
         &[LineEnding::None, LineEnding::CR, LineEnding::LF, LineEnding::CrLf]
     }
+    pub fn rx_all() -> &'static [LineEnding] {
+        &[
+            LineEnding::Auto,
+            LineEnding::None,
+            LineEnding::CR,
+            LineEnding::LF,
+            LineEnding::CrLf,
+        ]
+    }
+}
+
+/// Character set of the text exchanged with the device
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum TextEncoding {
+    Utf8,
+    Windows1252,
+    Windows1251,
+    Koi8R,
+    ShiftJis,
+    Gbk,
+}
+
+impl TextEncoding {
+    pub fn label(&self) -> &'static str {
+        match self {
+            TextEncoding::Utf8 => "UTF-8",
+            TextEncoding::Windows1252 => "Latin-1 / CP1252",
+            TextEncoding::Windows1251 => "Cyrillic / CP1251",
+            TextEncoding::Koi8R => "KOI8-R",
+            TextEncoding::ShiftJis => "Shift-JIS",
+            TextEncoding::Gbk => "GBK",
+        }
+    }
+    pub fn all() -> &'static [TextEncoding] {
+        &[
+            TextEncoding::Utf8,
+            TextEncoding::Windows1252,
+            TextEncoding::Windows1251,
+            TextEncoding::Koi8R,
+            TextEncoding::ShiftJis,
+            TextEncoding::Gbk,
+        ]
+    }
+    pub fn encoding(&self) -> &'static Encoding {
+        match self {
+            TextEncoding::Utf8 => encoding_rs::UTF_8,
+            TextEncoding::Windows1252 => encoding_rs::WINDOWS_1252,
+            TextEncoding::Windows1251 => encoding_rs::WINDOWS_1251,
+            TextEncoding::Koi8R => encoding_rs::KOI8_R,
+            TextEncoding::ShiftJis => encoding_rs::SHIFT_JIS,
+            TextEncoding::Gbk => encoding_rs::GBK,
+        }
+    }
+
+    /// `text` in this encoding; characters it can't represent are sent as '?'
+    pub fn encode(&self, text: &str) -> Vec<u8> {
+        let mut encoder = self.encoding().new_encoder();
+        let mut data = Vec::with_capacity(text.len());
+        let mut rest = text;
+        loop {
+            let (result, read) =
+                encoder.encode_from_utf8_to_vec_without_replacement(rest, &mut data, true);
+            rest = &rest[read..];
+            match result {
+                EncoderResult::InputEmpty => return data,
+                EncoderResult::OutputFull => data.reserve(rest.len() + 16),
+                EncoderResult::Unmappable(_) => data.push(b'?'),
+            }
+        }
+    }
+}
+
+/// What limits how much received data is kept in memory
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum RetentionPolicy {
+    Lines,
+    Time,
+    Size,
+}
+
+impl RetentionPolicy {
+    pub fn label(&self) -> &'static str {
+        match self {
+            RetentionPolicy::Lines => "Last N lines",
+            RetentionPolicy::Time => "Last N minutes",
+            RetentionPolicy::Size => "Last N megabytes",
+        }
+    }
+    pub fn all() -> &'static [RetentionPolicy] {
+        &[
+            RetentionPolicy::Lines,
+            RetentionPolicy::Time,
+            RetentionPolicy::Size,
+        ]
+    }
+}
+
+/// Running byte total of a buffer, so the size retention policy doesn't
+/// measure every entry each frame. Entries appended since the last call are
+/// measured on the next one; `remove` takes off the entries dropped from the
+/// front. Reset it when the buffer is replaced.
+#[derive(Default)]
+pub struct ByteTotal {
+    /// Entries measured so far
+    counted: Cell<usize>,
+    bytes: Cell<usize>,
+}
+
+impl ByteTotal {
+    /// Total size of `items`, the same buffer as on the previous calls
+    pub fn total<T>(&self, items: &[T], size: impl Fn(&T) -> usize) -> usize {
+        if items.len() < self.counted.get() {
+            // Cleared since the last call
+            self.reset();
+        }
+        let added: usize = items[self.counted.get()..].iter().map(size).sum();
+        self.counted.set(items.len());
+        self.bytes.set(self.bytes.get() + added);
+        self.bytes.get()
+    }
+
+    /// Takes `dropped`, the entries about to be drained from the front of
+    /// the buffer, off the total
+    pub fn remove<T>(&self, dropped: &[T], size: impl Fn(&T) -> usize) {
+        let counted = self.counted.get();
+        if dropped.len() > counted {
+            // Not all of them were measured
+            self.reset();
+            return;
+        }
+        let removed: usize = dropped.iter().map(size).sum();
+        self.bytes.set(self.bytes.get() - removed);
+        self.counted.set(counted - dropped.len());
+    }
+
+    pub fn reset(&self) {
+        self.counted.set(0);
+        self.bytes.set(0);
+    }
+}
+
+/// How received lines are split into columns
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub enum ParserMode {
+    #[default]
+    Regex,
+    /// One JSON object per line, columns from the keys
+    Json,
+    /// `key=value` pairs in any order, columns from the keys
+    KeyValue,
+    /// Fields separated by a delimiter character (plain CSV)
+    Delimiter,
+    /// NMEA 0183 sentences from GPS receivers
+    Nmea,
+}
+
+impl ParserMode {
+    pub fn label(&self) -> &'static str {
+        match self {
+            ParserMode::Regex => "Regex",
+            ParserMode::Json => "JSON Lines",
+            ParserMode::KeyValue => "key=value",
+            ParserMode::Delimiter => "Split by delimiter",
+            ParserMode::Nmea => "NMEA 0183",
+        }
+    }
+    pub fn all() -> &'static [ParserMode] {
+        &[
+            ParserMode::Regex,
+            ParserMode::Json,
+            ParserMode::KeyValue,
+            ParserMode::Delimiter,
+            ParserMode::Nmea,
+        ]
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub enum Delimiter {
+    #[default]
+    Comma,
+    Semicolon,
+    Tab,
+    /// `custom_delimiter`
+    Custom,
+}
+
+impl Delimiter {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Delimiter::Comma => "Comma ,",
+            Delimiter::Semicolon => "Semicolon ;",
+            Delimiter::Tab => "Tab",
+            Delimiter::Custom => "Custom",
+        }
+    }
+    pub fn all() -> &'static [Delimiter] {
+        &[Delimiter::Comma, Delimiter::Semicolon, Delimiter::Tab, Delimiter::Custom]
+    }
+}
+
+/// A saved parser configuration
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ParserProfile {
+    pub name: String,
+    pub parser_mode: ParserMode,
+    pub delimiter: Delimiter,
+    pub custom_delimiter: String,
+    pub regex_pattern: String,
+    pub column_names: String,
+    pub column_types: String,
+    pub column_scales: Vec<ColumnScale>,
+    pub column_layout: ColumnLayout,
+    pub derived_columns: String,
+    pub tx_pattern: String,
+    pub tx_char_map: Vec<CharMapping>,
+    pub macros: Vec<TxMacro>,
+}
+
+/// How the RX thread splits received data
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub enum RxFraming {
+    #[default]
+    Lines,
+    /// Header, length field, payload, trailer
+    LengthPrefixed,
+    /// RFC 1055 SLIP
+    Slip,
+    /// Consistent Overhead Byte Stuffing, 0x00-delimited
+    Cobs,
+    /// Frames delimited by 3.5 characters of silence, with CRC-16/MODBUS
+    ModbusRtu,
+    /// defmt log frames, decoded with the firmware ELF
+    Defmt,
+    /// Varint length-delimited protobuf messages, decoded with a schema
+    Protobuf,
+    /// Tag, length, value records
+    Tlv,
+}
+
+impl RxFraming {
+    pub fn label(&self) -> &'static str {
+        match self {
+            RxFraming::Lines => "Text lines",
+            RxFraming::LengthPrefixed => "Length-prefixed frames",
+            RxFraming::Slip => "SLIP frames",
+            RxFraming::Cobs => "COBS frames",
+            RxFraming::ModbusRtu => "Modbus RTU",
+            RxFraming::Defmt => "defmt log frames",
+            RxFraming::Protobuf => "Protobuf messages",
+            RxFraming::Tlv => "TLV records",
+        }
+    }
+    pub fn all() -> &'static [RxFraming] {
+        &[
+            RxFraming::Lines,
+            RxFraming::LengthPrefixed,
+            RxFraming::Slip,
+            RxFraming::Cobs,
+            RxFraming::ModbusRtu,
+            RxFraming::Defmt,
+            RxFraming::Protobuf,
+            RxFraming::Tlv,
+        ]
+    }
+}
+
+/// How the window asks for attention when a watched event happens
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub enum WindowAlert {
+    #[default]
+    Off,
+    /// Flash the taskbar entry / bounce the dock icon
+    Flash,
+    /// Raise and focus the window
+    Raise,
+}
+
+impl WindowAlert {
+    pub fn label(&self) -> &'static str {
+        match self {
+            WindowAlert::Off => "Off",
+            WindowAlert::Flash => "Flash window",
+            WindowAlert::Raise => "Bring to front",
+        }
+    }
+    pub fn all() -> &'static [WindowAlert] {
+        &[WindowAlert::Off, WindowAlert::Flash, WindowAlert::Raise]
+    }
+}
+
+/// Which of the table and raw log the data area shows
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub enum ViewLayout {
+    #[default]
+    Table,
+    Raw,
+    /// Table on the left, raw log on the right
+    SideBySide,
+    /// Table above, raw log below
+    Stacked,
+}
+
+impl ViewLayout {
+    pub fn label(&self) -> &'static str {
+        match self {
+            ViewLayout::Table => "Table",
+            ViewLayout::Raw => "Raw log",
+            ViewLayout::SideBySide => "Split ◫",
+            ViewLayout::Stacked => "Split ⊟",
+        }
+    }
+    pub fn all() -> &'static [ViewLayout] {
+        &[
+            ViewLayout::Table,
+            ViewLayout::Raw,
+            ViewLayout::SideBySide,
+            ViewLayout::Stacked,
+        ]
+    }
+
+    pub fn is_split(&self) -> bool {
+        matches!(self, ViewLayout::SideBySide | ViewLayout::Stacked)
+    }
+}
+
+/// How tightly the table rows are packed
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub enum Density {
+    #[default]
+    Comfortable,
+    /// Smaller text and no gap between rows
+    Compact,
+}
+
+impl Density {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Density::Comfortable => "Comfortable",
+            Density::Compact => "Compact",
+        }
+    }
+    pub fn all() -> &'static [Density] {
+        &[Density::Comfortable, Density::Compact]
+    }
+
+    /// Height of a row with the text scaled by `zoom`
+    pub fn row_height(&self, zoom: f32) -> f32 {
+        zoom * match self {
+            Density::Comfortable => 18.0,
+            Density::Compact => 13.0,
+        }
+    }
+
+    pub fn header_height(&self, zoom: f32) -> f32 {
+        zoom * match self {
+            Density::Comfortable => 22.0,
+            Density::Compact => 16.0,
+        }
+    }
+
+    /// Sets the text size and spacing of a table's ui, compact rows getting
+    /// smaller text and no gaps, and scales the text by `zoom`
+    pub fn apply(&self, ui: &mut egui::Ui, zoom: f32) {
+        let compact = *self == Density::Compact;
+        let style = ui.style_mut();
+        if compact {
+            style.spacing.item_spacing = egui::vec2(6.0, 0.0);
+        }
+        for text_style in [egui::TextStyle::Body, egui::TextStyle::Monospace] {
+            if let Some(font) = style.text_styles.get_mut(&text_style) {
+                font.size = if compact { 10.5 } else { font.size } * zoom;
+            }
+        }
+    }
 }
 
 pub const BAUD_RATES: &[u32] = &[
@@ -143,36 +581,315 @@ pub const BAUD_RATES: &[u32] = &[
 ];
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct Settings {
+    pub connection: ConnectionKind,
+    pub sim_mode: SimMode,
+    /// Simulated lines per second
+    pub sim_rate_hz: f64,
     pub port_name: String,
+    /// Device side port in sniffer mode (`port_name` is the host side)
+    pub sniffer_port: String,
     pub baud_rate: u32,
     pub data_bits: AppDataBits,
     pub stop_bits: AppStopBits,
     pub parity: AppParity,
     pub flow_control: AppFlowControl,
+    pub parser_mode: ParserMode,
+    pub delimiter: Delimiter,
+    /// Delimiter character with `Delimiter::Custom`
+    pub custom_delimiter: String,
     pub regex_pattern: String,
     pub column_names: String,
+    /// Comma-separated int/float/hex/text type of each column (empty = text)
+    pub column_types: String,
+    /// Scale, offset and unit of columns sent as raw numbers
+    pub column_scales: Vec<ColumnScale>,
+    /// Order and visibility of the table columns
+    pub column_layout: ColumnLayout,
+    /// `name = expression` lines computing extra columns from parsed ones
+    pub derived_columns: String,
+    /// Patterns whose matches are also shown in a table of their own
+    pub routes: Vec<Route>,
+    /// Regex the send bar input is checked against (empty = off)
+    pub tx_pattern: String,
+    /// ELF or linker map whose symbols replace addresses in received lines
+    /// (empty = off)
+    pub symbol_file: String,
+    /// Follow ESP-IDF "Backtrace:" lines with their resolved frames
+    pub expand_backtraces: bool,
+    /// How received lines are joined into multi-line records
+    pub record_mode: RecordMode,
+    /// Regex of the start or end line of a record
+    pub record_marker: String,
+    /// Lines per record with `RecordMode::LineCount`
+    pub record_lines: usize,
+    /// Rhai script run on each received line before parsing (empty = off)
+    pub line_script: String,
+    /// Saved parser configurations; the active one is mirrored in the parser
+    /// mode, delimiter, `regex_pattern`, `column_names`, `column_types`,
+    /// `column_scales`, `column_layout`, `derived_columns`, `tx_pattern`,
+    /// `tx_char_map` and `macros`
+    pub profiles: Vec<ParserProfile>,
+    /// Name of the active profile (empty = none)
+    pub active_profile: String,
+    /// Column holding a frame sequence counter, checked for gaps
+    pub seq_column: Option<usize>,
+    /// Column holding the device's own timestamp
+    pub device_time_column: Option<usize>,
+    pub device_time_format: DeviceTimeFormat,
+    pub retention: RetentionPolicy,
+    /// Lines kept with `RetentionPolicy::Lines`
     pub max_rows: usize,
+    /// Minutes kept with `RetentionPolicy::Time`
+    pub retention_minutes: f64,
+    /// Megabytes of line text kept with `RetentionPolicy::Size`
+    pub retention_mb: f64,
     pub show_timestamp: bool,
+    /// Show each row's line number
+    pub show_line_numbers: bool,
+    /// Show the time since the previous row and since the reference row
+    pub show_delta: bool,
+    /// Leave rows the parser didn't match out of the table
+    pub hide_unmatched: bool,
+    pub density: Density,
+    /// Text size of the tables relative to the normal size, set with
+    /// Ctrl+scroll
+    pub table_zoom: f32,
+    /// Text size of the raw log relative to the normal size
+    pub raw_zoom: f32,
+    /// Prefix raw log lines with their receive time
+    pub raw_timestamps: bool,
+    /// Wrap long raw log lines instead of scrolling sideways
+    pub raw_wrap: bool,
+    pub view_layout: ViewLayout,
+    /// Width or height of the raw log in a split layout, in points
+    pub split_size: f32,
+    pub theme: Theme,
+    /// Custom colors of the table and raw log
+    pub colors: Colors,
+    /// Clock timestamps are shown and exported in
+    pub time_source: TimeSource,
+    /// Plain table rendering, no plots and a slower repaint for low-end machines
+    pub lightweight: bool,
+    /// Rows per window of this many ms with the mean, min and max (0 = off)
+    pub aggregate_ms: u64,
+    /// Show the session statistics window after disconnecting
+    pub session_summary: bool,
+    /// Append every session summary to the session archive
+    pub archive_sessions: bool,
+    /// Number of session captures kept on disk (0 = off)
+    pub keep_captures: usize,
+    /// Reopen the newest saved capture read-only at startup
+    pub restore_capture: bool,
+    /// Draw attention to the window on trigger hits and disconnects while
+    /// it is in the background
+    pub window_alert: WindowAlert,
+    pub alert_on_trigger: bool,
+    pub alert_on_disconnect: bool,
     pub rx_line_ending: LineEnding,
     pub tx_line_ending: LineEnding,
+    /// Show sent text and frames in the raw log and table as TX rows
+    pub echo_tx: bool,
+    /// Character set received lines are decoded from and sent text encoded to
+    pub text_encoding: TextEncoding,
+    /// Split received data into lines or binary frames
+    pub rx_framing: RxFraming,
+    /// Hex bytes every frame starts with, e.g. "AA 55"
+    pub frame_header: String,
+    /// Offset of the length field from the start of the frame
+    pub frame_length_offset: usize,
+    /// Size of the length field in bytes
+    pub frame_length_size: usize,
+    pub frame_big_endian: bool,
+    /// Bytes after the payload not counted by the length field (e.g. CRC)
+    pub frame_trailer: usize,
+    /// Checksum verified at the end of each frame
+    pub frame_crc: Checksum,
+    /// Byte regex whose capture groups become the columns of a frame
+    pub frame_pattern: String,
+    /// Firmware ELF holding the defmt format strings
+    pub defmt_elf: String,
+    /// `.proto` file or compiled descriptor set for protobuf framing
+    pub proto_schema: String,
+    /// Full name of the message type in each frame (empty = the file's first)
+    pub proto_message: String,
+    /// Size of the TLV tag field in bytes (1, 2 or 4)
+    pub tlv_tag_size: usize,
+    /// Size of the TLV length field in bytes (1, 2 or 4)
+    pub tlv_length_size: usize,
+    pub tlv_big_endian: bool,
+    /// Names shown for TLV tags
+    pub tlv_labels: Vec<TlvLabel>,
+    /// Checksum shown as a pass/fail column
+    pub checksum: Checksum,
+    /// Text after which a line's checksum coverage starts
+    pub checksum_start: String,
+    /// Text the hex checksum of a line follows
+    pub checksum_separator: String,
+    /// Leading frame bytes not covered by the checksum
+    pub checksum_skip: usize,
+    pub dashboard: Vec<DashboardWidget>,
+    pub plots: Vec<PlotConfig>,
+    pub histogram: HistogramConfig,
+    /// Regex that fires the capture trigger (empty = disabled)
+    pub trigger_pattern: String,
+    pub trigger_pause_scroll: bool,
+    pub trigger_insert_marker: bool,
+    /// Stop capturing `trigger_post_lines` lines after the trigger fired
+    pub trigger_stop_capture: bool,
+    pub trigger_post_lines: usize,
+    /// Conditions that highlight rows and can raise notifications
+    pub alert_rules: Vec<AlertRule>,
+    /// Find severity markers in received lines and color rows by them
+    pub log_levels: bool,
+    /// Regex whose first group holds the level (empty = built-in markers)
+    pub level_pattern: String,
+    /// Number of extra connect attempts when opening the port fails (0 = off)
+    pub connect_retries: u32,
+    /// Delay before the first retry; doubled on every attempt
+    pub retry_delay_ms: u64,
+    /// Shorter read timeout plus ASYNC_LOW_LATENCY / FTDI latency timer on Linux
+    pub low_latency: bool,
+    /// DTR level right after opening
+    pub open_dtr: PinState,
+    /// RTS level right after opening
+    pub open_rts: PinState,
+    /// Keep the TIOCEXCL lock serialport takes on Linux
+    pub exclusive_lock: bool,
+    /// Set non-standard baud rates through the Linux custom divisor
+    pub custom_divisor: bool,
+    /// RS-485 half-duplex: assert RTS while transmitting
+    pub rs485: bool,
+    pub rs485_pre_delay_us: u64,
+    pub rs485_post_delay_us: u64,
+    /// Pause between transmitted bytes
+    pub tx_char_delay_ms: u64,
+    /// Extra pause after each transmitted line
+    pub tx_line_delay_ms: u64,
+    pub macros: Vec<TxMacro>,
+    /// Identification commands, one per line
+    pub id_commands: String,
+    /// Send the identification commands when the connection comes up
+    pub id_on_connect: bool,
+    /// Substitutions applied to outgoing data
+    pub tx_char_map: Vec<CharMapping>,
+    pub export_templates: Vec<ExportTemplate>,
 }
 
 impl Default for Settings {
     fn default() -> Self {
         Self {
+            connection: ConnectionKind::Serial,
+            sim_mode: SimMode::Telemetry,
+            sim_rate_hz: 10.0,
             port_name: String::new(),
+            sniffer_port: String::new(),
             baud_rate: 115200,
             data_bits: AppDataBits::Eight,
             stop_bits: AppStopBits::One,
             parity: AppParity::None,
             flow_control: AppFlowControl::None,
+            parser_mode: ParserMode::Regex,
+            delimiter: Delimiter::Comma,
+            custom_delimiter: "|".to_string(),
             regex_pattern: String::new(),
             column_names: String::new(),
+            column_types: String::new(),
+            column_scales: Vec::new(),
+            column_layout: ColumnLayout::default(),
+            derived_columns: String::new(),
+            routes: Vec::new(),
+            tx_pattern: String::new(),
+            symbol_file: String::new(),
+            expand_backtraces: true,
+            record_mode: RecordMode::Off,
+            record_marker: String::new(),
+            record_lines: 2,
+            line_script: String::new(),
+            profiles: Vec::new(),
+            active_profile: String::new(),
+            seq_column: None,
+            device_time_column: None,
+            device_time_format: DeviceTimeFormat::Seconds,
+            retention: RetentionPolicy::Lines,
             max_rows: 2000,
+            retention_minutes: 10.0,
+            retention_mb: 16.0,
             show_timestamp: true,
+            show_line_numbers: false,
+            show_delta: false,
+            hide_unmatched: false,
+            density: Density::Comfortable,
+            table_zoom: 1.0,
+            raw_zoom: 1.0,
+            raw_timestamps: false,
+            raw_wrap: true,
+            view_layout: ViewLayout::Table,
+            split_size: 400.0,
+            theme: Theme::Dark,
+            colors: Colors::default(),
+            time_source: TimeSource::HostWall,
+            lightweight: false,
+            aggregate_ms: 0,
+            session_summary: true,
+            archive_sessions: false,
+            keep_captures: 0,
+            restore_capture: false,
+            window_alert: WindowAlert::Off,
+            alert_on_trigger: true,
+            alert_on_disconnect: true,
             rx_line_ending: LineEnding::LF,
             tx_line_ending: LineEnding::CrLf,
+            echo_tx: false,
+            text_encoding: TextEncoding::Utf8,
+            rx_framing: RxFraming::Lines,
+            frame_header: "AA 55".to_string(),
+            frame_length_offset: 2,
+            frame_length_size: 1,
+            frame_big_endian: false,
+            frame_trailer: 0,
+            frame_crc: Checksum::None,
+            frame_pattern: String::new(),
+            defmt_elf: String::new(),
+            proto_schema: String::new(),
+            proto_message: String::new(),
+            tlv_tag_size: 1,
+            tlv_length_size: 1,
+            tlv_big_endian: true,
+            tlv_labels: Vec::new(),
+            checksum: Checksum::None,
+            checksum_start: String::new(),
+            checksum_separator: "*".to_string(),
+            checksum_skip: 0,
+            dashboard: Vec::new(),
+            plots: Vec::new(),
+            histogram: HistogramConfig::default(),
+            trigger_pattern: String::new(),
+            trigger_pause_scroll: true,
+            trigger_insert_marker: true,
+            trigger_stop_capture: false,
+            trigger_post_lines: 100,
+            alert_rules: Vec::new(),
+            log_levels: true,
+            level_pattern: String::new(),
+            connect_retries: 0,
+            retry_delay_ms: 500,
+            low_latency: false,
+            open_dtr: PinState::Driver,
+            open_rts: PinState::Driver,
+            exclusive_lock: true,
+            custom_divisor: false,
+            rs485: false,
+            rs485_pre_delay_us: 0,
+            rs485_post_delay_us: 0,
+            tx_char_delay_ms: 0,
+            tx_line_delay_ms: 0,
+            macros: Vec::new(),
+            id_commands: String::new(),
+            id_on_connect: false,
+            tx_char_map: Vec::new(),
+            export_templates: Vec::new(),
         }
     }
 }
@@ -201,6 +918,209 @@ impl Settings {
         path
     }
 
+    /// True if there is something to connect to
+    pub fn has_target(&self) -> bool {
+        match self.connection {
+            ConnectionKind::Serial => !self.port_name.is_empty(),
+            ConnectionKind::Simulator => true,
+            ConnectionKind::Sniffer => !self.port_name.is_empty() && !self.sniffer_port.is_empty(),
+        }
+    }
+
+    /// Human readable description of the connection target
+    pub fn target_label(&self) -> String {
+        match self.connection {
+            ConnectionKind::Serial => {
+                format!("{} @ {} baud", self.port_name, self.baud_rate)
+            }
+            ConnectionKind::Simulator => format!("simulator ({})", self.sim_mode.label()),
+            ConnectionKind::Sniffer => format!(
+                "{} <-> {} @ {} baud",
+                self.port_name, self.sniffer_port, self.baud_rate
+            ),
+        }
+    }
+
+    /// True if the display-only settings (previewed live) are equal
+    pub fn same_display(&self, other: &Settings) -> bool {
+        self.show_timestamp == other.show_timestamp
+            && self.show_line_numbers == other.show_line_numbers
+            && self.show_delta == other.show_delta
+            && self.hide_unmatched == other.hide_unmatched
+            && self.density == other.density
+            && self.raw_timestamps == other.raw_timestamps
+            && self.raw_wrap == other.raw_wrap
+            && self.theme == other.theme
+            && self.colors == other.colors
+            && self.time_source == other.time_source
+            && self.lightweight == other.lightweight
+            && self.retention == other.retention
+            && self.max_rows == other.max_rows
+            && self.retention_minutes == other.retention_minutes
+            && self.retention_mb == other.retention_mb
+            && self.rx_line_ending == other.rx_line_ending
+            && self.tx_line_ending == other.tx_line_ending
+            && self.echo_tx == other.echo_tx
+            && self.text_encoding == other.text_encoding
+            && self.column_names == other.column_names
+    }
+
+    /// Copies the display-only settings from `other`
+    pub fn copy_display(&mut self, other: &Settings) {
+        self.show_timestamp = other.show_timestamp;
+        self.show_line_numbers = other.show_line_numbers;
+        self.show_delta = other.show_delta;
+        self.hide_unmatched = other.hide_unmatched;
+        self.density = other.density;
+        self.raw_timestamps = other.raw_timestamps;
+        self.raw_wrap = other.raw_wrap;
+        self.theme = other.theme;
+        self.colors = other.colors.clone();
+        self.time_source = other.time_source;
+        self.lightweight = other.lightweight;
+        self.retention = other.retention.clone();
+        self.max_rows = other.max_rows;
+        self.retention_minutes = other.retention_minutes;
+        self.retention_mb = other.retention_mb;
+        self.rx_line_ending = other.rx_line_ending.clone();
+        self.tx_line_ending = other.tx_line_ending.clone();
+        self.echo_tx = other.echo_tx;
+        self.text_encoding = other.text_encoding;
+        self.column_names = other.column_names.clone();
+    }
+
+    /// Number of oldest entries in `items` that fall outside the retention policy.
+    /// `items` must be in arrival order; `bytes` keeps their total size.
+    pub fn retention_excess<T>(
+        &self,
+        items: &[T],
+        bytes: &ByteTotal,
+        time: impl Fn(&T) -> DateTime<Local>,
+        size: impl Fn(&T) -> usize,
+    ) -> usize {
+        match self.retention {
+            RetentionPolicy::Lines => items.len().saturating_sub(self.max_rows),
+            RetentionPolicy::Time => {
+                let max_age =
+                    chrono::Duration::milliseconds((self.retention_minutes * 60e3) as i64);
+                let cutoff = Local::now() - max_age;
+                items.partition_point(|item| time(item) < cutoff)
+            }
+            RetentionPolicy::Size => {
+                let max_bytes = (self.retention_mb * 1024.0 * 1024.0) as usize;
+                let mut total = bytes.total(items, &size);
+                let mut excess = 0;
+                while total > max_bytes && excess < items.len() {
+                    total -= size(&items[excess]);
+                    excess += 1;
+                }
+                excess
+            }
+        }
+    }
+
+    pub fn id_commands_list(&self) -> Vec<String> {
+        self.id_commands
+            .lines()
+            .map(str::trim)
+            .filter(|c| !c.is_empty())
+            .map(str::to_string)
+            .collect()
+    }
+
+    /// Builds the line parser for the current mode; None shows lines unsplit
+    pub fn build_parser(&self) -> Result<Option<LineParser>, String> {
+        match self.parser_mode {
+            ParserMode::Regex if self.regex_pattern.is_empty() => Ok(None),
+            ParserMode::Regex => regex_guard::compile(&self.regex_pattern)
+                .map(|re| Some(LineParser::Regex(re)))
+                .map_err(|e| format!("Regex error: {}", e)),
+            ParserMode::Json => Ok(Some(LineParser::Json(KeyedColumns::default()))),
+            ParserMode::KeyValue => Ok(Some(LineParser::KeyValue(KeyedColumns::default()))),
+            ParserMode::Nmea => Ok(Some(LineParser::Nmea(KeyedColumns::default()))),
+            ParserMode::Delimiter => {
+                let delimiter = match self.delimiter {
+                    Delimiter::Comma => ',',
+                    Delimiter::Semicolon => ';',
+                    Delimiter::Tab => '\t',
+                    Delimiter::Custom => match self.custom_delimiter.chars().next() {
+                        Some(c) => c,
+                        None => return Err("No custom delimiter set".to_string()),
+                    },
+                };
+                Ok(Some(LineParser::Delimiter {
+                    delimiter,
+                    widest: 0,
+                }))
+            }
+        }
+    }
+
+    /// Switches the parser to the named profile. Edits made to the current
+    /// pattern are kept in the previously active profile.
+    pub fn select_profile(&mut self, name: &str) {
+        let current = self.parser_profile(&self.active_profile);
+        if let Some(active) = self
+            .profiles
+            .iter_mut()
+            .find(|p| p.name == self.active_profile)
+        {
+            *active = current;
+        }
+        if let Some(profile) = self.profiles.iter().find(|p| p.name == name).cloned() {
+            self.parser_mode = profile.parser_mode;
+            self.delimiter = profile.delimiter;
+            self.custom_delimiter = profile.custom_delimiter;
+            self.regex_pattern = profile.regex_pattern;
+            self.column_names = profile.column_names;
+            self.column_types = profile.column_types;
+            self.column_scales = profile.column_scales;
+            self.column_layout = profile.column_layout;
+            self.derived_columns = profile.derived_columns;
+            self.tx_pattern = profile.tx_pattern;
+            self.tx_char_map = profile.tx_char_map;
+            self.macros = profile.macros;
+            self.active_profile = profile.name;
+        }
+    }
+
+    /// Stores the current parser settings as a profile, replacing
+    /// one with the same name, and makes it active
+    pub fn save_profile(&mut self, name: &str) {
+        let profile = self.parser_profile(name);
+        match self.profiles.iter_mut().find(|p| p.name == name) {
+            Some(existing) => *existing = profile,
+            None => self.profiles.push(profile),
+        }
+        self.active_profile = name.to_string();
+    }
+
+    /// The current parser settings under `name`
+    fn parser_profile(&self, name: &str) -> ParserProfile {
+        ParserProfile {
+            name: name.to_string(),
+            parser_mode: self.parser_mode.clone(),
+            delimiter: self.delimiter.clone(),
+            custom_delimiter: self.custom_delimiter.clone(),
+            regex_pattern: self.regex_pattern.clone(),
+            column_names: self.column_names.clone(),
+            column_types: self.column_types.clone(),
+            column_scales: self.column_scales.clone(),
+            column_layout: self.column_layout.clone(),
+            derived_columns: self.derived_columns.clone(),
+            tx_pattern: self.tx_pattern.clone(),
+            tx_char_map: self.tx_char_map.clone(),
+            macros: self.macros.clone(),
+        }
+    }
+
+    pub fn delete_profile(&mut self, name: &str) {
+        self.profiles.retain(|p| p.name != name);
+        if self.active_profile == name {
+            self.active_profile.clear();
+        }
+    }
+
     pub fn column_names_list(&self) -> Vec<String> {
         if self.column_names.trim().is_empty() {
             vec![]
@@ -213,22 +1133,58 @@ impl Settings {
     }
 }
 
+/// Result of one frame of the settings window
+pub enum SettingsAction {
+    /// Commit these settings (Apply keeps the window open, OK closes it)
+    Apply(Settings),
+    /// Display-only settings were edited; show them without committing
+    Preview(Settings),
+    /// The window was closed without applying; undo any preview
+    Cancel,
+}
+
+/// Editable port path plus a menu of the enumerated ports. Paths that are not
+/// enumerated, like a PTY from socat or QEMU (`/dev/pts/N`), can be typed in.
+pub fn port_picker(ui: &mut egui::Ui, port_name: &mut String, ports: &[PortEntry]) {
+    ui.add(
+        egui::TextEdit::singleline(port_name)
+            .hint_text("select or type a path")
+            .desired_width(140.0),
+    );
+    ui.menu_button("▼", |ui| {
+        if ports.is_empty() {
+            ui.weak("No ports found");
+        }
+        for port in ports {
+            if ui.selectable_label(*port_name == port.name, &port.label).clicked() {
+                *port_name = port.name.clone();
+                ui.close_menu();
+            }
+        }
+    });
+}
+
 // Settings window UI state
 pub struct SettingsWindow {
     pub edit: Settings,
+    /// Settings last committed or previewed, to detect display changes
+    shown: Settings,
     pub is_open: bool,
-    pub available_ports: Vec<String>,
+    pub available_ports: Vec<PortEntry>,
     pub test_input: String,
     pub test_result: String,
     pub regex_error: String,
     pub custom_baud: String,
     pub show_custom_baud: bool,
+    /// Name typed for "Save as profile"
+    pub profile_name: String,
 }
 
 impl SettingsWindow {
     pub fn new(settings: &Settings) -> Self {
         Self {
             edit: settings.clone(),
+            shown: settings.clone(),
             is_open: false,
             available_ports: vec![],
             test_input: String::new(),
@@ -236,11 +1192,14 @@ impl SettingsWindow {
             regex_error: String::new(),
             custom_baud: String::new(),
             show_custom_baud: false,
+            profile_name: String::new(),
         }
     }
 
     pub fn open(&mut self, settings: &Settings) {
         self.edit = settings.clone();
+        self.shown = settings.clone();
+        self.profile_name = settings.active_profile.clone();
         self.is_open = true;
         self.refresh_ports();
         self.regex_error.clear();
@@ -248,27 +1207,19 @@ impl SettingsWindow {
     }
 
     pub fn refresh_ports(&mut self) {
-        self.available_ports = serialport::available_ports()
-            .unwrap_or_default()
-            .into_iter()
-            .map(|p| p.port_name)
-            .collect();
+        self.available_ports = list_ports();
     }
 
     pub fn validate_regex(&mut self) {
-        if self.edit.regex_pattern.is_empty() {
-            self.regex_error.clear();
-            self.test_result.clear();
-            return;
-        }
-        match regex::Regex::new(&self.edit.regex_pattern) {
-            Ok(re) => {
+        match self.edit.build_parser() {
+            Ok(None) => {
+                self.regex_error.clear();
+                self.test_result.clear();
+            }
+            Ok(Some(mut parser)) => {
                 self.regex_error.clear();
                 if !self.test_input.is_empty() {
-                    if let Some(caps) = re.captures(&self.test_input) {
-                        let groups: Vec<String> = (1..caps.len())
-                            .map(|i| caps.get(i).map_or("", |m| m.as_str()).to_string())
-                            .collect();
+                    if let Some(groups) = parser.parse(&self.test_input) {
                         self.test_result = format!("Match: [{}]", groups.join("] ["));
                     } else {
                         self.test_result = "No match".to_string();
@@ -276,14 +1227,15 @@ impl SettingsWindow {
                 }
             }
             Err(e) => {
-                self.regex_error = format!("Regex error: {}", e);
+                self.regex_error = e;
                 self.test_result.clear();
             }
         }
     }
 
-    /// Renders the settings window. Returns Some(Settings) if Apply was clicked.
-    pub fn show(&mut self, ctx: &egui::Context) -> Option<Settings> {
+    /// Renders the settings window. Returns an action when settings were
+    /// applied, display settings changed, or the window was cancelled.
+    pub fn show(&mut self, ctx: &egui::Context) -> Option<SettingsAction> {
         if !self.is_open {
             return None;
         }
@@ -301,11 +1253,26 @@ impl SettingsWindow {
                 result = self.render_content(ui);
             });
 
-        self.is_open = open;
+        if !open {
+            self.is_open = false;
+        }
+        if result.is_none() {
+            if !self.is_open {
+                result = Some(SettingsAction::Cancel);
+            } else if !self.edit.same_display(&self.shown) {
+                result = Some(SettingsAction::Preview(self.edit.clone()));
+            }
+        }
+        match &result {
+            Some(SettingsAction::Apply(s)) | Some(SettingsAction::Preview(s)) => {
+                self.shown = s.clone();
+            }
+            _ => {}
+        }
         result
     }
 
-    fn render_content(&mut self, ui: &mut egui::Ui) -> Option<Settings> {
+    fn render_content(&mut self, ui: &mut egui::Ui) -> Option<SettingsAction> {
         let mut result = None;
 
         egui::ScrollArea::vertical().show(ui, |ui| {
@@ -318,31 +1285,66 @@ impl SettingsWindow {
                 .spacing([8.0, 6.0])
                 .striped(true)
                 .show(ui, |ui| {
-                    // Port
-                    ui.label("Port:");
-                    ui.horizontal(|ui| {
-                        egui::ComboBox::from_id_salt("port_combo")
-                            .selected_text(if self.edit.port_name.is_empty() {
-                                "-- select --"
-                            } else {
-                                &self.edit.port_name
-                            })
+                    // Connection type
+                    ui.label("Type:");
+                    egui::ComboBox::from_id_salt("conn_kind_combo")
+                        .selected_text(self.edit.connection.label())
+                        .width(140.0)
+                        .show_ui(ui, |ui| {
+                            for kind in ConnectionKind::all() {
+                                ui.selectable_value(
+                                    &mut self.edit.connection,
+                                    kind.clone(),
+                                    kind.label(),
+                                );
+                            }
+                        });
+                    ui.end_row();
+
+                    if self.edit.connection == ConnectionKind::Simulator {
+                        ui.label("Simulated data:");
+                        egui::ComboBox::from_id_salt("sim_mode_combo")
+                            .selected_text(self.edit.sim_mode.label())
                             .width(140.0)
                             .show_ui(ui, |ui| {
-                                for port in &self.available_ports.clone() {
+                                for mode in SimMode::all() {
                                     ui.selectable_value(
-                                        &mut self.edit.port_name,
-                                        port.clone(),
-                                        port,
+                                        &mut self.edit.sim_mode,
+                                        mode.clone(),
+                                        mode.label(),
                                     );
                                 }
                             });
+                        ui.end_row();
+
+                        ui.label("Lines/sec:");
+                        ui.add(
+                            egui::DragValue::new(&mut self.edit.sim_rate_hz)
+                                .range(0.1..=5000.0)
+                                .speed(1.0),
+                        );
+                        ui.end_row();
+                    }
+
+                    // Port
+                    let is_sniffer = self.edit.connection == ConnectionKind::Sniffer;
+                    ui.label(if is_sniffer { "Host port:" } else { "Port:" });
+                    ui.horizontal(|ui| {
+                        port_picker(ui, &mut self.edit.port_name, &self.available_ports);
                         if ui.button("Refresh").clicked() {
                             self.refresh_ports();
                         }
                     });
                     ui.end_row();
 
+                    if is_sniffer {
+                        ui.label("Device port:");
+                        ui.horizontal(|ui| {
+                            port_picker(ui, &mut self.edit.sniffer_port, &self.available_ports);
+                        });
+                        ui.end_row();
+                    }
+
                     // Baud rate
                     ui.label("Baud Rate:");
                     ui.horizontal(|ui| {
@@ -445,27 +1447,198 @@ impl SettingsWindow {
                             }
                         });
                     ui.end_row();
+
+                    // RS-485 direction control
+                    ui.label("RS-485 (RTS):");
+                    ui.horizontal(|ui| {
+                        ui.checkbox(&mut self.edit.rs485, "").on_hover_text(
+                            "Assert RTS before each write and release it once the last \
+                             byte has been sent. Use with flow control None.",
+                        );
+                        ui.add_enabled_ui(self.edit.rs485, |ui| {
+                            ui.label("pre (µs):");
+                            ui.add(
+                                egui::DragValue::new(&mut self.edit.rs485_pre_delay_us)
+                                    .range(0..=100_000)
+                                    .speed(10.0),
+                            );
+                            ui.label("post (µs):");
+                            ui.add(
+                                egui::DragValue::new(&mut self.edit.rs485_post_delay_us)
+                                    .range(0..=100_000)
+                                    .speed(10.0),
+                            );
+                        });
+                    });
+                    ui.end_row();
+
+                    // TX pacing
+                    ui.label("TX pacing:");
+                    ui.horizontal(|ui| {
+                        ui.label("char (ms):");
+                        ui.add(
+                            egui::DragValue::new(&mut self.edit.tx_char_delay_ms).range(0..=1000),
+                        )
+                        .on_hover_text("Pause after every sent byte, for slow bootloaders");
+                        ui.label("line (ms):");
+                        ui.add(
+                            egui::DragValue::new(&mut self.edit.tx_line_delay_ms).range(0..=10000),
+                        )
+                        .on_hover_text("Extra pause after every sent line");
+                    });
+                    ui.end_row();
+
+                    // Connect retry
+                    ui.label("Connect retries:");
+                    ui.horizontal(|ui| {
+                        ui.add(egui::DragValue::new(&mut self.edit.connect_retries).range(0..=20));
+                        ui.label("first delay (ms):");
+                        ui.add(
+                            egui::DragValue::new(&mut self.edit.retry_delay_ms)
+                                .range(50..=10000)
+                                .speed(10.0),
+                        );
+                    });
+                    ui.end_row();
                 });
 
             ui.add_space(12.0);
-            ui.heading("Regex Parser");
+            ui.heading("Parser");
             ui.separator();
 
-            ui.label("Regex pattern (each capture group = one column):");
-            let re_changed = ui
-                .add(
-                    egui::TextEdit::singleline(&mut self.edit.regex_pattern)
-                        .hint_text("e.g. T=([-\\d.]+),H=([-\\d.]+),P=([-\\d.]+)")
-                        .desired_width(f32::INFINITY),
-                )
-                .changed();
+            ui.horizontal(|ui| {
+                ui.label("Profile:");
+                let selected = if self.edit.active_profile.is_empty() {
+                    "(none)"
+                } else {
+                    &self.edit.active_profile
+                };
+                let mut chosen = None;
+                egui::ComboBox::from_id_salt("settings_profile")
+                    .selected_text(selected)
+                    .width(140.0)
+                    .show_ui(ui, |ui| {
+                        for p in &self.edit.profiles {
+                            if ui
+                                .selectable_label(p.name == self.edit.active_profile, &p.name)
+                                .clicked()
+                            {
+                                chosen = Some(p.name.clone());
+                            }
+                        }
+                    });
+                if let Some(name) = chosen {
+                    self.edit.select_profile(&name);
+                    self.profile_name = name;
+                    self.validate_regex();
+                }
+                ui.add(
+                    egui::TextEdit::singleline(&mut self.profile_name)
+                        .hint_text("profile name")
+                        .desired_width(120.0),
+                );
+                let name = self.profile_name.trim().to_string();
+                if ui
+                    .add_enabled(!name.is_empty(), egui::Button::new("Save as profile"))
+                    .on_hover_text("Store the mode, pattern and column names under this name")
+                    .clicked()
+                {
+                    self.edit.save_profile(&name);
+                }
+                if ui
+                    .add_enabled(
+                        self.edit.profiles.iter().any(|p| p.name == name),
+                        egui::Button::new("Delete"),
+                    )
+                    .clicked()
+                {
+                    self.edit.delete_profile(&name);
+                }
+            });
+            ui.add_space(6.0);
+
+            let mut mode_changed = false;
+            ui.horizontal(|ui| {
+                ui.label("Mode:");
+                egui::ComboBox::from_id_salt("parser_mode_combo")
+                    .selected_text(self.edit.parser_mode.label())
+                    .width(140.0)
+                    .show_ui(ui, |ui| {
+                        for mode in ParserMode::all() {
+                            mode_changed |= ui
+                                .selectable_value(
+                                    &mut self.edit.parser_mode,
+                                    mode.clone(),
+                                    mode.label(),
+                                )
+                                .changed();
+                        }
+                    });
+            });
+
+            let re_changed = match self.edit.parser_mode {
+                ParserMode::Regex => {
+                    ui.label("Regex pattern (each capture group = one column):");
+                    ui.add(
+                        egui::TextEdit::singleline(&mut self.edit.regex_pattern)
+                            .hint_text("e.g. T=([-\\d.]+),H=([-\\d.]+),P=([-\\d.]+)")
+                            .desired_width(f32::INFINITY),
+                    )
+                    .changed()
+                }
+                ParserMode::Json => {
+                    ui.label("Each line is a JSON object; keys become columns (nested as a.b).");
+                    false
+                }
+                ParserMode::KeyValue => {
+                    ui.label("key=value pairs in any order; each key gets its own column.");
+                    false
+                }
+                ParserMode::Nmea => {
+                    ui.label(
+                        "NMEA 0183 sentences; the *hh checksum is verified and GGA, RMC and \
+                         GSV are decoded into named columns.",
+                    );
+                    false
+                }
+                ParserMode::Delimiter => {
+                    let mut changed = false;
+                    ui.horizontal(|ui| {
+                        ui.label("Delimiter:");
+                        egui::ComboBox::from_id_salt("delimiter_combo")
+                            .selected_text(self.edit.delimiter.label())
+                            .width(120.0)
+                            .show_ui(ui, |ui| {
+                                for d in Delimiter::all() {
+                                    changed |= ui
+                                        .selectable_value(
+                                            &mut self.edit.delimiter,
+                                            d.clone(),
+                                            d.label(),
+                                        )
+                                        .changed();
+                                }
+                            });
+                        if self.edit.delimiter == Delimiter::Custom {
+                            changed |= ui
+                                .add(
+                                    egui::TextEdit::singleline(&mut self.edit.custom_delimiter)
+                                        .char_limit(1)
+                                        .desired_width(24.0),
+                                )
+                                .changed();
+                        }
+                    });
+                    changed
+                }
+            };
 
-            if re_changed {
+            if re_changed || mode_changed {
                 self.validate_regex();
             }
 
             if !self.regex_error.is_empty() {
-                ui.colored_label(egui::Color32::RED, &self.regex_error.clone());
+                ui.colored_label(egui::Color32::RED, &self.regex_error);
             }
 
             ui.add_space(6.0);
@@ -488,7 +1661,7 @@ impl SettingsWindow {
                 } else {
                     egui::Color32::YELLOW
                 };
-                ui.colored_label(color, &self.test_result.clone());
+                ui.colored_label(color, &self.test_result);
             }
 
             ui.add_space(6.0);
@@ -499,6 +1672,319 @@ impl SettingsWindow {
                     .desired_width(f32::INFINITY),
             );
 
+            ui.add_space(6.0);
+            ui.label("Column types (comma-separated int/float/hex, empty = text):");
+            ui.add(
+                egui::TextEdit::singleline(&mut self.edit.column_types)
+                    .hint_text("float, float, int")
+                    .desired_width(f32::INFINITY),
+            )
+            .on_hover_text(
+                "Typed columns are right-aligned, plotted by value and exported as numbers",
+            );
+            if let Err(e) = ColumnType::parse_list(&self.edit.column_types) {
+                ui.colored_label(egui::Color32::RED, e);
+            }
+
+            ui.add_space(6.0);
+            ui.label("Column scaling (value = raw × scale + offset):");
+            scale_editor(ui, &mut self.edit.column_scales);
+
+            ui.add_space(6.0);
+            ui.label("Derived columns (one `name = expression` per line):");
+            ui.add(
+                egui::TextEdit::multiline(&mut self.edit.derived_columns)
+                    .code_editor()
+                    .hint_text("power = volts * amps\ntemp_f = temp_c * 1.8 + 32")
+                    .desired_rows(2)
+                    .desired_width(f32::INFINITY),
+            )
+            .on_hover_text("Columns are named by their lower-cased header or c1, c2, ...");
+            if let Err(e) = DerivedColumns::from_settings(&self.edit) {
+                ui.colored_label(egui::Color32::RED, e);
+            }
+
+            ui.add_space(6.0);
+            ui.label("TX validation pattern (regex, empty = off):");
+            ui.add(
+                egui::TextEdit::singleline(&mut self.edit.tx_pattern)
+                    .hint_text(r"e.g. ^AT(\+\w+)?(=.*|\?)?$")
+                    .desired_width(f32::INFINITY),
+            )
+            .on_hover_text("The send bar warns before sending input that doesn't match");
+            if !self.edit.tx_pattern.is_empty() {
+                if let Err(e) = regex_guard::compile(&self.edit.tx_pattern) {
+                    ui.colored_label(egui::Color32::RED, e);
+                }
+            }
+
+            ui.add_space(6.0);
+            ui.horizontal(|ui| {
+                let mut enabled = self.edit.seq_column.is_some();
+                ui.checkbox(&mut enabled, "Sequence counter in column")
+                    .on_hover_text("Flag rows where the counter skips values (dropped frames)");
+                let mut col = self.edit.seq_column.unwrap_or(0) + 1;
+                ui.add_enabled(enabled, egui::DragValue::new(&mut col).range(1..=64));
+                self.edit.seq_column = enabled.then(|| col - 1);
+            });
+            ui.horizontal(|ui| {
+                let mut enabled = self.edit.device_time_column.is_some();
+                ui.checkbox(&mut enabled, "Device time in column").on_hover_text(
+                    "The device's own timestamp; shown and exported when the timestamp \
+                     source is one of the device times",
+                );
+                let mut col = self.edit.device_time_column.unwrap_or(0) + 1;
+                ui.add_enabled(enabled, egui::DragValue::new(&mut col).range(1..=64));
+                self.edit.device_time_column = enabled.then(|| col - 1);
+                ui.add_enabled_ui(enabled, |ui| {
+                    egui::ComboBox::from_id_salt("device_time_format_combo")
+                        .selected_text(self.edit.device_time_format.label())
+                        .show_ui(ui, |ui| {
+                            for format in DeviceTimeFormat::all() {
+                                ui.selectable_value(
+                                    &mut self.edit.device_time_format,
+                                    *format,
+                                    format.label(),
+                                );
+                            }
+                        });
+                });
+            });
+
+            ui.add_space(12.0);
+            ui.heading("Routes");
+            ui.separator();
+            route_editor(ui, &mut self.edit.routes);
+
+            ui.add_space(12.0);
+            ui.heading("Address Symbols");
+            ui.separator();
+            symbol_editor(ui, &mut self.edit);
+
+            ui.add_space(12.0);
+            ui.heading("Multi-line Records");
+            ui.separator();
+            record_editor(ui, &mut self.edit);
+
+            ui.add_space(12.0);
+            ui.heading("Line Script");
+            ui.separator();
+            script_editor(ui, &mut self.edit);
+
+            ui.add_space(12.0);
+            ui.heading("Trigger");
+            ui.separator();
+
+            ui.label("Trigger pattern (regex, empty = off):");
+            ui.add(
+                egui::TextEdit::singleline(&mut self.edit.trigger_pattern)
+                    .hint_text("e.g. ERROR|assert")
+                    .desired_width(f32::INFINITY),
+            );
+            ui.checkbox(&mut self.edit.trigger_pause_scroll, "Pause auto-scroll on trigger");
+            ui.checkbox(&mut self.edit.trigger_insert_marker, "Insert marker row");
+            ui.horizontal(|ui| {
+                ui.checkbox(&mut self.edit.trigger_stop_capture, "Stop capture after");
+                ui.add_enabled(
+                    self.edit.trigger_stop_capture,
+                    egui::DragValue::new(&mut self.edit.trigger_post_lines).range(0..=100000),
+                );
+                ui.label("more lines");
+            });
+
+            ui.add_space(12.0);
+            ui.heading("Alert Rules");
+            ui.separator();
+            alert_editor(ui, &mut self.edit.alert_rules);
+
+            ui.add_space(12.0);
+            ui.heading("Log Levels");
+            ui.separator();
+            level_editor(ui, &mut self.edit);
+
+            ui.add_space(12.0);
+            ui.heading("Macros");
+            ui.separator();
+            macro_editor(ui, &mut self.edit.macros);
+
+            ui.add_space(12.0);
+            ui.heading("Device Identification");
+            ui.separator();
+            ui.label("Commands whose responses are pinned above the table (one per line):");
+            ui.add(
+                egui::TextEdit::multiline(&mut self.edit.id_commands)
+                    .hint_text("e.g.\nversion\nATI")
+                    .desired_rows(3)
+                    .desired_width(f32::INFINITY),
+            );
+            ui.checkbox(&mut self.edit.id_on_connect, "Send on connect");
+
+            ui.add_space(12.0);
+            ui.heading("RX Framing");
+            ui.separator();
+            ui.horizontal(|ui| {
+                ui.label("Split received data into:");
+                egui::ComboBox::from_id_salt("rx_framing_combo")
+                    .selected_text(self.edit.rx_framing.label())
+                    .width(170.0)
+                    .show_ui(ui, |ui| {
+                        for f in RxFraming::all() {
+                            ui.selectable_value(&mut self.edit.rx_framing, f.clone(), f.label());
+                        }
+                    });
+            });
+            match self.edit.rx_framing {
+                RxFraming::Lines => {}
+                RxFraming::LengthPrefixed => {
+                    egui::Grid::new("frame_grid")
+                        .num_columns(2)
+                        .spacing([8.0, 4.0])
+                        .show(ui, |ui| {
+                            ui.label("Header (hex):");
+                            ui.add(
+                                egui::TextEdit::singleline(&mut self.edit.frame_header)
+                                    .hint_text("e.g. AA 55, empty = none")
+                                    .desired_width(140.0),
+                            );
+                            ui.end_row();
+
+                            ui.label("Length field offset:");
+                            ui.add(
+                                egui::DragValue::new(&mut self.edit.frame_length_offset)
+                                    .range(0..=64)
+                                    .suffix(" bytes"),
+                            );
+                            ui.end_row();
+
+                            ui.label("Length field size:");
+                            ui.horizontal(|ui| {
+                                for size in [1, 2, 4] {
+                                    ui.selectable_value(
+                                        &mut self.edit.frame_length_size,
+                                        size,
+                                        format!("{} B", size),
+                                    );
+                                }
+                                ui.checkbox(&mut self.edit.frame_big_endian, "Big endian");
+                            });
+                            ui.end_row();
+
+                            ui.label("Trailer after payload:");
+                            ui.add(
+                                egui::DragValue::new(&mut self.edit.frame_trailer)
+                                    .range(0..=64)
+                                    .suffix(" bytes"),
+                            );
+                            ui.end_row();
+                        });
+                }
+                RxFraming::Slip => {
+                    ui.label("Frames end with 0xC0; escaped bytes are decoded.");
+                }
+                RxFraming::Cobs => {
+                    ui.label("Frames end with 0x00 and are COBS-decoded.");
+                }
+                RxFraming::ModbusRtu => {
+                    ui.label(
+                        "Frames end after 3.5 characters of silence and are checked against \
+                         their CRC-16/MODBUS. Requests are built in the Modbus window; low \
+                         latency mode helps at high baud rates.",
+                    );
+                }
+                RxFraming::Defmt => {
+                    ui.label("Firmware ELF (the build running on the device):");
+                    ui.add(
+                        egui::TextEdit::singleline(&mut self.edit.defmt_elf)
+                            .hint_text("target/thumbv7em-none-eabihf/debug/firmware")
+                            .desired_width(f32::INFINITY),
+                    );
+                    ui.label(
+                        "rzCOBS frames from defmt-serial or a similar UART logger are decoded \
+                         into log lines with timestamp and level. The ELF is reloaded when \
+                         settings are applied, e.g. after flashing a new build.",
+                    );
+                }
+                RxFraming::Protobuf => {
+                    egui::Grid::new("proto_grid")
+                        .num_columns(2)
+                        .spacing([16.0, 6.0])
+                        .show(ui, |ui| {
+                            ui.label("Schema:");
+                            ui.add(
+                                egui::TextEdit::singleline(&mut self.edit.proto_schema)
+                                    .hint_text("telemetry.proto or telemetry.desc")
+                                    .desired_width(300.0),
+                            );
+                            ui.end_row();
+
+                            ui.label("Message type:");
+                            ui.add(
+                                egui::TextEdit::singleline(&mut self.edit.proto_message)
+                                    .hint_text("first message in the file")
+                                    .desired_width(300.0),
+                            );
+                            ui.end_row();
+                        });
+                    ui.label(
+                        "Each frame is a message preceded by its length as a varint \
+                         (writeDelimitedTo, pb_encode_delimited). The schema is a .proto file \
+                         (imports are looked up next to it) or a descriptor set from protoc \
+                         --descriptor_set_out. Messages become JSON objects, one column per \
+                         field with the JSON Lines parser.",
+                    );
+                    if self.edit.parser_mode != ParserMode::Json {
+                        ui.colored_label(
+                            egui::Color32::YELLOW,
+                            "Set the parser mode to JSON Lines for named columns.",
+                        );
+                    }
+                }
+                RxFraming::Tlv => tlv_editor(ui, &mut self.edit),
+            }
+            if !matches!(
+                self.edit.rx_framing,
+                RxFraming::Lines
+                    | RxFraming::ModbusRtu
+                    | RxFraming::Defmt
+                    | RxFraming::Protobuf
+                    | RxFraming::Tlv
+            ) {
+                ui.horizontal(|ui| {
+                    ui.label("Trailing checksum:");
+                    egui::ComboBox::from_id_salt("frame_crc_combo")
+                        .selected_text(self.edit.frame_crc.label())
+                        .width(170.0)
+                        .show_ui(ui, |ui| {
+                            for c in Checksum::all() {
+                                ui.selectable_value(&mut self.edit.frame_crc, c.clone(), c.label());
+                            }
+                        });
+                });
+                ui.label(
+                    "Frames are shown as hex rows; rows of frames that fail to decode or \
+                     whose checksum doesn't match are tinted purple.",
+                );
+                frame_pattern_editor(ui, &mut self.edit);
+            }
+            if let Err(e) = Framer::from_settings(&self.edit) {
+                ui.colored_label(egui::Color32::RED, e);
+            }
+
+            ui.add_space(12.0);
+            ui.heading("Checksum Column");
+            ui.separator();
+            checksum_editor(ui, &mut self.edit);
+
+            ui.add_space(12.0);
+            ui.heading("TX Character Map");
+            ui.separator();
+            char_map_editor(ui, &mut self.edit.tx_char_map);
+
+            ui.add_space(12.0);
+            ui.heading("Export Templates");
+            ui.separator();
+            template_editor(ui, &mut self.edit.export_templates);
+
             ui.add_space(12.0);
             ui.heading("Display");
             ui.separator();
@@ -507,20 +1993,183 @@ impl SettingsWindow {
                 .num_columns(2)
                 .spacing([8.0, 6.0])
                 .show(ui, |ui| {
-                    ui.label("Max rows:");
-                    ui.add(egui::DragValue::new(&mut self.edit.max_rows).range(100..=100000));
+                    ui.label("Keep:");
+                    ui.horizontal(|ui| {
+                        egui::ComboBox::from_id_salt("retention_combo")
+                            .selected_text(self.edit.retention.label())
+                            .width(140.0)
+                            .show_ui(ui, |ui| {
+                                for policy in RetentionPolicy::all() {
+                                    ui.selectable_value(
+                                        &mut self.edit.retention,
+                                        policy.clone(),
+                                        policy.label(),
+                                    );
+                                }
+                            });
+                        match self.edit.retention {
+                            RetentionPolicy::Lines => ui.add(
+                                egui::DragValue::new(&mut self.edit.max_rows).range(100..=100000),
+                            ),
+                            RetentionPolicy::Time => ui.add(
+                                egui::DragValue::new(&mut self.edit.retention_minutes)
+                                    .range(0.5..=1440.0)
+                                    .speed(0.5),
+                            ),
+                            RetentionPolicy::Size => ui.add(
+                                egui::DragValue::new(&mut self.edit.retention_mb)
+                                    .range(0.1..=1024.0)
+                                    .speed(0.1),
+                            ),
+                        };
+                    });
                     ui.end_row();
 
                     ui.label("Show timestamp:");
                     ui.checkbox(&mut self.edit.show_timestamp, "");
                     ui.end_row();
 
+                    ui.label("Show line numbers:");
+                    ui.checkbox(&mut self.edit.show_line_numbers, "").on_hover_text(
+                        "Numbered on receipt; the numbers carry on when old rows are dropped",
+                    );
+                    ui.end_row();
+
+                    ui.label("Show Δt columns:");
+                    ui.checkbox(&mut self.edit.show_delta, "").on_hover_text(
+                        "Time since the previous row, and since the row set as reference \
+                         from its context menu",
+                    );
+                    ui.end_row();
+
+                    ui.label("Table density:");
+                    egui::ComboBox::from_id_salt("density_combo")
+                        .selected_text(self.edit.density.label())
+                        .show_ui(ui, |ui| {
+                            for density in Density::all() {
+                                ui.selectable_value(
+                                    &mut self.edit.density,
+                                    *density,
+                                    density.label(),
+                                );
+                            }
+                        })
+                        .response
+                        .on_hover_text("Compact rows fit far more lines on screen");
+                    ui.end_row();
+
+                    ui.label("Hide unmatched rows:");
+                    ui.checkbox(&mut self.edit.hide_unmatched, "").on_hover_text(
+                        "Leave <no match> rows out of the table; the status bar still counts \
+                         them and clicking the count shows only them",
+                    );
+                    ui.end_row();
+
+                    ui.label("Raw log timestamps:");
+                    ui.checkbox(&mut self.edit.raw_timestamps, "");
+                    ui.end_row();
+
+                    ui.label("Wrap raw log lines:");
+                    ui.checkbox(&mut self.edit.raw_wrap, "");
+                    ui.end_row();
+
+                    ui.label("Timestamp source:");
+                    egui::ComboBox::from_id_salt("time_source_combo")
+                        .selected_text(self.edit.time_source.label())
+                        .show_ui(ui, |ui| {
+                            for source in TimeSource::all() {
+                                ui.selectable_value(
+                                    &mut self.edit.time_source,
+                                    *source,
+                                    source.label(),
+                                );
+                            }
+                        })
+                        .response
+                        .on_hover_text(
+                            "Clock shown in the table, timeline and raw log and written to \
+                             exports; rows are always ordered by the host monotonic clock",
+                        );
+                    ui.end_row();
+
+                    ui.label("Lightweight mode:");
+                    ui.checkbox(&mut self.edit.lightweight, "").on_hover_text(
+                        "No row stripes, colours or plots and fewer repaints, for old PCs \
+                         and Raspberry Pi-class machines",
+                    );
+                    ui.end_row();
+
+                    ui.label("Aggregate rows:");
+                    ui.add(
+                        egui::DragValue::new(&mut self.edit.aggregate_ms)
+                            .range(0..=3_600_000)
+                            .speed(10.0)
+                            .suffix(" ms"),
+                    )
+                    .on_hover_text(
+                        "One table row and plot point per window with the mean of each \
+                         column (min and max on hover and as a plot band), for high-rate \
+                         telemetry; CSV export still writes every line (0 = off)",
+                    );
+                    ui.end_row();
+
+                    ui.label("Session summary:");
+                    ui.horizontal(|ui| {
+                        ui.checkbox(&mut self.edit.session_summary, "Show on disconnect");
+                        ui.checkbox(&mut self.edit.archive_sessions, "Append to archive")
+                            .on_hover_text("uart_console_sessions.log next to the executable");
+                    });
+                    ui.end_row();
+
+                    ui.label("Saved captures:");
+                    ui.horizontal(|ui| {
+                        ui.add(
+                            egui::DragValue::new(&mut self.edit.keep_captures)
+                                .range(0..=100)
+                                .suffix(" sessions"),
+                        )
+                        .on_hover_text(
+                            "Lines of each session saved to uart_console_captures next to the \
+                             executable (0 = off)",
+                        );
+                        ui.add_enabled(
+                            self.edit.keep_captures > 0,
+                            egui::Checkbox::new(
+                                &mut self.edit.restore_capture,
+                                "Reopen the newest at startup",
+                            ),
+                        );
+                    });
+                    ui.end_row();
+
+                    ui.label("Window alert:");
+                    ui.horizontal(|ui| {
+                        egui::ComboBox::from_id_salt("window_alert_combo")
+                            .selected_text(self.edit.window_alert.label())
+                            .width(110.0)
+                            .show_ui(ui, |ui| {
+                                for a in WindowAlert::all() {
+                                    ui.selectable_value(
+                                        &mut self.edit.window_alert,
+                                        a.clone(),
+                                        a.label(),
+                                    );
+                                }
+                            });
+                        let enabled = self.edit.window_alert != WindowAlert::Off;
+                        ui.add_enabled_ui(enabled, |ui| {
+                            ui.checkbox(&mut self.edit.alert_on_trigger, "On trigger");
+                            ui.checkbox(&mut self.edit.alert_on_disconnect, "On disconnect");
+                        });
+                    });
+                    ui.end_row();
+
                     ui.label("RX line ending:");
                     egui::ComboBox::from_id_salt("rx_le_combo")
                         .selected_text(self.edit.rx_line_ending.label())
                         .width(140.0)
                         .show_ui(ui, |ui| {
-                            for le in LineEnding::all() {
+                            for le in LineEnding::rx_all() {
                                 ui.selectable_value(
                                     &mut self.edit.rx_line_ending,
                                     le.clone(),
@@ -544,18 +2193,62 @@ impl SettingsWindow {
                             }
                         });
                     ui.end_row();
+
+                    ui.label("Echo sent data:");
+                    ui.checkbox(&mut self.edit.echo_tx, "").on_hover_text(
+                        "Show what the send bar, macros and Modbus polls send as TX rows \
+                         in the raw log and table",
+                    );
+                    ui.end_row();
+
+                    ui.label("Text encoding:");
+                    egui::ComboBox::from_id_salt("encoding_combo")
+                        .selected_text(self.edit.text_encoding.label())
+                        .width(140.0)
+                        .show_ui(ui, |ui| {
+                            for enc in TextEncoding::all() {
+                                ui.selectable_value(
+                                    &mut self.edit.text_encoding,
+                                    *enc,
+                                    enc.label(),
+                                );
+                            }
+                        })
+                        .response
+                        .on_hover_text(
+                            "Used to decode received lines and encode sent text; \
+                             binary frames are not affected",
+                        );
+                    ui.end_row();
                 });
 
+            ui.add_space(12.0);
+            ui.heading("Theme");
+            ui.separator();
+            theme_editor(ui, &mut self.edit);
+
+            ui.add_space(12.0);
+            ui.heading("Advanced");
+            ui.separator();
+            quirks_editor(ui, &mut self.edit);
+
             ui.add_space(16.0);
             ui.separator();
+            ui.label("Display changes are previewed live; Cancel reverts them.");
             ui.horizontal(|ui| {
                 if ui
-                    .add_sized([100.0, 28.0], egui::Button::new("Apply"))
+                    .add_sized([100.0, 28.0], egui::Button::new("OK"))
                     .clicked()
                 {
-                    result = Some(self.edit.clone());
+                    result = Some(SettingsAction::Apply(self.edit.clone()));
                     self.is_open = false;
                 }
+                if ui
+                    .add_sized([100.0, 28.0], egui::Button::new("Apply"))
+                    .clicked()
+                {
+                    result = Some(SettingsAction::Apply(self.edit.clone()));
+                }
                 if ui
                     .add_sized([100.0, 28.0], egui::Button::new("Cancel"))
                     .clicked()
@@ -568,3 +2261,67 @@ impl SettingsWindow {
         result
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn line_retention_keeps_the_newest_rows() {
+        let settings = Settings {
+            retention: RetentionPolicy::Lines,
+            max_rows: 3,
+            ..Settings::default()
+        };
+        let bytes = ByteTotal::default();
+        let now = Local::now();
+        let excess = |items: &[u8]| settings.retention_excess(items, &bytes, |_| now, |_| 1);
+        assert_eq!(excess(&[1, 2]), 0);
+        assert_eq!(excess(&[1, 2, 3]), 0);
+        assert_eq!(excess(&[1, 2, 3, 4, 5]), 2);
+    }
+
+    #[test]
+    fn time_retention_drops_rows_older_than_the_window() {
+        let settings = Settings {
+            retention: RetentionPolicy::Time,
+            retention_minutes: 1.0,
+            ..Settings::default()
+        };
+        let bytes = ByteTotal::default();
+        let now = Local::now();
+        let ago = |secs| now - chrono::Duration::seconds(secs);
+        let items = [ago(300), ago(61), ago(59), ago(1), now];
+        let excess = settings.retention_excess(&items, &bytes, |&t| t, |_| 1);
+        assert_eq!(excess, 2);
+        assert_eq!(
+            settings.retention_excess(&items[2..], &bytes, |&t| t, |_| 1),
+            0
+        );
+    }
+
+    #[test]
+    fn size_retention_keeps_the_newest_bytes() {
+        let settings = Settings {
+            retention: RetentionPolicy::Size,
+            retention_mb: 10.0 / (1024.0 * 1024.0),
+            ..Settings::default()
+        };
+        let now = Local::now();
+        let bytes = ByteTotal::default();
+        let size = |s: &&str| s.len();
+        let mut items: Vec<&str> = Vec::new();
+        for item in ["abc", "defg", "hi", "jklmn", "o", "pqrstu"] {
+            items.push(item);
+            let drain = settings.retention_excess(&items, &bytes, |_| now, size);
+            bytes.remove(&items[..drain], size);
+            items.drain(..drain);
+            assert!(items.iter().map(size).sum::<usize>() <= 10);
+        }
+        assert_eq!(items, ["o", "pqrstu"]);
+        assert_eq!(bytes.total(&items, size), 7);
+        items.clear();
+        items.push("ab");
+        assert_eq!(bytes.total(&items, size), 2);
+    }
+}
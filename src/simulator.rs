@@ -0,0 +1,141 @@
+use std::sync::{mpsc, Arc};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use crate::serial_port::{IoCounters, SerialCommand, SerialEvent};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum SimMode {
+    Telemetry,
+    LogLines,
+    Bursts,
+}
+
+impl SimMode {
+    pub fn label(&self) -> &'static str {
+        match self {
+            SimMode::Telemetry => "Sine telemetry",
+            SimMode::LogLines => "Random log lines",
+            SimMode::Bursts => "Telemetry bursts",
+        }
+    }
+    pub fn all() -> &'static [SimMode] {
+        &[SimMode::Telemetry, SimMode::LogLines, SimMode::Bursts]
+    }
+}
+
+const LOG_MESSAGES: &[(&str, &str)] = &[
+    ("INFO", "main: heartbeat"),
+    ("INFO", "net: link up"),
+    ("DEBUG", "adc: sample ready"),
+    ("DEBUG", "sched: idle"),
+    ("WARN", "power: battery low"),
+    ("WARN", "i2c: retrying transfer"),
+    ("ERROR", "flash: write failed"),
+    ("INFO", "sensor: calibration done"),
+];
+
+/// Lines per burst and pause between bursts in `SimMode::Bursts`
+const BURST_LINES: u32 = 50;
+const BURST_PAUSE: Duration = Duration::from_secs(2);
+
+/// Small xorshift generator, good enough for fake data
+struct XorShift(u64);
+
+impl XorShift {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    /// Uniform value in [0, 1)
+    fn unit(&mut self) -> f64 {
+        (self.next() >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+fn telemetry_line(t: f64, rng: &mut XorShift) -> String {
+    let temp = 25.0 + 5.0 * (t * 0.2).sin() + (rng.unit() - 0.5) * 0.2;
+    let hum = 60.0 + 10.0 * (t * 0.05).cos() + (rng.unit() - 0.5) * 0.5;
+    let press = 1013.0 + 3.0 * (t * 0.01).sin();
+    format!("T={:.2},H={:.2},P={:.0}", temp, hum, press)
+}
+
+fn log_line(t: f64, rng: &mut XorShift) -> String {
+    let (level, msg) = LOG_MESSAGES[(rng.next() % LOG_MESSAGES.len() as u64) as usize];
+    format!("[{:10.3}] {}: {}", t, level, msg)
+}
+
+pub fn run_simulator_thread(
+    mode: SimMode,
+    rate_hz: f64,
+    cmd_rx: mpsc::Receiver<SerialCommand>,
+    event_tx: mpsc::SyncSender<SerialEvent>,
+    counters: Arc<IoCounters>,
+) {
+    let _ = event_tx.send(SerialEvent::Connected);
+
+    let period = Duration::from_secs_f64(1.0 / rate_hz.max(0.1));
+    let start = Instant::now();
+    let mut rng = XorShift(0x2545_F491_4F6C_DD1D);
+    let mut next_line = start;
+    let mut burst_count = 0;
+
+    loop {
+        loop {
+            match cmd_rx.try_recv() {
+                Ok(SerialCommand::Disconnect) | Err(mpsc::TryRecvError::Disconnected) => {
+                    let _ = event_tx.send(SerialEvent::Disconnected);
+                    return;
+                }
+                Ok(SerialCommand::Send(data)) => {
+                    counters.add_tx(data.len());
+                    // Echo sent commands like a device CLI would
+                    let text = String::from_utf8_lossy(&data);
+                    let text = text.trim_end_matches(['\r', '\n']);
+                    let echo = format!("> {}", text);
+                    counters.add_rx(echo.len() + 1);
+                    let _ = event_tx.send(SerialEvent::Data(echo));
+                }
+                Ok(SerialCommand::SetRxLineEnding(_))
+                | Ok(SerialCommand::SetEncoding(_))
+                | Ok(SerialCommand::FlushBuffers)
+                | Ok(SerialCommand::SetRts(_))
+                | Ok(SerialCommand::SetDtr(_))
+                | Ok(SerialCommand::Reconfigure(_)) => {}
+                Err(mpsc::TryRecvError::Empty) => break,
+            }
+        }
+
+        let now = Instant::now();
+        if now >= next_line {
+            let t = now.duration_since(start).as_secs_f64();
+            let line = match mode {
+                SimMode::Telemetry | SimMode::Bursts => telemetry_line(t, &mut rng),
+                SimMode::LogLines => log_line(t, &mut rng),
+            };
+            counters.add_rx(line.len() + 1);
+            let _ = event_tx.send(SerialEvent::Data(line));
+
+            next_line += period;
+            if mode == SimMode::Bursts {
+                burst_count += 1;
+                if burst_count >= BURST_LINES {
+                    burst_count = 0;
+                    next_line += BURST_PAUSE;
+                }
+            }
+            // Don't try to catch up after a stall
+            if next_line < now {
+                next_line = now;
+            }
+        }
+
+        let wait = next_line.saturating_duration_since(Instant::now());
+        thread::sleep(wait.min(Duration::from_millis(20)));
+    }
+}
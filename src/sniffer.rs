@@ -0,0 +1,178 @@
+// Two-port sniffer: bytes arriving on the host port are forwarded to the
+// device port and vice versa, and both streams are split into lines tagged
+// with their direction.
+
+use std::io;
+use std::sync::{mpsc, Arc};
+use std::thread;
+use std::time::Duration;
+
+use encoding_rs::Encoding;
+use serialport::SerialPort;
+
+use crate::serial_port::{take_lines, Direction, IoCounters, SerialCommand, SerialEvent};
+use crate::settings::LineEnding;
+
+/// Sleep between polls when neither port has data
+const IDLE_SLEEP: Duration = Duration::from_millis(1);
+
+/// Copies whatever is waiting on `from` to `to`; returns the bytes moved
+fn forward<'a>(
+    from: &mut dyn SerialPort,
+    to: &mut dyn SerialPort,
+    buf: &'a mut [u8],
+) -> io::Result<&'a [u8]> {
+    let available = from.bytes_to_read()? as usize;
+    if available == 0 {
+        return Ok(&[]);
+    }
+    let len = available.min(buf.len());
+    let n = from.read(&mut buf[..len])?;
+    to.write_all(&buf[..n])?;
+    Ok(&buf[..n])
+}
+
+fn emit_lines(
+    buf: &mut Vec<u8>,
+    dir: Direction,
+    line_ending: &LineEnding,
+    encoding: &'static Encoding,
+    event_tx: &mpsc::SyncSender<SerialEvent>,
+) {
+    for line in take_lines(buf, line_ending, encoding) {
+        let _ = event_tx.send(SerialEvent::Traffic(dir, line));
+    }
+}
+
+pub fn run_sniffer_thread(
+    mut host: Box<dyn SerialPort>,
+    mut device: Box<dyn SerialPort>,
+    cmd_rx: mpsc::Receiver<SerialCommand>,
+    event_tx: mpsc::SyncSender<SerialEvent>,
+    counters: Arc<IoCounters>,
+    mut line_ending: LineEnding,
+    mut encoding: &'static Encoding,
+) {
+    let mut tx_buf = Vec::<u8>::with_capacity(4096);
+    let mut rx_buf = Vec::<u8>::with_capacity(4096);
+    let mut read_buf = [0u8; 1024];
+
+    loop {
+        loop {
+            match cmd_rx.try_recv() {
+                Ok(SerialCommand::Disconnect) | Err(mpsc::TryRecvError::Disconnected) => {
+                    let _ = event_tx.send(SerialEvent::Disconnected);
+                    return;
+                }
+                Ok(SerialCommand::Send(data)) => {
+                    // Injected as if the host had sent it
+                    match device.write_all(&data) {
+                        Ok(()) => {
+                            counters.add_tx(data.len());
+                            tx_buf.extend_from_slice(&data);
+                            emit_lines(
+                                &mut tx_buf,
+                                Direction::Tx,
+                                &line_ending,
+                                encoding,
+                                &event_tx,
+                            );
+                        }
+                        Err(e) => {
+                            let _ =
+                                event_tx.send(SerialEvent::Error(format!("Write error: {}", e)));
+                        }
+                    }
+                }
+                Ok(SerialCommand::SetRxLineEnding(le)) => {
+                    line_ending = le;
+                    emit_lines(
+                        &mut tx_buf,
+                        Direction::Tx,
+                        &line_ending,
+                        encoding,
+                        &event_tx,
+                    );
+                    emit_lines(
+                        &mut rx_buf,
+                        Direction::Rx,
+                        &line_ending,
+                        encoding,
+                        &event_tx,
+                    );
+                }
+                Ok(SerialCommand::SetEncoding(enc)) => {
+                    encoding = enc;
+                    emit_lines(
+                        &mut tx_buf,
+                        Direction::Tx,
+                        &line_ending,
+                        encoding,
+                        &event_tx,
+                    );
+                    emit_lines(
+                        &mut rx_buf,
+                        Direction::Rx,
+                        &line_ending,
+                        encoding,
+                        &event_tx,
+                    );
+                }
+                Ok(SerialCommand::FlushBuffers) => {
+                    tx_buf.clear();
+                    rx_buf.clear();
+                    for port in [&mut host, &mut device] {
+                        if let Err(e) = port.clear(serialport::ClearBuffer::All) {
+                            let _ =
+                                event_tx.send(SerialEvent::Warning(format!("Flush failed: {}", e)));
+                        }
+                    }
+                }
+                Ok(SerialCommand::Reconfigure(config)) => {
+                    for port in [&mut host, &mut device] {
+                        if let Err(e) = config.apply(&mut **port) {
+                            let _ = event_tx
+                                .send(SerialEvent::Warning(format!("Reconfigure failed: {}", e)));
+                        }
+                    }
+                }
+                // Control lines belong to the devices being sniffed
+                Ok(SerialCommand::SetRts(_)) | Ok(SerialCommand::SetDtr(_)) => {}
+                Err(mpsc::TryRecvError::Empty) => break,
+            }
+        }
+
+        let mut idle = true;
+        for dir in [Direction::Tx, Direction::Rx] {
+            let (from, to, buf) = match dir {
+                Direction::Tx => (&mut host, &mut device, &mut tx_buf),
+                Direction::Rx => (&mut device, &mut host, &mut rx_buf),
+            };
+            match forward(&mut **from, &mut **to, &mut read_buf) {
+                Ok([]) => {}
+                Ok(data) => {
+                    idle = false;
+                    match dir {
+                        Direction::Tx => counters.add_tx(data.len()),
+                        Direction::Rx => counters.add_rx(data.len()),
+                    }
+                    buf.extend_from_slice(data);
+                    emit_lines(buf, dir, &line_ending, encoding, &event_tx);
+                }
+                Err(e) => {
+                    let _ = event_tx.send(SerialEvent::Error(format!(
+                        "{} forwarding error: {}",
+                        dir.label(),
+                        e
+                    )));
+                    let _ = event_tx.send(SerialEvent::Disconnected);
+                    return;
+                }
+            }
+        }
+
+        if idle {
+            thread::sleep(IDLE_SLEEP);
+        }
+    }
+}
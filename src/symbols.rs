@@ -0,0 +1,230 @@
+// Address symbolication: with the firmware ELF (or a GNU ld map file)
+// loaded, hex addresses in received lines, e.g. the PC and LR values of a
+// panic or HardFault dump, are rewritten to "function+0x12 (file:line)" so
+// backtraces don't have to be decoded by hand with addr2line. Function names
+// and sizes come from the symbol table, source lines from the DWARF debug
+// info; map files only give names.
+//
+// ESP-IDF panic output carries its call stack as "Backtrace: PC:SP PC:SP ...";
+// like `idf.py monitor`, such a line is kept as it is and followed by one
+// resolved line per frame.
+
+use std::borrow::Cow;
+use std::rc::Rc;
+
+use gimli::{EndianRcSlice, RunTimeEndian};
+use object::{Architecture, Object, ObjectSection, ObjectSymbol, SymbolKind};
+use regex::{Captures, Regex};
+
+use crate::settings::Settings;
+
+type DwarfReader = EndianRcSlice<RunTimeEndian>;
+
+/// A symbol without a size (map files, assembly labels) covers the addresses
+/// up to the next symbol, but at most this many
+const MAX_GAP: u64 = 64 * 1024;
+
+/// "0x08001234" or "0x0800_1234"
+const ADDRESS_PATTERN: &str = r"\b0x[0-9A-Fa-f](?:_?[0-9A-Fa-f]){3,15}\b";
+
+/// ESP-IDF's "Backtrace: 0x400d1234:0x3ffb5e10 0x400d5678:0x3ffb5e30 ..."
+const BACKTRACE_PATTERN: &str = r"Backtrace:\s*((?:0x[0-9A-Fa-f]+:0x[0-9A-Fa-f]+\s*)+)";
+
+struct Symbol {
+    start: u64,
+    end: u64,
+    name: String,
+}
+
+pub struct SymbolTable {
+    /// Sorted by start address
+    symbols: Vec<Symbol>,
+    /// Source lines, for ELF files with debug info
+    lines: Option<addr2line::Context<DwarfReader>>,
+    /// Thumb code addresses have bit 0 set
+    thumb: bool,
+    address_re: Regex,
+    backtrace_re: Regex,
+}
+
+impl SymbolTable {
+    /// Loads an ELF file, or a linker map if the file isn't an object file
+    pub fn load(path: &str) -> Result<Self, String> {
+        let data = std::fs::read(path).map_err(|e| format!("Cannot read {}: {}", path, e))?;
+        let (symbols, lines, thumb) = match object::File::parse(&*data) {
+            Ok(file) => (
+                elf_symbols(&file),
+                line_context(&file),
+                file.architecture() == Architecture::Arm,
+            ),
+            Err(_) => (map_symbols(&String::from_utf8_lossy(&data)), None, false),
+        };
+        if symbols.is_empty() {
+            return Err(format!("{} has no function symbols", path));
+        }
+        Ok(Self {
+            symbols: with_ends(symbols),
+            lines,
+            thumb,
+            address_re: Regex::new(ADDRESS_PATTERN).expect("valid address pattern"),
+            backtrace_re: Regex::new(BACKTRACE_PATTERN).expect("valid backtrace pattern"),
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.symbols.len()
+    }
+
+    /// Whether source lines are known, i.e. the ELF has debug info
+    pub fn has_lines(&self) -> bool {
+        self.lines.is_some()
+    }
+
+    /// `line` with every address inside a known symbol rewritten
+    pub fn rewrite<'a>(&self, line: &'a str) -> Cow<'a, str> {
+        self.address_re.replace_all(line, |caps: &Captures| {
+            let text = &caps[0];
+            u64::from_str_radix(&text[2..].replace('_', ""), 16)
+                .ok()
+                .and_then(|address| self.describe(address))
+                .unwrap_or_else(|| text.to_string())
+        })
+    }
+
+    /// The resolved frames of an ESP-IDF backtrace line, one line each as
+    /// `idf.py monitor` shows them; None if `line` isn't a backtrace
+    pub fn expand_backtrace(&self, line: &str) -> Option<Vec<String>> {
+        let caps = self.backtrace_re.captures(line)?;
+        let frames = caps[1]
+            .split_whitespace()
+            .filter_map(|frame| frame.split(':').next())
+            .map(|pc| {
+                let described = u64::from_str_radix(&pc[2..], 16)
+                    .ok()
+                    .and_then(|address| self.describe(address));
+                format!("--- {}: {}", pc, described.as_deref().unwrap_or("??"))
+            })
+            .collect();
+        Some(frames)
+    }
+
+    /// "function+0x12 (file:line)", or None outside every symbol
+    fn describe(&self, address: u64) -> Option<String> {
+        let address = if self.thumb { address & !1 } else { address };
+        let index = self.symbols.partition_point(|s| s.start <= address);
+        let symbol = &self.symbols[index.checked_sub(1)?];
+        if address >= symbol.end {
+            return None;
+        }
+        let mut text = symbol.name.clone();
+        if address > symbol.start {
+            text.push_str(&format!("+{:#x}", address - symbol.start));
+        }
+        let location = self
+            .lines
+            .as_ref()
+            .and_then(|lines| lines.find_location(address).ok().flatten());
+        if let Some(location) = location {
+            if let Some(file) = location.file {
+                text.push_str(&format!(" ({}", file));
+                if let Some(line) = location.line {
+                    text.push_str(&format!(":{}", line));
+                }
+                text.push(')');
+            }
+        }
+        Some(text)
+    }
+}
+
+fn elf_symbols(file: &object::File) -> Vec<Symbol> {
+    let thumb = file.architecture() == Architecture::Arm;
+    file.symbols()
+        .filter(|s| s.kind() == SymbolKind::Text && s.address() != 0)
+        .filter_map(|s| {
+            let name = s.name().ok().filter(|n| !n.is_empty())?;
+            let start = if thumb { s.address() & !1 } else { s.address() };
+            Some(Symbol {
+                start,
+                end: start + s.size(),
+                name: format!("{:#}", rustc_demangle::demangle(name)),
+            })
+        })
+        .collect()
+}
+
+/// Symbol lines of a GNU ld map, e.g. "                0x08000400                main"
+fn map_symbols(map: &str) -> Vec<Symbol> {
+    let symbol_re =
+        Regex::new(r"^\s+0x([0-9A-Fa-f]+)\s+([A-Za-z_.$][\w.$]*)\s*$").expect("valid map pattern");
+    map.lines()
+        .filter_map(|line| {
+            let caps = symbol_re.captures(line)?;
+            let start = u64::from_str_radix(&caps[1], 16).ok().filter(|&a| a != 0)?;
+            Some(Symbol {
+                start,
+                end: start,
+                name: format!("{:#}", rustc_demangle::demangle(&caps[2])),
+            })
+        })
+        .collect()
+}
+
+/// Sorts the symbols and extends those without a size to the next one
+fn with_ends(mut symbols: Vec<Symbol>) -> Vec<Symbol> {
+    symbols.sort_by(|a, b| a.start.cmp(&b.start).then(b.end.cmp(&a.end)));
+    // Aliases of one address: keep the first, which has the largest size
+    symbols.dedup_by_key(|s| s.start);
+    let starts: Vec<u64> = symbols.iter().map(|s| s.start).collect();
+    for (i, symbol) in symbols.iter_mut().enumerate() {
+        if symbol.end <= symbol.start {
+            let next = starts.get(i + 1).copied().unwrap_or(u64::MAX);
+            symbol.end = next.min(symbol.start.saturating_add(MAX_GAP));
+        }
+    }
+    symbols
+}
+
+/// DWARF line info of the file; None without debug info
+fn line_context(file: &object::File) -> Option<addr2line::Context<DwarfReader>> {
+    file.section_by_name(".debug_info")?;
+    let endian = if file.is_little_endian() {
+        RunTimeEndian::Little
+    } else {
+        RunTimeEndian::Big
+    };
+    let load = |id: gimli::SectionId| -> Result<DwarfReader, gimli::Error> {
+        let data = file
+            .section_by_name(id.name())
+            .and_then(|s| s.uncompressed_data().ok())
+            .unwrap_or_default();
+        Ok(EndianRcSlice::new(Rc::from(&*data), endian))
+    };
+    let dwarf = gimli::Dwarf::load(load).ok()?;
+    addr2line::Context::from_dwarf(dwarf).ok()
+}
+
+/// Settings window section for address symbolication
+pub fn symbol_editor(ui: &mut egui::Ui, settings: &mut Settings) {
+    ui.horizontal(|ui| {
+        ui.label("ELF or map file:");
+        ui.add(
+            egui::TextEdit::singleline(&mut settings.symbol_file)
+                .hint_text("target/thumbv7em-none-eabihf/debug/firmware")
+                .desired_width(f32::INFINITY),
+        );
+    });
+    ui.checkbox(
+        &mut settings.expand_backtraces,
+        "Expand ESP-IDF backtraces into one line per frame",
+    )
+    .on_hover_text(
+        "\"Backtrace: 0x400d1234:0x3ffb5e10 ...\" lines are kept as they are and followed \
+         by the resolved call stack, as idf.py monitor shows it",
+    );
+    ui.label(
+        "Hex addresses in received lines, such as 0x0800_1234, are replaced by \
+         function+offset (file:line). Source lines need an ELF with debug info; a linker \
+         map gives function names only. The file is reloaded when settings are applied.",
+    );
+}
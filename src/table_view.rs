@@ -0,0 +1,218 @@
+use std::cmp::Ordering;
+use std::ops::Range;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SortKey {
+    Time,
+    Column(usize),
+}
+
+/// Visible rows of the table in display order, as indices into the row buffer.
+/// Rebuilt lazily whenever the sort order or filters change; rows appended to
+/// the buffer are inserted in place and rows dropped from its front removed.
+pub struct TableView {
+    /// Sort key and ascending flag; None = arrival order
+    pub sort: Option<(SortKey, bool)>,
+    indices: Vec<usize>,
+    /// Display position of the newest visible row
+    newest_pos: Option<usize>,
+    /// Rows of the buffer the view covers
+    range: Range<usize>,
+    dirty: bool,
+}
+
+impl TableView {
+    pub fn new() -> Self {
+        Self {
+            sort: None,
+            indices: Vec::new(),
+            newest_pos: None,
+            range: 0..0,
+            dirty: true,
+        }
+    }
+
+    pub fn invalidate(&mut self) {
+        self.dirty = true;
+    }
+
+    /// True if the view shows rows `start..end` as they are
+    pub fn is_current(&self, start: usize, end: usize) -> bool {
+        !self.dirty && self.range == (start..end)
+    }
+
+    /// Follows `n` rows being dropped from the front of the buffer
+    pub fn drain(&mut self, n: usize) {
+        if self.dirty {
+            return;
+        }
+        self.indices.retain(|&i| i >= n);
+        for i in &mut self.indices {
+            *i -= n;
+        }
+        self.range = self.range.start.saturating_sub(n)..self.range.end.saturating_sub(n);
+        self.newest_pos = self.newest_position();
+    }
+
+    /// Cycles the sort on `key`: ascending -> descending -> arrival order
+    pub fn toggle_sort(&mut self, key: SortKey) {
+        self.sort = match self.sort {
+            Some((k, true)) if k == key => Some((key, false)),
+            Some((k, false)) if k == key => None,
+            _ => Some((key, true)),
+        };
+        self.dirty = true;
+    }
+
+    /// Arrow shown next to a header sorted by `key`
+    pub fn sort_indicator(&self, key: SortKey) -> &'static str {
+        match self.sort {
+            Some((k, true)) if k == key => " ▲",
+            Some((k, false)) if k == key => " ▼",
+            _ => "",
+        }
+    }
+
+    /// Brings the view up to rows `start..end` that pass `keep`, ordered by
+    /// `cmp`. Rows added at the end since the last update are inserted by
+    /// binary search; anything else rebuilds the view.
+    pub fn update(
+        &mut self,
+        start: usize,
+        end: usize,
+        keep: impl Fn(usize) -> bool,
+        cmp: impl Fn(usize, usize) -> Ordering,
+    ) {
+        if self.dirty || start != self.range.start || end < self.range.end {
+            self.rebuild(start, end, keep, cmp);
+            return;
+        }
+        for i in self.range.end..end {
+            if !keep(i) {
+                continue;
+            }
+            // After the rows comparing equal, like the stable sort does
+            let pos = match self.sort {
+                Some((_, ascending)) => self.indices.partition_point(|&j| {
+                    let ord = cmp(j, i);
+                    (if ascending { ord } else { ord.reverse() }) != Ordering::Greater
+                }),
+                None => self.indices.len(),
+            };
+            self.indices.insert(pos, i);
+            self.newest_pos = Some(pos);
+        }
+        self.range = start..end;
+    }
+
+    fn rebuild(
+        &mut self,
+        start: usize,
+        end: usize,
+        keep: impl Fn(usize) -> bool,
+        cmp: impl Fn(usize, usize) -> Ordering,
+    ) {
+        self.dirty = false;
+        self.range = start..end;
+        self.indices = (start..end).filter(|&i| keep(i)).collect();
+        if let Some((_, ascending)) = self.sort {
+            self.indices.sort_by(|&a, &b| {
+                let ord = cmp(a, b);
+                if ascending {
+                    ord
+                } else {
+                    ord.reverse()
+                }
+            });
+        }
+        self.newest_pos = self.newest_position();
+    }
+
+    fn newest_position(&self) -> Option<usize> {
+        let newest = self.indices.iter().copied().max();
+        newest.and_then(|n| self.indices.iter().position(|&i| i == n))
+    }
+
+    pub fn len(&self) -> usize {
+        self.indices.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.indices.is_empty()
+    }
+
+    /// Row index shown at display position `pos`
+    pub fn row(&self, pos: usize) -> Option<usize> {
+        self.indices.get(pos).copied()
+    }
+
+    /// Display position of row index `row`, if visible
+    pub fn pos_of(&self, row: usize) -> Option<usize> {
+        self.indices.iter().position(|&i| i == row)
+    }
+
+    /// Display position to follow when auto-scrolling
+    pub fn newest_pos(&self) -> Option<usize> {
+        self.newest_pos
+    }
+}
+
+/// Compares two cells: numbers first, in numeric order, then text. Ties
+/// between equal numbers written differently ("1" and "1.0") go by the text,
+/// so this is a total order.
+pub fn compare_cells(a: &str, b: &str) -> Ordering {
+    match (a.trim().parse::<f64>(), b.trim().parse::<f64>()) {
+        (Ok(x), Ok(y)) => x.total_cmp(&y).then_with(|| a.cmp(b)),
+        (Ok(_), Err(_)) => Ordering::Less,
+        (Err(_), Ok(_)) => Ordering::Greater,
+        (Err(_), Err(_)) => a.cmp(b),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn numbers_sort_before_text() {
+        let mut cells = vec!["b", "10", "NaN", "a", "-1", "2", " 2", "1e1"];
+        cells.sort_by(|a, b| compare_cells(a, b));
+        assert_eq!(cells, ["-1", " 2", "2", "10", "1e1", "NaN", "a", "b"]);
+    }
+
+    #[test]
+    fn appended_rows_land_where_a_rebuild_puts_them() {
+        let cells = ["5", "x", "1", "5", "3", "", "1", "9", "x", "0"];
+        let keep = |i: usize| i != 4;
+        let cmp = |a: usize, b: usize| compare_cells(cells[a], cells[b]);
+        for ascending in [true, false] {
+            let mut incremental = TableView::new();
+            incremental.sort = Some((SortKey::Column(0), ascending));
+            for end in 1..=cells.len() {
+                incremental.update(0, end, keep, cmp);
+            }
+            let mut rebuilt = TableView::new();
+            rebuilt.sort = Some((SortKey::Column(0), ascending));
+            rebuilt.update(0, cells.len(), keep, cmp);
+            assert_eq!(incremental.indices, rebuilt.indices);
+            assert_eq!(incremental.newest_pos(), rebuilt.newest_pos());
+        }
+    }
+
+    #[test]
+    fn drain_shifts_the_indices() {
+        let mut view = TableView::new();
+        view.update(0, 6, |i| i % 2 == 0, |a, b| a.cmp(&b));
+        view.drain(3);
+        assert_eq!(view.indices, [1]);
+        assert!(view.is_current(0, 3));
+    }
+
+    #[test]
+    fn first_position_is_the_first_visible_row() {
+        let mut view = TableView::new();
+        view.update(3, 8, |i| i != 3, |a, b| a.cmp(&b));
+        assert_eq!(view.row(0), Some(4));
+        assert_eq!(view.row(view.len() - 1), Some(7));
+    }
+}
@@ -0,0 +1,445 @@
+// Terminal view: a minimal VT100/ANSI emulator fed with the received bytes,
+// for interactive firmware shells and menus (U-Boot, Zephyr shell) that move
+// the cursor, clear the screen and redraw lines. The screen is sized to the
+// view; lines scrolled off the top are kept as scrollback. While the view has
+// focus, keys go straight to the port: printable text in the text encoding,
+// Enter as CR, Backspace as DEL, arrows and editing keys as escape sequences
+// and Ctrl+letter as the control character.
+
+use egui::text::LayoutJob;
+use egui::{Color32, FontId};
+use encoding_rs::{Decoder, Encoding};
+
+use crate::ansi::{self, Style};
+
+/// Lines kept above the screen
+const SCROLLBACK: usize = 2000;
+
+/// Longest escape sequence buffered; longer ones are dropped
+const MAX_SEQUENCE: usize = 256;
+
+#[derive(Clone, Copy)]
+struct Cell {
+    ch: char,
+    style: Style,
+}
+
+const BLANK: Cell = Cell {
+    ch: ' ',
+    style: Style::PLAIN,
+};
+
+pub struct Terminal {
+    cols: usize,
+    /// The screen, `cols` cells per line
+    lines: Vec<Vec<Cell>>,
+    scrollback: Vec<Vec<Cell>>,
+    row: usize,
+    col: usize,
+    saved: (usize, usize),
+    style: Style,
+    cursor_visible: bool,
+    /// Escape sequence being received
+    sequence: Option<String>,
+    decoder: Option<(&'static Encoding, Decoder)>,
+    /// Replies and keystrokes waiting to be sent to the port
+    output: Vec<u8>,
+    /// The view is shown. Status queries are only answered then, since the
+    /// output isn't sent otherwise.
+    pub active: bool,
+}
+
+impl Default for Terminal {
+    fn default() -> Self {
+        Self {
+            cols: 80,
+            lines: vec![vec![BLANK; 80]; 24],
+            scrollback: Vec::new(),
+            row: 0,
+            col: 0,
+            saved: (0, 0),
+            style: Style::PLAIN,
+            cursor_visible: true,
+            sequence: None,
+            decoder: None,
+            output: Vec::new(),
+            active: false,
+        }
+    }
+}
+
+impl Terminal {
+    pub fn clear(&mut self) {
+        let (cols, rows) = (self.cols, self.lines.len());
+        *self = Self::default();
+        self.resize(cols, rows);
+    }
+
+    /// Bytes to send to the port: typed keys and answers to status queries
+    pub fn take_output(&mut self) -> Vec<u8> {
+        std::mem::take(&mut self.output)
+    }
+
+    /// Interprets received bytes; a character split across reads is held back
+    pub fn feed(&mut self, data: &[u8], encoding: &'static Encoding) {
+        if self.decoder.as_ref().is_none_or(|(e, _)| *e != encoding) {
+            self.decoder = Some((encoding, encoding.new_decoder_without_bom_handling()));
+        }
+        let Some((_, decoder)) = &mut self.decoder else {
+            return;
+        };
+        let capacity = decoder.max_utf8_buffer_length(data.len()).unwrap_or(0);
+        let mut text = String::with_capacity(capacity);
+        let _ = decoder.decode_to_string(data, &mut text, false);
+        for c in text.chars() {
+            self.put(c);
+        }
+    }
+
+    fn put(&mut self, c: char) {
+        if let Some(sequence) = &mut self.sequence {
+            sequence.push(c);
+            if sequence_complete(sequence) {
+                let sequence = self.sequence.take().unwrap_or_default();
+                self.escape(&sequence);
+            } else if sequence.len() > MAX_SEQUENCE
+                || (c.is_control() && !matches!(c, '\x07' | '\x1b'))
+            {
+                self.sequence = None;
+            }
+            return;
+        }
+        match c {
+            '\x1b' => self.sequence = Some(c.to_string()),
+            '\r' => self.col = 0,
+            '\n' | '\x0b' | '\x0c' => self.line_feed(),
+            '\x08' => self.col = self.col.saturating_sub(1).min(self.cols - 1),
+            '\t' => self.col = ((self.col / 8 + 1) * 8).min(self.cols - 1),
+            c if c.is_control() => {}
+            c => {
+                // The cursor stays past the last column until the next
+                // character wraps it
+                if self.col >= self.cols {
+                    self.col = 0;
+                    self.line_feed();
+                }
+                self.lines[self.row][self.col] = Cell {
+                    ch: c,
+                    style: self.style,
+                };
+                self.col += 1;
+            }
+        }
+    }
+
+    fn line_feed(&mut self) {
+        if self.row + 1 < self.lines.len() {
+            self.row += 1;
+            return;
+        }
+        let top = self.lines.remove(0);
+        self.scrollback.push(top);
+        if self.scrollback.len() > SCROLLBACK {
+            self.scrollback.remove(0);
+        }
+        self.lines.push(vec![BLANK; self.cols]);
+    }
+
+    fn escape(&mut self, sequence: &str) {
+        let (csi, _) = ansi::take_sequence(sequence);
+        match csi {
+            Some((params, command)) => self.csi(params, command),
+            None => match sequence.as_bytes().get(1) {
+                Some(b'7') => self.saved = (self.row, self.col),
+                Some(b'8') => (self.row, self.col) = self.saved,
+                Some(b'c') => self.clear(),
+                Some(b'M') => self.row = self.row.saturating_sub(1),
+                _ => {}
+            },
+        }
+    }
+
+    fn csi(&mut self, params: &str, command: char) {
+        if let Some(private) = params.strip_prefix('?') {
+            // Only cursor visibility; other modes (alternate screen,
+            // autowrap, bracketed paste) are accepted and ignored
+            if private == "25" {
+                self.cursor_visible = command == 'h';
+            }
+            return;
+        }
+        let args: Vec<usize> = params.split(';').map(|p| p.parse().unwrap_or(0)).collect();
+        let arg =
+            |i: usize, default: usize| args.get(i).copied().filter(|&n| n > 0).unwrap_or(default);
+        let (rows, cols) = (self.lines.len(), self.cols);
+        match command {
+            'A' => self.row = self.row.saturating_sub(arg(0, 1)),
+            'B' => self.row = (self.row + arg(0, 1)).min(rows - 1),
+            'C' => self.col = (self.col + arg(0, 1)).min(cols - 1),
+            'D' => self.col = self.col.min(cols - 1).saturating_sub(arg(0, 1)),
+            'E' => (self.row, self.col) = ((self.row + arg(0, 1)).min(rows - 1), 0),
+            'F' => (self.row, self.col) = (self.row.saturating_sub(arg(0, 1)), 0),
+            'G' => self.col = (arg(0, 1) - 1).min(cols - 1),
+            'd' => self.row = (arg(0, 1) - 1).min(rows - 1),
+            'H' | 'f' => {
+                self.row = (arg(0, 1) - 1).min(rows - 1);
+                self.col = (arg(1, 1) - 1).min(cols - 1);
+            }
+            'J' => self.erase_display(args[0]),
+            'K' => self.erase_line(args[0]),
+            'P' => {
+                let line = &mut self.lines[self.row];
+                let at = self.col.min(cols - 1);
+                let n = arg(0, 1).min(cols - at);
+                line.drain(at..at + n);
+                line.resize(cols, BLANK);
+            }
+            '@' => {
+                let line = &mut self.lines[self.row];
+                let at = self.col.min(cols - 1);
+                for _ in 0..arg(0, 1).min(cols - at) {
+                    line.insert(at, BLANK);
+                }
+                line.truncate(cols);
+            }
+            'L' => {
+                for _ in 0..arg(0, 1).min(rows - self.row) {
+                    self.lines.pop();
+                    self.lines.insert(self.row, vec![BLANK; cols]);
+                }
+            }
+            'M' => {
+                for _ in 0..arg(0, 1).min(rows - self.row) {
+                    self.lines.remove(self.row);
+                    self.lines.push(vec![BLANK; cols]);
+                }
+            }
+            'm' => self.style.apply_sgr(params),
+            's' => self.saved = (self.row, self.col),
+            'u' => (self.row, self.col) = self.saved,
+            // Device status report: shells ask for the cursor position to
+            // find out the terminal width
+            'n' if !self.active => {}
+            'n' if args[0] == 6 => {
+                let reply = format!("\x1b[{};{}R", self.row + 1, self.col.min(cols - 1) + 1);
+                self.output.extend_from_slice(reply.as_bytes());
+            }
+            'n' if args[0] == 5 => self.output.extend_from_slice(b"\x1b[0n"),
+            _ => {}
+        }
+    }
+
+    fn erase_display(&mut self, mode: usize) {
+        match mode {
+            0 => {
+                self.erase_line(0);
+                for line in &mut self.lines[self.row + 1..] {
+                    line.fill(BLANK);
+                }
+            }
+            1 => {
+                self.erase_line(1);
+                for line in &mut self.lines[..self.row] {
+                    line.fill(BLANK);
+                }
+            }
+            _ => {
+                for line in &mut self.lines {
+                    line.fill(BLANK);
+                }
+            }
+        }
+    }
+
+    fn erase_line(&mut self, mode: usize) {
+        let line = &mut self.lines[self.row];
+        let col = self.col.min(self.cols);
+        match mode {
+            0 => line[col..].fill(BLANK),
+            1 => line[..(col + 1).min(self.cols)].fill(BLANK),
+            _ => line.fill(BLANK),
+        }
+    }
+
+    /// Fits the screen to the view; lines pushed off the top go to the
+    /// scrollback
+    fn resize(&mut self, cols: usize, rows: usize) {
+        if cols != self.cols {
+            for line in self.lines.iter_mut().chain(&mut self.scrollback) {
+                line.resize(cols, BLANK);
+            }
+            self.cols = cols;
+        }
+        while self.lines.len() > rows {
+            if self.row == 0 {
+                self.lines.pop();
+            } else {
+                let top = self.lines.remove(0);
+                self.scrollback.push(top);
+                self.row -= 1;
+            }
+        }
+        while self.lines.len() < rows {
+            self.lines.push(vec![BLANK; cols]);
+        }
+        self.col = self.col.min(cols);
+        self.saved = (self.saved.0.min(rows - 1), self.saved.1.min(cols - 1));
+    }
+
+    /// Renders scrollback and screen; keys typed while the view has focus
+    /// are queued for the port
+    pub fn show(&mut self, ui: &mut egui::Ui, encoding: &'static Encoding) {
+        let font_id = egui::FontId::monospace(13.0);
+        let char_width = ui.fonts(|f| f.glyph_width(&font_id, 'M'));
+        let row_height = ui.fonts(|f| f.row_height(&font_id));
+        let size = ui.available_size();
+        let cols = ((size.x - 16.0) / char_width).floor().max(20.0) as usize;
+        let rows = (size.y / row_height).floor().max(5.0) as usize;
+        self.resize(cols, rows);
+
+        let response = ui.interact(
+            ui.available_rect_before_wrap(),
+            ui.id().with("terminal"),
+            egui::Sense::click(),
+        );
+        if response.clicked() {
+            response.request_focus();
+        }
+        let focused = response.has_focus();
+        if focused {
+            ui.memory_mut(|m| {
+                m.set_focus_lock_filter(
+                    response.id,
+                    egui::EventFilter {
+                        tab: true,
+                        horizontal_arrows: true,
+                        vertical_arrows: true,
+                        escape: true,
+                    },
+                );
+            });
+            let events = ui.input(|i| i.events.clone());
+            for event in &events {
+                self.key(event, encoding);
+            }
+        }
+
+        let default_fg = ui.visuals().text_color();
+        let cursor = (focused && self.cursor_visible).then_some((self.row, self.col));
+        egui::ScrollArea::vertical()
+            .auto_shrink(false)
+            .stick_to_bottom(true)
+            .show_rows(
+                ui,
+                row_height,
+                self.scrollback.len() + self.lines.len(),
+                |ui, range| {
+                    ui.spacing_mut().item_spacing.y = 0.0;
+                    for i in range {
+                        let (line, cursor_col) = match i.checked_sub(self.scrollback.len()) {
+                            Some(row) => (
+                                &self.lines[row],
+                                cursor.filter(|&(r, _)| r == row).map(|(_, c)| c),
+                            ),
+                            None => (&self.scrollback[i], None),
+                        };
+                        let job = line_layout(line, cursor_col, &font_id, default_fg);
+                        ui.add(egui::Label::new(job).extend().selectable(false));
+                    }
+                },
+            );
+        if !focused {
+            ui.painter().text(
+                response.rect.right_top() + egui::vec2(-20.0, 4.0),
+                egui::Align2::RIGHT_TOP,
+                "Click to type",
+                egui::FontId::proportional(11.0),
+                Color32::GRAY,
+            );
+        }
+    }
+
+    /// Queues the bytes a key event sends
+    fn key(&mut self, event: &egui::Event, encoding: &'static Encoding) {
+        let bytes: Vec<u8> = match event {
+            egui::Event::Text(text) | egui::Event::Paste(text) => encoding.encode(text).0.to_vec(),
+            egui::Event::Copy => vec![0x03],
+            egui::Event::Cut => vec![0x18],
+            egui::Event::Key {
+                key,
+                pressed: true,
+                modifiers,
+                ..
+            } => match key {
+                egui::Key::Enter => b"\r".to_vec(),
+                egui::Key::Backspace => vec![0x7f],
+                egui::Key::Tab => b"\t".to_vec(),
+                egui::Key::Escape => vec![0x1b],
+                egui::Key::ArrowUp => b"\x1b[A".to_vec(),
+                egui::Key::ArrowDown => b"\x1b[B".to_vec(),
+                egui::Key::ArrowRight => b"\x1b[C".to_vec(),
+                egui::Key::ArrowLeft => b"\x1b[D".to_vec(),
+                egui::Key::Home => b"\x1b[H".to_vec(),
+                egui::Key::End => b"\x1b[F".to_vec(),
+                egui::Key::Insert => b"\x1b[2~".to_vec(),
+                egui::Key::Delete => b"\x1b[3~".to_vec(),
+                egui::Key::PageUp => b"\x1b[5~".to_vec(),
+                egui::Key::PageDown => b"\x1b[6~".to_vec(),
+                key if modifiers.ctrl => match key.name().as_bytes() {
+                    [letter @ b'A'..=b'Z'] => vec![letter - b'A' + 1],
+                    _ => return,
+                },
+                _ => return,
+            },
+            _ => return,
+        };
+        self.output.extend(bytes);
+    }
+}
+
+/// Whether an escape sequence starting with ESC is complete
+fn sequence_complete(sequence: &str) -> bool {
+    let bytes = sequence.as_bytes();
+    match bytes.get(1) {
+        None => false,
+        Some(b'[') => bytes.len() > 2 && (0x40..=0x7e).contains(&bytes[bytes.len() - 1]),
+        Some(b']') => sequence.ends_with('\x07') || sequence.ends_with("\x1b\\"),
+        // Character set selection takes one more character
+        Some(b'(' | b')') => bytes.len() > 2,
+        Some(_) => true,
+    }
+}
+
+/// One line as runs of equally styled text, with the cursor cell inverted
+fn line_layout(
+    line: &[Cell],
+    cursor: Option<usize>,
+    font_id: &FontId,
+    default_fg: Color32,
+) -> LayoutJob {
+    // Trailing blanks are left out, except up to the cursor
+    let used = line
+        .iter()
+        .rposition(|c| c.ch != ' ' || c.style != Style::PLAIN)
+        .map_or(0, |i| i + 1);
+    let end = cursor.map_or(used, |c| used.max(c + 1)).min(line.len());
+    let mut job = LayoutJob::default();
+    let mut run = String::new();
+    let mut run_style: Option<Style> = None;
+    for (i, cell) in line[..end].iter().enumerate() {
+        let mut style = cell.style;
+        if cursor == Some(i) {
+            style.toggle_inverse();
+        }
+        if run_style.is_some_and(|s| s != style) {
+            let format = run_style.unwrap_or_default().format(font_id, default_fg);
+            job.append(&std::mem::take(&mut run), 0.0, format);
+        }
+        run_style = Some(style);
+        run.push(cell.ch);
+    }
+    if let Some(style) = run_style {
+        job.append(&run, 0.0, style.format(font_id, default_fg));
+    }
+    job
+}
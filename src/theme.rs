@@ -0,0 +1,167 @@
+// Themes: egui's dark or light look, or whichever the OS uses, and the colors
+// of the table and raw log. Each color can be overridden in the settings;
+// colors left alone follow the theme, so the defaults stay readable on both
+// backgrounds.
+
+use egui::Color32;
+use serde::{Deserialize, Serialize};
+
+use crate::settings::Settings;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub enum Theme {
+    #[default]
+    Dark,
+    Light,
+    /// Follow the operating system
+    System,
+}
+
+impl Theme {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Theme::Dark => "Dark",
+            Theme::Light => "Light",
+            Theme::System => "System",
+        }
+    }
+    pub fn all() -> &'static [Theme] {
+        &[Theme::Dark, Theme::Light, Theme::System]
+    }
+
+    pub fn preference(&self) -> egui::ThemePreference {
+        match self {
+            Theme::Dark => egui::ThemePreference::Dark,
+            Theme::Light => egui::ThemePreference::Light,
+            Theme::System => egui::ThemePreference::System,
+        }
+    }
+}
+
+/// Custom colors as RGB; None follows the theme
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Colors {
+    pub timestamp: Option<[u8; 3]>,
+    pub tx: Option<[u8; 3]>,
+    pub rx: Option<[u8; 3]>,
+    pub matched: Option<[u8; 3]>,
+    pub unmatched: Option<[u8; 3]>,
+    pub raw_text: Option<[u8; 3]>,
+    pub highlight: Option<[u8; 3]>,
+}
+
+/// Colors of the data views for the current theme
+#[derive(Clone, Copy)]
+pub struct Palette {
+    pub timestamp: Color32,
+    pub tx: Color32,
+    pub rx: Color32,
+    pub matched: Color32,
+    pub unmatched: Color32,
+    pub raw_text: Color32,
+    /// Background of search matches
+    pub highlight: Color32,
+}
+
+impl Palette {
+    /// Theme colors; matched rows use the normal text color
+    fn defaults(visuals: &egui::Visuals) -> Self {
+        if visuals.dark_mode {
+            Self {
+                timestamp: Color32::from_rgb(140, 140, 200),
+                tx: Color32::from_rgb(230, 160, 80),
+                rx: Color32::from_rgb(100, 200, 120),
+                matched: visuals.text_color(),
+                unmatched: Color32::from_rgb(160, 100, 100),
+                raw_text: Color32::from_rgb(180, 220, 180),
+                highlight: Color32::from_rgb(110, 90, 20),
+            }
+        } else {
+            Self {
+                timestamp: Color32::from_rgb(60, 60, 160),
+                tx: Color32::from_rgb(170, 90, 0),
+                rx: Color32::from_rgb(20, 130, 50),
+                matched: visuals.text_color(),
+                unmatched: Color32::from_rgb(170, 50, 50),
+                raw_text: Color32::from_rgb(30, 90, 30),
+                highlight: Color32::from_rgb(255, 220, 110),
+            }
+        }
+    }
+
+    pub fn new(colors: &Colors, visuals: &egui::Visuals) -> Self {
+        let defaults = Self::defaults(visuals);
+        let pick = |custom: Option<[u8; 3]>, default| {
+            custom.map_or(default, |[r, g, b]| Color32::from_rgb(r, g, b))
+        };
+        Self {
+            timestamp: pick(colors.timestamp, defaults.timestamp),
+            tx: pick(colors.tx, defaults.tx),
+            rx: pick(colors.rx, defaults.rx),
+            matched: pick(colors.matched, defaults.matched),
+            unmatched: pick(colors.unmatched, defaults.unmatched),
+            raw_text: pick(colors.raw_text, defaults.raw_text),
+            highlight: pick(colors.highlight, defaults.highlight),
+        }
+    }
+}
+
+/// A color button for one custom color, with a reset button while it's set
+fn color_row(ui: &mut egui::Ui, label: &str, custom: &mut Option<[u8; 3]>, default: Color32) {
+    ui.label(label);
+    ui.horizontal(|ui| {
+        let mut rgb = custom.unwrap_or([default.r(), default.g(), default.b()]);
+        if ui.color_edit_button_srgb(&mut rgb).changed() {
+            *custom = Some(rgb);
+        }
+        if custom.is_some()
+            && ui
+                .small_button("↺")
+                .on_hover_text("Theme default")
+                .clicked()
+        {
+            *custom = None;
+        }
+    });
+    ui.end_row();
+}
+
+/// Settings section for the theme and colors
+pub fn theme_editor(ui: &mut egui::Ui, settings: &mut Settings) {
+    egui::Grid::new("theme_grid")
+        .num_columns(2)
+        .spacing([8.0, 6.0])
+        .show(ui, |ui| {
+            ui.label("Theme:");
+            egui::ComboBox::from_id_salt("theme_combo")
+                .selected_text(settings.theme.label())
+                .show_ui(ui, |ui| {
+                    for theme in Theme::all() {
+                        ui.selectable_value(&mut settings.theme, *theme, theme.label());
+                    }
+                });
+            ui.end_row();
+
+            // The defaults shown are those of the theme in use right now
+            let defaults = Palette::defaults(ui.visuals());
+            let colors = &mut settings.colors;
+            color_row(ui, "Timestamps:", &mut colors.timestamp, defaults.timestamp);
+            color_row(ui, "TX:", &mut colors.tx, defaults.tx);
+            color_row(ui, "RX:", &mut colors.rx, defaults.rx);
+            color_row(ui, "Matched rows:", &mut colors.matched, defaults.matched);
+            color_row(
+                ui,
+                "Unmatched rows:",
+                &mut colors.unmatched,
+                defaults.unmatched,
+            );
+            color_row(ui, "Raw log text:", &mut colors.raw_text, defaults.raw_text);
+            color_row(
+                ui,
+                "Search highlight:",
+                &mut colors.highlight,
+                defaults.highlight,
+            );
+        });
+}
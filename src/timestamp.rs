@@ -0,0 +1,230 @@
+// Row timestamps: every received line records the host monotonic clock (for
+// ordering, immune to wall-clock adjustments), the host wall clock and, once
+// parsed, the device's own time if a column carries it (seconds, millis or an
+// ISO time). Display and exports format whichever source is selected, so the
+// table, the merged timeline, restored captures and exports agree.
+//
+// Offset-corrected device time maps the device clock onto the host clock by
+// the smallest host-minus-device difference seen, i.e. the reading with the
+// least transmission delay.
+
+use std::sync::OnceLock;
+use std::time::Instant;
+
+use chrono::{DateTime, Local, NaiveDateTime, NaiveTime, TimeZone};
+use serde::{Deserialize, Serialize};
+
+/// Which clock timestamps are shown and exported in
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub enum TimeSource {
+    /// Host time of day
+    #[default]
+    HostWall,
+    /// Seconds since the app started, by the host monotonic clock
+    HostMonotonic,
+    /// The device's time column
+    Device,
+    /// The device's time column shifted onto the host clock
+    DeviceCorrected,
+}
+
+impl TimeSource {
+    pub fn label(&self) -> &'static str {
+        match self {
+            TimeSource::HostWall => "Host clock",
+            TimeSource::HostMonotonic => "Host elapsed (s)",
+            TimeSource::Device => "Device time",
+            TimeSource::DeviceCorrected => "Device time, offset-corrected",
+        }
+    }
+    pub fn all() -> &'static [TimeSource] {
+        &[
+            TimeSource::HostWall,
+            TimeSource::HostMonotonic,
+            TimeSource::Device,
+            TimeSource::DeviceCorrected,
+        ]
+    }
+}
+
+/// How the device writes its timestamp column
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub enum DeviceTimeFormat {
+    #[default]
+    Seconds,
+    Millis,
+    /// ISO 8601 date and time, or a time of day (taken as today)
+    Iso,
+}
+
+impl DeviceTimeFormat {
+    pub fn label(&self) -> &'static str {
+        match self {
+            DeviceTimeFormat::Seconds => "Seconds",
+            DeviceTimeFormat::Millis => "Milliseconds",
+            DeviceTimeFormat::Iso => "ISO time",
+        }
+    }
+    pub fn all() -> &'static [DeviceTimeFormat] {
+        &[
+            DeviceTimeFormat::Seconds,
+            DeviceTimeFormat::Millis,
+            DeviceTimeFormat::Iso,
+        ]
+    }
+
+    /// The cell's time in seconds; ISO times are seconds since the Unix epoch
+    pub fn parse(&self, cell: &str) -> Option<f64> {
+        let cell = cell.trim();
+        match self {
+            DeviceTimeFormat::Seconds => cell.parse::<f64>().ok().filter(|v| v.is_finite()),
+            DeviceTimeFormat::Millis => cell
+                .parse::<f64>()
+                .ok()
+                .filter(|v| v.is_finite())
+                .map(|v| v / 1000.0),
+            DeviceTimeFormat::Iso => parse_iso(cell).map(|t| t.timestamp_micros() as f64 / 1e6),
+        }
+    }
+}
+
+fn parse_iso(cell: &str) -> Option<DateTime<Local>> {
+    if let Ok(time) = DateTime::parse_from_rfc3339(cell) {
+        return Some(time.with_timezone(&Local));
+    }
+    let naive = ["%Y-%m-%dT%H:%M:%S%.f", "%Y-%m-%d %H:%M:%S%.f"]
+        .iter()
+        .find_map(|f| NaiveDateTime::parse_from_str(cell, f).ok())
+        .or_else(|| {
+            let time = NaiveTime::parse_from_str(cell, "%H:%M:%S%.f").ok()?;
+            Some(Local::now().date_naive().and_time(time))
+        })?;
+    Local.from_local_datetime(&naive).earliest()
+}
+
+fn from_epoch_secs(secs: f64) -> Option<DateTime<Local>> {
+    DateTime::from_timestamp_micros((secs * 1e6) as i64).map(|t| t.with_timezone(&Local))
+}
+
+/// Everything needed to show a timestamp the way the settings ask for
+#[derive(Debug, Clone, Copy)]
+pub struct TimeDisplay {
+    pub source: TimeSource,
+    pub device_format: DeviceTimeFormat,
+    /// Host minus device time in seconds, for `TimeSource::DeviceCorrected`
+    pub device_offset: Option<f64>,
+}
+
+/// Smallest host-minus-device difference seen so far
+#[derive(Debug, Clone, Copy, Default)]
+pub struct OffsetEstimate(Option<f64>);
+
+impl OffsetEstimate {
+    pub fn add(&mut self, time: &Timestamp) {
+        if let Some(device) = time.device {
+            let offset = time.wall_secs() - device;
+            self.0 = Some(self.0.map_or(offset, |o| o.min(offset)));
+        }
+    }
+
+    pub fn get(&self) -> Option<f64> {
+        self.0
+    }
+
+    pub fn reset(&mut self) {
+        self.0 = None;
+    }
+}
+
+/// The monotonic and wall-clock time the app started at
+fn epoch() -> &'static (Instant, DateTime<Local>) {
+    static EPOCH: OnceLock<(Instant, DateTime<Local>)> = OnceLock::new();
+    EPOCH.get_or_init(|| (Instant::now(), Local::now()))
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Timestamp {
+    /// Host monotonic time since the app started, in microseconds; negative
+    /// for lines restored from an earlier run
+    mono_us: i64,
+    /// Host wall-clock time
+    pub wall: DateTime<Local>,
+    /// Time reported by the device, in seconds (see `DeviceTimeFormat::parse`)
+    pub device: Option<f64>,
+}
+
+impl Timestamp {
+    pub fn now() -> Self {
+        let (start, _) = epoch();
+        Self {
+            mono_us: start.elapsed().as_micros() as i64,
+            wall: Local::now(),
+            device: None,
+        }
+    }
+
+    /// A timestamp known only by its wall-clock time, e.g. from a saved
+    /// capture; it is placed on the monotonic scale by its distance to the
+    /// app start
+    pub fn from_wall(wall: DateTime<Local>) -> Self {
+        let (_, start) = epoch();
+        Self {
+            mono_us: (wall - *start).num_microseconds().unwrap_or(i64::MIN),
+            wall,
+            device: None,
+        }
+    }
+
+    /// Host seconds since the app started
+    pub fn elapsed_secs(&self) -> f64 {
+        self.mono_us as f64 / 1e6
+    }
+
+    /// Host wall-clock seconds since the Unix epoch
+    pub fn wall_secs(&self) -> f64 {
+        self.wall.timestamp_micros() as f64 / 1e6
+    }
+
+    /// The time as `display` asks for; `wall_format` is the strftime format
+    /// of clock times. Rows without a device time give an empty string.
+    pub fn format(&self, display: &TimeDisplay, wall_format: &str) -> String {
+        let clock = |time: Option<DateTime<Local>>| {
+            time.map_or_else(String::new, |t| t.format(wall_format).to_string())
+        };
+        match display.source {
+            TimeSource::HostWall => self.wall.format(wall_format).to_string(),
+            TimeSource::HostMonotonic => format!("{:.3}", self.elapsed_secs()),
+            TimeSource::Device => match display.device_format {
+                DeviceTimeFormat::Iso => clock(self.device.and_then(from_epoch_secs)),
+                _ => self
+                    .device
+                    .map_or_else(String::new, |t| format!("{:.3}", t)),
+            },
+            TimeSource::DeviceCorrected => {
+                let corrected = self.device.zip(display.device_offset).map(|(t, o)| t + o);
+                clock(corrected.and_then(from_epoch_secs))
+            }
+        }
+    }
+}
+
+impl PartialEq for Timestamp {
+    fn eq(&self, other: &Self) -> bool {
+        self.mono_us == other.mono_us
+    }
+}
+
+impl Eq for Timestamp {}
+
+impl PartialOrd for Timestamp {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Ordered by the host monotonic clock
+impl Ord for Timestamp {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.mono_us.cmp(&other.mono_us)
+    }
+}
@@ -0,0 +1,185 @@
+// TLV (type-length-value) decoding: with TLV framing the stream is a
+// sequence of records made of a tag field, a length field counting the value
+// bytes, and the value. Each record becomes one row, written as a JSON object
+// with the tag's label (or its hex number), the length and the value in hex,
+// so the JSON Lines parser shows them as the columns tag, length and value.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::framing::to_hex;
+use crate::settings::{ParserMode, RxFraming, Settings};
+
+/// Name shown for a tag
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TlvLabel {
+    pub tag: u32,
+    pub name: String,
+}
+
+pub struct TlvDecoder {
+    tag_size: usize,
+    length_size: usize,
+    big_endian: bool,
+    labels: HashMap<u32, String>,
+}
+
+impl TlvDecoder {
+    /// None unless TLV framing is selected
+    pub fn from_settings(settings: &Settings) -> Option<Self> {
+        if settings.rx_framing != RxFraming::Tlv {
+            return None;
+        }
+        let labels = settings
+            .tlv_labels
+            .iter()
+            .filter(|l| !l.name.is_empty())
+            .map(|l| (l.tag, l.name.clone()))
+            .collect();
+        Some(Self {
+            tag_size: settings.tlv_tag_size.clamp(1, 4),
+            length_size: settings.tlv_length_size.clamp(1, 4),
+            big_endian: settings.tlv_big_endian,
+            labels,
+        })
+    }
+
+    /// Splits a framed record into tag, length and value
+    pub fn decode(&self, frame: &[u8]) -> String {
+        let tag_field = &frame[..self.tag_size.min(frame.len())];
+        let fold = |acc: u32, &b: &u8| (acc << 8) | b as u32;
+        let tag = if self.big_endian {
+            tag_field.iter().fold(0, fold)
+        } else {
+            tag_field.iter().rev().fold(0, fold)
+        };
+        let tag_text = match self.labels.get(&tag) {
+            Some(name) => name.clone(),
+            None => format!("0x{:01$X}", tag, self.tag_size * 2),
+        };
+        let value = frame.get(self.tag_size + self.length_size..).unwrap_or(&[]);
+        json!({
+            "tag": tag_text,
+            "length": value.len(),
+            "value": to_hex(value),
+        })
+        .to_string()
+    }
+}
+
+/// RX Framing section for TLV records
+pub fn tlv_editor(ui: &mut egui::Ui, settings: &mut Settings) {
+    egui::Grid::new("tlv_grid")
+        .num_columns(2)
+        .spacing([8.0, 4.0])
+        .show(ui, |ui| {
+            for (label, size) in [
+                ("Tag field size:", &mut settings.tlv_tag_size),
+                ("Length field size:", &mut settings.tlv_length_size),
+            ] {
+                ui.label(label);
+                ui.horizontal(|ui| {
+                    for s in [1, 2, 4] {
+                        ui.selectable_value(size, s, format!("{} B", s));
+                    }
+                });
+                ui.end_row();
+            }
+            ui.label("Byte order:");
+            ui.checkbox(&mut settings.tlv_big_endian, "Big endian");
+            ui.end_row();
+        });
+
+    ui.label("Tag labels:");
+    let mut remove = None;
+    egui::Grid::new("tlv_label_grid")
+        .num_columns(3)
+        .spacing([6.0, 4.0])
+        .striped(true)
+        .show(ui, |ui| {
+            ui.strong("Tag");
+            ui.strong("Name");
+            ui.end_row();
+
+            for (i, label) in settings.tlv_labels.iter_mut().enumerate() {
+                ui.add(egui::DragValue::new(&mut label.tag).hexadecimal(2, false, true));
+                ui.add(egui::TextEdit::singleline(&mut label.name).desired_width(160.0));
+                if ui.small_button("Remove").clicked() {
+                    remove = Some(i);
+                }
+                ui.end_row();
+            }
+        });
+    if let Some(i) = remove {
+        settings.tlv_labels.remove(i);
+    }
+    if ui.button("Add label").clicked() {
+        let next = settings
+            .tlv_labels
+            .iter()
+            .map(|l| l.tag.saturating_add(1))
+            .max()
+            .unwrap_or(1);
+        settings.tlv_labels.push(TlvLabel {
+            tag: next,
+            name: String::new(),
+        });
+    }
+    ui.label(
+        "Each record is one row with its tag (the label, or the number in hex), the length \
+         of the value and the value in hex.",
+    );
+    if settings.parser_mode != ParserMode::Json {
+        ui.colored_label(
+            egui::Color32::YELLOW,
+            "Set the parser mode to JSON Lines for tag, length and value columns.",
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn decoder(tag_size: usize, big_endian: bool) -> TlvDecoder {
+        let settings = Settings {
+            rx_framing: RxFraming::Tlv,
+            tlv_tag_size: tag_size,
+            tlv_length_size: 1,
+            tlv_big_endian: big_endian,
+            tlv_labels: vec![TlvLabel {
+                tag: 0x0102,
+                name: "temp".to_string(),
+            }],
+            ..Settings::default()
+        };
+        TlvDecoder::from_settings(&settings).unwrap()
+    }
+
+    #[test]
+    fn decodes_records() {
+        let big = decoder(2, true);
+        assert_eq!(
+            big.decode(&[0x01, 0x02, 0x02, 0x0A, 0x0B]),
+            r#"{"tag":"temp","length":2,"value":"0A 0B"}"#
+        );
+        // Unlabelled tags show as hex, padded to the field size
+        let little = decoder(2, false);
+        assert_eq!(
+            little.decode(&[0x01, 0x02, 0x00]),
+            r#"{"tag":"0x0201","length":0,"value":""}"#
+        );
+        assert_eq!(
+            decoder(1, true).decode(&[0x0F, 0x01, 0xFF]),
+            r#"{"tag":"0x0F","length":1,"value":"FF"}"#
+        );
+    }
+
+    #[test]
+    fn only_for_tlv_framing() {
+        assert!(TlvDecoder::from_settings(&Settings::default()).is_none());
+    }
+}
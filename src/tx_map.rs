@@ -0,0 +1,111 @@
+use serde::{Deserialize, Serialize};
+
+/// One substitution applied to outgoing data. Both sides accept the escapes
+/// `\n`, `\r`, `\t`, `\e`, `\\` and `\xHH`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CharMapping {
+    pub from: String,
+    pub to: String,
+}
+
+/// Decodes the escapes listed on `CharMapping`; unknown escapes are kept as-is
+pub fn unescape(text: &str) -> Vec<u8> {
+    let mut out = Vec::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            let mut utf8 = [0u8; 4];
+            out.extend_from_slice(c.encode_utf8(&mut utf8).as_bytes());
+            continue;
+        }
+        match chars.next() {
+            Some('n') => out.push(b'\n'),
+            Some('r') => out.push(b'\r'),
+            Some('t') => out.push(b'\t'),
+            Some('e') => out.push(0x1b),
+            Some('\\') => out.push(b'\\'),
+            Some('x') => {
+                let hex: String = (0..2)
+                    .filter_map(|_| chars.next_if(char::is_ascii_hexdigit))
+                    .collect();
+                match u8::from_str_radix(&hex, 16) {
+                    Ok(b) => out.push(b),
+                    Err(_) => out.extend_from_slice(format!("\\x{}", hex).as_bytes()),
+                }
+            }
+            Some(other) => {
+                out.push(b'\\');
+                let mut utf8 = [0u8; 4];
+                out.extend_from_slice(other.encode_utf8(&mut utf8).as_bytes());
+            }
+            None => out.push(b'\\'),
+        }
+    }
+    out
+}
+
+/// Applies the map in a single left-to-right pass; at each position the
+/// longest matching `from` wins, and replaced output is not rescanned.
+pub fn apply(map: &[CharMapping], data: &[u8]) -> Vec<u8> {
+    let rules: Vec<(Vec<u8>, Vec<u8>)> = map
+        .iter()
+        .map(|m| (unescape(&m.from), unescape(&m.to)))
+        .filter(|(from, _)| !from.is_empty())
+        .collect();
+    if rules.is_empty() {
+        return data.to_vec();
+    }
+
+    let mut out = Vec::with_capacity(data.len());
+    let mut i = 0;
+    while i < data.len() {
+        let hit = rules
+            .iter()
+            .filter(|(from, _)| data[i..].starts_with(from))
+            .max_by_key(|(from, _)| from.len());
+        match hit {
+            Some((from, to)) => {
+                out.extend_from_slice(to);
+                i += from.len();
+            }
+            None => {
+                out.push(data[i]);
+                i += 1;
+            }
+        }
+    }
+    out
+}
+
+/// Settings editor for the TX character map
+pub fn char_map_editor(ui: &mut egui::Ui, map: &mut Vec<CharMapping>) {
+    let mut remove = None;
+
+    egui::Grid::new("tx_map_grid")
+        .num_columns(3)
+        .spacing([6.0, 4.0])
+        .striped(true)
+        .show(ui, |ui| {
+            ui.strong("Replace");
+            ui.strong("With");
+            ui.end_row();
+
+            for (i, m) in map.iter_mut().enumerate() {
+                ui.add(egui::TextEdit::singleline(&mut m.from).desired_width(80.0));
+                ui.add(egui::TextEdit::singleline(&mut m.to).desired_width(140.0));
+                if ui.small_button("Remove").clicked() {
+                    remove = Some(i);
+                }
+                ui.end_row();
+            }
+        });
+
+    if let Some(i) = remove {
+        map.remove(i);
+    }
+    if ui.button("Add mapping").clicked() {
+        map.push(CharMapping::default());
+    }
+    ui.label("Applied to sent text and macros, including the TX line ending. Escapes: \\n \\r \\t \\e \\\\ \\xHH");
+}
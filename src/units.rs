@@ -0,0 +1,101 @@
+// Column scaling: firmware often sends raw integers (ADC counts, tenths of a
+// degree) and leaves the conversion to the reader. A scale, offset and unit
+// per column turn them into engineering units, value = raw × scale + offset,
+// for the table, plots, dashboard, alerts and exports; the received text
+// stays untouched. Scales are part of the parser profile, like column types.
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ColumnScale {
+    /// Column (0-based)
+    pub column: usize,
+    pub scale: f64,
+    pub offset: f64,
+    /// Shown after the column name, e.g. "V"
+    pub unit: String,
+    /// Decimal places of scaled values
+    pub decimals: usize,
+}
+
+impl Default for ColumnScale {
+    fn default() -> Self {
+        Self {
+            column: 0,
+            scale: 1.0,
+            offset: 0.0,
+            unit: String::new(),
+            decimals: 3,
+        }
+    }
+}
+
+impl ColumnScale {
+    pub fn apply(&self, raw: f64) -> f64 {
+        raw * self.scale + self.offset
+    }
+
+    /// A scaled value as shown and exported
+    pub fn format(&self, value: f64) -> String {
+        format!("{:.*}", self.decimals, value)
+    }
+}
+
+/// The scale of `column`, if it has one
+pub fn scale_of(scales: &[ColumnScale], column: usize) -> Option<&ColumnScale> {
+    scales.iter().find(|s| s.column == column)
+}
+
+/// Scales the converted values of a row in place
+pub fn scale_values(scales: &[ColumnScale], values: &mut [Option<f64>]) {
+    for scale in scales {
+        if let Some(value) = values.get_mut(scale.column).and_then(Option::as_mut) {
+            *value = scale.apply(*value);
+        }
+    }
+}
+
+/// Parser section for the column scales
+pub fn scale_editor(ui: &mut egui::Ui, scales: &mut Vec<ColumnScale>) {
+    let mut remove = None;
+
+    if !scales.is_empty() {
+        egui::Grid::new("scale_grid")
+            .num_columns(6)
+            .spacing([6.0, 4.0])
+            .striped(true)
+            .show(ui, |ui| {
+                ui.strong("Column");
+                ui.strong("Scale");
+                ui.strong("Offset");
+                ui.strong("Unit");
+                ui.strong("Decimals");
+                ui.end_row();
+
+                for (i, scale) in scales.iter_mut().enumerate() {
+                    let mut col = scale.column + 1;
+                    ui.add(egui::DragValue::new(&mut col).range(1..=64));
+                    scale.column = col - 1;
+                    ui.add(egui::DragValue::new(&mut scale.scale).speed(0.001));
+                    ui.add(egui::DragValue::new(&mut scale.offset).speed(0.01));
+                    ui.add(egui::TextEdit::singleline(&mut scale.unit).desired_width(50.0));
+                    ui.add(egui::DragValue::new(&mut scale.decimals).range(0..=9));
+                    if ui.small_button("Remove").clicked() {
+                        remove = Some(i);
+                    }
+                    ui.end_row();
+                }
+            });
+    }
+    if let Some(i) = remove {
+        scales.remove(i);
+    }
+    if ui.button("Add scale").clicked() {
+        let next = scales.iter().map(|s| s.column + 1).max().unwrap_or(0);
+        scales.push(ColumnScale {
+            column: next,
+            ..ColumnScale::default()
+        });
+    }
+}